@@ -65,6 +65,7 @@ fn smoke_test_inputs() {
     dbg!(cargo_pkg_version_pre());
     dbg!(debug());
     dbg!(dep_metadata("z", "include"));
+    dbg!(dep_metadata_json("z", "include"));
     dbg!(host());
     dbg!(num_jobs());
     dbg!(opt_level());
@@ -9,9 +9,9 @@ fn rust_version_minor() -> u32 {
     })
 }
 
-fn cargo_version_minor() -> u32 {
-    static VERSION_MINOR: OnceLock<u32> = OnceLock::new();
-    *VERSION_MINOR.get_or_init(|| {
+fn cargo_version() -> &'static str {
+    static VERSION: OnceLock<String> = OnceLock::new();
+    VERSION.get_or_init(|| {
         let out = Command::new(crate::input::cargo())
             .arg("-V")
             .output()
@@ -22,11 +22,22 @@ fn cargo_version_minor() -> u32 {
         // cargo 1.82.0 (8f40fc59f 2024-08-21)
 
         let out = std::str::from_utf8(&out.stdout).expect("`cargo -V` should output valid UTF-8");
-        let version = out.split(' ').nth(1).unwrap();
-        version_minor(version).unwrap()
+        out.split(' ').nth(1).unwrap().to_owned()
     })
 }
 
+fn cargo_version_minor() -> u32 {
+    static VERSION_MINOR: OnceLock<u32> = OnceLock::new();
+    *VERSION_MINOR.get_or_init(|| version_minor(cargo_version()).unwrap())
+}
+
+fn unstable_instructions() -> bool {
+    // cargo ignores the new `cargo::` directives unless they are enabled with
+    // `-Zbuild-script-instructions`, which only nightly cargo accepts
+    let version = cargo_version();
+    cargo_version_minor() >= 85 && (version.contains("-nightly") || version.contains("-dev"))
+}
+
 fn version_minor(version: &str) -> Option<u32> {
     let minor = version.split('.').nth(1)?;
     let minor = minor.parse().unwrap();
@@ -42,3 +53,15 @@ pub(crate) fn check_cfg() -> bool {
     // emit check-cfg if the toolchain being used supports it
     cargo_version_minor() >= 80
 }
+
+pub(crate) fn rerun_if_changed_glob() -> bool {
+    unstable_instructions()
+}
+
+pub(crate) fn input_output_files() -> bool {
+    unstable_instructions()
+}
+
+pub(crate) fn metadata_json() -> bool {
+    unstable_instructions()
+}
@@ -25,3 +25,9 @@ pub(crate) fn is_crate_name(s: &str) -> bool {
         .is_some_and(|ch| is_xid_start(ch) || matches!(ch, '-' | '_'))
         && cs.all(|ch| is_xid_continue(ch) || matches!(ch, '-'))
 }
+
+pub(crate) fn is_metadata_json_key(s: &str) -> bool {
+    !s.is_empty()
+        && s.chars()
+            .all(|ch| ch.is_ascii_alphanumeric() || matches!(ch, '-' | '_'))
+}
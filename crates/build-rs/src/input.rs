@@ -9,7 +9,7 @@
 use std::env::var_os;
 use std::path::PathBuf;
 
-use crate::ident::{is_ascii_ident, is_crate_name, is_feature_name};
+use crate::ident::{is_ascii_ident, is_crate_name, is_feature_name, is_metadata_json_key};
 
 /// Path to the `cargo` binary performing the build.
 #[track_caller]
@@ -419,6 +419,35 @@ pub fn dep_metadata(name: &str, key: &str) -> Option<String> {
     var_os(&key).map(to_string)
 }
 
+/// [JSON metadata] set by dependencies, as JSON text.
+///
+/// Unlike [`dep_metadata`], this is looked up by the name of the dependency
+/// package rather than its `links` value, and the key is used as is. The
+/// result can be deserialized with a JSON library like `serde_json`.
+///
+/// JSON metadata is unstable, and Cargo only passes it on nightly with
+/// `-Zbuild-script-instructions`. With other versions of Cargo, this returns
+/// `None`.
+///
+/// [JSON metadata]: crate::output::metadata_json
+#[track_caller]
+pub fn dep_metadata_json(package: &str, key: &str) -> Option<String> {
+    if !is_crate_name(package) {
+        panic!("invalid dependency name {package:?}")
+    }
+    if !is_metadata_json_key(key) {
+        panic!("invalid metadata key {key:?}")
+    }
+
+    let dir = var_os("CARGO_DEP_METADATA_DIR")?;
+    let path = PathBuf::from(dir).join(package).join(format!("{key}.json"));
+    match std::fs::read_to_string(&path) {
+        Ok(json) => Some(json),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => None,
+        Err(e) => panic!("failed to read {}: {e}", path.display()),
+    }
+}
+
 /// The compiler that Cargo has resolved to use.
 #[track_caller]
 pub fn rustc() -> PathBuf {
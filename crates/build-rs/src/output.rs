@@ -8,7 +8,7 @@
 
 use crate::{
     allow_use,
-    ident::{is_ascii_ident, is_ident},
+    ident::{is_ascii_ident, is_ident, is_metadata_json_key},
 };
 use std::{ffi::OsStr, fmt::Display, fmt::Write, path::Path, str};
 
@@ -48,6 +48,34 @@ pub fn rerun_if_changed(path: impl AsRef<Path>) {
     emit("rerun-if-changed", path);
 }
 
+/// The `rerun-if-changed-glob` instruction tells Cargo to re-run the build
+/// script if any file matching the given glob pattern has changed, or if a file
+/// starts or stops matching it.
+///
+/// Relative patterns are relative to the package root. In patterns, `*` matches
+/// any part of a file name, `**` matches any number of directories, and `?` and
+/// `[...]` work as in Unix shells.
+///
+/// This instruction is unstable, and Cargo only respects it on nightly with
+/// `-Zbuild-script-instructions`. With other versions of Cargo, this instead
+/// emits `rerun-if-changed` for the directory the pattern starts with, which
+/// catches modified files but may miss added or removed ones.
+#[track_caller]
+pub fn rerun_if_changed_glob(pattern: &str) {
+    if pattern.contains('\n') {
+        panic!("cannot emit rerun-if-changed-glob: pattern contains newline");
+    }
+    if allow_use::rerun_if_changed_glob() {
+        emit("rerun-if-changed-glob", pattern);
+    } else {
+        let base = match pattern.find(['*', '?', '[']) {
+            Some(i) => pattern[..i].rfind('/').map_or("", |j| &pattern[..=j]),
+            None => pattern,
+        };
+        rerun_if_changed(if base.is_empty() { "." } else { base });
+    }
+}
+
 /// The `rerun-if-env-changed` instruction tells Cargo to re-run the build script
 /// if the value of an environment variable of the given name has changed.
 ///
@@ -69,6 +97,54 @@ pub fn rerun_if_env_changed(key: impl AsRef<OsStr>) {
     emit("rerun-if-env-changed", key);
 }
 
+/// The `input-file` instruction declares a file read by the build script.
+///
+/// Relative paths are relative to the package root. Cargo re-runs the build
+/// script when the file changes, like with [`rerun_if_changed`], and reports
+/// it to other tools in dep-info files and JSON messages.
+///
+/// This instruction is unstable, and Cargo only respects it on nightly with
+/// `-Zbuild-script-instructions`. With other versions of Cargo, this instead
+/// emits `rerun-if-changed` for the path.
+#[track_caller]
+pub fn input_file(path: impl AsRef<Path>) {
+    let Some(path) = path.as_ref().to_str() else {
+        panic!("cannot emit input-file: path is not UTF-8");
+    };
+    if path.contains('\n') {
+        panic!("cannot emit input-file: path contains newline");
+    }
+    if allow_use::input_output_files() {
+        emit("input-file", path);
+    } else {
+        rerun_if_changed(path);
+    }
+}
+
+/// The `output-file` instruction declares a file generated by the build
+/// script.
+///
+/// Relative paths are relative to `OUT_DIR`, and the file must be inside of
+/// it. Cargo re-runs the build script if the file goes missing, reports it to
+/// other tools in dep-info files and JSON messages, and removes it once the
+/// build script stops declaring it.
+///
+/// This instruction is unstable, and Cargo only respects it on nightly with
+/// `-Zbuild-script-instructions`. With other versions of Cargo, this does
+/// nothing.
+#[track_caller]
+pub fn output_file(path: impl AsRef<Path>) {
+    let Some(path) = path.as_ref().to_str() else {
+        panic!("cannot emit output-file: path is not UTF-8");
+    };
+    if path.contains('\n') {
+        panic!("cannot emit output-file: path contains newline");
+    }
+    if allow_use::input_output_files() {
+        emit("output-file", path);
+    }
+}
+
 /// The `rustc-link-arg` instruction tells Cargo to pass the
 /// [`-C link-arg=FLAG` option][link-arg] to the compiler, but only when building
 /// supported targets (benchmarks, binaries, cdylib crates, examples, and tests).
@@ -427,3 +503,28 @@ pub fn metadata(key: &str, val: &str) {
         emit(key, val);
     }
 }
+
+/// JSON metadata for dependent build scripts.
+///
+/// `json` must be a single line of JSON text. Dependents read it with
+/// [`dep_metadata_json`], using the name of this package and `key`, which may
+/// contain ASCII letters, digits, `-` and `_`. As with [`metadata`], only
+/// packages with a `links` key can pass metadata to dependents.
+///
+/// This instruction is unstable, and Cargo only respects it on nightly with
+/// `-Zbuild-script-instructions`. With other versions of Cargo, this does
+/// nothing.
+///
+/// [`dep_metadata_json`]: crate::input::dep_metadata_json
+#[track_caller]
+pub fn metadata_json(key: &str, json: &str) {
+    if !is_metadata_json_key(key) {
+        panic!("cannot emit metadata-json: invalid key {key:?}");
+    }
+    if json.contains('\n') {
+        panic!("cannot emit metadata-json: JSON contains newline");
+    }
+    if allow_use::metadata_json() {
+        emit("metadata-json", format_args!("{}={}", key, json));
+    }
+}
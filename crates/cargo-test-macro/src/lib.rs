@@ -38,6 +38,7 @@ use std::sync::Once;
 ///   For example, `requires_rustfmt` means the test will only run if the executable `rustfmt` is installed.
 ///   These tests are *always* run on CI.
 ///   This is mainly used to avoid requiring contributors from having every dependency installed.
+/// * `requires_ssh_keygen` --- Like `requires_<cmd>`, but for OpenSSH's `ssh-keygen`, which has no `--version` flag.
 /// * `build_std_real` --- This is a "real" `-Zbuild-std` test (in the `build_std` integration test).
 ///   This only runs on nightly, and only if the environment variable `CARGO_RUN_BUILD_STD_TESTS` is set (these tests on run on Linux).
 /// * `build_std_mock` --- This is a "mock" `-Zbuild-std` test (which uses a mock standard library).
@@ -133,6 +134,9 @@ pub fn cargo_test(attr: TokenStream, item: TokenStream) -> TokenStream {
                     "rustup or stable toolchain not installed"
                 );
             }
+            "requires_ssh_keygen" => {
+                set_ignore!(!has_ssh_keygen(), "ssh-keygen not installed");
+            }
             s if s.starts_with("requires_") => {
                 let command = &s[9..];
                 set_ignore!(!has_command(command), "{command} not installed");
@@ -297,6 +301,16 @@ fn has_command(command: &str) -> bool {
     check_command(Path::new(command), &["--version"])
 }
 
+/// `ssh-keygen` exits with an error for any informational flag, so just check
+/// that it can be spawned.
+fn has_ssh_keygen() -> bool {
+    let found = Command::new("ssh-keygen").arg("-?").output().is_ok();
+    if !found && is_ci() {
+        panic!("expected command `ssh-keygen` to be somewhere in PATH");
+    }
+    found
+}
+
 fn has_rustup_stable() -> bool {
     if option_env!("CARGO_TEST_DISABLE_NIGHTLY").is_some() {
         // This cannot run on rust-lang/rust CI due to the lack of rustup.
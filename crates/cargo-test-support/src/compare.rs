@@ -306,6 +306,7 @@ static E2E_LITERAL_REDACTIONS: &[(&str, &str)] = &[
     ("[UPLOADING]", "   Uploading"),
     ("[UPLOADED]", "    Uploaded"),
     ("[VERIFYING]", "   Verifying"),
+    ("[VERIFIED]", "    Verified"),
     ("[ARCHIVING]", "   Archiving"),
     ("[INSTALLING]", "  Installing"),
     ("[REPLACING]", "   Replacing"),
@@ -325,6 +326,7 @@ static E2E_LITERAL_REDACTIONS: &[(&str, &str)] = &[
     ("[EXECUTABLE]", "  Executable"),
     ("[SKIPPING]", "    Skipping"),
     ("[WAITING]", "     Waiting"),
+    ("[RETRYING]", "    Retrying"),
     ("[PUBLISHED]", "   Published"),
     ("[BLOCKING]", "    Blocking"),
     ("[GENERATED]", "   Generated"),
@@ -42,7 +42,9 @@
 
 use crate::{paths::CargoPathExt, project, Project, ProjectBuilder, SymlinkBuilder};
 use std::fs;
+use std::io::Write as _;
 use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
 use std::sync::Once;
 use url::Url;
 
@@ -243,6 +245,80 @@ pub fn tag(repo: &git2::Repository, name: &str) {
     ));
 }
 
+/// Generates an SSH key for signing with [`commit_signed`] and [`tag_signed`].
+///
+/// Returns the path to the private key, and the path to an OpenSSH "allowed
+/// signers" file that trusts it. Requires `ssh-keygen` to be installed.
+pub fn ssh_signing_key(dir: &Path) -> (PathBuf, PathBuf) {
+    t!(fs::create_dir_all(dir));
+    let key = dir.join("id_ed25519");
+    let output = t!(Command::new("ssh-keygen")
+        .args(["-q", "-t", "ed25519", "-N", "", "-C", "foo@bar.com", "-f"])
+        .arg(&key)
+        .output());
+    assert!(output.status.success(), "ssh-keygen failed: {output:?}");
+    let public_key = t!(fs::read_to_string(key.with_extension("pub")));
+    let allowed_signers = dir.join("allowed_signers");
+    t!(fs::write(
+        &allowed_signers,
+        format!("foo@bar.com namespaces=\"git\" {public_key}")
+    ));
+    (key, allowed_signers)
+}
+
+/// Signs `data` for git with the SSH private `key`.
+fn ssh_sign(data: &[u8], key: &Path) -> String {
+    let mut child = t!(Command::new("ssh-keygen")
+        .args(["-Y", "sign", "-n", "git", "-f"])
+        .arg(key)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn());
+    t!(child.stdin.take().unwrap().write_all(data));
+    let output = t!(child.wait_with_output());
+    assert!(output.status.success(), "ssh-keygen failed: {output:?}");
+    t!(String::from_utf8(output.stdout))
+}
+
+/// *(`git2`)* Commit changes to the git repository, signed with the SSH
+/// private `key` from [`ssh_signing_key`]
+pub fn commit_signed(repo: &git2::Repository, key: &Path) -> git2::Oid {
+    let tree_id = t!(t!(repo.index()).write_tree());
+    let sig = t!(repo.signature());
+    let mut parents = Vec::new();
+    if let Some(parent) = repo.head().ok().map(|h| h.target().unwrap()) {
+        parents.push(t!(repo.find_commit(parent)))
+    }
+    let parents = parents.iter().collect::<Vec<_>>();
+    let buf =
+        t!(repo.commit_create_buffer(&sig, &sig, "test", &t!(repo.find_tree(tree_id)), &parents));
+    let signature = ssh_sign(&buf, key);
+    let id = t!(repo.commit_signed(buf.as_str().unwrap(), &signature, None));
+    let head = t!(repo.find_reference("HEAD"));
+    let branch = head.symbolic_target().unwrap();
+    t!(repo.reference(branch, id, true, "signed commit"));
+    id
+}
+
+/// *(`git2`)* Create a new annotated tag in the git repository, signed with
+/// the SSH private `key` from [`ssh_signing_key`]
+pub fn tag_signed(repo: &git2::Repository, name: &str, key: &Path) -> git2::Oid {
+    let head = repo.head().unwrap().target().unwrap();
+    let tagger = t!(repo.signature());
+    let mut buf = format!(
+        "object {head}\ntype commit\ntag {name}\ntagger {} <{}> {} +0000\n\nmake a new tag\n",
+        tagger.name().unwrap(),
+        tagger.email().unwrap(),
+        tagger.when().seconds(),
+    );
+    let signature = ssh_sign(buf.as_bytes(), key);
+    buf.push_str(&signature);
+    let id = t!(t!(repo.odb()).write(git2::ObjectType::Tag, buf.as_bytes()));
+    t!(repo.reference(&format!("refs/tags/{name}"), id, false, "signed tag"));
+    id
+}
+
 /// Returns true if gitoxide is globally activated.
 ///
 /// That way, tests that normally use `git2` can transparently use `gitoxide`.
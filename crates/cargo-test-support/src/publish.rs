@@ -243,6 +243,7 @@ pub(crate) fn create_index_line(
     yanked: bool,
     links: Option<String>,
     rust_version: Option<&str>,
+    sig: Option<&str>,
     v: Option<u32>,
 ) -> String {
     // This emulates what crates.io does to retain backwards compatibility.
@@ -266,6 +267,9 @@ pub(crate) fn create_index_line(
     if let Some(rust_version) = rust_version {
         json["rust_version"] = serde_json::json!(rust_version);
     }
+    if let Some(sig) = sig {
+        json["sig"] = serde_json::json!(sig);
+    }
 
     json.to_string()
 }
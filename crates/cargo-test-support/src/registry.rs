@@ -179,6 +179,8 @@ pub struct RegistryBuilder {
     delayed_index_update: usize,
     /// Credential provider in configuration
     credential_provider: Option<String>,
+    /// If set, config.json advertises support for staged publishing.
+    staged_publish: bool,
 }
 
 /// A local registry fixture
@@ -251,6 +253,7 @@ impl RegistryBuilder {
             not_found_handler: Box::new(not_found),
             delayed_index_update: 0,
             credential_provider: None,
+            staged_publish: false,
         }
     }
 
@@ -295,6 +298,13 @@ impl RegistryBuilder {
         self.alternative_named("alternative")
     }
 
+    /// Advertises support for staged publishing in config.json.
+    #[must_use]
+    pub fn staged_publish(mut self) -> Self {
+        self.staged_publish = true;
+        self
+    }
+
     /// Prevents placing a token in the configuration
     #[must_use]
     pub fn no_configure_token(mut self) -> Self {
@@ -522,11 +532,16 @@ impl RegistryBuilder {
         } else {
             String::new()
         };
+        let staged = if self.staged_publish {
+            r#","staged-publish":true"#
+        } else {
+            ""
+        };
         // Initialize a new registry.
         repo(&registry.path)
             .file(
                 "config.json",
-                &format!(r#"{{"dl":"{}"{api}{auth}}}"#, registry.dl_url),
+                &format!(r#"{{"dl":"{}"{api}{auth}{staged}}}"#, registry.dl_url),
             )
             .build();
         fs::create_dir_all(api_path.join("api/v1/crates")).unwrap();
@@ -577,6 +592,7 @@ pub struct Package {
     links: Option<String>,
     rust_version: Option<String>,
     cargo_features: Vec<String>,
+    signing_key: Option<String>,
     v: Option<u32>,
 }
 
@@ -981,6 +997,10 @@ impl HttpServer {
             }
             // publish
             ("put", ["api", "v1", "crates", "new"]) => self.check_authorized_publish(req),
+            // staged publish
+            ("put", ["api", "v1", "crates", "staged", "new"]) => self.stage_publish(req),
+            ("put", ["api", "v1", "crates", "staged", "commit"]) => self.commit_staged(req),
+            ("delete", ["api", "v1", "crates", "staged"]) => self.discard_staged(req),
             // The remainder of the operators in the test framework do nothing other than responding 'ok'.
             //
             // Note: We don't need to support anything real here because there are no tests that
@@ -1131,15 +1151,7 @@ impl HttpServer {
             t!(fs::create_dir_all(path.parent().unwrap()));
             t!(fs::write(&path, body));
 
-            // Get the metadata of the package
-            let (len, remaining) = body.split_at(4);
-            let json_len = u32::from_le_bytes(len.try_into().unwrap());
-            let (json, remaining) = remaining.split_at(json_len as usize);
-            let new_crate = serde_json::from_slice::<crates_io::NewCrate>(json).unwrap();
-            // Get the `.crate` file
-            let (len, remaining) = remaining.split_at(4);
-            let file_len = u32::from_le_bytes(len.try_into().unwrap());
-            let (file, _remaining) = remaining.split_at(file_len as usize);
+            let (new_crate, file) = parse_publish_body(body);
             let file_cksum = cksum(&file);
 
             if !self.check_authorized(
@@ -1181,6 +1193,136 @@ impl HttpServer {
             }
         }
     }
+
+    /// The directory holding the uploads of the staging session in `req`.
+    fn staging_dir(&self, req: &Request) -> PathBuf {
+        let session = req
+            .url
+            .query_pairs()
+            .find(|(k, _)| k == "session")
+            .map(|(_, v)| v.into_owned())
+            .unwrap();
+        self.api_path.join("api/v1/crates/staged").join(session)
+    }
+
+    /// Whether `req` is authorized to publish any of the crates staged in `dir`.
+    fn check_authorized_staged(&self, req: &Request, dir: &Path) -> bool {
+        let Ok(entries) = fs::read_dir(dir) else {
+            return false;
+        };
+        entries.into_iter().any(|entry| {
+            let body = t!(fs::read(t!(entry).path()));
+            let (new_crate, file) = parse_publish_body(&body);
+            self.check_authorized(
+                req,
+                Some(Mutation {
+                    mutation: "publish",
+                    name: Some(&new_crate.name),
+                    vers: Some(&new_crate.vers),
+                    cksum: Some(&cksum(file)),
+                }),
+            )
+        })
+    }
+
+    pub fn stage_publish(&self, req: &Request) -> Response {
+        let Some(body) = &req.body else {
+            return Response {
+                code: 400,
+                headers: vec![],
+                body: b"The request was missing a body".to_vec(),
+            };
+        };
+        let (new_crate, file) = parse_publish_body(body);
+        if !self.check_authorized(
+            req,
+            Some(Mutation {
+                mutation: "publish",
+                name: Some(&new_crate.name),
+                vers: Some(&new_crate.vers),
+                cksum: Some(&cksum(file)),
+            }),
+        ) {
+            return self.unauthorized(req);
+        }
+        let dir = self.staging_dir(req);
+        t!(fs::create_dir_all(&dir));
+        t!(fs::write(
+            dir.join(format!("{}-{}", new_crate.name, new_crate.vers)),
+            body
+        ));
+        self.ok(&req)
+    }
+
+    pub fn commit_staged(&self, req: &Request) -> Response {
+        let dir = self.staging_dir(req);
+        if !self.check_authorized_staged(req, &dir) {
+            return self.unauthorized(req);
+        }
+        for entry in t!(fs::read_dir(&dir)) {
+            let body = t!(fs::read(t!(entry).path()));
+            let (new_crate, file) = parse_publish_body(&body);
+            let file_cksum = cksum(file);
+            let dst = self
+                .dl_path
+                .join(&new_crate.name)
+                .join(&new_crate.vers)
+                .join("download");
+            save_new_crate(dst, new_crate, file, file_cksum, &self.registry_path);
+        }
+        t!(fs::remove_dir_all(&dir));
+        self.ok(&req)
+    }
+
+    pub fn discard_staged(&self, req: &Request) -> Response {
+        let dir = self.staging_dir(req);
+        if !self.check_authorized_staged(req, &dir) {
+            return self.unauthorized(req);
+        }
+        t!(fs::remove_dir_all(&dir));
+        self.ok(&req)
+    }
+}
+
+/// Splits the body of a publish request into the metadata of the package and
+/// its `.crate` file.
+fn parse_publish_body(body: &[u8]) -> (crates_io::NewCrate, &[u8]) {
+    // Get the metadata of the package
+    let (len, remaining) = body.split_at(4);
+    let json_len = u32::from_le_bytes(len.try_into().unwrap());
+    let (json, remaining) = remaining.split_at(json_len as usize);
+    let new_crate = serde_json::from_slice::<crates_io::NewCrate>(json).unwrap();
+    // Get the `.crate` file
+    let (len, remaining) = remaining.split_at(4);
+    let file_len = u32::from_le_bytes(len.try_into().unwrap());
+    let (file, _remaining) = remaining.split_at(file_len as usize);
+    (new_crate, file)
+}
+
+/// Creates a package signature the way `cargo publish -Zpackage-signing`
+/// does through the `cargo:paseto` credential provider.
+pub fn sign_package(secret_key: &str, name: &str, vers: &str, cksum: &str) -> String {
+    let secret: AsymmetricSecretKey<pasetors::version3::V3> = secret_key.try_into().unwrap();
+    let public: AsymmetricPublicKey<pasetors::version3::V3> = (&secret).try_into().unwrap();
+    let mut kip = String::new();
+    FormatAsPaserk::fmt(&pasetors::paserk::Id::from(&public), &mut kip).unwrap();
+    let message = serde_json::json!({
+        "iat": OffsetDateTime::now_utc().format(&Rfc3339).unwrap(),
+        "mutation": "sign",
+        "name": name,
+        "vers": vers,
+        "cksum": cksum,
+    });
+    let footer = serde_json::json!({
+        "url": "",
+        "kip": kip,
+    });
+    t!(pasetors::version3::PublicToken::sign(
+        &secret,
+        message.to_string().as_bytes(),
+        Some(footer.to_string().as_bytes()),
+        None,
+    ))
 }
 
 fn save_new_crate(
@@ -1228,7 +1370,13 @@ fn save_new_crate(
         false,
         new_crate.links,
         new_crate.rust_version.as_deref(),
-        None,
+        new_crate.sig.as_deref(),
+        // Artifact dependencies require schema version 3.
+        new_crate
+            .deps
+            .iter()
+            .any(|dep| dep.artifact.is_some())
+            .then_some(3),
     );
 
     write_to_index(registry_path, &new_crate.name, line, false);
@@ -1258,6 +1406,7 @@ impl Package {
             links: None,
             rust_version: None,
             cargo_features: Vec::new(),
+            signing_key: None,
             v: None,
         }
     }
@@ -1439,12 +1588,19 @@ impl Package {
 
     /// Sets the index schema version for this package.
     ///
-    /// See `cargo::sources::registry::IndexPackage` for more information.
+    /// See `cargo_util_schemas::index::IndexPackage` for more information.
     pub fn schema_version(&mut self, version: u32) -> &mut Package {
         self.v = Some(version);
         self
     }
 
+    /// Signs the index entry with a PASERK `k3.secret` key, as if it was
+    /// published with `-Zpackage-signing`.
+    pub fn signed_by(&mut self, secret_key: &str) -> &mut Package {
+        self.signing_key = Some(secret_key.to_string());
+        self
+    }
+
     /// Creates the package and place it in the registry.
     ///
     /// This does not actually use Cargo's publishing system, but instead
@@ -1510,6 +1666,10 @@ impl Package {
             self.yanked,
             self.links.clone(),
             self.rust_version.as_deref(),
+            self.signing_key
+                .as_deref()
+                .map(|key| sign_package(key, &self.name, &self.vers, &cksum))
+                .as_deref(),
             self.v,
         );
 
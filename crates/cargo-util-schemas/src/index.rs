@@ -0,0 +1,138 @@
+//! Schema for the [registry index].
+//!
+//! [registry index]: https://doc.rust-lang.org/cargo/reference/registry-index.html
+
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::manifest::RustVersion;
+
+/// A single line in the index representing a single version of a package.
+#[derive(Deserialize, Serialize)]
+pub struct IndexPackage<'a> {
+    /// Name of the package.
+    #[serde(borrow)]
+    pub name: Cow<'a, str>,
+    /// The version of this dependency.
+    pub vers: semver::Version,
+    /// All kinds of direct dependencies of the package, including dev and
+    /// build dependencies.
+    #[serde(borrow)]
+    pub deps: Vec<RegistryDependency<'a>>,
+    /// Set of features defined for the package, i.e., `[features]` table.
+    #[serde(default)]
+    pub features: BTreeMap<Cow<'a, str>, Vec<Cow<'a, str>>>,
+    /// This field contains features with new, extended syntax. Specifically,
+    /// namespaced features (`dep:`) and weak dependencies (`pkg?/feat`).
+    ///
+    /// This is separated from `features` because versions older than 1.19
+    /// will fail to load due to not being able to parse the new syntax, even
+    /// with a `Cargo.lock` file.
+    pub features2: Option<BTreeMap<Cow<'a, str>, Vec<Cow<'a, str>>>>,
+    /// Checksum for verifying the integrity of the corresponding downloaded package.
+    pub cksum: String,
+    /// If `true`, Cargo will skip this version when resolving.
+    ///
+    /// This was added in 2014. Everything in the crates.io index has this set
+    /// now, so this probably doesn't need to be an option anymore.
+    pub yanked: Option<bool>,
+    /// Native library name this package links to.
+    ///
+    /// Added early 2018 (see <https://github.com/rust-lang/cargo/pull/4978>),
+    /// can be `None` if published before then.
+    pub links: Option<Cow<'a, str>>,
+    /// Required version of rust
+    ///
+    /// Corresponds to `package.rust-version`.
+    ///
+    /// Added in 2023 (see <https://github.com/rust-lang/crates.io/pull/6267>),
+    /// can be `None` if published before then or if not set in the manifest.
+    pub rust_version: Option<RustVersion>,
+    /// Signature of the corresponding downloaded package.
+    ///
+    /// This is a PASETO `v3.public` token over the name, version and
+    /// checksum of the package, made with a key held by the publisher.
+    ///
+    /// Can be `None` if the package was not signed.
+    pub sig: Option<String>,
+    /// The schema version for this entry.
+    ///
+    /// If this is None, it defaults to version `1`. Entries with unknown
+    /// versions are ignored.
+    ///
+    /// Version `2` schema adds the `features2` field.
+    ///
+    /// Version `3` schema adds `artifact`, `bindep_target`, and `lib` for
+    /// artifact dependencies support. Understood as of 1.85.
+    ///
+    /// This provides a method to safely introduce changes to index entries
+    /// and allow older versions of cargo to ignore newer entries it doesn't
+    /// understand. This is honored as of 1.51, so unfortunately older
+    /// versions will ignore it, and potentially misinterpret version 2 and
+    /// newer entries.
+    ///
+    /// The intent is that versions older than 1.51 will work with a
+    /// pre-existing `Cargo.lock`, but they may not correctly process `cargo
+    /// update` or build a lock from scratch. In that case, cargo may
+    /// incorrectly select a new package that uses a new index schema. A
+    /// workaround is to downgrade any packages that are incompatible with the
+    /// `--precise` flag of `cargo update`.
+    pub v: Option<u32>,
+}
+
+/// A dependency as encoded in the [`IndexPackage`] index JSON.
+#[derive(Deserialize, Serialize)]
+pub struct RegistryDependency<'a> {
+    /// Name of the dependency. If the dependency is renamed, the original
+    /// would be stored in [`RegistryDependency::package`].
+    #[serde(borrow)]
+    pub name: Cow<'a, str>,
+    /// The SemVer requirement for this dependency.
+    #[serde(borrow)]
+    pub req: Cow<'a, str>,
+    /// Set of features enabled for this dependency.
+    #[serde(default)]
+    pub features: Vec<Cow<'a, str>>,
+    /// Whether or not this is an optional dependency.
+    #[serde(default)]
+    pub optional: bool,
+    /// Whether or not default features are enabled.
+    #[serde(default = "default_true")]
+    pub default_features: bool,
+    /// The target platform for this dependency.
+    pub target: Option<Cow<'a, str>>,
+    /// The dependency kind. "dev", "build", and "normal".
+    pub kind: Option<Cow<'a, str>>,
+    /// The URL of the index of the registry where this dependency is from.
+    /// `None` if it is from the same index.
+    pub registry: Option<Cow<'a, str>>,
+    /// The original name if the dependency is renamed.
+    pub package: Option<Cow<'a, str>>,
+    /// Whether or not this is a public dependency. Unstable. See [RFC 1977].
+    ///
+    /// [RFC 1977]: https://rust-lang.github.io/rfcs/1977-public-private-dependencies.html
+    pub public: Option<bool>,
+    /// The artifact kinds requested by an artifact dependency, i.e.,
+    /// `{ artifact = ["bin", "cdylib"] }`. Requires schema version `3`.
+    pub artifact: Option<Vec<Cow<'a, str>>>,
+    /// The target platform an artifact dependency is built for, i.e.,
+    /// `{ target = "…" }`. Requires schema version `3`.
+    pub bindep_target: Option<Cow<'a, str>>,
+    /// Whether the library of an artifact dependency is also used, i.e.,
+    /// `{ lib = true }`. Requires schema version `3`.
+    #[serde(default)]
+    pub lib: bool,
+}
+
+impl RegistryDependency<'_> {
+    /// Whether this is an artifact dependency, which needs schema version `3`.
+    pub fn is_artifact(&self) -> bool {
+        self.artifact.is_some()
+    }
+}
+
+fn default_true() -> bool {
+    true
+}
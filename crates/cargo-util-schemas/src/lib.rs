@@ -9,6 +9,7 @@
 //! > ecosystem. This crate follows semver compatibility for its APIs.
 
 pub mod core;
+pub mod index;
 pub mod manifest;
 #[cfg(feature = "unstable-schema")]
 pub mod schema;
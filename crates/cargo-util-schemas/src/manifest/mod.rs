@@ -927,10 +927,26 @@ pub struct TomlProfile {
     pub strip: Option<StringOrBool>,
     // Note that `rustflags` is used for the cargo-feature `profile_rustflags`
     pub rustflags: Option<Vec<String>>,
-    // These two fields must be last because they are sub-tables, and TOML
+    /// Unstable feature `build-std`.
+    pub build_std: Option<Vec<String>>,
+    /// Unstable feature `build-std`.
+    pub build_std_features: Option<Vec<String>>,
+    /// Unstable feature `-Zcoverage`.
+    pub coverage: Option<bool>,
+    // These fields must be last because they are sub-tables, and TOML
     // requires all non-tables to be listed first.
     pub package: Option<BTreeMap<ProfilePackageSpec, TomlProfile>>,
     pub build_override: Option<Box<TomlProfile>>,
+    /// Unstable feature `profile-target-overrides`.
+    pub lib: Option<Box<TomlProfile>>,
+    /// Unstable feature `profile-target-overrides`.
+    pub bin: Option<Box<TomlProfile>>,
+    /// Unstable feature `profile-target-overrides`.
+    pub example: Option<Box<TomlProfile>>,
+    /// Unstable feature `profile-target-overrides`.
+    pub test: Option<Box<TomlProfile>>,
+    /// Unstable feature `profile-target-overrides`.
+    pub bench: Option<Box<TomlProfile>>,
     /// Unstable feature `-Ztrim-paths`.
     pub trim_paths: Option<TomlTrimPaths>,
 }
@@ -986,6 +1002,18 @@ impl TomlProfile {
             self.rustflags = Some(v.clone());
         }
 
+        if let Some(v) = &profile.build_std {
+            self.build_std = Some(v.clone());
+        }
+
+        if let Some(v) = &profile.build_std_features {
+            self.build_std_features = Some(v.clone());
+        }
+
+        if let Some(v) = profile.coverage {
+            self.coverage = Some(v);
+        }
+
         if let Some(other_package) = &profile.package {
             match &mut self.package {
                 Some(self_package) => {
@@ -1009,6 +1037,21 @@ impl TomlProfile {
             }
         }
 
+        for (self_kind, other_kind) in [
+            (&mut self.lib, &profile.lib),
+            (&mut self.bin, &profile.bin),
+            (&mut self.example, &profile.example),
+            (&mut self.test, &profile.test),
+            (&mut self.bench, &profile.bench),
+        ] {
+            if let Some(other_kind) = other_kind {
+                match self_kind {
+                    Some(self_kind) => self_kind.merge(other_kind),
+                    None => *self_kind = Some(other_kind.clone()),
+                }
+            }
+        }
+
         if let Some(v) = &profile.inherits {
             self.inherits = Some(v.clone());
         }
@@ -1025,11 +1068,27 @@ impl TomlProfile {
             self.trim_paths = Some(v.clone())
         }
     }
+
+    /// Returns the overrides for kinds of targets, like `[profile.dev.bin]`,
+    /// with the name of their kind.
+    pub fn target_kind_overrides(&self) -> impl Iterator<Item = (&'static str, &TomlProfile)> {
+        [
+            ("lib", &self.lib),
+            ("bin", &self.bin),
+            ("example", &self.example),
+            ("test", &self.test),
+            ("bench", &self.bench),
+        ]
+        .into_iter()
+        .filter_map(|(kind, profile)| Some((kind, profile.as_deref()?)))
+    }
 }
 
 #[derive(Clone, Debug, PartialEq, Eq, Ord, PartialOrd, Hash)]
 pub enum ProfilePackageSpec {
     Spec(PackageIdSpec),
+    /// A glob of package names, like `aws-*`.
+    Glob(String),
     All,
 }
 
@@ -1037,6 +1096,7 @@ impl fmt::Display for ProfilePackageSpec {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             ProfilePackageSpec::Spec(spec) => spec.fmt(f),
+            ProfilePackageSpec::Glob(glob) => f.write_str(glob),
             ProfilePackageSpec::All => f.write_str("*"),
         }
     }
@@ -1059,6 +1119,8 @@ impl<'de> de::Deserialize<'de> for ProfilePackageSpec {
         let string = String::deserialize(d)?;
         if string == "*" {
             Ok(ProfilePackageSpec::All)
+        } else if string.contains(['*', '?', '[']) {
+            Ok(ProfilePackageSpec::Glob(string))
         } else {
             PackageIdSpec::parse(&string)
                 .map_err(de::Error::custom)
@@ -1496,6 +1558,8 @@ impl<T: AsRef<str>> PathBaseName<T> {
 #[serde(rename_all = "kebab-case")]
 #[cfg_attr(feature = "unstable-schema", derive(schemars::JsonSchema))]
 pub struct TomlPlatform {
+    /// Unstable feature `build-std`.
+    pub build_std: Option<Vec<String>>,
     pub dependencies: Option<BTreeMap<PackageName, InheritableDependency>>,
     pub build_dependencies: Option<BTreeMap<PackageName, InheritableDependency>>,
     #[serde(rename = "build_dependencies")]
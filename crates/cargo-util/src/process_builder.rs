@@ -335,6 +335,20 @@ impl ProcessBuilder {
         on_stderr_line: &mut dyn FnMut(&str) -> Result<()>,
         capture_output: bool,
     ) -> Result<Output> {
+        self.exec_with_streaming_peak_rss(on_stdout_line, on_stderr_line, capture_output)
+            .map(|(output, _)| output)
+    }
+
+    /// Like [`ProcessBuilder::exec_with_streaming`], but also returns the
+    /// peak resident set size of the process in bytes.
+    ///
+    /// The peak is `None` on platforms where it can't be queried.
+    pub fn exec_with_streaming_peak_rss(
+        &self,
+        on_stdout_line: &mut dyn FnMut(&str) -> Result<()>,
+        on_stderr_line: &mut dyn FnMut(&str) -> Result<()>,
+        capture_output: bool,
+    ) -> Result<(Output, Option<u64>)> {
         let mut stdout = Vec::new();
         let mut stderr = Vec::new();
 
@@ -354,7 +368,7 @@ impl ProcessBuilder {
             Ok((piped(&mut cmd, false).spawn()?, Some(argfile)))
         };
 
-        let status = (|| {
+        let (status, peak_rss) = (|| {
             let cmd = self.build_command();
             let (mut child, argfile) = spawn(cmd)?;
             let out = child.stdout.take().unwrap();
@@ -402,7 +416,7 @@ impl ProcessBuilder {
                 data.drain(..idx);
                 *pos = 0;
             })?;
-            let status = child.wait();
+            let status = imp::wait_with_peak_rss(&mut child);
             if let Some(argfile) = argfile {
                 close_tempfile_and_log_error(argfile);
             }
@@ -433,7 +447,7 @@ impl ProcessBuilder {
             }
         }
 
-        Ok(output)
+        Ok((output, peak_rss))
     }
 
     /// Builds the command with an `@<path>` argfile that contains all the
@@ -568,6 +582,7 @@ mod imp {
     use anyhow::Result;
     use std::io;
     use std::os::unix::process::CommandExt;
+    use std::process::{Child, ExitStatus};
 
     pub fn exec_replace(process_builder: &ProcessBuilder) -> Result<()> {
         let mut error;
@@ -599,6 +614,42 @@ mod imp {
     pub fn command_line_too_big(err: &io::Error) -> bool {
         err.raw_os_error() == Some(libc::E2BIG)
     }
+
+    #[cfg(any(target_os = "linux", target_os = "macos", target_os = "freebsd"))]
+    pub fn wait_with_peak_rss(child: &mut Child) -> io::Result<(ExitStatus, Option<u64>)> {
+        use std::os::unix::process::ExitStatusExt;
+
+        let pid = child.id() as libc::pid_t;
+        let mut status = 0;
+        // SAFETY: `rusage` is a plain C struct for which all zeroes is valid.
+        let mut usage: libc::rusage = unsafe { std::mem::zeroed() };
+        loop {
+            // SAFETY: `pid` is our own child which hasn't been reaped yet, and
+            // both out-pointers are valid for writes. `child` is never waited
+            // on again after this.
+            let r = unsafe { libc::wait4(pid, &mut status, 0, &mut usage) };
+            if r == pid {
+                break;
+            }
+            let err = io::Error::last_os_error();
+            if err.kind() != io::ErrorKind::Interrupted {
+                return Err(err);
+            }
+        }
+        // `ru_maxrss` is in bytes on macOS, and in kilobytes elsewhere.
+        let max_rss = usage.ru_maxrss as u64;
+        let peak_rss = if cfg!(target_os = "macos") {
+            max_rss
+        } else {
+            max_rss * 1024
+        };
+        Ok((ExitStatus::from_raw(status), Some(peak_rss)))
+    }
+
+    #[cfg(not(any(target_os = "linux", target_os = "macos", target_os = "freebsd")))]
+    pub fn wait_with_peak_rss(child: &mut Child) -> io::Result<(ExitStatus, Option<u64>)> {
+        child.wait().map(|status| (status, None))
+    }
 }
 
 #[cfg(windows)]
@@ -606,6 +657,7 @@ mod imp {
     use super::{ProcessBuilder, ProcessError};
     use anyhow::Result;
     use std::io;
+    use std::process::{Child, ExitStatus};
     use windows_sys::Win32::Foundation::{BOOL, FALSE, TRUE};
     use windows_sys::Win32::System::Console::SetConsoleCtrlHandler;
 
@@ -629,6 +681,24 @@ mod imp {
         use windows_sys::Win32::Foundation::ERROR_FILENAME_EXCED_RANGE;
         err.raw_os_error() == Some(ERROR_FILENAME_EXCED_RANGE as i32)
     }
+
+    pub fn wait_with_peak_rss(child: &mut Child) -> io::Result<(ExitStatus, Option<u64>)> {
+        use std::os::windows::io::AsRawHandle;
+        use windows_sys::Win32::System::ProcessStatus::{
+            GetProcessMemoryInfo, PROCESS_MEMORY_COUNTERS,
+        };
+
+        let status = child.wait()?;
+        let cb = std::mem::size_of::<PROCESS_MEMORY_COUNTERS>() as u32;
+        // SAFETY: `PROCESS_MEMORY_COUNTERS` is a plain C struct for which all
+        // zeroes is valid.
+        let mut counters: PROCESS_MEMORY_COUNTERS = unsafe { std::mem::zeroed() };
+        counters.cb = cb;
+        // SAFETY: the process handle stays open until `child` is dropped.
+        let ok = unsafe { GetProcessMemoryInfo(child.as_raw_handle(), &mut counters, cb) };
+        let peak_rss = (ok != FALSE).then(|| counters.PeakWorkingSetSize as u64);
+        Ok((status, peak_rss))
+    }
 }
 
 #[cfg(test)]
@@ -63,6 +63,9 @@ pub struct NewCrate {
     pub badges: BTreeMap<String, BTreeMap<String, String>>,
     pub links: Option<String>,
     pub rust_version: Option<String>,
+    /// Signature of the crate file, see `-Zpackage-signing`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sig: Option<String>,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -265,7 +268,46 @@ impl Registry {
         Ok(serde_json::from_str::<Users>(&body)?.users)
     }
 
-    pub fn publish(&mut self, krate: &NewCrate, mut tarball: &File) -> Result<Warnings> {
+    pub fn publish(&mut self, krate: &NewCrate, tarball: &File) -> Result<Warnings> {
+        self.upload_crate("/crates/new", krate, tarball)
+    }
+
+    /// Uploads a crate to the staging area identified by `session`.
+    ///
+    /// Staged crates are not visible until [`Registry::commit_staged`] is
+    /// called for the same session, at which point all of them are published
+    /// together.
+    pub fn stage(&mut self, session: &str, krate: &NewCrate, tarball: &File) -> Result<Warnings> {
+        let session = percent_encode(session.as_bytes(), NON_ALPHANUMERIC);
+        self.upload_crate(
+            &format!("/crates/staged/new?session={}", session),
+            krate,
+            tarball,
+        )
+    }
+
+    /// Publishes every crate uploaded to the staging area `session`.
+    pub fn commit_staged(&mut self, session: &str) -> Result<()> {
+        let session = percent_encode(session.as_bytes(), NON_ALPHANUMERIC);
+        let body = self.put(&format!("/crates/staged/commit?session={}", session), &[])?;
+        assert!(serde_json::from_str::<R>(&body)?.ok);
+        Ok(())
+    }
+
+    /// Discards every crate uploaded to the staging area `session`.
+    pub fn discard_staged(&mut self, session: &str) -> Result<()> {
+        let session = percent_encode(session.as_bytes(), NON_ALPHANUMERIC);
+        let body = self.delete(&format!("/crates/staged?session={}", session), None)?;
+        assert!(serde_json::from_str::<R>(&body)?.ok);
+        Ok(())
+    }
+
+    fn upload_crate(
+        &mut self,
+        path: &str,
+        krate: &NewCrate,
+        mut tarball: &File,
+    ) -> Result<Warnings> {
         let json = serde_json::to_string(krate)?;
         // Prepare the body. The format of the upload request is:
         //
@@ -291,7 +333,7 @@ impl Registry {
         let size = tarball_len as usize + header.len();
         let mut body = Cursor::new(header).chain(tarball);
 
-        let url = format!("{}/api/v1/crates/new", self.host);
+        let url = format!("{}/api/v1{}", self.host, path);
 
         self.handle.put(true)?;
         self.handle.url(&url)?;
@@ -407,7 +449,12 @@ impl Registry {
                 self.handle(&mut |buf| body.read(buf).unwrap_or(0))
                     .map_err(|e| e.into())
             }
-            None => self.handle(&mut |_| 0).map_err(|e| e.into()),
+            None => {
+                // The handle may still be set up for the body of a previous
+                // request, such as a staged upload before discarding it.
+                self.handle.upload(false)?;
+                self.handle(&mut |_| 0).map_err(|e| e.into())
+            }
         }
     }
 
@@ -173,6 +173,18 @@ pub enum Operation<'a> {
         /// The name of the crate
         name: &'a str,
     },
+    /// The user is attempting to sign a crate file before publishing it.
+    ///
+    /// The returned token is stored by the registry as the signature of the
+    /// crate, so it must not be cached or reused for other operations.
+    Sign {
+        /// The name of the crate
+        name: &'a str,
+        /// The version of the crate
+        vers: &'a str,
+        /// The checksum of the crate file being signed
+        cksum: &'a str,
+    },
     #[serde(other)]
     Unknown,
 }
@@ -22,6 +22,7 @@ pub fn cli() -> Command {
             "no-fail-fast",
             "Run all benchmarks regardless of failure",
         ))
+        .arg_test_report()
         .arg_message_format()
         .arg_silent_suggestion()
         .arg_package_spec(
@@ -69,6 +70,10 @@ pub fn exec(gctx: &mut GlobalContext, args: &ArgMatches) -> CliResult {
     let ops = TestOptions {
         no_run: args.flag("no-run"),
         no_fail_fast: args.flag("no-fail-fast"),
+        partition: None,
+        partition_durations: None,
+        report: args.test_report(gctx)?,
+        retries: 0,
         compile_opts,
     };
 
@@ -15,6 +15,7 @@ pub fn cli() -> Command {
             "Build all packages in the workspace",
             "Exclude packages from the build",
         )
+        .arg_changed_since()
         .arg_targets_all(
             "Build only this package's library",
             "Build only the specified binary",
@@ -15,6 +15,7 @@ pub fn cli() -> Command {
             "Check all packages in the workspace",
             "Exclude packages from the check",
         )
+        .arg_changed_since()
         .arg_targets_all(
             "Check only this package's library",
             "Check only the specified binary",
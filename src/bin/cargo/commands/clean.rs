@@ -3,6 +3,7 @@ use crate::util::cache_lock::CacheLockMode;
 use cargo::core::gc::Gc;
 use cargo::core::gc::{parse_human_size, parse_time_span, GcOpts};
 use cargo::core::global_cache_tracker::GlobalCacheTracker;
+use cargo::core::target_cache_tracker;
 use cargo::ops::CleanContext;
 use cargo::ops::{self, CleanOptions};
 use cargo::util::print_available_packages;
@@ -119,7 +120,26 @@ pub fn cli() -> Command {
                     )
                     .value_name("SIZE")
                     .value_parser(parse_human_size),
-                ),
+                )
+                .arg(
+                    opt(
+                        "max-unit-age",
+                        "Deletes build artifacts in the target directory that \
+                        have not been used since the given age (unstable)",
+                    )
+                    .value_name("DURATION")
+                    .value_parser(parse_time_span),
+                )
+                .arg(
+                    opt(
+                        "max-target-size",
+                        "Deletes build artifacts until the target directory is \
+                        under the given size (unstable)",
+                    )
+                    .value_name("SIZE")
+                    .value_parser(parse_human_size),
+                )
+                .arg_manifest_path(),
         )
         .after_help(color_print::cstr!(
             "Run `<cyan,bold>cargo help clean</>` for more detailed information.\n"
@@ -177,15 +197,23 @@ fn gc(gctx: &GlobalContext, args: &ArgMatches) -> CliResult {
         max_crate_size: size_opt("max-crate-size"),
         max_git_size: size_opt("max-git-size"),
         max_download_size: size_opt("max-download-size"),
+        max_unit_age: duration_opt("max-unit-age"),
+        max_target_size: size_opt("max-target-size"),
     };
     if let Some(age) = duration_opt("max-download-age") {
         gc_opts.set_max_download_age(age);
     }
     // If the user sets any options, then only perform the options requested.
     // If no options are set, do the default behavior.
-    if !gc_opts.is_download_cache_opt_set() {
+    if !gc_opts.is_download_cache_opt_set() && !gc_opts.is_target_opt_set() {
         gc_opts.update_for_auto_gc(gctx)?;
     }
+    // Only cleaning the target directory needs a workspace.
+    let ws = if gc_opts.is_target_opt_set() {
+        Some(args.workspace(gctx)?)
+    } else {
+        None
+    };
 
     let _lock = gctx.acquire_package_cache_lock(CacheLockMode::MutateExclusive)?;
     let mut cache_track = GlobalCacheTracker::new(&gctx)?;
@@ -193,6 +221,9 @@ fn gc(gctx: &GlobalContext, args: &ArgMatches) -> CliResult {
     let mut clean_ctx = CleanContext::new(gctx);
     clean_ctx.dry_run = args.dry_run();
     gc.gc(&mut clean_ctx, &gc_opts)?;
+    if let Some(ws) = &ws {
+        target_cache_tracker::clean(&mut clean_ctx, &ws.target_dir(), &gc_opts)?;
+    }
     clean_ctx.display_summary()?;
     Ok(())
 }
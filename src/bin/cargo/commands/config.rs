@@ -26,7 +26,22 @@ pub fn cli() -> Command {
                     opt("merged", "Whether or not to merge config values")
                         .value_parser(["yes", "no"])
                         .default_value("yes"),
-                ),
+                )
+                .arg(flag(
+                    "effective",
+                    "Display a profile merged with the profiles of the workspace",
+                ))
+                .arg(
+                    opt("package", "Display the profile of this package")
+                        .value_name("SPEC")
+                        .requires("effective"),
+                )
+                .arg(
+                    opt("target-kind", "Display the profile of this kind of target")
+                        .value_parser(["lib", "bin", "example", "test", "bench"])
+                        .requires("effective"),
+                )
+                .arg_manifest_path(),
         )
 }
 
@@ -46,7 +61,16 @@ pub fn exec(gctx: &mut GlobalContext, args: &ArgMatches) -> CliResult {
                 show_origin: args.flag("show-origin"),
                 merged: args.get_one::<String>("merged").map(String::as_str) == Some("yes"),
             };
-            cargo_config::get(gctx, &opts)?;
+            if args.flag("effective") {
+                let ws = args.workspace(gctx)?;
+                let effective = cargo_config::EffectiveOptions {
+                    package: args.get_one::<String>("package").map(String::as_str),
+                    target_kind: args.get_one::<String>("target-kind").map(String::as_str),
+                };
+                cargo_config::get_effective(&ws, &opts, &effective)?;
+            } else {
+                cargo_config::get(gctx, &opts)?;
+            }
         }
         Some((cmd, _)) => {
             unreachable!("unexpected command {}", cmd)
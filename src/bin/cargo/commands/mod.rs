@@ -25,6 +25,7 @@ pub fn builtin() -> Vec<Command> {
         owner::cli(),
         package::cli(),
         pkgid::cli(),
+        profile::cli(),
         publish::cli(),
         read_manifest::cli(),
         remove::cli(),
@@ -71,6 +72,7 @@ pub fn builtin_exec(cmd: &str) -> Option<Exec> {
         "owner" => owner::exec,
         "package" => package::exec,
         "pkgid" => pkgid::exec,
+        "profile" => profile::exec,
         "publish" => publish::exec,
         "read-manifest" => read_manifest::exec,
         "remove" => remove::exec,
@@ -115,6 +117,7 @@ pub mod new;
 pub mod owner;
 pub mod package;
 pub mod pkgid;
+pub mod profile;
 pub mod publish;
 pub mod read_manifest;
 pub mod remove;
@@ -0,0 +1,75 @@
+use crate::command_prelude::*;
+use cargo::ops::cargo_profile;
+
+pub fn cli() -> Command {
+    subcommand("profile")
+        .about("Inspect the profiles units are built with")
+        .subcommand_required(true)
+        .arg_required_else_help(true)
+        .subcommand(
+            subcommand("show")
+                .about("Print the profile of every unit the build would compile")
+                .arg(
+                    opt("format", "Display format")
+                        .value_parser(cargo_profile::ProfileFormat::POSSIBLE_VALUES)
+                        .default_value("text"),
+                )
+                .arg_silent_suggestion()
+                .arg_package_spec(
+                    "Package to show (see `cargo help pkgid`)",
+                    "Show all packages in the workspace",
+                    "Exclude packages",
+                )
+                .arg_targets_all(
+                    "Show only this package's library",
+                    "Show only the specified binary",
+                    "Show all binaries",
+                    "Show only the specified example",
+                    "Show all examples",
+                    "Show only the specified test target",
+                    "Show all targets that have `test = true` set",
+                    "Show only the specified bench target",
+                    "Show all targets that have `bench = true` set",
+                    "Show all targets",
+                )
+                .arg_features()
+                .arg_release("Show the release profile")
+                .arg_profile("Show the specified profile")
+                .arg_target_triple("Show for the target triple")
+                .arg_manifest_path()
+                .arg_lockfile_path()
+                .arg_ignore_rust_version(),
+        )
+}
+
+pub fn exec(gctx: &mut GlobalContext, args: &ArgMatches) -> CliResult {
+    gctx.cli_unstable().fail_if_stable_command(
+        gctx,
+        "profile",
+        None,
+        "unstable-options",
+        gctx.cli_unstable().unstable_options,
+    )?;
+    match args.subcommand() {
+        Some(("show", args)) => {
+            let ws = args.workspace(gctx)?;
+            let opts = cargo_profile::ShowOptions {
+                compile_opts: args.compile_options(
+                    gctx,
+                    CompileMode::Build,
+                    Some(&ws),
+                    ProfileChecking::Custom,
+                )?,
+                format: args.get_one::<String>("format").unwrap().parse()?,
+            };
+            cargo_profile::show(&ws, &opts)?;
+        }
+        Some((cmd, _)) => {
+            unreachable!("unexpected command {}", cmd)
+        }
+        None => {
+            unreachable!("unexpected command")
+        }
+    }
+    Ok(())
+}
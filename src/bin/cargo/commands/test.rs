@@ -20,6 +20,37 @@ pub fn cli() -> Command {
         )
         .arg(flag("no-run", "Compile, but don't run tests"))
         .arg(flag("no-fail-fast", "Run all tests regardless of failure"))
+        .arg(
+            opt(
+                "partition",
+                "Only run the tests of one partition, e.g. `hash:1/4` or `count:1/4` (unstable)",
+            )
+            .value_name("STRATEGY:M/N"),
+        )
+        .arg(
+            opt(
+                "partition-durations",
+                "Balance `count` partitions by the test durations in this unit history file (unstable)",
+            )
+            .value_name("PATH")
+            .requires("partition"),
+        )
+        .arg(
+            opt(
+                "retries",
+                "Run failed tests again up to N times, reporting those that pass as flaky (unstable)",
+            )
+            .value_name("N")
+            .value_parser(clap::value_parser!(u32)),
+        )
+        .arg(
+            flag(
+                "coverage",
+                "Collect the coverage of the tests with the `coverage` profile (unstable)",
+            )
+            .conflicts_with_all(["release", "profile"]),
+        )
+        .arg_test_report()
         .arg_future_incompat_report()
         .arg_message_format()
         .arg(
@@ -34,6 +65,7 @@ pub fn cli() -> Command {
             "Test all packages in the workspace",
             "Exclude packages from the test",
         )
+        .arg_changed_since()
         .arg_targets_all(
             "Test only this package's library",
             "Test only the specified binary",
@@ -76,6 +108,15 @@ pub fn exec(gctx: &mut GlobalContext, args: &ArgMatches) -> CliResult {
 
     compile_opts.build_config.requested_profile =
         args.get_profile_name("test", ProfileChecking::Custom)?;
+    if args.flag("coverage") {
+        gctx.cli_unstable().fail_if_stable_opt_custom_z(
+            "--coverage",
+            None,
+            "coverage",
+            gctx.cli_unstable().coverage,
+        )?;
+        compile_opts.build_config.requested_profile = "coverage".into();
+    }
 
     // `TESTNAME` is actually an argument of the test binary, but it's
     // important, so we explicitly mention it and reconfigure.
@@ -105,9 +146,46 @@ pub fn exec(gctx: &mut GlobalContext, args: &ArgMatches) -> CliResult {
         compile_opts.filter = ops::CompileFilter::all_test_targets();
     }
 
+    let partition = args
+        .get_one::<String>("partition")
+        .map(|partition| partition.parse::<ops::Partition>())
+        .transpose()?;
+    if partition.is_some() {
+        gctx.cli_unstable()
+            .fail_if_stable_opt("--partition", None)?;
+    }
+
+    // Tests run as soon as they are built, but a `count` partition can only
+    // be picked once all tests are known.
+    if partition.is_some_and(|p| p.strategy == ops::PartitionStrategy::Count)
+        && gctx.cli_unstable().pipelined_tests
+        && !no_run
+    {
+        return Err(anyhow::format_err!(
+            "`--partition count:M/N` can't be used with `-Zpipelined-tests`\n\
+             help: use `--partition hash:M/N` to run tests as soon as they are built"
+        )
+        .into());
+    }
+
+    let partition_durations = args.value_of_path("partition-durations", gctx);
+    if partition_durations.is_some() {
+        gctx.cli_unstable()
+            .fail_if_stable_opt("--partition-durations", None)?;
+    }
+
+    let retries = args.get_one::<u32>("retries").copied();
+    if retries.is_some() {
+        gctx.cli_unstable().fail_if_stable_opt("--retries", None)?;
+    }
+
     let ops = ops::TestOptions {
         no_run,
         no_fail_fast: args.flag("no-fail-fast"),
+        partition,
+        partition_durations,
+        report: args.test_report(gctx)?,
+        retries: retries.unwrap_or(0),
         compile_opts,
     };
 
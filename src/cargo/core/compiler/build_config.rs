@@ -1,4 +1,4 @@
-use crate::core::compiler::CompileKind;
+use crate::core::compiler::{Compilation, CompileKind, UnitOutput};
 use crate::util::context::JobsConfig;
 use crate::util::interning::InternedString;
 use crate::util::{CargoResult, GlobalContext, RustfixDiagnosticServer};
@@ -48,6 +48,56 @@ pub struct BuildConfig {
     pub future_incompat_report: bool,
     /// Which kinds of build timings to output (empty if none).
     pub timing_outputs: Vec<TimingOutput>,
+    /// Runs test executables as soon as they are built, while the rest of
+    /// the build continues. Set by `cargo test -Zpipelined-tests`.
+    pub test_runner: Option<Rc<RefCell<dyn TestRunner>>>,
+}
+
+/// Runs test executables during the build.
+///
+/// Test executables are queued as soon as they have finished building. The
+/// job queue runs them one at a time on a separate thread, asking
+/// [`TestRunner::start`] for the process of each, and reporting its result
+/// back through [`TestRunner::finished`].
+pub trait TestRunner: std::fmt::Debug {
+    /// Returns the process that runs a test executable, or `None` if it
+    /// shouldn't run.
+    fn start(
+        &mut self,
+        gctx: &GlobalContext,
+        test: &UnitOutput,
+        compilation: &Compilation<'_>,
+    ) -> CargoResult<Option<ProcessBuilder>>;
+
+    /// Whether the output of the test processes is passed to
+    /// [`TestRunner::stdout`] and [`TestRunner::stderr`] line by line,
+    /// instead of going straight to the terminal.
+    fn captures_output(&self) -> bool;
+
+    /// Handles a line of the stdout of the running test process.
+    fn stdout(&mut self, gctx: &GlobalContext, line: &str) -> CargoResult<()>;
+
+    /// Handles a line of the stderr of the running test process.
+    fn stderr(&mut self, gctx: &GlobalContext, line: &str) -> CargoResult<()>;
+
+    /// Called once the running test process has exited.
+    fn finished(
+        &mut self,
+        gctx: &GlobalContext,
+        compilation: &Compilation<'_>,
+        result: CargoResult<()>,
+    ) -> CargoResult<AfterTest>;
+}
+
+/// What the job queue does once a test process has exited.
+pub enum AfterTest {
+    /// Runs another process for the same test executable, like a retry of
+    /// its failed tests.
+    Rerun(ProcessBuilder),
+    /// Moves on to the next test executable.
+    Continue,
+    /// Stops the build, as a test failed. The failure has been reported.
+    Stop,
 }
 
 fn default_parallelism() -> CargoResult<u32> {
@@ -115,6 +165,7 @@ impl BuildConfig {
             export_dir: None,
             future_incompat_report: false,
             timing_outputs: Vec::new(),
+            test_runner: None,
         })
     }
 
@@ -231,11 +231,17 @@ impl<'a, 'gctx: 'a> CompilationFiles<'a, 'gctx> {
     /// taken in those cases!
     fn pkg_dir(&self, unit: &Unit) -> String {
         let name = unit.pkg.package_id().name();
+        format!("{}-{}", name, self.unit_dir_hash(unit))
+    }
+
+    /// The hash in the names of the files of a unit, like the `HASH` of the
+    /// `NAME-HASH` directory from `pkg_dir`.
+    pub fn unit_dir_hash(&self, unit: &Unit) -> String {
         let meta = self.metas[unit];
         if let Some(c_extra_filename) = meta.c_extra_filename() {
-            format!("{}-{}", name, c_extra_filename)
+            c_extra_filename.to_string()
         } else {
-            format!("{}-{}", name, self.target_short_hash(unit))
+            self.target_short_hash(unit)
         }
     }
 
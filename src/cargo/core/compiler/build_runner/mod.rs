@@ -6,6 +6,8 @@ use std::sync::{Arc, Mutex};
 
 use crate::core::compiler::compilation::{self, UnitOutput};
 use crate::core::compiler::{self, artifact, Unit};
+use crate::core::global_cache_tracker;
+use crate::core::target_cache_tracker::TargetCacheTracker;
 use crate::core::PackageId;
 use crate::util::cache_lock::CacheLockMode;
 use crate::util::errors::CargoResult;
@@ -174,6 +176,9 @@ impl<'a, 'gctx> BuildRunner<'a, 'gctx> {
         custom_build::build_map(&mut self)?;
         self.check_collisions()?;
         self.compute_metadata_for_doc_units();
+        if !build_plan {
+            self.record_units_used();
+        }
 
         // We need to make sure that if there were any previous docs
         // already compiled, they were compiled with the same Rustc version that we're currently
@@ -210,31 +215,7 @@ impl<'a, 'gctx> BuildRunner<'a, 'gctx> {
             plan.output_plan(self.bcx.gctx);
         }
 
-        // Add `OUT_DIR` to env vars if unit has a build script.
-        let units_with_build_script = &self
-            .bcx
-            .roots
-            .iter()
-            .filter(|unit| self.build_scripts.contains_key(unit))
-            .dedup_by(|x, y| x.pkg.package_id() == y.pkg.package_id())
-            .collect::<Vec<_>>();
-        for unit in units_with_build_script {
-            for dep in &self.bcx.unit_graph[unit] {
-                if dep.unit.mode.is_run_custom_build() {
-                    let out_dir = self
-                        .files()
-                        .build_script_out_dir(&dep.unit)
-                        .display()
-                        .to_string();
-                    let script_meta = self.get_run_build_script_metadata(&dep.unit);
-                    self.compilation
-                        .extra_env
-                        .entry(script_meta)
-                        .or_insert_with(Vec::new)
-                        .push(("OUT_DIR".to_string(), out_dir));
-                }
-            }
-        }
+        self.collect_build_script_env();
 
         // Collect the result of the build into `self.compilation`.
         for unit in &self.bcx.roots {
@@ -293,6 +274,45 @@ impl<'a, 'gctx> BuildRunner<'a, 'gctx> {
             super::output_depinfo(&mut self, unit)?;
         }
 
+        Ok(self.compilation)
+    }
+
+    /// Collects the environment set by build scripts for running the outputs
+    /// of the build into `self.compilation`.
+    ///
+    /// Only build scripts that already ran are included. This is called again
+    /// whenever test executables are run before the build has finished with
+    /// `-Zpipelined-tests`, so it starts over from scratch each time.
+    pub fn collect_build_script_env(&mut self) {
+        self.compilation.extra_env.clear();
+        self.compilation.native_dirs.clear();
+
+        // Add `OUT_DIR` to env vars if unit has a build script.
+        let units_with_build_script = &self
+            .bcx
+            .roots
+            .iter()
+            .filter(|unit| self.build_scripts.contains_key(unit))
+            .dedup_by(|x, y| x.pkg.package_id() == y.pkg.package_id())
+            .collect::<Vec<_>>();
+        for unit in units_with_build_script {
+            for dep in &self.bcx.unit_graph[unit] {
+                if dep.unit.mode.is_run_custom_build() {
+                    let out_dir = self
+                        .files()
+                        .build_script_out_dir(&dep.unit)
+                        .display()
+                        .to_string();
+                    let script_meta = self.get_run_build_script_metadata(&dep.unit);
+                    self.compilation
+                        .extra_env
+                        .entry(script_meta)
+                        .or_insert_with(Vec::new)
+                        .push(("OUT_DIR".to_string(), out_dir));
+                }
+            }
+        }
+
         for (script_meta, output) in self.build_script_outputs.lock().unwrap().iter() {
             self.compilation
                 .extra_env
@@ -304,7 +324,6 @@ impl<'a, 'gctx> BuildRunner<'a, 'gctx> {
                 self.compilation.native_dirs.insert(dir.clone());
             }
         }
-        Ok(self.compilation)
     }
 
     fn collect_tests_and_executables(&mut self, unit: &Unit) -> CargoResult<()> {
@@ -401,6 +420,40 @@ impl<'a, 'gctx> BuildRunner<'a, 'gctx> {
         Ok(())
     }
 
+    /// Records the last use of every unit of this build in its build
+    /// directory, so `cargo clean gc` knows which artifacts are stale.
+    ///
+    /// See [`crate::core::target_cache_tracker`] for more.
+    fn record_units_used(&self) {
+        let gctx = self.bcx.gctx;
+        if !gctx.cli_unstable().gc {
+            return;
+        }
+        let files = self.files();
+        let mut hashes: HashMap<&Path, Vec<String>> = HashMap::new();
+        for unit in self.bcx.unit_graph.keys() {
+            hashes
+                .entry(files.layout(unit.kind).dest())
+                .or_default()
+                .push(files.unit_dir_hash(unit));
+        }
+        for (dest, hashes) in hashes {
+            let result = TargetCacheTracker::new(dest)
+                .and_then(|mut tracker| tracker.mark_units_used(&hashes));
+            if let Err(e) = result {
+                if global_cache_tracker::is_silent_error(&e) && !gctx.extra_verbose() {
+                    tracing::warn!(target: "gc", "failed to record unit last use: {e:?}");
+                } else {
+                    crate::display_warning_with_error(
+                        "failed to record unit last use",
+                        &e,
+                        &mut gctx.shell(),
+                    );
+                }
+            }
+        }
+    }
+
     pub fn files(&self) -> &CompilationFiles<'a, 'gctx> {
         self.files.as_ref().unwrap()
     }
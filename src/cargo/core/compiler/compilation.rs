@@ -1,6 +1,6 @@
 //! Type definitions for the result of a compilation.
 
-use std::collections::{BTreeSet, HashMap};
+use std::collections::{BTreeSet, HashMap, HashSet};
 use std::ffi::{OsStr, OsString};
 use std::path::PathBuf;
 
@@ -11,7 +11,8 @@ use crate::core::compiler::apply_env_config;
 use crate::core::compiler::BuildContext;
 use crate::core::compiler::{CompileKind, Unit, UnitHash};
 use crate::core::Package;
-use crate::util::{context, CargoResult, GlobalContext};
+use crate::util::context::{self, RunnerKind, TargetRunner};
+use crate::util::{CargoResult, GlobalContext};
 
 /// Represents the kind of process we are creating.
 #[derive(Debug)]
@@ -52,7 +53,7 @@ pub struct Doctest {
 }
 
 /// Information about the output of a unit.
-#[derive(Ord, PartialOrd, Eq, PartialEq)]
+#[derive(Clone, Ord, PartialOrd, Eq, PartialEq)]
 pub struct UnitOutput {
     /// The unit that generated this output.
     pub unit: Unit,
@@ -96,6 +97,9 @@ pub struct Compilation<'gctx> {
     /// The path to libstd for each target
     sysroot_target_libdir: HashMap<CompileKind, PathBuf>,
 
+    /// The kinds for which the standard library is built from source.
+    build_std_kinds: HashSet<CompileKind>,
+
     /// Extra environment variables that were passed to compilations and should
     /// be passed to future invocations of programs.
     ///
@@ -119,7 +123,8 @@ pub struct Compilation<'gctx> {
     /// `rustc_workspace_wrapper_process`
     primary_rustc_process: Option<ProcessBuilder>,
 
-    target_runners: HashMap<CompileKind, Option<(PathBuf, Vec<String>)>>,
+    /// The runner for each host or target, and kind of executable.
+    target_runners: HashMap<(CompileKind, RunnerKind), Option<(PathBuf, Vec<String>)>>,
     /// The linker to use for each host or target.
     target_linkers: HashMap<CompileKind, Option<PathBuf>>,
 
@@ -137,6 +142,12 @@ impl<'gctx> Compilation<'gctx> {
             root_output: HashMap::new(),
             deps_output: HashMap::new(),
             sysroot_target_libdir: get_sysroot_target_libdir(bcx)?,
+            build_std_kinds: bcx
+                .unit_graph
+                .keys()
+                .filter(|unit| unit.is_std)
+                .map(|unit| unit.kind)
+                .collect(),
             tests: Vec::new(),
             binaries: Vec::new(),
             cdylibs: Vec::new(),
@@ -153,7 +164,8 @@ impl<'gctx> Compilation<'gctx> {
                 .requested_kinds
                 .iter()
                 .chain(Some(&CompileKind::Host))
-                .map(|kind| Ok((*kind, target_runner(bcx, *kind)?)))
+                .flat_map(|kind| RunnerKind::ALL.map(|runner| (*kind, runner)))
+                .map(|(kind, runner)| Ok(((kind, runner), target_runner(bcx, kind, runner)?)))
                 .collect::<CargoResult<HashMap<_, _>>>()?,
             target_linkers: bcx
                 .build_config
@@ -235,8 +247,22 @@ impl<'gctx> Compilation<'gctx> {
         )
     }
 
-    pub fn target_runner(&self, kind: CompileKind) -> Option<&(PathBuf, Vec<String>)> {
-        self.target_runners.get(&kind).and_then(|x| x.as_ref())
+    /// Gets the user-specified runner for a kind of executable of a
+    /// particular host or target.
+    pub fn target_runner(
+        &self,
+        kind: CompileKind,
+        runner: RunnerKind,
+    ) -> Option<&(PathBuf, Vec<String>)> {
+        self.target_runners
+            .get(&(kind, runner))
+            .and_then(|x| x.as_ref())
+    }
+
+    /// Gets the directory the `llvm-tools` component installs its tools to,
+    /// like `llvm-profdata`, in the sysroot of the host.
+    pub fn host_llvm_tools_dir(&self) -> PathBuf {
+        self.sysroot_target_libdir[&CompileKind::Host].with_file_name("bin")
     }
 
     /// Gets the user-specified linker for a particular host or target.
@@ -248,6 +274,9 @@ impl<'gctx> Compilation<'gctx> {
     /// target platform. This is typically used for `cargo run` and `cargo
     /// test`.
     ///
+    /// `runner` is the kind of executable, which picks the runner to wrap it
+    /// in.
+    ///
     /// `script_meta` is the metadata for the `RunCustomBuild` unit that this
     /// unit used for its build script. Use `None` if the package did not have
     /// a build script.
@@ -255,10 +284,11 @@ impl<'gctx> Compilation<'gctx> {
         &self,
         cmd: T,
         kind: CompileKind,
+        runner: RunnerKind,
         pkg: &Package,
         script_meta: Option<UnitHash>,
     ) -> CargoResult<ProcessBuilder> {
-        let builder = if let Some((runner, args)) = self.target_runner(kind) {
+        let builder = if let Some((runner, args)) = self.target_runner(kind, runner) {
             let mut builder = ProcessBuilder::new(runner);
             builder.args(args);
             builder.arg(cmd);
@@ -315,7 +345,7 @@ impl<'gctx> Compilation<'gctx> {
             // libs from the sysroot that ships with rustc. This may not be
             // required (at least I cannot craft a situation where it
             // matters), but is here to be safe.
-            if self.gctx.cli_unstable().build_std.is_none() {
+            if !self.build_std_kinds.contains(&kind) {
                 search_path.push(self.sysroot_target_libdir[&kind].clone());
             }
         }
@@ -447,48 +477,85 @@ fn get_sysroot_target_libdir(
         .collect()
 }
 
+/// Gets the user-specified runner for a kind of executable of a particular
+/// host or target from the configuration.
+///
+/// A runner for the kind in `target.{}.runner` comes first, then one in a
+/// matching `target.'cfg(...)'.runner`.
 fn target_runner(
     bcx: &BuildContext<'_, '_>,
     kind: CompileKind,
+    runner_kind: RunnerKind,
 ) -> CargoResult<Option<(PathBuf, Vec<String>)>> {
     let target = bcx.target_data.short_name(&kind);
-
+    let gctx = bcx.gctx;
     // try target.{}.runner
     let key = format!("target.{}.runner", target);
 
-    if let Some(v) = bcx.gctx.get::<Option<context::PathAndArgs>>(&key)? {
-        let path = v.path.resolve_program(bcx.gctx);
-        return Ok(Some((path, v.args)));
+    if let Some(v) = gctx.target_runner(&key)? {
+        if let Some(runner) = runner_of_kind(gctx, &key, &v, runner_kind)? {
+            let path = runner.path.clone().resolve_program(gctx);
+            return Ok(Some((path, runner.args.clone())));
+        }
     }
 
     // try target.'cfg(...)'.runner
     let target_cfg = bcx.target_data.info(kind).cfg();
-    let mut cfgs = bcx
-        .gctx
-        .target_cfgs()?
-        .iter()
-        .filter_map(|(key, cfg)| cfg.runner.as_ref().map(|runner| (key, runner)))
-        .filter(|(key, _runner)| CfgExpr::matches_key(key, target_cfg));
-    let matching_runner = cfgs.next();
-    if let Some((key, runner)) = cfgs.next() {
+    let mut cfgs = Vec::new();
+    for (key, cfg) in gctx.target_cfgs()? {
+        let Some(runner) = &cfg.runner else {
+            continue;
+        };
+        if !CfgExpr::matches_key(key, target_cfg) {
+            continue;
+        }
+        let runner_key = format!("target.{key}.runner");
+        if let Some(path_and_args) = runner_of_kind(gctx, &runner_key, runner, runner_kind)? {
+            cfgs.push((key, runner, path_and_args));
+        }
+    }
+    if let [(first_key, first, _), (key, runner, _), ..] = cfgs.as_slice() {
         anyhow::bail!(
             "several matching instances of `target.'cfg(..)'.runner` in configurations\n\
              first match `{}` located in {}\n\
              second match `{}` located in {}",
-            matching_runner.unwrap().0,
-            matching_runner.unwrap().1.definition,
+            first_key,
+            first.definition,
             key,
             runner.definition
         );
     }
-    Ok(matching_runner.map(|(_k, runner)| {
+    Ok(cfgs.pop().map(|(_key, _runner, runner)| {
         (
-            runner.val.path.clone().resolve_program(bcx.gctx),
-            runner.val.args.clone(),
+            runner.path.clone().resolve_program(gctx),
+            runner.args.clone(),
         )
     }))
 }
 
+/// Gets the runner for a kind of executable from the `runner` at `key`.
+fn runner_of_kind<'a>(
+    gctx: &GlobalContext,
+    key: &str,
+    runner: &'a context::Value<TargetRunner>,
+    runner_kind: RunnerKind,
+) -> CargoResult<Option<&'a context::PathAndArgs>> {
+    if let TargetRunner::ByKind(_) = runner.val {
+        if !gctx.cli_unstable().runner_kinds {
+            anyhow::bail!(
+                "a runner for each kind of executable in `{key}` requires `-Zrunner-kinds`\n\
+                 defined in {}",
+                runner.definition
+            );
+        }
+    } else if runner_kind == RunnerKind::Doctest && !gctx.cli_unstable().doctest_xcompile {
+        // Doctests only use a runner for every kind of executable with
+        // `-Zdoctest-xcompile`.
+        return Ok(None);
+    }
+    Ok(runner.val.get(runner_kind))
+}
+
 /// Gets the user-specified linker for a particular host or target from the configuration.
 fn target_linker(bcx: &BuildContext<'_, '_>, kind: CompileKind) -> CargoResult<Option<PathBuf>> {
     // Try host.linker and target.{}.linker.
@@ -31,7 +31,7 @@
 //! [`CompileMode::RunCustomBuild`]: super::CompileMode
 //! [instructions]: https://doc.rust-lang.org/cargo/reference/build-scripts.html#outputs-of-the-build-script
 
-use super::{fingerprint, BuildRunner, Job, Unit, Work};
+use super::{fingerprint, BuildRunner, Job, SandboxPolicy, Unit, Work};
 use crate::core::compiler::artifact;
 use crate::core::compiler::build_runner::UnitHash;
 use crate::core::compiler::fingerprint::DirtyReason;
@@ -91,11 +91,23 @@ pub struct BuildOutput {
     pub env: Vec<(String, String)>,
     /// Metadata to pass to the immediate dependencies.
     pub metadata: Vec<(String, String)>,
+    /// JSON metadata to pass to the immediate dependencies, as key and JSON
+    /// text.
+    pub metadata_json: Vec<(String, String)>,
     /// Paths to trigger a rerun of this build script.
     /// May be absolute or relative paths (relative to package root).
     pub rerun_if_changed: Vec<PathBuf>,
+    /// Glob patterns of files to trigger a rerun of this build script.
+    /// May be absolute or relative patterns (relative to package root).
+    pub rerun_if_changed_glob: Vec<String>,
     /// Environment variables which, when changed, will cause a rebuild.
     pub rerun_if_env_changed: Vec<String>,
+    /// Files read by the build script, which trigger a rerun when changed.
+    /// May be absolute or relative paths (relative to package root).
+    pub input_files: Vec<PathBuf>,
+    /// Files generated by the build script.
+    /// Absolute paths inside `OUT_DIR`.
+    pub output_files: Vec<PathBuf>,
     /// Errors and warnings generated by this build.
     ///
     /// These are only displayed if this is a "local" package, `-vv` is used, or
@@ -164,8 +176,17 @@ pub struct BuildDeps {
     pub build_script_output: PathBuf,
     /// Files that trigger a rebuild if they change.
     pub rerun_if_changed: Vec<PathBuf>,
+    /// Glob patterns of files that trigger a rebuild if any matching file
+    /// changes, or if files start or stop matching.
+    pub rerun_if_changed_glob: Vec<String>,
     /// Environment variables that trigger a rebuild if they change.
     pub rerun_if_env_changed: Vec<String>,
+    /// Files read by the build script, which trigger a rebuild if they
+    /// change.
+    pub input_files: Vec<PathBuf>,
+    /// Files generated by the build script, which trigger a rebuild if they
+    /// go missing.
+    pub output_files: Vec<PathBuf>,
 }
 
 /// Represents one of the instructions from `cargo::rustc-link-arg-*` build
@@ -233,12 +254,18 @@ fn emit_build_output(
     output: &BuildOutput,
     out_dir: &Path,
     package_id: PackageId,
+    pkg_root: &Path,
 ) -> CargoResult<()> {
     let library_paths = output
         .library_paths
         .iter()
         .map(|l| l.display().to_string())
         .collect::<Vec<_>>();
+    let input_files = output
+        .input_files
+        .iter()
+        .map(|p| pkg_root.join(p))
+        .collect::<Vec<_>>();
 
     let msg = machine_message::BuildScript {
         package_id: package_id.to_spec(),
@@ -247,6 +274,8 @@ fn emit_build_output(
         cfgs: &output.cfgs,
         env: &output.env,
         out_dir,
+        input_files: &input_files,
+        output_files: &output.output_files,
     }
     .to_json_string();
     state.stdout(msg)?;
@@ -416,12 +445,30 @@ fn build_work(build_runner: &mut BuildRunner<'_, '_>, unit: &Unit) -> CargoResul
     let json_messages = bcx.build_config.emit_json();
     let extra_verbose = bcx.gctx.extra_verbose();
     let (prev_output, prev_script_out_dir) = prev_build_output(build_runner, unit);
+    let prev_output_files = prev_output
+        .as_ref()
+        .map(|o| o.output_files.clone())
+        .unwrap_or_default();
+    let pkg_root = unit.pkg.root().to_path_buf();
+    let pkg_root_fresh = pkg_root.clone();
     let metadata_hash = build_runner.get_run_build_script_metadata(unit);
+    let rerun_if_changed = prev_output
+        .iter()
+        .flat_map(|o| &o.rerun_if_changed)
+        .map(|path| unit.pkg.root().join(path))
+        .collect::<Vec<_>>();
+    let sandbox = SandboxPolicy::for_unit(build_runner, unit, &script_out_dir, &rerun_if_changed)?;
+    let cargo_exe = build_runner.bcx.gctx.cargo_exe()?.to_path_buf();
 
     paths::create_dir_all(&script_dir)?;
     paths::create_dir_all(&script_out_dir)?;
 
     let nightly_features_allowed = build_runner.bcx.gctx.nightly_features_allowed;
+    let unstable_instructions = build_runner
+        .bcx
+        .gctx
+        .cli_unstable()
+        .build_script_instructions;
     let targets: Vec<Target> = unit.pkg.targets().to_vec();
     let msrv = unit.pkg.rust_version().cloned();
     // Need a separate copy for the fresh closure.
@@ -456,6 +503,13 @@ fn build_work(build_runner: &mut BuildRunner<'_, '_>, unit: &Unit) -> CargoResul
         // native dynamic libraries.
         if !build_plan {
             let build_script_outputs = build_script_outputs.lock().unwrap();
+            // JSON metadata is handed over as one file per package and key,
+            // see `cargo::metadata-json`.
+            let dep_metadata_dir = script_run_dir.join("dep-metadata");
+            if dep_metadata_dir.exists() {
+                paths::remove_dir_all(&dep_metadata_dir)?;
+            }
+            let mut has_metadata_json = false;
             for (name, dep_id, dep_metadata) in lib_deps {
                 let script_output = build_script_outputs.get(dep_metadata).ok_or_else(|| {
                     internal(format!(
@@ -470,6 +524,15 @@ fn build_work(build_runner: &mut BuildRunner<'_, '_>, unit: &Unit) -> CargoResul
                         value,
                     );
                 }
+                for (key, json) in script_output.metadata_json.iter() {
+                    let dir = dep_metadata_dir.join(dep_id.name());
+                    paths::create_dir_all(&dir)?;
+                    paths::write(dir.join(format!("{key}.json")), json)?;
+                    has_metadata_json = true;
+                }
+            }
+            if has_metadata_json {
+                cmd.env("CARGO_DEP_METADATA_DIR", &dep_metadata_dir);
             }
             if let Some(build_scripts) = build_scripts {
                 super::add_plugin_deps(
@@ -488,11 +551,15 @@ fn build_work(build_runner: &mut BuildRunner<'_, '_>, unit: &Unit) -> CargoResul
 
         // And now finally, run the build command itself!
         state.running(&cmd);
+        let cmd = match &sandbox {
+            Some(sandbox) => sandbox.wrap(cmd, &cargo_exe)?,
+            None => cmd,
+        };
         let timestamp = paths::set_invocation_time(&script_run_dir)?;
         let prefix = format!("[{} {}] ", id.name(), id.version());
         let mut log_messages_in_case_of_panic = Vec::new();
         let output = cmd
-            .exec_with_streaming(
+            .exec_with_streaming_peak_rss(
                 &mut |stdout| {
                     if let Some(error) = stdout.strip_prefix(CARGO_ERROR_SYNTAX) {
                         log_messages_in_case_of_panic.push((Severity::Error, error.to_owned()));
@@ -538,6 +605,11 @@ fn build_work(build_runner: &mut BuildRunner<'_, '_>, unit: &Unit) -> CargoResul
                     }
                 }
 
+                if let Some(sandbox) = &sandbox {
+                    build_error_context.push('\n');
+                    build_error_context.push_str(&sandbox.failure_help(&id.name()));
+                }
+
                 build_error_context
             });
 
@@ -565,7 +637,8 @@ fn build_work(build_runner: &mut BuildRunner<'_, '_>, unit: &Unit) -> CargoResul
             anyhow::bail!("build script logged errors");
         }
 
-        let output = output.unwrap();
+        let (output, peak_rss) = output.unwrap();
+        state.peak_rss(peak_rss);
 
         // After the build command has finished running, we need to be sure to
         // remember all of its output so we can later discover precisely what it
@@ -587,12 +660,29 @@ fn build_work(build_runner: &mut BuildRunner<'_, '_>, unit: &Unit) -> CargoResul
             &script_out_dir,
             &script_out_dir,
             nightly_features_allowed,
+            unstable_instructions,
             &targets,
             &msrv,
         )?;
 
+        // Remove the files a previous run generated, but this one didn't.
+        for stale in prev_output_files
+            .iter()
+            .filter(|p| !parsed_output.output_files.contains(p))
+        {
+            if stale.exists() {
+                paths::remove_file(stale)?;
+            }
+        }
+
         if json_messages {
-            emit_build_output(state, &parsed_output, script_out_dir.as_path(), id)?;
+            emit_build_output(
+                state,
+                &parsed_output,
+                script_out_dir.as_path(),
+                id,
+                &pkg_root,
+            )?;
         }
         build_script_outputs
             .lock()
@@ -615,13 +705,20 @@ fn build_work(build_runner: &mut BuildRunner<'_, '_>, unit: &Unit) -> CargoResul
                 &prev_script_out_dir,
                 &script_out_dir,
                 nightly_features_allowed,
+                unstable_instructions,
                 &targets_fresh,
                 &msrv_fresh,
             )?,
         };
 
         if json_messages {
-            emit_build_output(state, &output, script_out_dir.as_path(), id)?;
+            emit_build_output(
+                state,
+                &output,
+                script_out_dir.as_path(),
+                id,
+                &pkg_root_fresh,
+            )?;
         }
 
         build_script_outputs
@@ -672,6 +769,7 @@ impl BuildOutput {
         script_out_dir_when_generated: &Path,
         script_out_dir: &Path,
         nightly_features_allowed: bool,
+        unstable_instructions: bool,
         targets: &[Target],
         msrv: &Option<RustVersion>,
     ) -> CargoResult<BuildOutput> {
@@ -683,6 +781,7 @@ impl BuildOutput {
             script_out_dir_when_generated,
             script_out_dir,
             nightly_features_allowed,
+            unstable_instructions,
             targets,
             msrv,
         )
@@ -692,6 +791,7 @@ impl BuildOutput {
     ///
     /// * `pkg_descr` --- for error messages
     /// * `library_name` --- for determining if `RUSTC_BOOTSTRAP` should be allowed
+    /// * `unstable_instructions` --- whether `-Zbuild-script-instructions` is enabled
     pub fn parse(
         input: &[u8],
         // Takes String instead of InternedString so passing `unit.pkg.name()` will give a compile error.
@@ -700,6 +800,7 @@ impl BuildOutput {
         script_out_dir_when_generated: &Path,
         script_out_dir: &Path,
         nightly_features_allowed: bool,
+        unstable_instructions: bool,
         targets: &[Target],
         msrv: &Option<RustVersion>,
     ) -> CargoResult<BuildOutput> {
@@ -710,8 +811,12 @@ impl BuildOutput {
         let mut check_cfgs = Vec::new();
         let mut env = Vec::new();
         let mut metadata = Vec::new();
+        let mut metadata_json = Vec::new();
         let mut rerun_if_changed = Vec::new();
+        let mut rerun_if_changed_glob = Vec::new();
         let mut rerun_if_env_changed = Vec::new();
+        let mut input_files = Vec::new();
+        let mut output_files = Vec::new();
         let mut log_messages = Vec::new();
         let whence = format!("build script of `{}`", pkg_descr);
         // Old syntax:
@@ -740,6 +845,13 @@ impl BuildOutput {
             "rerun-if-changed=",
             "rerun-if-env-changed=",
         ];
+        // Instructions that are ignored without `-Zbuild-script-instructions`.
+        const UNSTABLE_KEYS: &[&str] = &[
+            "rerun-if-changed-glob",
+            "input-file",
+            "output-file",
+            "metadata-json",
+        ];
         const DOCS_LINK_SUGGESTION: &str = "See https://doc.rust-lang.org/cargo/reference/build-scripts.html#outputs-of-the-build-script \
                 for more information about build script outputs.";
 
@@ -856,6 +968,16 @@ impl BuildOutput {
             );
 
             let syntax_prefix = if old_syntax { "cargo:" } else { "cargo::" };
+            if !unstable_instructions && UNSTABLE_KEYS.contains(&key) {
+                log_messages.push((
+                    Severity::Warning,
+                    format!(
+                        "`{syntax_prefix}{key}` is unstable and was ignored, \
+                        pass `-Zbuild-script-instructions` to enable it"
+                    ),
+                ));
+                continue;
+            }
             macro_rules! check_and_add_target {
                 ($target_kind: expr, $is_target_kind: expr, $link_type: expr) => {
                     if !targets.iter().any(|target| $is_target_kind(target)) {
@@ -1003,11 +1125,69 @@ impl BuildOutput {
                 "error" => log_messages.push((Severity::Error, value.to_string())),
                 "warning" => log_messages.push((Severity::Warning, value.to_string())),
                 "rerun-if-changed" => rerun_if_changed.push(PathBuf::from(value)),
+                "rerun-if-changed-glob" => {
+                    if let Err(e) = glob::Pattern::new(&value) {
+                        bail!(
+                            "invalid output in {whence}: `{line}`\n\
+                            Invalid glob pattern: {e}.\n\
+                            {DOCS_LINK_SUGGESTION}",
+                        );
+                    }
+                    rerun_if_changed_glob.push(value);
+                }
                 "rerun-if-env-changed" => rerun_if_env_changed.push(value.to_string()),
+                "input-file" => input_files.push(PathBuf::from(value)),
+                "output-file" => {
+                    // Output files may be cleaned up by Cargo, so keep them
+                    // from pointing anywhere but `OUT_DIR`.
+                    let path = Path::new(&value);
+                    if path
+                        .components()
+                        .any(|c| matches!(c, std::path::Component::ParentDir))
+                        || (path.is_absolute() && !path.starts_with(script_out_dir))
+                    {
+                        bail!(
+                            "invalid output in {whence}: `{line}`\n\
+                            Output files must be inside `OUT_DIR`.\n\
+                            {DOCS_LINK_SUGGESTION}",
+                        );
+                    }
+                    output_files.push(script_out_dir.join(path));
+                }
                 "metadata" => {
                     let (key, value) = parse_metadata(whence.as_str(), line, &value, old_syntax)?;
                     metadata.push((key.to_owned(), value.to_owned()));
                 }
+                "metadata-json" => {
+                    let Some((key, json)) = value.split_once('=') else {
+                        bail!(
+                            "invalid output in {whence}: `{line}`\n\
+                            Expected a line with `cargo::metadata-json=KEY=JSON` with an `=` character, \
+                            but none was found.\n\
+                            {DOCS_LINK_SUGGESTION}",
+                        );
+                    };
+                    // The key becomes a file name for dependent build scripts.
+                    if key.is_empty()
+                        || !key
+                            .chars()
+                            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+                    {
+                        bail!(
+                            "invalid output in {whence}: `{line}`\n\
+                            Metadata keys may only contain ASCII letters, digits, `-` and `_`.\n\
+                            {DOCS_LINK_SUGGESTION}",
+                        );
+                    }
+                    if let Err(e) = serde_json::from_str::<serde_json::Value>(json) {
+                        bail!(
+                            "invalid output in {whence}: `{line}`\n\
+                            Invalid JSON: {e}.\n\
+                            {DOCS_LINK_SUGGESTION}",
+                        );
+                    }
+                    metadata_json.push((key.to_owned(), json.to_owned()));
+                }
                 _ => bail!(
                     "invalid output in {whence}: `{line}`\n\
                     Unknown key: `{key}`.\n\
@@ -1024,8 +1204,12 @@ impl BuildOutput {
             check_cfgs,
             env,
             metadata,
+            metadata_json,
             rerun_if_changed,
+            rerun_if_changed_glob,
             rerun_if_env_changed,
+            input_files,
+            output_files,
             log_messages,
         })
     }
@@ -1134,10 +1318,16 @@ impl BuildDeps {
                 .map(|p| &p.rerun_if_changed)
                 .cloned()
                 .unwrap_or_default(),
+            rerun_if_changed_glob: output
+                .map(|p| &p.rerun_if_changed_glob)
+                .cloned()
+                .unwrap_or_default(),
             rerun_if_env_changed: output
                 .map(|p| &p.rerun_if_env_changed)
                 .cloned()
                 .unwrap_or_default(),
+            input_files: output.map(|p| &p.input_files).cloned().unwrap_or_default(),
+            output_files: output.map(|p| &p.output_files).cloned().unwrap_or_default(),
         }
     }
 }
@@ -1287,6 +1477,11 @@ fn prev_build_output(
             &prev_script_out_dir,
             &script_out_dir,
             build_runner.bcx.gctx.nightly_features_allowed,
+            build_runner
+                .bcx
+                .gctx
+                .cli_unstable()
+                .build_script_instructions,
             unit.pkg.targets(),
             &unit.pkg.rust_version().cloned(),
         )
@@ -49,6 +49,18 @@ pub enum DirtyReason {
         old: Vec<PathBuf>,
         new: Vec<PathBuf>,
     },
+    RerunIfChangedGlobPatternsChanged {
+        old: Vec<String>,
+        new: Vec<String>,
+    },
+    RerunIfChangedGlobMatchesChanged {
+        old: Vec<PathBuf>,
+        new: Vec<PathBuf>,
+    },
+    OutputFilesChanged {
+        old: Vec<PathBuf>,
+        new: Vec<PathBuf>,
+    },
     EnvVarsChanged {
         old: String,
         new: String,
@@ -205,6 +217,33 @@ impl DirtyReason {
             DirtyReason::RerunIfChangedOutputPathsChanged { .. } => {
                 s.dirty_because(unit, "the rerun-if-changed instructions changed")
             }
+            DirtyReason::OutputFilesChanged { .. } => {
+                s.dirty_because(unit, "the output-file instructions changed")
+            }
+            DirtyReason::RerunIfChangedGlobPatternsChanged { .. } => {
+                s.dirty_because(unit, "the rerun-if-changed-glob instructions changed")
+            }
+            DirtyReason::RerunIfChangedGlobMatchesChanged { old, new } => {
+                if let Some(added) = new.iter().find(|p| !old.contains(p)) {
+                    s.dirty_because(
+                        unit,
+                        format_args!(
+                            "the file `{}` started matching rerun-if-changed-glob",
+                            added.display()
+                        ),
+                    )
+                } else if let Some(removed) = old.iter().find(|p| !new.contains(p)) {
+                    s.dirty_because(
+                        unit,
+                        format_args!(
+                            "the file `{}` stopped matching rerun-if-changed-glob",
+                            removed.display()
+                        ),
+                    )
+                } else {
+                    s.dirty_because(unit, "the files matching rerun-if-changed-glob changed")
+                }
+            }
             DirtyReason::EnvVarsChanged { .. } => {
                 s.dirty_because(unit, "the environment variables changed")
             }
@@ -263,7 +263,9 @@
 //! In the new-style, each `rerun-if` directive is translated to the
 //! corresponding [`LocalFingerprint`] variant. The [`RerunIfChanged`] variant
 //! compares the mtime of the given filenames against the mtime of the
-//! "output" file.
+//! "output" file. The [`RerunIfChangedGlob`] variant does the same for the
+//! files matching its patterns, and also hashes the set of matching files so
+//! that adding or removing one is detected as well.
 //!
 //! Similar to normal units, the build script "output" file mtime is rewound
 //! to the time just before the build script is executed to handle mid-build
@@ -356,6 +358,7 @@
 //! [`output_depinfo`]: super::output_depinfo()
 //! [`CheckDepInfo`]: LocalFingerprint::CheckDepInfo
 //! [`RerunIfChanged`]: LocalFingerprint::RerunIfChanged
+//! [`RerunIfChangedGlob`]: LocalFingerprint::RerunIfChangedGlob
 //! [`CompileMode::RunCustomBuild`]: crate::core::compiler::CompileMode::RunCustomBuild
 //! [`A-rebuild-detection`]: https://github.com/rust-lang/cargo/issues?q=is%3Aissue+is%3Aopen+label%3AA-rebuild-detection
 
@@ -363,6 +366,7 @@ mod dep_info;
 mod dirty_reason;
 
 use std::collections::hash_map::{Entry, HashMap};
+use std::collections::BTreeSet;
 use std::env;
 use std::fs;
 use std::fs::File;
@@ -756,6 +760,30 @@ enum LocalFingerprint {
         paths: Vec<PathBuf>,
     },
 
+    /// This represents the `rerun-if-changed-glob` annotations printed out by
+    /// a build script. Like [`LocalFingerprint::RerunIfChanged`], `output` is
+    /// relative to `target_root(...)`, and the `patterns` may be relative to
+    /// `pkg.root()`.
+    ///
+    /// `matches` is the sorted list of files matching any of the patterns
+    /// when this fingerprint was calculated, relative to `pkg.root()` when
+    /// possible. Since it is hashed, a file starting or ceasing to match
+    /// causes a rerun. Otherwise this is up-to-date if all of the `matches`
+    /// are older than `output`.
+    RerunIfChangedGlob {
+        output: PathBuf,
+        patterns: Vec<String>,
+        matches: Vec<PathBuf>,
+    },
+
+    /// This represents the `output-file` annotations printed out by a build
+    /// script. The `paths` are relative to `target_root(...)`.
+    ///
+    /// Generated files are written while the build script runs, so they are
+    /// expected to be newer than its output. This is only considered stale
+    /// if any of them is missing.
+    OutputFiles { paths: Vec<PathBuf> },
+
     /// This represents a single `rerun-if-env-changed` annotation printed by a
     /// build script. The exact env var and value are hashed here. There's no
     /// filesystem dependence here, and if the values are changed the hash will
@@ -891,6 +919,20 @@ impl LocalFingerprint {
                 paths.iter().map(|p| (pkg_root.join(p), None)),
                 false,
             )),
+            LocalFingerprint::RerunIfChangedGlob {
+                output, matches, ..
+            } => Ok(find_stale_file(
+                mtime_cache,
+                checksum_cache,
+                &target_root.join(output),
+                matches.iter().map(|p| (pkg_root.join(p), None)),
+                false,
+            )),
+            LocalFingerprint::OutputFiles { paths } => Ok(paths
+                .iter()
+                .map(|p| target_root.join(p))
+                .find(|p| !p.exists())
+                .map(StaleItem::MissingFile)),
 
             // These have no dependencies on the filesystem, and their values
             // are included natively in the `Fingerprint` hash so nothing
@@ -905,6 +947,8 @@ impl LocalFingerprint {
             LocalFingerprint::Precalculated(..) => "precalculated",
             LocalFingerprint::CheckDepInfo { .. } => "dep-info",
             LocalFingerprint::RerunIfChanged { .. } => "rerun-if-changed",
+            LocalFingerprint::RerunIfChangedGlob { .. } => "rerun-if-changed-glob",
+            LocalFingerprint::OutputFiles { .. } => "output-file",
             LocalFingerprint::RerunIfEnvChanged { .. } => "rerun-if-env-changed",
         }
     }
@@ -1053,6 +1097,48 @@ impl Fingerprint {
                         };
                     }
                 }
+                (
+                    LocalFingerprint::RerunIfChangedGlob {
+                        output: aout,
+                        patterns: apatterns,
+                        matches: amatches,
+                    },
+                    LocalFingerprint::RerunIfChangedGlob {
+                        output: bout,
+                        patterns: bpatterns,
+                        matches: bmatches,
+                    },
+                ) => {
+                    if aout != bout {
+                        return DirtyReason::RerunIfChangedOutputFileChanged {
+                            old: bout.clone(),
+                            new: aout.clone(),
+                        };
+                    }
+                    if apatterns != bpatterns {
+                        return DirtyReason::RerunIfChangedGlobPatternsChanged {
+                            old: bpatterns.clone(),
+                            new: apatterns.clone(),
+                        };
+                    }
+                    if amatches != bmatches {
+                        return DirtyReason::RerunIfChangedGlobMatchesChanged {
+                            old: bmatches.clone(),
+                            new: amatches.clone(),
+                        };
+                    }
+                }
+                (
+                    LocalFingerprint::OutputFiles { paths: apaths },
+                    LocalFingerprint::OutputFiles { paths: bpaths },
+                ) => {
+                    if apaths != bpaths {
+                        return DirtyReason::OutputFilesChanged {
+                            old: bpaths.clone(),
+                            new: apaths.clone(),
+                        };
+                    }
+                }
                 (
                     LocalFingerprint::RerunIfEnvChanged {
                         var: akey,
@@ -1703,7 +1789,11 @@ fn build_script_local_fingerprints(
     let target_dir = target_root(build_runner);
     let calculate =
         move |deps: &BuildDeps, pkg_fingerprint: Option<&dyn Fn() -> CargoResult<String>>| {
-            if deps.rerun_if_changed.is_empty() && deps.rerun_if_env_changed.is_empty() {
+            if deps.rerun_if_changed.is_empty()
+                && deps.rerun_if_changed_glob.is_empty()
+                && deps.rerun_if_env_changed.is_empty()
+                && deps.input_files.is_empty()
+            {
                 match pkg_fingerprint {
                     // FIXME: this is somewhat buggy with respect to docker and
                     // weird filesystems. The `Precalculated` variant
@@ -1721,7 +1811,9 @@ fn build_script_local_fingerprints(
                             "old local fingerprints deps {:?} precalculated={:?}",
                             pkg_root, s
                         );
-                        return Ok(Some(vec![LocalFingerprint::Precalculated(s)]));
+                        let mut local = vec![LocalFingerprint::Precalculated(s)];
+                        local.extend(output_files_fingerprint(deps, &target_dir));
+                        return Ok(Some(local));
                     }
                     None => return Ok(None),
                 }
@@ -1769,7 +1861,7 @@ fn local_fingerprints_deps(
     debug!("new local fingerprints deps {:?}", pkg_root);
     let mut local = Vec::new();
 
-    if !deps.rerun_if_changed.is_empty() {
+    if !deps.rerun_if_changed.is_empty() || !deps.input_files.is_empty() {
         // Note that like the module comment above says we are careful to never
         // store an absolute path in `LocalFingerprint`, so ensure that we strip
         // absolute prefixes from them.
@@ -1778,14 +1870,36 @@ fn local_fingerprints_deps(
             .strip_prefix(target_root)
             .unwrap()
             .to_path_buf();
+        // Declared input files behave just like `rerun-if-changed` files.
         let paths = deps
             .rerun_if_changed
             .iter()
+            .chain(&deps.input_files)
             .map(|p| p.strip_prefix(pkg_root).unwrap_or(p).to_path_buf())
             .collect();
         local.push(LocalFingerprint::RerunIfChanged { output, paths });
     }
 
+    if !deps.rerun_if_changed_glob.is_empty() {
+        let output = deps
+            .build_script_output
+            .strip_prefix(target_root)
+            .unwrap()
+            .to_path_buf();
+        let patterns = deps.rerun_if_changed_glob.clone();
+        let matches = glob_matches(&patterns, pkg_root)
+            .into_iter()
+            .map(|p| p.strip_prefix(pkg_root).map(Path::to_path_buf).unwrap_or(p))
+            .collect();
+        local.push(LocalFingerprint::RerunIfChangedGlob {
+            output,
+            patterns,
+            matches,
+        });
+    }
+
+    local.extend(output_files_fingerprint(deps, target_root));
+
     local.extend(
         deps.rerun_if_env_changed
             .iter()
@@ -1795,6 +1909,46 @@ fn local_fingerprints_deps(
     local
 }
 
+/// Creates the [`LocalFingerprint::OutputFiles`] for the files a build script
+/// declared to generate, if any.
+fn output_files_fingerprint(deps: &BuildDeps, target_root: &Path) -> Option<LocalFingerprint> {
+    if deps.output_files.is_empty() {
+        return None;
+    }
+    let paths = deps
+        .output_files
+        .iter()
+        .map(|p| p.strip_prefix(target_root).unwrap_or(p).to_path_buf())
+        .collect();
+    Some(LocalFingerprint::OutputFiles { paths })
+}
+
+/// Returns the sorted list of files matching any of the `rerun-if-changed-glob`
+/// `patterns`, relative patterns being anchored at `pkg_root`.
+///
+/// Directories are skipped, since only the files in them are of interest, and
+/// so are entries that can't be read.
+fn glob_matches(patterns: &[String], pkg_root: &Path) -> Vec<PathBuf> {
+    let mut matches = BTreeSet::new();
+    for pattern in patterns {
+        let pattern = if Path::new(pattern).is_absolute() {
+            pattern.clone()
+        } else {
+            let Some(root) = pkg_root.to_str() else {
+                continue;
+            };
+            format!("{}/{pattern}", glob::Pattern::escape(root))
+        };
+        let Ok(paths) = glob::glob(&pattern) else {
+            // Invalid patterns are rejected when parsing the build script
+            // output.
+            continue;
+        };
+        matches.extend(paths.filter_map(Result::ok).filter(|p| p.is_file()));
+    }
+    matches.into_iter().collect()
+}
+
 /// Writes the short fingerprint hash value to `<loc>`
 /// and logs detailed JSON information to `<loc>.json`.
 fn write_fingerprint(loc: &Path, fingerprint: &Fingerprint) -> CargoResult<()> {
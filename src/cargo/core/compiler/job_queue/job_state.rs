@@ -72,6 +72,15 @@ impl<'a, 'gctx> JobState<'a, 'gctx> {
         self.messages.push(Message::Run(self.id, cmd.to_string()));
     }
 
+    /// Records the peak memory usage of the process this job ran, if known.
+    ///
+    /// See [`Message::PeakRss`].
+    pub fn peak_rss(&self, peak_rss: Option<u64>) {
+        if let Some(peak_rss) = peak_rss {
+            self.messages.push(Message::PeakRss(self.id, peak_rss));
+        }
+    }
+
     pub fn build_plan(
         &self,
         module_name: String,
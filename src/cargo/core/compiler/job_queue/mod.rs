@@ -63,12 +63,18 @@
 //! sum of the cost of each dependencies of each node, including transitively.
 //! The sum of dependency cost turns out to be the cost of each given node.
 //!
-//! At the time being, the cost is just passed as a fixed placeholder in
-//! [`JobQueue::enqueue`]. In the future, we could explore more possibilities
-//! around it. For instance, we start persisting timing information for each
-//! build somewhere. For a subsequent build, we can look into the historical
-//! data and perform a PGO-like optimization to prioritize jobs, making a build
-//! fully pipelined.
+//! By default, the cost is just passed as a fixed placeholder in
+//! [`JobQueue::enqueue`]. With `-Zscheduling-history`, the duration of each
+//! unit is persisted in [`UnitHistory`] after every build. A subsequent build
+//! uses those durations as the costs instead, and prioritizes each node by its
+//! critical path, i.e. the most expensive chain of units that depend on it.
+//! This way the longest chains start as early as possible.
+//!
+//! With `-Zbuild-memory-limit`, the peak memory usage of each unit is saved
+//! in [`UnitHistory`]. When `build.memory-limit` is set, a job only starts if
+//! the expected memory usage of all active jobs stays under the limit, and
+//! the heaviest jobs that fit are started first. See
+//! [`DrainState::next_pending_job`].
 //!
 //! ## Message queue
 //!
@@ -114,7 +120,7 @@ mod job;
 mod job_state;
 
 use std::cell::RefCell;
-use std::collections::{HashMap, HashSet};
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::fmt::Write as _;
 use std::io;
 use std::path::{Path, PathBuf};
@@ -134,11 +140,15 @@ pub use self::job_state::JobState;
 use super::build_runner::OutputFile;
 use super::custom_build::Severity;
 use super::timings::Timings;
-use super::{BuildContext, BuildPlan, BuildRunner, CompileMode, Unit};
+use super::unit_history::UnitHistory;
+use super::{
+    AfterTest, BuildContext, BuildPlan, BuildRunner, CompileMode, FileFlavor, Unit, UnitOutput,
+};
 use crate::core::compiler::descriptive_pkg_name;
 use crate::core::compiler::future_incompat::{
     self, FutureBreakageItem, FutureIncompatReportPackage,
 };
+use crate::core::gc::parse_human_size;
 use crate::core::resolver::ResolveBehavior;
 use crate::core::{PackageId, Shell, TargetKind};
 use crate::util::context::WarningHandling;
@@ -157,6 +167,12 @@ pub struct JobQueue<'gctx> {
     queue: DependencyQueue<Unit, Artifact, Job>,
     counts: HashMap<PackageId, usize>,
     timings: Timings<'gctx>,
+    /// Measurements of units from previous builds. Only present with
+    /// `-Zscheduling-history` or `-Zbuild-memory-limit`.
+    history: Option<UnitHistory>,
+    /// The average duration of units in previous builds, when jobs are
+    /// prioritized by their durations with `-Zscheduling-history`.
+    mean_duration: Option<f64>,
 }
 
 /// This structure is backed by the `DependencyQueue` type and manages the
@@ -199,6 +215,26 @@ struct DrainState<'gctx> {
     pending_queue: Vec<(Unit, Job, usize)>,
     print: DiagnosticPrinter<'gctx>,
 
+    /// Measurements of units from previous builds, used to estimate the
+    /// memory usage of each job. Updated with the measurements of this build.
+    history: Option<UnitHistory>,
+    /// The `build.memory-limit` config in bytes.
+    memory_limit: Option<u64>,
+    /// Estimated memory usage in bytes of each active job.
+    active_memory: HashMap<JobId, u64>,
+
+    /// Test executables waiting to be run by the [`TestRunner`].
+    ///
+    /// [`TestRunner`]: super::TestRunner
+    pending_tests: VecDeque<UnitOutput>,
+    /// A process the [`TestRunner`] asked to run for the last test
+    /// executable, before moving on to the next one.
+    ///
+    /// [`TestRunner`]: super::TestRunner
+    rerun_test: Option<ProcessBuilder>,
+    /// Whether a test process is running.
+    running_test: bool,
+
     /// How many jobs we've finished
     finished: usize,
     per_package_future_incompat_reports: Vec<FutureIncompatReportPackage>,
@@ -374,14 +410,31 @@ enum Message {
     Token(io::Result<Acquired>),
     Finish(JobId, Artifact, CargoResult<()>),
     FutureIncompatReport(JobId, Vec<FutureBreakageItem>),
+    // The peak resident set size in bytes of the process run by a job.
+    PeakRss(JobId, u64),
+    // A line of output of the running test process, when the `TestRunner`
+    // captures it.
+    TestStdout(String),
+    TestStderr(String),
+    // The running test process exited.
+    TestFinished(CargoResult<()>),
 }
 
 impl<'gctx> JobQueue<'gctx> {
     pub fn new(bcx: &BuildContext<'_, 'gctx>) -> JobQueue<'gctx> {
+        let unstable = bcx.gctx.cli_unstable();
+        let history = (unstable.scheduling_history || unstable.build_memory_limit)
+            .then(|| UnitHistory::load(bcx.ws));
+        let mean_duration = history
+            .as_ref()
+            .filter(|_| unstable.scheduling_history)
+            .and_then(|history| history.mean_duration());
         JobQueue {
             queue: DependencyQueue::new(),
             counts: HashMap::new(),
             timings: Timings::new(bcx, &bcx.roots),
+            history,
+            mean_duration,
         }
     }
 
@@ -455,15 +508,34 @@ impl<'gctx> JobQueue<'gctx> {
             }
         }
 
-        // For now we use a fixed placeholder value for the cost of each unit, but
-        // in the future this could be used to allow users to provide hints about
-        // relative expected costs of units, or this could be automatically set in
-        // a smarter way using timing data from a previous compilation.
-        self.queue.queue(unit.clone(), job, queue_deps, 100);
+        let cost = self.cost(build_runner, unit, &job);
+        self.queue.queue(unit.clone(), job, queue_deps, cost);
         *self.counts.entry(unit.pkg.package_id()).or_insert(0) += 1;
         Ok(())
     }
 
+    /// The expected cost of building a unit, which determines its priority.
+    ///
+    /// With `-Zscheduling-history`, this is how many milliseconds the unit
+    /// took the last time it ran, or the average of all units if it hasn't
+    /// run yet. Fresh units take no time.
+    ///
+    /// Otherwise we use a fixed placeholder value for the cost of each unit,
+    /// but in the future this could be used to allow users to provide hints
+    /// about relative expected costs of units.
+    fn cost(&self, build_runner: &BuildRunner<'_, 'gctx>, unit: &Unit, job: &Job) -> usize {
+        let (Some(history), Some(mean_duration)) = (&self.history, self.mean_duration) else {
+            return 100;
+        };
+        if job.freshness().is_fresh() {
+            return 0;
+        }
+        let duration = history
+            .duration(build_runner.files().metadata(unit).unit_id())
+            .unwrap_or(mean_duration);
+        (duration * 1000.0) as usize
+    }
+
     /// Executes all jobs necessary to build the dependency graph.
     ///
     /// This function will spawn off `config.jobs()` workers to build all of the
@@ -475,10 +547,26 @@ impl<'gctx> JobQueue<'gctx> {
         build_runner: &mut BuildRunner<'_, '_>,
         plan: &mut BuildPlan,
     ) -> CargoResult<()> {
-        self.queue.queue_finished();
+        if self.mean_duration.is_some() {
+            self.queue.queue_finished_by_critical_path();
+        } else {
+            self.queue.queue_finished();
+        }
 
         let progress =
             Progress::with_style("Building", ProgressStyle::Ratio, build_runner.bcx.gctx);
+        let gctx = build_runner.bcx.gctx;
+        let memory_limit = if gctx.cli_unstable().build_memory_limit {
+            gctx.build_config()?
+                .memory_limit
+                .as_deref()
+                .map(|limit| {
+                    parse_human_size(limit).context("failed to parse `build.memory-limit`")
+                })
+                .transpose()?
+        } else {
+            None
+        };
         let state = DrainState {
             total_units: self.queue.len(),
             queue: self.queue,
@@ -503,6 +591,12 @@ impl<'gctx> JobQueue<'gctx> {
                 build_runner.bcx.gctx,
                 &build_runner.bcx.rustc().workspace_wrapper,
             ),
+            history: self.history,
+            memory_limit,
+            active_memory: HashMap::new(),
+            pending_tests: VecDeque::new(),
+            rerun_test: None,
+            running_test: false,
             finished: 0,
             per_package_future_incompat_reports: Vec::new(),
         };
@@ -573,7 +667,10 @@ impl<'gctx> DrainState<'gctx> {
         // remove items from its end to schedule the highest priority items
         // sooner.
         while self.has_extra_tokens() && !self.pending_queue.is_empty() {
-            let (unit, job, _) = self.pending_queue.pop().unwrap();
+            let Some(idx) = self.next_pending_job(build_runner) else {
+                break;
+            };
+            let (unit, job, _) = self.pending_queue.remove(idx);
             *self.counts.get_mut(&unit.pkg.package_id()).unwrap() -= 1;
             if !build_runner.bcx.build_config.build_plan {
                 // Print out some nice progress information.
@@ -590,6 +687,66 @@ impl<'gctx> DrainState<'gctx> {
             self.run(&unit, job, build_runner, scope);
         }
 
+        self.start_test_if_possible(build_runner, scope)
+    }
+
+    /// Starts running the next test process, unless one is running already.
+    ///
+    /// Tests run one at a time on their own thread, just like after the
+    /// build. Their output goes straight to the terminal, unless the
+    /// [`TestRunner`] captures it.
+    ///
+    /// [`TestRunner`]: super::TestRunner
+    fn start_test_if_possible<'s>(
+        &mut self,
+        build_runner: &mut BuildRunner<'_, '_>,
+        scope: &'s Scope<'s, '_>,
+    ) -> CargoResult<()> {
+        if self.running_test {
+            return Ok(());
+        }
+        let Some(test_runner) = build_runner.bcx.build_config.test_runner.clone() else {
+            return Ok(());
+        };
+        let cmd = match self.rerun_test.take() {
+            Some(cmd) => cmd,
+            None => loop {
+                let Some(test) = self.pending_tests.pop_front() else {
+                    return Ok(());
+                };
+                self.progress.clear();
+                let cmd = test_runner.borrow_mut().start(
+                    build_runner.bcx.gctx,
+                    &test,
+                    &build_runner.compilation,
+                )?;
+                if let Some(cmd) = cmd {
+                    break cmd;
+                }
+            },
+        };
+        self.running_test = true;
+        let captures_output = test_runner.borrow().captures_output();
+        let messages = self.messages.clone();
+        scope.spawn(move || {
+            let result = if captures_output {
+                cmd.exec_with_streaming(
+                    &mut |line| {
+                        messages.push_bounded(Message::TestStdout(line.to_string()));
+                        Ok(())
+                    },
+                    &mut |line| {
+                        messages.push_bounded(Message::TestStderr(line.to_string()));
+                        Ok(())
+                    },
+                    false,
+                )
+                .map(drop)
+            } else {
+                cmd.exec()
+            };
+            messages.push(Message::TestFinished(result));
+        });
         Ok(())
     }
 
@@ -597,6 +754,51 @@ impl<'gctx> DrainState<'gctx> {
         self.active.len() < self.tokens.len() + 1
     }
 
+    /// Picks the job in `pending_queue` to start next, returning its index.
+    ///
+    /// Without a `build.memory-limit` this is always the highest priority
+    /// job. With one, this is the job expected to use the most memory among
+    /// those that still fit under the limit, or `None` if none fit. When
+    /// nothing is running the heaviest job is picked regardless, so that a
+    /// single unit above the limit can't stall the build.
+    fn next_pending_job(&self, build_runner: &BuildRunner<'_, '_>) -> Option<usize> {
+        let Some(memory_limit) = self.memory_limit else {
+            return self.pending_queue.len().checked_sub(1);
+        };
+        let in_use: u64 = self.active_memory.values().sum();
+        let estimates = self
+            .pending_queue
+            .iter()
+            .map(|(unit, job, _)| self.estimated_memory(build_runner, unit, job))
+            .enumerate();
+        // Ties are broken by the position in `pending_queue`, which keeps
+        // preferring the highest priority.
+        let weight = |&(idx, memory): &(usize, u64)| (memory, idx);
+        let fitting = estimates
+            .clone()
+            .filter(|&(_, memory)| in_use + memory <= memory_limit)
+            .max_by_key(weight);
+        let next = match fitting {
+            None if self.active.is_empty() => estimates.max_by_key(weight),
+            next => next,
+        };
+        next.map(|(idx, _)| idx)
+    }
+
+    /// The memory in bytes a job is expected to use, based on the peak usage
+    /// of its unit the last time it ran.
+    ///
+    /// Fresh jobs and units without any measurements count as zero.
+    fn estimated_memory(&self, build_runner: &BuildRunner<'_, '_>, unit: &Unit, job: &Job) -> u64 {
+        if job.freshness().is_fresh() {
+            return 0;
+        }
+        self.history
+            .as_ref()
+            .and_then(|history| history.peak_rss(build_runner.files().metadata(unit).unit_id()))
+            .unwrap_or(0)
+    }
+
     fn handle_event(
         &mut self,
         build_runner: &mut BuildRunner<'_, '_>,
@@ -668,6 +870,7 @@ impl<'gctx> DrainState<'gctx> {
                             id,
                             &build_runner.bcx.rustc().workspace_wrapper,
                         );
+                        self.active_memory.remove(&id);
                         self.active.remove(&id).unwrap()
                     }
                     // ... otherwise if it hasn't finished we leave it
@@ -704,6 +907,42 @@ impl<'gctx> DrainState<'gctx> {
                 self.per_package_future_incompat_reports
                     .push(FutureIncompatReportPackage { package_id, items });
             }
+            Message::PeakRss(id, peak_rss) => {
+                self.timings.unit_peak_rss(id, peak_rss);
+                if let Some(history) = &mut self.history {
+                    let unit_id = build_runner.files().metadata(&self.active[&id]).unit_id();
+                    history.record_peak_rss(unit_id, peak_rss);
+                }
+            }
+            Message::TestStdout(line) => {
+                let test_runner = build_runner.bcx.build_config.test_runner.as_ref().unwrap();
+                test_runner
+                    .borrow_mut()
+                    .stdout(build_runner.bcx.gctx, &line)?;
+            }
+            Message::TestStderr(line) => {
+                let test_runner = build_runner.bcx.build_config.test_runner.as_ref().unwrap();
+                test_runner
+                    .borrow_mut()
+                    .stderr(build_runner.bcx.gctx, &line)?;
+            }
+            Message::TestFinished(result) => {
+                self.running_test = false;
+                let test_runner = build_runner.bcx.build_config.test_runner.as_ref().unwrap();
+                let after = test_runner.borrow_mut().finished(
+                    build_runner.bcx.gctx,
+                    &build_runner.compilation,
+                    result,
+                )?;
+                match after {
+                    AfterTest::Rerun(cmd) => self.rerun_test = Some(cmd),
+                    AfterTest::Continue => {}
+                    AfterTest::Stop => {
+                        // The test runner has already reported the failure.
+                        return Err(AlreadyPrintedError::new(format_err!("test failed")).into());
+                    }
+                }
+            }
             Message::Token(acquired_token) => {
                 let token = acquired_token.context("failed to acquire jobserver token")?;
                 self.tokens.push(token);
@@ -725,7 +964,7 @@ impl<'gctx> DrainState<'gctx> {
         if events.is_empty() {
             loop {
                 self.tick_progress();
-                self.tokens.truncate(self.active.len() - 1);
+                self.tokens.truncate(self.active.len().saturating_sub(1));
                 match self.messages.pop(Duration::from_millis(500)) {
                     Some(message) => {
                         events.push(message);
@@ -776,7 +1015,7 @@ impl<'gctx> DrainState<'gctx> {
 
             // If after all that we're not actually running anything then we're
             // done!
-            if self.active.is_empty() {
+            if self.active.is_empty() && !self.running_test {
                 break;
             }
 
@@ -815,6 +1054,13 @@ impl<'gctx> DrainState<'gctx> {
         if let Err(e) = self.timings.finished(build_runner, &errors.to_error()) {
             self.handle_error(&mut build_runner.bcx.gctx.shell(), &mut errors, e);
         }
+        if let Some(history) = &mut self.history {
+            for (unit, duration) in self.timings.unit_durations() {
+                let unit_id = build_runner.files().metadata(unit).unit_id();
+                history.record_duration(unit_id, duration);
+            }
+            history.save(build_runner.bcx.ws);
+        }
         if build_runner.bcx.build_config.emit_json() {
             let mut shell = build_runner.bcx.gctx.shell();
             let msg = machine_message::BuildFinished {
@@ -862,7 +1108,10 @@ impl<'gctx> DrainState<'gctx> {
         let new_err = new_err.into();
         if new_err.print_always || err_state.count == 0 {
             crate::display_error(&new_err.error, shell);
-            if err_state.count == 0 && !self.active.is_empty() {
+            // A failed test reported by the `TestRunner` isn't a build
+            // failure, so don't call it one.
+            let is_build_failure = !new_err.error.is::<AlreadyPrintedError>();
+            if err_state.count == 0 && !self.active.is_empty() && is_build_failure {
                 let _ = shell.warn("build failed, waiting for other jobs to finish...");
             }
             err_state.count += 1;
@@ -885,6 +1134,12 @@ impl<'gctx> DrainState<'gctx> {
         );
         self.timings.record_cpu();
 
+        // The output of a running test goes straight to the terminal, so the
+        // progress bar would only get in the way.
+        if self.running_test {
+            return;
+        }
+
         let active_names = self
             .active
             .values()
@@ -943,6 +1198,10 @@ impl<'gctx> DrainState<'gctx> {
         debug!("start {}: {:?}", id, unit);
 
         assert!(self.active.insert(id, unit.clone()).is_none());
+        let memory = self.estimated_memory(build_runner, unit, &job);
+        if memory > 0 {
+            self.active_memory.insert(id, memory);
+        }
 
         let messages = self.messages.clone();
         let is_fresh = job.freshness().is_fresh();
@@ -1118,12 +1377,43 @@ impl<'gctx> DrainState<'gctx> {
         }
         let unlocked = self.queue.finish(unit, &artifact);
         match artifact {
-            Artifact::All => self.timings.unit_finished(id, unlocked),
+            Artifact::All => {
+                self.timings.unit_finished(id, unlocked);
+                self.enqueue_tests(unit, build_runner)?;
+            }
             Artifact::Metadata => self.timings.unit_rmeta_finished(id, unlocked),
         }
         Ok(())
     }
 
+    /// Hands the test executables of a unit that just finished building to
+    /// the [`TestRunner`], if there is one, so they can run while the rest of
+    /// the build continues.
+    ///
+    /// [`TestRunner`]: super::TestRunner
+    fn enqueue_tests(
+        &mut self,
+        unit: &Unit,
+        build_runner: &mut BuildRunner<'_, '_>,
+    ) -> CargoResult<()> {
+        if build_runner.bcx.build_config.test_runner.is_none() {
+            return Ok(());
+        }
+        if unit.mode != CompileMode::Test || !build_runner.bcx.roots.contains(unit) {
+            return Ok(());
+        }
+        // Any build script this test depends on has run by now.
+        build_runner.collect_build_script_env();
+        for output in build_runner.outputs(unit)?.iter() {
+            if output.flavor == FileFlavor::DebugInfo || output.flavor == FileFlavor::Auxiliary {
+                continue;
+            }
+            let test = build_runner.unit_output(unit, &output.path);
+            self.pending_tests.push_back(test);
+        }
+        Ok(())
+    }
+
     // This isn't super trivial because we don't want to print loads and
     // loads of information to the console, but we also want to produce a
     // faithful representation of what's happening. This is somewhat nuanced
@@ -48,11 +48,13 @@ mod links;
 mod lto;
 mod output_depinfo;
 pub mod rustdoc;
+mod sandbox;
 pub mod standard_lib;
 mod timings;
 mod unit;
 pub mod unit_dependencies;
 pub mod unit_graph;
+pub(crate) mod unit_history;
 
 use std::borrow::Cow;
 use std::collections::{HashMap, HashSet};
@@ -68,7 +70,9 @@ use anyhow::{Context as _, Error};
 use lazycell::LazyCell;
 use tracing::{debug, trace};
 
-pub use self::build_config::{BuildConfig, CompileMode, MessageFormat, TimingOutput};
+pub use self::build_config::{
+    AfterTest, BuildConfig, CompileMode, MessageFormat, TestRunner, TimingOutput,
+};
 pub use self::build_context::{
     BuildContext, FileFlavor, FileType, RustDocFingerprint, RustcTargetData, TargetInfo,
 };
@@ -85,6 +89,7 @@ use self::job_queue::{Job, JobQueue, JobState, Work};
 pub(crate) use self::layout::Layout;
 pub use self::lto::Lto;
 use self::output_depinfo::output_depinfo;
+pub use self::sandbox::{sandbox_exec, sandbox_get_policy, SandboxPolicy};
 use self::unit_graph::UnitDep;
 use crate::core::compiler::future_incompat::FutureIncompatReport;
 pub use crate::core::compiler::unit::{Unit, UnitInterner};
@@ -125,6 +130,24 @@ pub trait Executor: Send + Sync + 'static {
         on_stderr_line: &mut dyn FnMut(&str) -> CargoResult<()>,
     ) -> CargoResult<()>;
 
+    /// Like [`Executor::exec`], but also returns the peak resident set size
+    /// of the process in bytes, if it's known.
+    ///
+    /// This feeds memory-aware scheduling. The default implementation calls
+    /// [`Executor::exec`] and doesn't report anything.
+    fn exec_with_peak_rss(
+        &self,
+        cmd: &ProcessBuilder,
+        id: PackageId,
+        target: &Target,
+        mode: CompileMode,
+        on_stdout_line: &mut dyn FnMut(&str) -> CargoResult<()>,
+        on_stderr_line: &mut dyn FnMut(&str) -> CargoResult<()>,
+    ) -> CargoResult<Option<u64>> {
+        self.exec(cmd, id, target, mode, on_stdout_line, on_stderr_line)
+            .map(|()| None)
+    }
+
     /// Queried when queuing each unit of work. If it returns true, then the
     /// unit will always be rebuilt, independent of whether it needs to be.
     fn force_rebuild(&self, _unit: &Unit) -> bool {
@@ -150,6 +173,19 @@ impl Executor for DefaultExecutor {
         cmd.exec_with_streaming(on_stdout_line, on_stderr_line, false)
             .map(drop)
     }
+
+    fn exec_with_peak_rss(
+        &self,
+        cmd: &ProcessBuilder,
+        _id: PackageId,
+        _target: &Target,
+        _mode: CompileMode,
+        on_stdout_line: &mut dyn FnMut(&str) -> CargoResult<()>,
+        on_stderr_line: &mut dyn FnMut(&str) -> CargoResult<()>,
+    ) -> CargoResult<Option<u64>> {
+        cmd.exec_with_streaming_peak_rss(on_stdout_line, on_stderr_line, false)
+            .map(|(_, peak_rss)| peak_rss)
+    }
 }
 
 /// Builds up and enqueue a list of pending jobs onto the `job` queue.
@@ -393,7 +429,7 @@ fn rustc(
             state.build_plan(buildkey, rustc.clone(), outputs.clone());
         } else {
             let result = exec
-                .exec(
+                .exec_with_peak_rss(
                     &rustc,
                     package_id,
                     &target,
@@ -437,12 +473,15 @@ fn rustc(
                     format!("could not compile {name}{errors}{warnings}")
                 });
 
-            if let Err(e) = result {
-                if let Some(diagnostic) = failed_scrape_diagnostic {
-                    state.warning(diagnostic)?;
-                }
+            match result {
+                Ok(peak_rss) => state.peak_rss(peak_rss),
+                Err(e) => {
+                    if let Some(diagnostic) = failed_scrape_diagnostic {
+                        state.warning(diagnostic)?;
+                    }
 
-                return Err(e);
+                    return Err(e);
+                }
             }
 
             // Exec should never return with success *and* generate an error.
@@ -898,7 +937,7 @@ fn rustdoc(build_runner: &mut BuildRunner<'_, '_>, unit: &Unit) -> CargoResult<W
         state.running(&rustdoc);
 
         let result = rustdoc
-            .exec_with_streaming(
+            .exec_with_streaming_peak_rss(
                 &mut |line| on_stdout_line(state, line, package_id, &target),
                 &mut |line| {
                     on_stderr_line(
@@ -915,12 +954,15 @@ fn rustdoc(build_runner: &mut BuildRunner<'_, '_>, unit: &Unit) -> CargoResult<W
             .map_err(verbose_if_simple_exit_code)
             .with_context(|| format!("could not document `{}`", name));
 
-        if let Err(e) = result {
-            if let Some(diagnostic) = failed_scrape_diagnostic {
-                state.warning(diagnostic)?;
-            }
+        match result {
+            Ok((_, peak_rss)) => state.peak_rss(peak_rss),
+            Err(e) => {
+                if let Some(diagnostic) = failed_scrape_diagnostic {
+                    state.warning(diagnostic)?;
+                }
 
-            return Err(e);
+                return Err(e);
+            }
         }
 
         Ok(())
@@ -1023,6 +1065,7 @@ fn build_base_args(
         strip,
         rustflags: profile_rustflags,
         trim_paths,
+        coverage,
         ..
     } = unit.profile.clone();
     let test = unit.mode.is_any_test();
@@ -1105,6 +1148,10 @@ fn build_base_args(
         trim_paths_args(cmd, build_runner, unit, &trim_paths)?;
     }
 
+    if coverage {
+        cmd.arg("-C").arg("instrument-coverage");
+    }
+
     cmd.args(unit.pkg.manifest().lint_rustflags());
     cmd.args(&profile_rustflags);
     if !cargo_rustc_higher_args_precedence(build_runner) {
@@ -74,7 +74,7 @@ fn add_deps_for_unit(
         }
     }
 
-    // Add rerun-if-changed dependencies
+    // Add rerun-if-changed, input-file and output-file dependencies
     if let Some(metadata) = build_runner.find_build_script_metadata(unit) {
         if let Some(output) = build_runner
             .build_script_outputs
@@ -82,12 +82,13 @@ fn add_deps_for_unit(
             .unwrap()
             .get(metadata)
         {
-            for path in &output.rerun_if_changed {
+            for path in output.rerun_if_changed.iter().chain(&output.input_files) {
                 // The paths we have saved from the unit are of arbitrary relativeness and may be
                 // relative to the crate root of the dependency.
                 let path = unit.pkg.root().join(path);
                 deps.insert(path);
             }
+            deps.extend(output.output_files.iter().cloned());
         }
     }
 
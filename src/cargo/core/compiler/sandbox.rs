@@ -0,0 +1,423 @@
+//! Sandboxing of build scripts for `-Zbuild-sandbox`.
+//!
+//! Build scripts are arbitrary code running with the full privileges of the
+//! user invoking Cargo. With `build.sandbox.enable`, Cargo instead runs each
+//! build script with a restricted view of the system:
+//!
+//! - Only `OUT_DIR`, the target directory and a few devices like `/dev/null`
+//!   are writable.
+//! - Only the package source, the toolchain, system directories, dependency
+//!   sources, the previously declared `rerun-if-changed` inputs and any
+//!   `build.sandbox.allow-read` paths are readable. In particular, the rest
+//!   of the user's home directory is not.
+//! - There is no network access, unless the package is listed in
+//!   `build.sandbox.allow-net`.
+//!
+//! Packages listed in `build.sandbox.exclude` are run without a sandbox.
+//!
+//! The restrictions are applied by Cargo itself: the build script is run
+//! through the `cargo` executable with [`SANDBOX_ENV_INTERNAL`] set to the
+//! serialized [`SandboxPolicy`]. [`sandbox_get_policy`] detects this at
+//! startup, and [`sandbox_exec`] restricts the process and then replaces it
+//! with the build script. This is the same trick `cargo fix` uses to act as
+//! a rustc wrapper.
+//!
+//! Filesystem access is restricted with [Landlock], and network access by
+//! moving the build script into a new user and network namespace. Both are
+//! only available on Linux, and the build script fails if they cannot be set
+//! up, rather than silently running unsandboxed.
+//!
+//! [Landlock]: https://docs.kernel.org/userspace-api/landlock.html
+
+use std::collections::BTreeSet;
+use std::env;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::core::compiler::{BuildRunner, CompileKind, Unit};
+use crate::util::errors::CargoResult;
+
+/// Environment variable used to pass the [`SandboxPolicy`] to the `cargo`
+/// process wrapping a build script.
+const SANDBOX_ENV_INTERNAL: &str = "__CARGO_BUILD_SANDBOX";
+
+/// System directories that are readable by every build script, if they exist.
+const SYSTEM_READ_DIRS: &[&str] = &[
+    "/bin",
+    "/etc",
+    "/lib",
+    "/lib32",
+    "/lib64",
+    "/nix/store",
+    "/opt",
+    "/proc",
+    "/sbin",
+    "/sys",
+    "/usr",
+];
+
+/// Devices that are writable by every build script, if they exist. The rest
+/// of `/dev` isn't, as it has shared memory in `/dev/shm` and disks.
+const DEVICES: &[&str] = &[
+    "/dev/full",
+    "/dev/null",
+    "/dev/tty",
+    "/dev/urandom",
+    "/dev/zero",
+];
+
+/// What a sandboxed build script is allowed to access.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct SandboxPolicy {
+    /// Paths that can be read and executed.
+    read: BTreeSet<PathBuf>,
+    /// Paths that can be read, executed and modified.
+    write: BTreeSet<PathBuf>,
+    /// Whether network access is allowed.
+    net: bool,
+}
+
+impl SandboxPolicy {
+    /// Computes the policy for running the build script of `unit`.
+    ///
+    /// Returns `None` if the build script should not be sandboxed.
+    pub(crate) fn for_unit(
+        build_runner: &BuildRunner<'_, '_>,
+        unit: &Unit,
+        script_out_dir: &Path,
+        rerun_if_changed: &[PathBuf],
+    ) -> CargoResult<Option<SandboxPolicy>> {
+        let gctx = build_runner.bcx.gctx;
+        if !gctx.cli_unstable().build_sandbox {
+            return Ok(None);
+        }
+        let Some(config) = &gctx.build_config()?.sandbox else {
+            return Ok(None);
+        };
+        if !config.enable.unwrap_or(false) {
+            return Ok(None);
+        }
+        let name = unit.pkg.name();
+        let listed = |list: &Option<Vec<String>>| {
+            list.iter()
+                .flatten()
+                .any(|pkg| pkg.as_str() == name.as_str())
+        };
+        if listed(&config.exclude) {
+            return Ok(None);
+        }
+        if !cfg!(target_os = "linux") {
+            anyhow::bail!(
+                "`build.sandbox` is only supported on Linux\n\n\
+                 help: remove `build.sandbox.enable` from your config, \
+                 or add `{name}` to `build.sandbox.exclude`"
+            );
+        }
+
+        let mut read = BTreeSet::new();
+        read.extend(SYSTEM_READ_DIRS.iter().map(PathBuf::from));
+        read.insert(unit.pkg.root().to_path_buf());
+        read.extend(rerun_if_changed.iter().cloned());
+        // Dependencies may be read through `DEP_*` metadata or `include!`,
+        // and their sources live in Cargo's home. Only the source caches are
+        // exposed, as the home also holds credentials.
+        read.insert(gctx.home().as_path_unlocked().join("bin"));
+        read.insert(gctx.registry_base_path().as_path_unlocked().join("src"));
+        read.insert(gctx.git_checkouts_path().into_path_unlocked());
+        // The toolchain, for build scripts that invoke `$RUSTC` or `$CARGO`.
+        read.insert(gctx.cargo_exe()?.to_path_buf());
+        read.insert(build_runner.bcx.rustc().path.clone());
+        if let Some(wrapper) = &build_runner.bcx.rustc().wrapper {
+            read.insert(wrapper.clone());
+        }
+        read.insert(
+            build_runner
+                .bcx
+                .target_data
+                .info(CompileKind::Host)
+                .sysroot
+                .clone(),
+        );
+        if let Ok(rustup_home) = home::rustup_home() {
+            read.insert(rustup_home);
+        }
+        for path in config.allow_read.iter().flatten() {
+            read.insert(path.resolve_path(gctx));
+        }
+
+        let mut write = BTreeSet::new();
+        write.insert(script_out_dir.to_path_buf());
+        write.insert(
+            build_runner
+                .bcx
+                .ws
+                .target_dir()
+                .as_path_unlocked()
+                .to_path_buf(),
+        );
+        write.extend(DEVICES.iter().map(PathBuf::from));
+
+        Ok(Some(SandboxPolicy {
+            read,
+            write,
+            net: listed(&config.allow_net),
+        }))
+    }
+
+    /// Wraps `cmd` so that it runs in this sandbox.
+    pub(crate) fn wrap(
+        &self,
+        cmd: cargo_util::ProcessBuilder,
+        cargo_exe: &Path,
+    ) -> CargoResult<cargo_util::ProcessBuilder> {
+        let mut cmd = cmd.wrapped(Some(cargo_exe));
+        cmd.env(SANDBOX_ENV_INTERNAL, serde_json::to_string(self)?);
+        Ok(cmd)
+    }
+
+    /// A note explaining how to lift the restrictions for `pkg`, for when
+    /// its build script fails.
+    pub(crate) fn failure_help(&self, pkg: &str) -> String {
+        let mut help = format!(
+            "note: the build script of `{pkg}` was run in a sandbox, \
+             which may have caused this failure"
+        );
+        if !self.net {
+            help.push_str(&format!(
+                "\nhelp: to allow network access, add `{pkg}` to `build.sandbox.allow-net`"
+            ));
+        }
+        help.push_str(
+            "\nhelp: to allow reading files outside of the package, \
+             add them to `build.sandbox.allow-read`",
+        );
+        help.push_str(&format!(
+            "\nhelp: to run it without a sandbox, add `{pkg}` to `build.sandbox.exclude`"
+        ));
+        help
+    }
+}
+
+/// Returns the sandbox policy if `cargo` is running as a build script
+/// sandbox.
+///
+/// This is called every time `cargo` is run.
+pub fn sandbox_get_policy() -> Option<SandboxPolicy> {
+    // ALLOWED: For the internal mechanism of `-Zbuild-sandbox` only.
+    // Shouldn't be set directly by anyone.
+    #[allow(clippy::disallowed_methods)]
+    let policy = env::var(SANDBOX_ENV_INTERNAL).ok()?;
+    serde_json::from_str(&policy).ok()
+}
+
+/// Entry point for `cargo` running as a build script sandbox.
+///
+/// Restricts the current process according to `policy`, and then replaces
+/// it with the build script given as the first argument. This only returns
+/// if something went wrong.
+pub fn sandbox_exec(policy: &SandboxPolicy) -> CargoResult<()> {
+    let mut args = env::args_os().skip(1);
+    let Some(program) = args.next() else {
+        anyhow::bail!("no build script to run in the sandbox");
+    };
+    imp::restrict(policy).map_err(|e| {
+        e.context(format!(
+            "failed to sandbox build script `{}`",
+            Path::new(&program).display()
+        ))
+    })?;
+    let mut cmd = cargo_util::ProcessBuilder::new(program);
+    cmd.args(&args.collect::<Vec<_>>())
+        .env_remove(SANDBOX_ENV_INTERNAL);
+    cmd.exec_replace()
+}
+
+#[cfg(target_os = "linux")]
+mod imp {
+    //! Landlock has no wrapper in `libc` beyond the syscall numbers, so the
+    //! ABI is declared here. See `include/uapi/linux/landlock.h`.
+
+    use super::SandboxPolicy;
+    use crate::util::errors::CargoResult;
+    use anyhow::Context as _;
+    use std::ffi::CString;
+    use std::io;
+    use std::os::unix::ffi::OsStrExt;
+    use std::path::Path;
+
+    const LANDLOCK_CREATE_RULESET_VERSION: u32 = 1 << 0;
+    const LANDLOCK_RULE_PATH_BENEATH: libc::c_int = 1;
+
+    const ACCESS_FS_EXECUTE: u64 = 1 << 0;
+    const ACCESS_FS_WRITE_FILE: u64 = 1 << 1;
+    const ACCESS_FS_READ_FILE: u64 = 1 << 2;
+    const ACCESS_FS_READ_DIR: u64 = 1 << 3;
+    /// `REMOVE_DIR` through `MAKE_SYM`, all available since ABI 1.
+    const ACCESS_FS_ABI_1: u64 = (1 << 13) - 1;
+    const ACCESS_FS_REFER: u64 = 1 << 13;
+    const ACCESS_FS_TRUNCATE: u64 = 1 << 14;
+    const ACCESS_FS_IOCTL_DEV: u64 = 1 << 15;
+
+    const ACCESS_FS_READ: u64 = ACCESS_FS_EXECUTE | ACCESS_FS_READ_FILE | ACCESS_FS_READ_DIR;
+    /// Rights that may be granted on a file rather than a directory.
+    const ACCESS_FS_FILE: u64 = ACCESS_FS_EXECUTE
+        | ACCESS_FS_WRITE_FILE
+        | ACCESS_FS_READ_FILE
+        | ACCESS_FS_TRUNCATE
+        | ACCESS_FS_IOCTL_DEV;
+
+    #[repr(C)]
+    struct RulesetAttr {
+        handled_access_fs: u64,
+    }
+
+    #[repr(C, packed)]
+    struct PathBeneathAttr {
+        allowed_access: u64,
+        parent_fd: i32,
+    }
+
+    pub fn restrict(policy: &SandboxPolicy) -> CargoResult<()> {
+        if !policy.net {
+            isolate_network()?;
+        }
+        restrict_filesystem(policy)
+    }
+
+    /// Moves the process into new user and network namespaces, which leaves
+    /// it with nothing but an unconfigured loopback interface.
+    fn isolate_network() -> CargoResult<()> {
+        // SAFETY: these are plain syscalls without pointer arguments.
+        let (uid, gid) = unsafe { (libc::getuid(), libc::getgid()) };
+        // SAFETY: see above.
+        if unsafe { libc::unshare(libc::CLONE_NEWUSER | libc::CLONE_NEWNET) } != 0 {
+            return Err(io::Error::last_os_error())
+                .context("failed to create a network namespace to disable network access");
+        }
+        // Keep the same identity inside the namespace, so files are still
+        // owned by the user running the build.
+        std::fs::write("/proc/self/setgroups", "deny")
+            .and_then(|()| std::fs::write("/proc/self/uid_map", format!("{uid} {uid} 1")))
+            .and_then(|()| std::fs::write("/proc/self/gid_map", format!("{gid} {gid} 1")))
+            .context("failed to set up the user namespace")?;
+        Ok(())
+    }
+
+    fn restrict_filesystem(policy: &SandboxPolicy) -> CargoResult<()> {
+        // SAFETY: querying the ABI version takes no attributes.
+        let abi = unsafe {
+            libc::syscall(
+                libc::SYS_landlock_create_ruleset,
+                std::ptr::null::<RulesetAttr>(),
+                0,
+                LANDLOCK_CREATE_RULESET_VERSION,
+            )
+        };
+        if abi < 1 {
+            return Err(io::Error::last_os_error())
+                .context("Landlock is not supported by this kernel");
+        }
+        let mut handled = ACCESS_FS_ABI_1;
+        if abi >= 2 {
+            handled |= ACCESS_FS_REFER;
+        }
+        if abi >= 3 {
+            handled |= ACCESS_FS_TRUNCATE;
+        }
+        if abi >= 5 {
+            handled |= ACCESS_FS_IOCTL_DEV;
+        }
+
+        let attr = RulesetAttr {
+            handled_access_fs: handled,
+        };
+        // SAFETY: `attr` is valid for the size given.
+        let ruleset = unsafe {
+            libc::syscall(
+                libc::SYS_landlock_create_ruleset,
+                &attr as *const RulesetAttr,
+                std::mem::size_of::<RulesetAttr>(),
+                0,
+            )
+        };
+        if ruleset < 0 {
+            return Err(io::Error::last_os_error()).context("failed to create Landlock ruleset");
+        }
+        let ruleset = ruleset as libc::c_int;
+        let result = (|| {
+            for path in &policy.read {
+                add_rule(ruleset, path, ACCESS_FS_READ & handled)?;
+            }
+            for path in &policy.write {
+                add_rule(ruleset, path, handled)?;
+            }
+            // SAFETY: plain syscalls on a ruleset we own.
+            unsafe {
+                if libc::prctl(libc::PR_SET_NO_NEW_PRIVS, 1, 0, 0, 0) != 0 {
+                    return Err(io::Error::last_os_error()).context("failed to set `no_new_privs`");
+                }
+                if libc::syscall(libc::SYS_landlock_restrict_self, ruleset, 0) != 0 {
+                    return Err(io::Error::last_os_error())
+                        .context("failed to enforce Landlock ruleset");
+                }
+            }
+            Ok(())
+        })();
+        // SAFETY: the ruleset is no longer needed once enforced.
+        unsafe { libc::close(ruleset) };
+        result
+    }
+
+    /// Allows `access` beneath `path`. Paths that don't exist are skipped.
+    fn add_rule(ruleset: libc::c_int, path: &Path, access: u64) -> CargoResult<()> {
+        let c_path = CString::new(path.as_os_str().as_bytes())?;
+        // SAFETY: `c_path` is a valid C string.
+        let fd = unsafe { libc::open(c_path.as_ptr(), libc::O_PATH | libc::O_CLOEXEC) };
+        if fd < 0 {
+            let err = io::Error::last_os_error();
+            if err.kind() == io::ErrorKind::NotFound {
+                return Ok(());
+            }
+            return Err(err).with_context(|| format!("failed to open `{}`", path.display()));
+        }
+        let access = if path.is_dir() {
+            access
+        } else {
+            access & ACCESS_FS_FILE
+        };
+        let attr = PathBeneathAttr {
+            allowed_access: access,
+            parent_fd: fd,
+        };
+        // SAFETY: `attr` matches `LANDLOCK_RULE_PATH_BENEATH`.
+        let ret = unsafe {
+            libc::syscall(
+                libc::SYS_landlock_add_rule,
+                ruleset,
+                LANDLOCK_RULE_PATH_BENEATH,
+                &attr as *const PathBeneathAttr,
+                0,
+            )
+        };
+        let err = io::Error::last_os_error();
+        // SAFETY: `fd` was opened above.
+        unsafe { libc::close(fd) };
+        if ret != 0 {
+            return Err(err).with_context(|| {
+                format!("failed to allow access to `{}` in sandbox", path.display())
+            });
+        }
+        Ok(())
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+mod imp {
+    use super::SandboxPolicy;
+    use crate::util::errors::CargoResult;
+
+    pub fn restrict(_policy: &SandboxPolicy) -> CargoResult<()> {
+        anyhow::bail!("build script sandboxing is only supported on Linux")
+    }
+}
@@ -3,13 +3,14 @@
 use crate::core::compiler::unit_dependencies::IsArtifact;
 use crate::core::compiler::UnitInterner;
 use crate::core::compiler::{CompileKind, CompileMode, RustcTargetData, Unit};
-use crate::core::profiles::{Profiles, UnitFor};
-use crate::core::resolver::features::{CliFeatures, FeaturesFor, ResolvedFeatures};
+use crate::core::profiles::{PanicStrategy, Profiles, UnitFor};
+use crate::core::resolver::features::{CliFeatures, ResolvedFeatures};
 use crate::core::resolver::HasDevUnits;
 use crate::core::{PackageId, PackageSet, Resolve, Workspace};
 use crate::ops::{self, Packages};
 use crate::util::errors::CargoResult;
 use crate::GlobalContext;
+use cargo_platform::{Cfg, Platform};
 use std::collections::{HashMap, HashSet};
 use std::path::PathBuf;
 
@@ -20,7 +21,12 @@ pub fn parse_unstable_flag(value: Option<&str>) -> Vec<String> {
     // This is a temporary hack until there is a more principled way to
     // declare dependencies in Cargo.toml.
     let value = value.unwrap_or("std");
-    let mut crates: HashSet<&str> = value.split(',').collect();
+    expand_crates(value.split(','))
+}
+
+/// Adds the crates implied by the given set of standard library crates.
+fn expand_crates<'a>(crates: impl IntoIterator<Item = &'a str>) -> Vec<String> {
+    let mut crates: HashSet<&str> = crates.into_iter().collect();
     if crates.contains("std") {
         crates.insert("core");
         crates.insert("alloc");
@@ -33,39 +39,152 @@ pub fn parse_unstable_flag(value: Option<&str>) -> Vec<String> {
     crates.into_iter().map(|s| s.to_string()).collect()
 }
 
-pub(crate) fn std_crates(gctx: &GlobalContext, units: Option<&[Unit]>) -> Option<Vec<String>> {
-    let crates = gctx.cli_unstable().build_std.as_ref()?.clone();
+/// Determines which standard library crates to build for each of the given
+/// kinds.
+///
+/// The crates come from the first of these that is set:
+///
+/// 1. The `-Zbuild-std` flag.
+/// 2. The `build-std` key of a `[target]` table in a workspace member that
+///    matches the kind.
+/// 3. The `build-std` key of the requested profile. When `profiles` is
+///    `None`, such as for `cargo fetch`, the keys of all profiles in the
+///    workspace are combined.
+///
+/// Kinds that do not build the standard library are left out of the map.
+pub(crate) fn std_crates(
+    ws: &Workspace<'_>,
+    profiles: Option<&Profiles>,
+    target_data: &RustcTargetData<'_>,
+    kinds: &[CompileKind],
+    units: Option<&[Unit]>,
+) -> CargoResult<HashMap<CompileKind, Vec<String>>> {
+    let gctx = ws.gctx();
+    let profile_crates = match profiles {
+        Some(profiles) => profiles.build_std().map(|c| c.to_vec()),
+        None => ws.profiles().and_then(|profiles| {
+            let crates: Vec<_> = profiles
+                .get_all()
+                .values()
+                .filter_map(|p| p.build_std.as_ref())
+                .flatten()
+                .cloned()
+                .collect();
+            (!crates.is_empty()).then_some(crates)
+        }),
+    };
+    let panic_abort = profiles.is_some_and(|p| p.base_profile().panic == PanicStrategy::Abort);
 
-    // Only build libtest if it looks like it is needed.
-    let mut crates = crates.clone();
-    // If we know what units we're building, we can filter for libtest depending on the jobs.
-    if let Some(units) = units {
-        if units
-            .iter()
-            .any(|unit| unit.mode.is_rustc_test() && unit.target.harness())
+    let mut ret = HashMap::new();
+    for kind in kinds {
+        let mut crates = match &gctx.cli_unstable().build_std {
+            Some(crates) => crates.clone(),
+            None => {
+                let Some(crates) =
+                    target_crates(ws, target_data, *kind)?.or_else(|| profile_crates.clone())
+                else {
+                    continue;
+                };
+                expand_crates(crates.iter().map(String::as_str))
+            }
+        };
+
+        // With `panic = "abort"`, whether from the profile or the target
+        // spec, std needs the abort runtime instead of the unwinding one.
+        let target_panic_abort = target_data
+            .cfg(*kind)
+            .contains(&Cfg::KeyPair("panic".to_string(), "abort".to_string()));
+        if (panic_abort || target_panic_abort)
+            && crates.iter().any(|c| c == "std")
+            && !crates.iter().any(|c| c == "panic_abort")
         {
-            // Only build libtest when libstd is built (libtest depends on libstd)
-            if crates.iter().any(|c| c == "std") && !crates.iter().any(|c| c == "test") {
-                crates.push("test".to_string());
+            crates.push("panic_abort".to_string());
+        }
+
+        // Only build libtest if it looks like it is needed.
+        // If we know what units we're building, we can filter for libtest depending on the jobs.
+        if let Some(units) = units {
+            if units.iter().any(|unit| {
+                unit.kind == *kind && unit.mode.is_rustc_test() && unit.target.harness()
+            }) {
+                // Only build libtest when libstd is built (libtest depends on libstd)
+                if crates.iter().any(|c| c == "std") && !crates.iter().any(|c| c == "test") {
+                    crates.push("test".to_string());
+                }
+            }
+        } else {
+            // We don't know what jobs are going to be run, so download libtest just in case.
+            if !crates.iter().any(|c| c == "test") {
+                crates.push("test".to_string())
             }
         }
-    } else {
-        // We don't know what jobs are going to be run, so download libtest just in case.
-        if !crates.iter().any(|c| c == "test") {
-            crates.push("test".to_string())
+
+        ret.insert(*kind, crates);
+    }
+
+    Ok(ret)
+}
+
+/// Collects the `build-std` keys of the `[target]` tables in workspace
+/// members that apply to the given kind.
+fn target_crates(
+    ws: &Workspace<'_>,
+    target_data: &RustcTargetData<'_>,
+    kind: CompileKind,
+) -> CargoResult<Option<Vec<String>>> {
+    let mut ret: Option<Vec<String>> = None;
+    for member in ws.members() {
+        for (name, platform) in member.manifest().normalized_toml().target.iter().flatten() {
+            let Some(crates) = &platform.build_std else {
+                continue;
+            };
+            let platform: Platform = name.parse()?;
+            if platform.matches(target_data.short_name(&kind), target_data.cfg(kind)) {
+                let list = ret.get_or_insert_with(Vec::new);
+                for krate in crates {
+                    if !list.contains(krate) {
+                        list.push(krate.clone());
+                    }
+                }
+            }
         }
     }
+    Ok(ret)
+}
 
-    Some(crates)
+/// Returns the features to enable for the standard library, from
+/// `-Zbuild-std-features` or else the `build-std-features` key of the
+/// requested profile.
+fn std_features(gctx: &GlobalContext, profiles: Option<&Profiles>) -> Vec<String> {
+    if let Some(list) = &gctx.cli_unstable().build_std_features {
+        return list.clone();
+    }
+    if let Some(list) = profiles.and_then(|p| p.build_std_features()) {
+        return list.to_vec();
+    }
+    vec![
+        "panic-unwind".to_string(),
+        "backtrace".to_string(),
+        "default".to_string(),
+    ]
 }
 
 /// Resolve the standard library dependencies.
+///
+/// `crates` holds the crates to build for each kind, see [`std_crates`]. The
+/// resolve is locked to the `Cargo.lock` shipped with the standard library sources,
+/// so that a given toolchain always builds the same dependencies.
 pub fn resolve_std<'gctx>(
     ws: &Workspace<'gctx>,
     target_data: &mut RustcTargetData<'gctx>,
     build_config: &BuildConfig,
-    crates: &[String],
+    profiles: Option<&Profiles>,
+    crates: &HashMap<CompileKind, Vec<String>>,
 ) -> CargoResult<(PackageSet<'gctx>, Resolve, ResolvedFeatures)> {
+    let mut crates: Vec<String> = crates.values().flatten().cloned().collect();
+    crates.sort();
+    crates.dedup();
+
     if build_config.build_plan {
         ws.gctx()
             .shell()
@@ -86,8 +205,6 @@ pub fn resolve_std<'gctx>(
     let src_path = detect_sysroot_src_path(target_data)?;
     let std_ws_manifest_path = src_path.join("Cargo.toml");
     let gctx = ws.gctx();
-    // TODO: Consider doing something to enforce --locked? Or to prevent the
-    // lock file from being written, such as setting ephemeral.
     let mut std_ws = Workspace::new(&std_ws_manifest_path, gctx)?;
     // Don't require optional dependencies in this workspace, aka std's own
     // `[dev-dependencies]`. No need for us to generate a `Resolve` which has
@@ -95,22 +212,17 @@ pub fn resolve_std<'gctx>(
     std_ws.set_require_optional_deps(false);
     // `sysroot` is not in the default set because it is optional, but it needs
     // to be part of the resolve in case we do need it or `libtest`.
-    let mut spec_pkgs = Vec::from(crates);
+    let mut spec_pkgs = crates;
     spec_pkgs.push("sysroot".to_string());
     let spec = Packages::Packages(spec_pkgs);
     let specs = spec.to_package_id_specs(&std_ws)?;
-    let features = match &gctx.cli_unstable().build_std_features {
-        Some(list) => list.clone(),
-        None => vec![
-            "panic-unwind".to_string(),
-            "backtrace".to_string(),
-            "default".to_string(),
-        ],
-    };
+    let features = std_features(gctx, profiles);
     let cli_features = CliFeatures::from_command_line(
         &features, /*all_features*/ false, /*uses_default_features*/ false,
     )?;
-    let dry_run = false;
+    // The lock file of the standard library is never written to, the sources
+    // are owned by the toolchain.
+    let dry_run = true;
     let resolve = ops::resolve_ws_with_opts(
         &std_ws,
         target_data,
@@ -121,6 +233,24 @@ pub fn resolve_std<'gctx>(
         crate::core::resolver::features::ForceAllTargets::No,
         dry_run,
     )?;
+    // Like with `--locked`, the resolve must not pick anything that isn't in
+    // the lock file on disk. `workspace_resolve` can't be used for this, as it
+    // already includes whatever the resolver added.
+    if let Some(lock) = ops::load_pkg_lockfile(&std_ws)? {
+        if let Some(id) = resolve
+            .targeted_resolve
+            .iter()
+            .find(|id| !lock.contains(id))
+        {
+            anyhow::bail!(
+                "the standard library's lock file `{}` does not contain `{}`\n\
+                 The standard library must be built with the dependencies it \
+                 was locked with, check that its sources are complete and unmodified.",
+                src_path.join("Cargo.lock").display(),
+                id,
+            );
+        }
+    }
     Ok((
         resolve.pkg_set,
         resolve.targeted_resolve,
@@ -130,46 +260,48 @@ pub fn resolve_std<'gctx>(
 
 /// Generate a list of root `Unit`s for the standard library.
 ///
-/// The given slice of crate names is the root set.
+/// The given map of crate names per kind is the root set, see [`std_crates`].
 pub fn generate_std_roots(
-    crates: &[String],
+    crates: &HashMap<CompileKind, Vec<String>>,
     std_resolve: &Resolve,
     std_features: &ResolvedFeatures,
-    kinds: &[CompileKind],
     package_set: &PackageSet<'_>,
     interner: &UnitInterner,
     profiles: &Profiles,
     target_data: &RustcTargetData<'_>,
 ) -> CargoResult<HashMap<CompileKind, Vec<Unit>>> {
-    // Generate the root Units for the standard library.
-    let std_ids = crates
-        .iter()
-        .map(|crate_name| std_resolve.query(crate_name))
-        .collect::<CargoResult<Vec<PackageId>>>()?;
-    // Convert PackageId to Package.
-    let std_pkgs = package_set.get_many(std_ids)?;
     // Generate a map of Units for each kind requested.
     let mut ret = HashMap::new();
-    for pkg in std_pkgs {
-        let lib = pkg
-            .targets()
+    for (kind, crates) in crates {
+        // Generate the root Units for the standard library.
+        let std_ids = crates
             .iter()
-            .find(|t| t.is_lib())
-            .expect("std has a lib");
-        // I don't think we need to bother with Check here, the difference
-        // in time is minimal, and the difference in caching is
-        // significant.
-        let mode = CompileMode::Build;
-        let features = std_features.activated_features(pkg.package_id(), FeaturesFor::NormalOrDev);
-        for kind in kinds {
-            let list = ret.entry(*kind).or_insert_with(Vec::new);
+            .map(|crate_name| std_resolve.query(crate_name))
+            .collect::<CargoResult<Vec<PackageId>>>()?;
+        // Convert PackageId to Package.
+        let std_pkgs = package_set.get_many(std_ids)?;
+        let list = ret.entry(*kind).or_insert_with(Vec::new);
+        for pkg in std_pkgs {
+            let lib = pkg
+                .targets()
+                .iter()
+                .find(|t| t.is_lib())
+                .expect("std has a lib");
+            // I don't think we need to bother with Check here, the difference
+            // in time is minimal, and the difference in caching is
+            // significant.
+            let mode = CompileMode::Build;
             let unit_for = UnitFor::new_normal(*kind);
+            let features = std_features
+                .activated_features(pkg.package_id(), unit_for.map_to_features_for(None));
             let profile = profiles.get_profile(
                 pkg.package_id(),
                 /*is_member*/ false,
                 /*is_local*/ false,
                 unit_for,
                 *kind,
+                lib,
+                mode,
             );
             list.push(interner.intern(
                 pkg,
@@ -177,7 +309,7 @@ pub fn generate_std_roots(
                 profile,
                 *kind,
                 mode,
-                features.clone(),
+                features,
                 target_data.info(*kind).rustflags.clone(),
                 target_data.info(*kind).rustdocflags.clone(),
                 target_data.target_config(*kind).links_overrides.clone(),
@@ -7,6 +7,7 @@ use crate::core::compiler::job_queue::JobId;
 use crate::core::compiler::{BuildContext, BuildRunner, TimingOutput};
 use crate::core::PackageId;
 use crate::util::cpu::State;
+use crate::util::human_readable_bytes;
 use crate::util::machine_message::{self, Message};
 use crate::util::style;
 use crate::util::{CargoResult, GlobalContext};
@@ -26,7 +27,9 @@ use std::time::{Duration, Instant, SystemTime};
 /// [`JobQueue`]: super::JobQueue
 pub struct Timings<'gctx> {
     gctx: &'gctx GlobalContext,
-    /// Whether or not timings should be captured.
+    /// Whether or not timings should be captured. This is the case when
+    /// generating a report, or when saving the durations of units for
+    /// `-Zscheduling-history`.
     enabled: bool,
     /// If true, saves an HTML report to disk.
     report_html: bool,
@@ -79,6 +82,9 @@ struct UnitTime {
     unlocked_units: Vec<Unit>,
     /// Same as `unlocked_units`, but unlocked by rmeta.
     unlocked_rmeta_units: Vec<Unit>,
+    /// Peak resident set size in bytes of the process run for this unit, if
+    /// the platform reports it.
+    peak_rss: Option<u64>,
 }
 
 /// Periodic concurrency tracking information.
@@ -100,7 +106,8 @@ impl<'gctx> Timings<'gctx> {
         let has_report = |what| bcx.build_config.timing_outputs.contains(&what);
         let report_html = has_report(TimingOutput::Html);
         let report_json = has_report(TimingOutput::Json);
-        let enabled = report_html | report_json;
+        let report = report_html | report_json;
+        let enabled = report | bcx.gctx.cli_unstable().scheduling_history;
 
         let mut root_map: HashMap<PackageId, Vec<String>> = HashMap::new();
         for unit in root_units {
@@ -119,7 +126,7 @@ impl<'gctx> Timings<'gctx> {
             .collect();
         let start_str = humantime::format_rfc3339_seconds(SystemTime::now()).to_string();
         let profile = bcx.build_config.requested_profile.to_string();
-        let last_cpu_state = if enabled {
+        let last_cpu_state = if report {
             match State::current() {
                 Ok(state) => Some(state),
                 Err(e) => {
@@ -182,6 +189,7 @@ impl<'gctx> Timings<'gctx> {
             rmeta_time: None,
             unlocked_units: Vec::new(),
             unlocked_rmeta_units: Vec::new(),
+            peak_rss: None,
         };
         assert!(self.active.insert(id, unit_time).is_none());
     }
@@ -205,6 +213,16 @@ impl<'gctx> Timings<'gctx> {
             .extend(unlocked.iter().cloned().cloned());
     }
 
+    /// Record the peak memory usage of the process run for a unit.
+    pub fn unit_peak_rss(&mut self, id: JobId, peak_rss: u64) {
+        if !self.enabled {
+            return;
+        }
+        if let Some(unit_time) = self.active.get_mut(&id) {
+            unit_time.peak_rss = Some(peak_rss);
+        }
+    }
+
     /// Mark that a unit has finished running.
     pub fn unit_finished(&mut self, id: JobId, unlocked: Vec<&Unit>) {
         if !self.enabled {
@@ -227,6 +245,7 @@ impl<'gctx> Timings<'gctx> {
                 mode: unit_time.unit.mode,
                 duration: unit_time.duration,
                 rmeta_time: unit_time.rmeta_time,
+                peak_rss: unit_time.peak_rss,
             }
             .to_json_string();
             crate::drop_println!(self.gctx, "{}", msg);
@@ -258,6 +277,11 @@ impl<'gctx> Timings<'gctx> {
         self.total_dirty += 1;
     }
 
+    /// The duration in seconds of each unit that finished running.
+    pub fn unit_durations(&self) -> impl Iterator<Item = (&Unit, f64)> {
+        self.unit_times.iter().map(|ut| (&ut.unit, ut.duration))
+    }
+
     /// Take a sample of CPU usage
     pub fn record_cpu(&mut self) {
         if !self.enabled {
@@ -457,6 +481,7 @@ impl<'gctx> Timings<'gctx> {
             rmeta_time: Option<f64>,
             unlocked_units: Vec<usize>,
             unlocked_rmeta_units: Vec<usize>,
+            peak_rss: Option<u64>,
         }
         let round = |x: f64| (x * 100.0).round() / 100.0;
         let unit_data: Vec<UnitData> = self
@@ -495,6 +520,7 @@ impl<'gctx> Timings<'gctx> {
                     rmeta_time: ut.rmeta_time.map(round),
                     unlocked_units,
                     unlocked_rmeta_units,
+                    peak_rss: ut.peak_rss,
                 }
             })
             .collect();
@@ -528,6 +554,7 @@ impl<'gctx> Timings<'gctx> {
       <th>Unit</th>
       <th>Total</th>
       <th>Codegen</th>
+      <th>Peak memory</th>
       <th>Features</th>
     </tr>
   </thead>
@@ -541,6 +568,13 @@ impl<'gctx> Timings<'gctx> {
                 None => "".to_string(),
                 Some((_rt, ctime, cent)) => format!("{:.1}s ({:.0}%)", ctime, cent),
             };
+            let peak_rss = match unit.peak_rss {
+                None => "".to_string(),
+                Some(bytes) => {
+                    let (size, unit) = human_readable_bytes(bytes);
+                    format!("{size:.1} {unit}")
+                }
+            };
             let features = unit.unit.features.join(", ");
             write!(
                 f,
@@ -551,6 +585,7 @@ impl<'gctx> Timings<'gctx> {
   <td>{:.1}s</td>
   <td>{}</td>
   <td>{}</td>
+  <td>{}</td>
 </tr>
 "#,
                 i + 1,
@@ -558,6 +593,7 @@ impl<'gctx> Timings<'gctx> {
                 unit.target,
                 unit.duration,
                 codegen,
+                peak_rss,
                 features,
             )?;
         }
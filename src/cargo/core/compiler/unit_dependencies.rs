@@ -372,6 +372,7 @@ fn compute_deps(
                                 state.features(),
                                 state.package_set,
                                 id,
+                                unit.kind,
                             );
                             rf.iter().all(|f| features.contains(f))
                         }
@@ -400,6 +401,9 @@ fn compute_deps(
 
 /// Find artifacts for all `deps` of `unit` and add units that build these artifacts
 /// to `ret`.
+///
+/// Artifact dependencies are always enabled, so this applies to every
+/// dependency with an `artifact` key, regardless of `-Z` flags.
 fn calc_artifact_deps<'a>(
     unit: &Unit,
     unit_for: UnitFor,
@@ -489,7 +493,8 @@ fn compute_deps_custom_build(
 
     let mut result = vec![compile_script_unit];
 
-    // Include any artifact dependencies.
+    // Include any artifact dependencies. Like `calc_artifact_deps`, this
+    // doesn't depend on any unstable flag.
     //
     // This is essentially the same as `calc_artifact_deps`, but there are some
     // subtle differences that require this to be implemented differently.
@@ -817,6 +822,8 @@ fn new_unit_dep(
         is_local,
         unit_for,
         kind,
+        target,
+        mode,
     );
     new_unit_dep_with_profile(
         state, parent, pkg, target, unit_for, kind, mode, profile, artifact,
@@ -0,0 +1,209 @@
+//! Measurements of units from previous builds.
+//!
+//! Cargo doesn't know how expensive a unit is before running it. To help the
+//! [`JobQueue`] make better decisions, a few measurements of every unit that
+//! ran are saved in [`UNIT_HISTORY_FILE`] in the top-level `target`
+//! directory, keyed by the unit's [`UnitHash`]. The next build loads them
+//! back as estimates.
+//!
+//! Currently this records how long each unit took, which orders jobs with
+//! `-Zscheduling-history`, and its peak memory usage, which drives
+//! `build.memory-limit` (`-Zbuild-memory-limit`). `cargo test --partition`
+//! also records how long the tests of each test target took to run, so that
+//! a copy of the file can balance the partitions with
+//! `--partition-durations`.
+//!
+//! The file is only a cache. If it's missing, corrupt, or from a different
+//! version of Cargo, it's silently ignored and rebuilt.
+//!
+//! [`JobQueue`]: super::job_queue::JobQueue
+
+use std::collections::BTreeMap;
+use std::io::{Read, Write};
+use std::path::Path;
+
+use anyhow::{bail, Context as _};
+use cargo_util::paths;
+use serde::{Deserialize, Serialize};
+
+use crate::core::compiler::build_runner::UnitHash;
+use crate::core::Workspace;
+use crate::CargoResult;
+
+/// The filename in the top-level `target` directory where we store the
+/// measurements.
+const UNIT_HISTORY_FILE: &str = ".unit-history.json";
+
+/// The version of the on-disk format. Bump this when changing it in an
+/// incompatible way.
+const ON_DISK_VERSION: u32 = 0;
+
+/// Measurements of units, loaded from and saved to [`UNIT_HISTORY_FILE`].
+#[derive(Serialize, Deserialize)]
+pub struct UnitHistory {
+    version: u32,
+    /// Measurements keyed by the hex-formatted [`UnitHash`].
+    units: BTreeMap<String, UnitRecord>,
+    /// The average time in seconds a single test of a test target took to
+    /// run, keyed by a description of the target chosen by `cargo test`, and
+    /// the time of single tests whose harness reported it, keyed by that
+    /// description followed by the name of the test.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    tests: BTreeMap<String, f64>,
+    /// Whether anything was recorded since loading.
+    #[serde(skip)]
+    dirty: bool,
+}
+
+/// Measurements of a single unit from the last time it ran.
+#[derive(Default, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+struct UnitRecord {
+    /// How long the unit took to run, in seconds.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    duration: Option<f64>,
+    /// Peak resident set size in bytes of the process run for the unit.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    peak_rss: Option<u64>,
+}
+
+impl Default for UnitHistory {
+    fn default() -> UnitHistory {
+        UnitHistory {
+            version: ON_DISK_VERSION,
+            units: BTreeMap::new(),
+            tests: BTreeMap::new(),
+            dirty: false,
+        }
+    }
+}
+
+impl UnitHistory {
+    /// Loads the measurements from previous builds of this workspace.
+    ///
+    /// Returns an empty history if there isn't a usable one on disk.
+    pub fn load(ws: &Workspace<'_>) -> UnitHistory {
+        match Self::try_load(ws) {
+            Ok(Some(history)) if history.version == ON_DISK_VERSION => history,
+            Ok(_) => UnitHistory::default(),
+            Err(e) => {
+                tracing::debug!("failed to load unit history: {e:?}");
+                UnitHistory::default()
+            }
+        }
+    }
+
+    fn try_load(ws: &Workspace<'_>) -> CargoResult<Option<UnitHistory>> {
+        let target_dir = ws.target_dir();
+        if !target_dir
+            .as_path_unlocked()
+            .join(UNIT_HISTORY_FILE)
+            .exists()
+        {
+            return Ok(None);
+        }
+        let file = target_dir.open_ro_shared(UNIT_HISTORY_FILE, ws.gctx(), "unit history")?;
+        let mut contents = String::new();
+        file.file().read_to_string(&mut contents)?;
+        Ok(Some(serde_json::from_str(&contents)?))
+    }
+
+    /// Loads the measurements from a history file at the given path, such as
+    /// a copy of [`UNIT_HISTORY_FILE`] that several machines share.
+    ///
+    /// Unlike [`UnitHistory::load`], a file that can't be used is an error,
+    /// since it was explicitly asked for.
+    pub fn load_from(path: &Path) -> CargoResult<UnitHistory> {
+        let contents = paths::read(path)?;
+        let history: UnitHistory = serde_json::from_str(&contents)
+            .with_context(|| format!("failed to parse unit history `{}`", path.display()))?;
+        if history.version != ON_DISK_VERSION {
+            bail!(
+                "unit history `{}` has unsupported version {}, expected {ON_DISK_VERSION}",
+                path.display(),
+                history.version
+            );
+        }
+        Ok(history)
+    }
+
+    /// Saves the measurements if anything changed since they were loaded.
+    ///
+    /// Failures are only reported as a warning, since the history is just a
+    /// cache.
+    pub fn save(&self, ws: &Workspace<'_>) {
+        if !self.dirty {
+            return;
+        }
+        let on_disk = serde_json::to_vec(self).unwrap();
+        if let Err(e) = ws
+            .target_dir()
+            .open_rw_exclusive_create(UNIT_HISTORY_FILE, ws.gctx(), "unit history")
+            .and_then(|file| {
+                let mut file = file.file();
+                file.set_len(0)?;
+                file.write_all(&on_disk)?;
+                Ok(())
+            })
+        {
+            crate::display_warning_with_error(
+                "failed to write unit history",
+                &e,
+                &mut ws.gctx().shell(),
+            );
+        }
+    }
+
+    /// How long the unit took in seconds the last time it ran.
+    pub fn duration(&self, unit_id: UnitHash) -> Option<f64> {
+        self.units.get(&unit_id.to_string())?.duration
+    }
+
+    /// The average of all recorded durations in seconds, used as an estimate
+    /// for units that haven't run yet.
+    pub fn mean_duration(&self) -> Option<f64> {
+        let durations: Vec<f64> = self.units.values().filter_map(|r| r.duration).collect();
+        if durations.is_empty() {
+            return None;
+        }
+        Some(durations.iter().sum::<f64>() / durations.len() as f64)
+    }
+
+    /// Records how long in seconds a unit that just ran took.
+    pub fn record_duration(&mut self, unit_id: UnitHash, duration: f64) {
+        let record = self.units.entry(unit_id.to_string()).or_default();
+        if record.duration != Some(duration) {
+            record.duration = Some(duration);
+            self.dirty = true;
+        }
+    }
+
+    /// The time in seconds a test took the last time it ran, or the average
+    /// of a single test of a test target.
+    pub fn test_duration(&self, test: &str) -> Option<f64> {
+        self.tests.get(test).copied()
+    }
+
+    /// Records the time in seconds a test took to run, or the average of a
+    /// single test of a test target.
+    pub fn record_test_duration(&mut self, test: &str, duration: f64) {
+        if self.tests.get(test) != Some(&duration) {
+            self.tests.insert(test.to_string(), duration);
+            self.dirty = true;
+        }
+    }
+
+    /// The peak memory usage in bytes of the unit the last time it ran.
+    pub fn peak_rss(&self, unit_id: UnitHash) -> Option<u64> {
+        self.units.get(&unit_id.to_string())?.peak_rss
+    }
+
+    /// Records the peak memory usage in bytes of a unit that just ran.
+    pub fn record_peak_rss(&mut self, unit_id: UnitHash, peak_rss: u64) {
+        let record = self.units.entry(unit_id.to_string()).or_default();
+        if record.peak_rss != Some(peak_rss) {
+            record.peak_rss = Some(peak_rss);
+            self.dirty = true;
+        }
+    }
+}
@@ -519,6 +519,15 @@ features! {
 
     /// Allow paths that resolve relatively to a base specified in the config.
     (unstable, path_bases, "", "reference/unstable.html#path-bases"),
+
+    /// Allow building the standard library from `[profile]` and `[target]` tables.
+    (unstable, build_std, "", "reference/unstable.html#build-std"),
+
+    /// Allow overriding profile settings for kinds of targets, like `[profile.dev.bin]`.
+    (unstable, profile_target_overrides, "", "reference/unstable.html#profile-target-overrides"),
+
+    /// Allow globs of package names in profile package overrides, like `[profile.dev.package."aws-*"]`.
+    (unstable, profile_package_globs, "", "reference/unstable.html#profile-package-globs"),
 }
 
 /// Status and metadata for a single unstable feature.
@@ -758,7 +767,9 @@ unstable_cli_options!(
     asymmetric_token: bool = ("Allows authenticating with asymmetric tokens"),
     avoid_dev_deps: bool = ("Avoid installing dev-dependencies if possible"),
     binary_dep_depinfo: bool = ("Track changes to dependency artifacts"),
-    bindeps: bool = ("Allow Cargo packages to depend on bin, cdylib, and staticlib crates, and use the artifacts built by those crates"),
+    build_memory_limit: bool = ("Limit parallel jobs by their expected memory usage, configured by `build.memory-limit`"),
+    build_sandbox: bool = ("Run build scripts in a sandbox configured by `build.sandbox`"),
+    build_script_instructions: bool = ("Enable new `cargo::` instructions for build scripts"),
     #[serde(deserialize_with = "deserialize_build_std")]
     build_std: Option<Vec<String>>  = ("Enable Cargo to compile the standard library itself as part of a crate graph compilation"),
     build_std_features: Option<Vec<String>>  = ("Configure features enabled for the standard library itself when building the standard library"),
@@ -766,6 +777,7 @@ unstable_cli_options!(
     checksum_freshness: bool = ("Use a checksum to determine if output is fresh rather than filesystem mtime"),
     codegen_backend: bool = ("Enable the `codegen-backend` option in profiles in .cargo/config.toml file"),
     config_include: bool = ("Enable the `include` key in config files"),
+    coverage: bool = ("Enable the `coverage` option in profiles and `cargo test --coverage`"),
     direct_minimal_versions: bool = ("Resolve minimal dependency versions instead of maximum (direct dependencies only)"),
     doctest_xcompile: bool = ("Compile and run doctests for non-host target using runner config"),
     dual_proc_macros: bool = ("Build proc-macros for both the host and the target"),
@@ -773,26 +785,37 @@ unstable_cli_options!(
     gc: bool = ("Track cache usage and \"garbage collect\" unused files"),
     #[serde(deserialize_with = "deserialize_git_features")]
     git: Option<GitFeatures> = ("Enable support for shallow git fetch operations"),
+    git_verify_signature: bool = ("Enable the `git.verify-signature` config to require signed commits or tags for git dependencies"),
     #[serde(deserialize_with = "deserialize_gitoxide_features")]
     gitoxide: Option<GitoxideFeatures> = ("Use gitoxide for the given git interactions, or all of them if no argument is given"),
     host_config: bool = ("Enable the `[host]` section in the .cargo/config.toml file"),
+    json_test_events: bool = ("Emit a message for every test with `cargo test --message-format=json`"),
     minimal_versions: bool = ("Resolve minimal dependency versions instead of maximum"),
     msrv_policy: bool = ("Enable rust-version aware policy within cargo"),
     mtime_on_use: bool = ("Configure Cargo to update the mtime of used files"),
     next_lockfile_bump: bool,
     no_index_update: bool = ("Do not update the registry index even if the cache is outdated"),
+    package_signing: bool = ("Sign packages when publishing and verify their signatures when downloading"),
     package_workspace: bool = ("Handle intra-workspace dependencies when packaging"),
     panic_abort_tests: bool = ("Enable support to run tests with -Cpanic=abort"),
+    pipelined_tests: bool = ("Run test executables as soon as they are built, while the rest of the build continues"),
+    profile_package_globs: bool = ("Enable globs of package names in profile package overrides in .cargo/config.toml file"),
     profile_rustflags: bool = ("Enable the `rustflags` option in profiles in .cargo/config.toml file"),
+    profile_target_overrides: bool = ("Enable overrides for kinds of targets in profiles in .cargo/config.toml file"),
     public_dependency: bool = ("Respect a dependency's `public` field in Cargo.toml to control public/private dependencies"),
     publish_timeout: bool = ("Enable the `publish.timeout` key in .cargo/config.toml file"),
+    registry_owns: bool = ("Allow registries to claim package names with `owns` in their configuration"),
     root_dir: Option<PathBuf> = ("Set the root directory relative to which paths are printed (defaults to workspace root)"),
+    runner_kinds: bool = ("Enable a `runner` for each kind of executable, like `runner.test`, in `[target]` tables"),
     rustdoc_map: bool = ("Allow passing external documentation mappings to rustdoc"),
     rustdoc_scrape_examples: bool = ("Allows Rustdoc to scrape code examples from reverse-dependencies"),
+    scheduling_history: bool = ("Order jobs using how long units took in previous builds"),
     script: bool = ("Enable support for single-file, `.rs` packages"),
     separate_nightlies: bool,
+    shared_registry_src: bool = ("Share extracted registry sources with the same checksum between registries"),
     skip_rustdoc_fingerprint: bool,
     target_applies_to_host: bool = ("Enable the `target-applies-to-host` key in the .cargo/config.toml file"),
+    test_quarantine: bool = ("Enable the `test.quarantine` config key for tests whose failures don't fail `cargo test`"),
     trim_paths: bool = ("Enable the `trim-paths` option in profiles"),
     unstable_options: bool = ("Allow the usage of unstable options"),
     warnings: bool = ("Allow use of the build.warnings config key"),
@@ -873,6 +896,9 @@ const STABILIZED_LINTS: &str = "The `[lints]` table is now always available.";
 const STABILIZED_CHECK_CFG: &str =
     "Compile-time checking of conditional (a.k.a. `-Zcheck-cfg`) is now always enabled.";
 
+const STABILIZED_BINDEPS: &str =
+    "Artifact dependencies (`artifact = …` in dependencies) are now always available.";
+
 fn deserialize_build_std<'de, D>(deserializer: D) -> Result<Option<Vec<String>>, D::Error>
 where
     D: serde::Deserializer<'de>,
@@ -1248,6 +1274,7 @@ impl CliUnstable {
             "lints" => stabilized_warn(k, "1.74", STABILIZED_LINTS),
             "registry-auth" => stabilized_warn(k, "1.74", STABILIZED_REGISTRY_AUTH),
             "check-cfg" => stabilized_warn(k, "1.80", STABILIZED_CHECK_CFG),
+            "bindeps" => stabilized_warn(k, "1.85", STABILIZED_BINDEPS),
 
             // Unstable features
             // Sorted alphabetically:
@@ -1255,7 +1282,9 @@ impl CliUnstable {
             "asymmetric-token" => self.asymmetric_token = parse_empty(k, v)?,
             "avoid-dev-deps" => self.avoid_dev_deps = parse_empty(k, v)?,
             "binary-dep-depinfo" => self.binary_dep_depinfo = parse_empty(k, v)?,
-            "bindeps" => self.bindeps = parse_empty(k, v)?,
+            "build-memory-limit" => self.build_memory_limit = parse_empty(k, v)?,
+            "build-sandbox" => self.build_sandbox = parse_empty(k, v)?,
+            "build-script-instructions" => self.build_script_instructions = parse_empty(k, v)?,
             "build-std" => {
                 self.build_std = Some(crate::core::compiler::standard_lib::parse_unstable_flag(v))
             }
@@ -1263,6 +1292,7 @@ impl CliUnstable {
             "cargo-lints" => self.cargo_lints = parse_empty(k, v)?,
             "codegen-backend" => self.codegen_backend = parse_empty(k, v)?,
             "config-include" => self.config_include = parse_empty(k, v)?,
+            "coverage" => self.coverage = parse_empty(k, v)?,
             "direct-minimal-versions" => self.direct_minimal_versions = parse_empty(k, v)?,
             "doctest-xcompile" => self.doctest_xcompile = parse_empty(k, v)?,
             "dual-proc-macros" => self.dual_proc_macros = parse_empty(k, v)?,
@@ -1273,6 +1303,7 @@ impl CliUnstable {
                     |v| parse_git(v.split(',')),
                 )?
             }
+            "git-verify-signature" => self.git_verify_signature = parse_empty(k, v)?,
             "gitoxide" => {
                 self.gitoxide = v.map_or_else(
                     || Ok(Some(GitoxideFeatures::all())),
@@ -1280,26 +1311,36 @@ impl CliUnstable {
                 )?
             }
             "host-config" => self.host_config = parse_empty(k, v)?,
+            "json-test-events" => self.json_test_events = parse_empty(k, v)?,
             "next-lockfile-bump" => self.next_lockfile_bump = parse_empty(k, v)?,
             "minimal-versions" => self.minimal_versions = parse_empty(k, v)?,
             "msrv-policy" => self.msrv_policy = parse_empty(k, v)?,
             // can also be set in .cargo/config or with and ENV
             "mtime-on-use" => self.mtime_on_use = parse_empty(k, v)?,
             "no-index-update" => self.no_index_update = parse_empty(k, v)?,
+            "package-signing" => self.package_signing = parse_empty(k, v)?,
             "package-workspace" => self.package_workspace= parse_empty(k, v)?,
             "panic-abort-tests" => self.panic_abort_tests = parse_empty(k, v)?,
+            "pipelined-tests" => self.pipelined_tests = parse_empty(k, v)?,
             "public-dependency" => self.public_dependency = parse_empty(k, v)?,
+            "profile-package-globs" => self.profile_package_globs = parse_empty(k, v)?,
             "profile-rustflags" => self.profile_rustflags = parse_empty(k, v)?,
+            "profile-target-overrides" => self.profile_target_overrides = parse_empty(k, v)?,
             "trim-paths" => self.trim_paths = parse_empty(k, v)?,
             "publish-timeout" => self.publish_timeout = parse_empty(k, v)?,
+            "registry-owns" => self.registry_owns = parse_empty(k, v)?,
             "root-dir" => self.root_dir = v.map(|v| v.into()),
+            "runner-kinds" => self.runner_kinds = parse_empty(k, v)?,
             "rustdoc-map" => self.rustdoc_map = parse_empty(k, v)?,
             "rustdoc-scrape-examples" => self.rustdoc_scrape_examples = parse_empty(k, v)?,
             "separate-nightlies" => self.separate_nightlies = parse_empty(k, v)?,
+            "shared-registry-src" => self.shared_registry_src = parse_empty(k, v)?,
             "checksum-freshness" => self.checksum_freshness = parse_empty(k, v)?,
             "skip-rustdoc-fingerprint" => self.skip_rustdoc_fingerprint = parse_empty(k, v)?,
+            "scheduling-history" => self.scheduling_history = parse_empty(k, v)?,
             "script" => self.script = parse_empty(k, v)?,
             "target-applies-to-host" => self.target_applies_to_host = parse_empty(k, v)?,
+            "test-quarantine" => self.test_quarantine = parse_empty(k, v)?,
             "unstable-options" => self.unstable_options = parse_empty(k, v)?,
             "warnings" => self.warnings = parse_empty(k, v)?,
             _ => bail!("\
@@ -1313,35 +1354,30 @@ impl CliUnstable {
 
     /// Generates an error if `-Z unstable-options` was not used for a new,
     /// unstable command-line flag.
-    pub fn fail_if_stable_opt(&self, flag: &str, issue: u32) -> CargoResult<()> {
+    ///
+    /// `issue` is the tracking issue of the flag, if it has one.
+    pub fn fail_if_stable_opt(&self, flag: &str, issue: impl Into<Option<u32>>) -> CargoResult<()> {
         self.fail_if_stable_opt_custom_z(flag, issue, "unstable-options", self.unstable_options)
     }
 
     pub fn fail_if_stable_opt_custom_z(
         &self,
         flag: &str,
-        issue: u32,
+        issue: impl Into<Option<u32>>,
         z_name: &str,
         enabled: bool,
     ) -> CargoResult<()> {
         if !enabled {
-            let see = format!(
-                "See https://github.com/rust-lang/cargo/issues/{issue} for more \
-                 information about the `{flag}` flag."
-            );
+            let see = see_issue(issue.into(), &format!("the `{flag}` flag"));
             // NOTE: a `config` isn't available here, check the channel directly
             let channel = channel();
             if channel == "nightly" || channel == "dev" {
-                bail!(
-                    "the `{flag}` flag is unstable, pass `-Z {z_name}` to enable it\n\
-                     {see}"
-                );
+                bail!("the `{flag}` flag is unstable, pass `-Z {z_name}` to enable it{see}");
             } else {
                 bail!(
                     "the `{flag}` flag is unstable, and only available on the nightly channel \
                      of Cargo, but this is the `{channel}` channel\n\
-                     {SEE_CHANNELS}\n\
-                     {see}"
+                     {SEE_CHANNELS}{see}"
                 );
             }
         }
@@ -1354,30 +1390,24 @@ impl CliUnstable {
         &self,
         gctx: &GlobalContext,
         command: &str,
-        issue: u32,
+        issue: impl Into<Option<u32>>,
         z_name: &str,
         enabled: bool,
     ) -> CargoResult<()> {
         if enabled {
             return Ok(());
         }
-        let see = format!(
-            "See https://github.com/rust-lang/cargo/issues/{} for more \
-            information about the `cargo {}` command.",
-            issue, command
-        );
+        let see = see_issue(issue.into(), &format!("the `cargo {command}` command"));
         if gctx.nightly_features_allowed {
             bail!(
                 "the `cargo {command}` command is unstable, pass `-Z {z_name}` \
-                 to enable it\n\
-                 {see}",
+                 to enable it{see}",
             );
         } else {
             bail!(
                 "the `cargo {}` command is unstable, and only available on the \
                  nightly channel of Cargo, but this is the `{}` channel\n\
-                 {}\n\
-                 {}",
+                 {}{}",
                 command,
                 channel(),
                 SEE_CHANNELS,
@@ -1387,6 +1417,18 @@ impl CliUnstable {
     }
 }
 
+/// Points to the tracking issue of an unstable feature on a line of its own,
+/// or nothing when it doesn't have one yet.
+fn see_issue(issue: Option<u32>, what: &str) -> String {
+    match issue {
+        Some(issue) => format!(
+            "\nSee https://github.com/rust-lang/cargo/issues/{issue} for more \
+             information about {what}."
+        ),
+        None => String::new(),
+    }
+}
+
 /// Returns the current release channel ("stable", "beta", "nightly", "dev").
 pub fn channel() -> String {
     // ALLOWED: For testing cargo itself only.
@@ -18,6 +18,9 @@
 //! implemented in the [`crate::core::global_cache_tracker`] module. See that
 //! module documentation for an in-depth explanation of how global cache
 //! tracking works.
+//!
+//! Garbage collection for the target directory is only done manually, and is
+//! guided by the tracking in [`crate::core::target_cache_tracker`].
 
 use crate::core::global_cache_tracker::{self, GlobalCacheTracker};
 use crate::ops::CleanContext;
@@ -135,6 +138,10 @@ pub struct GcOpts {
     pub max_git_size: Option<u64>,
     /// The `--max-download-size` CLI option.
     pub max_download_size: Option<u64>,
+    /// The `--max-unit-age` CLI option.
+    pub max_unit_age: Option<Duration>,
+    /// The `--max-target-size` CLI option.
+    pub max_target_size: Option<u64>,
 }
 
 impl GcOpts {
@@ -151,6 +158,11 @@ impl GcOpts {
             || self.max_download_size.is_some()
     }
 
+    /// Returns whether any target directory cleaning options are set.
+    pub fn is_target_opt_set(&self) -> bool {
+        self.max_unit_age.is_some() || self.max_target_size.is_some()
+    }
+
     /// Returns whether any download cache cleaning options based on size are set.
     pub fn is_download_cache_size_set(&self) -> bool {
         self.max_src_size.is_some()
@@ -289,7 +301,8 @@ impl<'a, 'gctx> Gc<'a, 'gctx> {
     /// Performs garbage collection based on the given options.
     pub fn gc(&mut self, clean_ctx: &mut CleanContext<'gctx>, gc_opts: &GcOpts) -> CargoResult<()> {
         self.global_cache_tracker.clean(clean_ctx, gc_opts)?;
-        // In the future, other gc operations go here, such as target cleaning.
+        // Cleaning the target directory is done separately with
+        // `target_cache_tracker::clean`, since it needs a workspace.
         Ok(())
     }
 }
@@ -125,7 +125,7 @@ use crate::{CargoResult, GlobalContext};
 use anyhow::{bail, Context as _};
 use cargo_util::paths;
 use rusqlite::{params, Connection, ErrorCode};
-use std::collections::{hash_map, HashMap};
+use std::collections::{hash_map, HashMap, HashSet};
 use std::path::{Path, PathBuf};
 use std::time::{Duration, SystemTime};
 use tracing::{debug, trace};
@@ -209,6 +209,17 @@ pub struct GitCheckout {
     pub size: Option<u64>,
 }
 
+/// The key for a verified git signature entry stored in the database.
+#[derive(Clone, Debug, Hash, Eq, PartialEq)]
+pub struct GitSignature {
+    /// A unique name of the git database.
+    pub encoded_git_name: InternedString,
+    /// The full commit hash that was verified.
+    pub rev: InternedString,
+    /// Identifies the set of keys the signature was checked against.
+    pub signers: InternedString,
+}
+
 /// Filesystem paths in the global cache.
 ///
 /// Accessing these assumes a lock has already been acquired.
@@ -223,6 +234,8 @@ struct BasePaths {
     crate_dir: PathBuf,
     /// Root path to the `src` directories.
     src: PathBuf,
+    /// Root path to the shared store of `src` directories.
+    src_store: PathBuf,
 }
 
 /// Migrations which initialize the database, and can be used to evolve it over time.
@@ -303,6 +316,24 @@ fn migrations() -> Vec<Migration> {
             )?;
             Ok(())
         }),
+        // Git revisions whose signature has been successfully verified with
+        // `-Zgit-verify-signature`. `signers` identifies the set of trusted
+        // keys, so changing them causes revisions to be verified again.
+        basic_migration(
+            "CREATE TABLE git_signature (
+                git_id INTEGER NOT NULL,
+                rev TEXT NOT NULL,
+                signers TEXT NOT NULL,
+                timestamp INTEGER NOT NULL,
+                PRIMARY KEY (git_id, rev, signers),
+                FOREIGN KEY (git_id) REFERENCES git_db (id) ON DELETE CASCADE
+             )",
+        ),
+        // With `-Zshared-registry-src`, a src directory may be a symlink to
+        // an entry in the shared store, named by the checksum of the
+        // `.crate` file. The store entries themselves aren't tracked. They
+        // are kept for as long as any src directory refers to them.
+        basic_migration("ALTER TABLE registry_src ADD COLUMN store TEXT"),
     ]
 }
 
@@ -512,6 +543,24 @@ impl GlobalCacheTracker {
         Ok(rows)
     }
 
+    /// Returns whether the given git revision has previously had its signature
+    /// verified against the same set of signers.
+    pub fn git_signature_verified(&self, signature: &GitSignature) -> CargoResult<bool> {
+        let mut stmt = self.conn.prepare_cached(
+            "SELECT 1 FROM git_db, git_signature
+             WHERE git_signature.git_id = git_db.id
+               AND git_db.name = ?1
+               AND git_signature.rev = ?2
+               AND git_signature.signers = ?3",
+        )?;
+        let found = stmt.exists(params![
+            signature.encoded_git_name,
+            signature.rev,
+            signature.signers
+        ])?;
+        Ok(found)
+    }
+
     /// Returns whether or not an auto GC should be performed, compared to the
     /// last time it was recorded in the database.
     pub fn should_run_auto_gc(&mut self, frequency: Duration) -> CargoResult<bool> {
@@ -559,6 +608,7 @@ impl GlobalCacheTracker {
             git_co: gctx.git_checkouts_path().into_path_unlocked(),
             crate_dir: gctx.registry_cache_path().into_path_unlocked(),
             src: gctx.registry_source_path().into_path_unlocked(),
+            src_store: gctx.registry_src_store_path().into_path_unlocked(),
         };
         let now = now();
         trace!(target: "gc", "cleaning {gc_opts:?}");
@@ -646,6 +696,11 @@ impl GlobalCacheTracker {
         if let Some(max_size) = gc_opts.max_download_size {
             Self::get_registry_items_to_clean_size_both(&tx, max_size, &base, &mut delete_paths)?;
         }
+        // This must come last, so that store entries are removed along with
+        // the last src directory referring to them.
+        if gc_opts.is_download_cache_opt_set() {
+            Self::get_registry_src_store_to_clean(&tx, &base, &mut delete_paths)?;
+        }
 
         clean_ctx.remove_paths(&delete_paths)?;
 
@@ -771,6 +826,7 @@ impl GlobalCacheTracker {
             &base.git_co,
             sync_size,
         )?;
+        Self::populate_untracked_src_store(conn, base)?;
 
         // Update any NULL sizes if needed.
         if sync_size {
@@ -916,6 +972,42 @@ impl GlobalCacheTracker {
         Ok(())
     }
 
+    /// Fills in the `store` column for src directories that link to the
+    /// shared store but aren't recorded as such, for example because they
+    /// were added by [`Self::populate_untracked`].
+    ///
+    /// Otherwise the store entry would be removed while still in use.
+    #[tracing::instrument(skip_all)]
+    fn populate_untracked_src_store(conn: &Connection, base: &BasePaths) -> CargoResult<()> {
+        trace!(target: "gc", "populating untracked src store links");
+        let mut select_stmt = conn.prepare_cached(
+            "SELECT registry_src.rowid, registry_index.name, registry_src.name
+             FROM registry_index, registry_src
+             WHERE registry_src.registry_id = registry_index.id
+               AND registry_src.store IS NULL",
+        )?;
+        let mut update_stmt =
+            conn.prepare_cached("UPDATE registry_src SET store = ?2 WHERE rowid = ?1")?;
+        let mut rows = select_stmt.query([])?;
+        while let Some(row) = rows.next()? {
+            let rowid: i64 = row.get_unwrap(0);
+            let id_name: String = row.get_unwrap(1);
+            let name: String = row.get_unwrap(2);
+            let Ok(target) = base.src.join(id_name).join(name).read_link() else {
+                continue;
+            };
+            // The target is `../../src-store/<checksum>/<name>`.
+            let store = target
+                .parent()
+                .and_then(|p| p.file_name())
+                .and_then(|p| p.to_str());
+            if let Some(store) = store {
+                update_stmt.execute(params![rowid, store])?;
+            }
+        }
+        Ok(())
+    }
+
     /// Updates the database to add any files that are currently not tracked
     /// (such as when they are downloaded by an older version of cargo).
     #[tracing::instrument(skip(conn, now, gctx, base_path, populate_size))]
@@ -1313,6 +1405,27 @@ impl GlobalCacheTracker {
         Ok(())
     }
 
+    /// Adds paths to delete from the shared store of src directories for
+    /// entries that no src directory refers to anymore.
+    fn get_registry_src_store_to_clean(
+        conn: &Connection,
+        base: &BasePaths,
+        delete_paths: &mut Vec<PathBuf>,
+    ) -> CargoResult<()> {
+        debug!(target: "gc", "cleaning unreferenced src store entries");
+        let mut stmt =
+            conn.prepare_cached("SELECT DISTINCT store FROM registry_src WHERE store IS NOT NULL")?;
+        let referenced = stmt
+            .query_map([], |row| row.get::<_, String>(0))?
+            .collect::<Result<HashSet<_>, _>>()?;
+        for name in Self::names_from(&base.src_store)? {
+            if !referenced.contains(&name) {
+                delete_paths.push(base.src_store.join(name));
+            }
+        }
+        Ok(())
+    }
+
     /// Adds paths to delete from `git_db` in order to keep the total size
     /// under the given max size.
     fn get_git_db_items_to_clean(
@@ -1420,10 +1533,15 @@ pub struct DeferredGlobalLastUse {
     registry_crate_timestamps: HashMap<RegistryCrate, Timestamp>,
     /// New registry src directory entries to insert.
     registry_src_timestamps: HashMap<RegistrySrc, Timestamp>,
+    /// Newly created src directories, along with the shared store entry
+    /// they link to, if any.
+    registry_src_stores: HashMap<RegistrySrc, Option<InternedString>>,
     /// New git db entries to insert.
     git_db_timestamps: HashMap<GitDb, Timestamp>,
     /// New git checkout entries to insert.
     git_checkout_timestamps: HashMap<GitCheckout, Timestamp>,
+    /// New verified git signature entries to insert.
+    git_signature_timestamps: HashMap<GitSignature, Timestamp>,
     /// This is used so that a warning about failing to update the database is
     /// only displayed once.
     save_err_has_warned: bool,
@@ -1440,8 +1558,10 @@ impl DeferredGlobalLastUse {
             registry_index_timestamps: HashMap::new(),
             registry_crate_timestamps: HashMap::new(),
             registry_src_timestamps: HashMap::new(),
+            registry_src_stores: HashMap::new(),
             git_db_timestamps: HashMap::new(),
             git_checkout_timestamps: HashMap::new(),
+            git_signature_timestamps: HashMap::new(),
             save_err_has_warned: false,
             now: now(),
         }
@@ -1453,14 +1573,17 @@ impl DeferredGlobalLastUse {
             && self.registry_src_timestamps.is_empty()
             && self.git_db_timestamps.is_empty()
             && self.git_checkout_timestamps.is_empty()
+            && self.git_signature_timestamps.is_empty()
     }
 
     fn clear(&mut self) {
         self.registry_index_timestamps.clear();
         self.registry_crate_timestamps.clear();
         self.registry_src_timestamps.clear();
+        self.registry_src_stores.clear();
         self.git_db_timestamps.clear();
         self.git_checkout_timestamps.clear();
+        self.git_signature_timestamps.clear();
     }
 
     /// Indicates the given [`RegistryIndex`] has been used right now.
@@ -1482,6 +1605,19 @@ impl DeferredGlobalLastUse {
         self.mark_registry_src_used_stamp(registry_src, None);
     }
 
+    /// Indicates the given [`RegistrySrc`] has just been created, either as
+    /// a directory of its own or as a link to the shared store entry `store`.
+    ///
+    /// Also implicitly marks the index used, too.
+    pub fn mark_registry_src_linked(
+        &mut self,
+        registry_src: RegistrySrc,
+        store: Option<InternedString>,
+    ) {
+        self.registry_src_stores.insert(registry_src.clone(), store);
+        self.mark_registry_src_used(registry_src);
+    }
+
     /// Indicates the given [`GitCheckout`] has been used right now.
     ///
     /// Also implicitly marks the git db used, too.
@@ -1489,6 +1625,18 @@ impl DeferredGlobalLastUse {
         self.mark_git_checkout_used_stamp(git_checkout, None);
     }
 
+    /// Indicates the given [`GitSignature`] has been verified right now.
+    ///
+    /// Also implicitly marks the git db used, too.
+    pub fn mark_git_signature_verified(&mut self, git_signature: GitSignature) {
+        let db = GitDb {
+            encoded_git_name: git_signature.encoded_git_name,
+        };
+        self.git_db_timestamps.insert(db, self.now);
+        self.git_signature_timestamps
+            .insert(git_signature, self.now);
+    }
+
     /// Indicates the given [`RegistryIndex`] has been used with the given
     /// time (or "now" if `None`).
     pub fn mark_registry_index_used_stamp(
@@ -1569,6 +1717,7 @@ impl DeferredGlobalLastUse {
         self.insert_registry_crate_from_cache(&tx)?;
         self.insert_registry_src_from_cache(&tx)?;
         self.insert_git_checkout_from_cache(&tx)?;
+        self.insert_git_signature_from_cache(&tx)?;
         tx.commit()?;
         trace!(target: "gc", "last-use save complete");
         Ok(())
@@ -1680,6 +1829,25 @@ impl DeferredGlobalLastUse {
             ])?;
         }
 
+        // A src directory that was created again may have a different size
+        // and refer to a different store entry, or none at all, than what
+        // was recorded before.
+        let registry_src_stores = std::mem::take(&mut self.registry_src_stores);
+        for (registry_src, store) in registry_src_stores {
+            trace!(target: "gc", "link registry src {registry_src:?} to {store:?}");
+            let registry_id = self.registry_id(conn, registry_src.encoded_registry_name)?;
+            let mut stmt = conn.prepare_cached(
+                "UPDATE registry_src SET size = ?3, store = ?4
+                 WHERE registry_id = ?1 AND name = ?2",
+            )?;
+            stmt.execute(params![
+                registry_id,
+                registry_src.package_dir,
+                registry_src.size,
+                store
+            ])?;
+        }
+
         Ok(())
     }
 
@@ -1707,6 +1875,31 @@ impl DeferredGlobalLastUse {
         Ok(())
     }
 
+    /// Flushes all of the `git_signature_timestamps` to the database,
+    /// clearing `git_signature_timestamps`.
+    fn insert_git_signature_from_cache(&mut self, conn: &Connection) -> CargoResult<()> {
+        let git_signature_timestamps = std::mem::take(&mut self.git_signature_timestamps);
+        for (git_signature, timestamp) in git_signature_timestamps {
+            trace!(target: "gc", "insert git signature {git_signature:?} {timestamp}");
+            let git_id = self.git_id(conn, git_signature.encoded_git_name)?;
+            let mut stmt = conn.prepare_cached(
+                "INSERT INTO git_signature (git_id, rev, signers, timestamp)
+                 VALUES (?1, ?2, ?3, ?4)
+                 ON CONFLICT DO UPDATE SET timestamp=excluded.timestamp
+                    WHERE timestamp < ?5",
+            )?;
+            stmt.execute(params![
+                git_id,
+                git_signature.rev,
+                git_signature.signers,
+                timestamp,
+                timestamp - UPDATE_RESOLUTION
+            ])?;
+        }
+
+        Ok(())
+    }
+
     /// Returns the numeric ID of the registry, either fetching from the local
     /// cache, or getting it from the database.
     ///
@@ -1772,7 +1965,7 @@ fn to_timestamp(t: &SystemTime) -> Timestamp {
 /// If possible, try to avoid calling this too often since accessing clocks
 /// can be a little slow on some systems.
 #[allow(clippy::disallowed_methods)]
-fn now() -> Timestamp {
+pub(crate) fn now() -> Timestamp {
     match std::env::var("__CARGO_TEST_LAST_USE_NOW") {
         Ok(now) => now.parse().unwrap(),
         Err(_) => to_timestamp(&SystemTime::now()),
@@ -31,4 +31,5 @@ pub mod resolver;
 pub mod shell;
 mod source_id;
 pub mod summary;
+pub mod target_cache_tracker;
 mod workspace;
@@ -21,13 +21,12 @@
 //! The precedence is explained in [`ProfileMaker`].
 //! The algorithm happens within [`ProfileMaker::get_profile`].
 
-use crate::core::compiler::{CompileKind, CompileTarget, Unit};
+use crate::core::compiler::{CompileKind, CompileMode, CompileTarget, Unit};
 use crate::core::dependency::Artifact;
 use crate::core::resolver::features::FeaturesFor;
 use crate::core::Feature;
-use crate::core::{
-    PackageId, PackageIdSpec, PackageIdSpecQuery, Resolve, Shell, Target, Workspace,
-};
+use crate::core::{PackageId, PackageIdSpecQuery, Resolve, Shell, Target, TargetKind, Workspace};
+use crate::util::context::{ConfigKey, Definition};
 use crate::util::interning::InternedString;
 use crate::util::toml::validate_profile;
 use crate::util::{closest_msg, context, CargoResult, GlobalContext};
@@ -39,6 +38,7 @@ use cargo_util_schemas::manifest::{
 };
 use std::collections::{BTreeMap, HashMap, HashSet};
 use std::hash::Hash;
+use std::path::PathBuf;
 use std::{cmp, fmt, hash};
 
 /// Collection of all profiles.
@@ -92,7 +92,8 @@ impl Profiles {
 
         // Merge with predefined profiles.
         use std::collections::btree_map::Entry;
-        for (predef_name, mut predef_prof) in Self::predefined_profiles().into_iter() {
+        let predefined = Self::predefined_profiles(gctx.cli_unstable().coverage);
+        for (predef_name, mut predef_prof) in predefined.into_iter() {
             match profiles.entry(InternedString::new(predef_name)) {
                 Entry::Vacant(vac) => {
                     vac.insert(predef_prof);
@@ -135,12 +136,17 @@ impl Profiles {
     ) {
         profile_makers.by_name.insert(
             InternedString::new("dev"),
-            ProfileMaker::new(Profile::default_dev(), profiles.get("dev").cloned()),
+            ProfileMaker::new(
+                InternedString::new("dev"),
+                Profile::default_dev(),
+                profiles.get("dev").cloned(),
+            ),
         );
 
         profile_makers.by_name.insert(
             InternedString::new("release"),
             ProfileMaker::new(
+                InternedString::new("release"),
                 Profile::default_release(trim_paths_enabled),
                 profiles.get("release").cloned(),
             ),
@@ -149,8 +155,8 @@ impl Profiles {
 
     /// Returns the built-in profiles (not including dev/release, which are
     /// "root" profiles).
-    fn predefined_profiles() -> Vec<(&'static str, TomlProfile)> {
-        vec![
+    fn predefined_profiles(coverage_enabled: bool) -> Vec<(&'static str, TomlProfile)> {
+        let mut profiles = vec![
             (
                 "bench",
                 TomlProfile {
@@ -172,7 +178,18 @@ impl Profiles {
                     ..TomlProfile::default()
                 },
             ),
-        ]
+        ];
+        if coverage_enabled {
+            profiles.push((
+                "coverage",
+                TomlProfile {
+                    inherits: Some(String::from("test")),
+                    coverage: Some(true),
+                    ..TomlProfile::default()
+                },
+            ));
+        }
+        profiles
     }
 
     /// Creates a `ProfileMaker`, and inserts it into `self.by_name`.
@@ -262,6 +279,7 @@ impl Profiles {
             Some(toml) => toml.merge(profile),
             None => maker.toml = Some(profile.clone()),
         };
+        maker.chain.push(name);
         Ok(maker)
     }
 
@@ -275,14 +293,58 @@ impl Profiles {
         is_local: bool,
         unit_for: UnitFor,
         kind: CompileKind,
+        target: &Target,
+        mode: CompileMode,
+    ) -> Profile {
+        self.make_profile(
+            pkg_id,
+            is_member,
+            is_local,
+            unit_for.is_for_host(),
+            unit_for.panic_setting(),
+            kind,
+            target_kind_override(target, mode),
+            None,
+        )
+    }
+
+    /// Makes the profile for [`Profiles::get_profile`], recording what set
+    /// each of its fields in `sources` for [`Profiles::explain`].
+    fn make_profile<'a>(
+        &'a self,
+        pkg_id: PackageId,
+        is_member: bool,
+        is_local: bool,
+        is_for_host: bool,
+        panic_setting: PanicSetting,
+        kind: CompileKind,
+        target_kind: Option<&str>,
+        mut sources: Option<&mut FieldSources<'a>>,
     ) -> Profile {
         let maker = self.get_profile_maker(&self.requested_profile).unwrap();
-        let mut profile = maker.get_profile(Some(pkg_id), is_member, unit_for.is_for_host());
+        let mut profile = maker.make_profile(
+            Some(pkg_id),
+            is_member,
+            is_for_host,
+            target_kind,
+            sources.as_deref_mut(),
+        );
+
+        // Coverage is only collected for the code of workspace members, so
+        // dependencies and anything run by the build itself aren't
+        // instrumented.
+        if !is_member || is_for_host {
+            profile.coverage = false;
+            picked_by_cargo(&mut sources, "coverage");
+        }
 
         // Dealing with `panic=abort` and `panic=unwind` requires some special
         // treatment. Be sure to process all the various options here.
-        match unit_for.panic_setting() {
-            PanicSetting::AlwaysUnwind => profile.panic = PanicStrategy::Unwind,
+        match panic_setting {
+            PanicSetting::AlwaysUnwind => {
+                profile.panic = PanicStrategy::Unwind;
+                picked_by_cargo(&mut sources, "panic");
+            }
             PanicSetting::ReadProfile => {}
         }
 
@@ -298,12 +360,16 @@ impl Profiles {
             };
             if target.contains("-apple-") {
                 profile.split_debuginfo = Some(InternedString::new("unpacked"));
+                picked_by_cargo(&mut sources, "split-debuginfo");
             }
         }
 
         // Incremental can be globally overridden.
         if let Some(v) = self.incremental {
             profile.incremental = v;
+            if let Some(sources) = sources.as_deref_mut() {
+                sources.insert("incremental", FieldSource::Incremental);
+            }
         }
 
         // Only enable incremental compilation for sources the user can
@@ -314,6 +380,7 @@ impl Profiles {
         // (see also https://github.com/rust-lang/cargo/issues/3972)
         if !is_local {
             profile.incremental = false;
+            picked_by_cargo(&mut sources, "incremental");
         }
         profile.name = self.requested_profile;
         profile
@@ -340,7 +407,9 @@ impl Profiles {
     pub fn base_profile(&self) -> Profile {
         let profile_name = self.requested_profile;
         let maker = self.get_profile_maker(&profile_name).unwrap();
-        maker.get_profile(None, /*is_member*/ true, /*is_for_host*/ false)
+        maker.get_profile(
+            None, /*is_member*/ true, /*is_for_host*/ false, /*target_kind*/ None,
+        )
     }
 
     /// Gets the directory name for a profile, like `debug` or `release`.
@@ -351,6 +420,63 @@ impl Profiles {
             .unwrap_or(&self.requested_profile)
     }
 
+    /// Returns the standard library crates to build, as set by `build-std` in
+    /// the requested profile.
+    pub fn build_std(&self) -> Option<&[String]> {
+        self.requested_toml()?.build_std.as_deref()
+    }
+
+    /// Returns the standard library features to enable, as set by
+    /// `build-std-features` in the requested profile.
+    pub fn build_std_features(&self) -> Option<&[String]> {
+        self.requested_toml()?.build_std_features.as_deref()
+    }
+
+    /// The TOML of the requested profile, with its `inherits` chain merged.
+    fn requested_toml(&self) -> Option<&TomlProfile> {
+        self.get_profile_maker(&self.requested_profile)
+            .ok()?
+            .toml
+            .as_ref()
+    }
+
+    /// The TOML of the requested profile, with its `inherits` chain and
+    /// config merged, for `cargo config get --effective`.
+    ///
+    /// With a package, given with whether it is a workspace member, or a kind
+    /// of target, the override tables that apply to it are merged in the
+    /// order [`ProfileMaker::get_profile`] applies them, and the other
+    /// override tables are left out.
+    pub fn effective_toml(
+        &self,
+        pkg: Option<(PackageId, bool)>,
+        target_kind: Option<&str>,
+    ) -> TomlProfile {
+        let mut toml = self.requested_toml().cloned().unwrap_or_default();
+        if pkg.is_none() && target_kind.is_none() {
+            return toml;
+        }
+        let (pkg_id, is_member) = match pkg {
+            Some((pkg_id, is_member)) => (Some(pkg_id), is_member),
+            None => (None, true),
+        };
+        let tables: Vec<_> = override_tables(pkg_id, is_member, false, target_kind, &toml)
+            .into_iter()
+            .map(|(_, table)| table.clone())
+            .collect();
+        toml.package = None;
+        toml.build_override = None;
+        toml.lib = None;
+        toml.bin = None;
+        toml.example = None;
+        toml.test = None;
+        toml.bench = None;
+        for table in &tables {
+            toml.merge(table);
+        }
+        toml
+    }
+
     /// Used to check for overrides for non-existing packages.
     pub fn validate_packages(
         &self,
@@ -385,6 +511,114 @@ impl Profiles {
         Ok(())
     }
 
+    /// Explains the profile of a unit, with where the value of each of its
+    /// fields comes from.
+    ///
+    /// `is_for_host` is whether the unit is built for the host, like a build
+    /// script or proc-macro and their dependencies.
+    pub fn explain(
+        &self,
+        ws: &Workspace<'_>,
+        unit: &Unit,
+        is_for_host: bool,
+    ) -> CargoResult<Vec<ProfileField>> {
+        let maker = self.get_profile_maker(&self.requested_profile)?;
+        let pkg_id = unit.pkg.package_id();
+        let is_member = ws.is_member(&unit.pkg);
+        let is_local = pkg_id.source_id().is_path() && !unit.is_std;
+        let mut sources = FieldSources::new();
+        let made = self.make_profile(
+            pkg_id,
+            is_member,
+            is_local,
+            is_for_host,
+            PanicSetting::ReadProfile,
+            unit.kind,
+            target_kind_override(&unit.target, unit.mode),
+            Some(&mut sources),
+        );
+        // Units that always unwind, and the deferred `debug` of build
+        // dependencies, are only settled once the unit is in the unit graph.
+        if made.panic != unit.profile.panic {
+            sources.remove("panic");
+        }
+        if made.debuginfo.is_deferred()
+            && made.debuginfo.into_inner() != unit.profile.debuginfo.into_inner()
+        {
+            sources.remove("debug");
+        }
+        let profile = serde_json::to_value(&unit.profile)?;
+
+        let mut fields = Vec::new();
+        for &(key, field) in PROFILE_FIELDS {
+            // Unstable fields are left out when they aren't set.
+            let Some(value) = profile.get(field) else {
+                continue;
+            };
+            let origin = match sources.get(key) {
+                // A table only set by a built-in profile, like `inherits` of
+                // `test`, leaves the default.
+                Some(FieldSource::Table(table)) => self
+                    .table_origin(ws, &maker.chain, *table, key)?
+                    .unwrap_or(ProfileOrigin::Default),
+                Some(FieldSource::Incremental) => incremental_origin(ws.gctx())?,
+                None => ProfileOrigin::Default,
+            };
+            // Whether Cargo may still weaken `strip` is an implementation
+            // detail, show the setting as it's written in a profile.
+            let value = match key {
+                "strip" => serde_json::Value::String(unit.profile.strip.into_inner().to_string()),
+                _ => value.clone(),
+            };
+            fields.push(ProfileField { key, value, origin });
+        }
+        Ok(fields)
+    }
+
+    /// Finds the last profile of `chain` that sets `key` in the given table,
+    /// with config winning over the manifest for the same profile.
+    fn table_origin(
+        &self,
+        ws: &Workspace<'_>,
+        chain: &[InternedString],
+        table: Option<OverrideTable<'_>>,
+        key: &str,
+    ) -> CargoResult<Option<ProfileOrigin>> {
+        let mut origin = None;
+        for name in chain {
+            let mut config_key = ConfigKey::new();
+            config_key.push("profile");
+            config_key.push(name);
+            for part in table.map(|table| table.keys()).unwrap_or_default() {
+                config_key.push(&part);
+            }
+            let table_name = config_key.to_string();
+
+            let manifest_table = ws
+                .profiles()
+                .and_then(|profiles| profiles.get(name))
+                .and_then(|toml| match table {
+                    Some(table) => table.get(toml),
+                    None => Some(toml),
+                });
+            if manifest_table.map_or(false, |toml| is_set(toml, key)) {
+                origin = Some(ProfileOrigin::Manifest {
+                    table: table_name.clone(),
+                    path: ws.root_manifest().to_path_buf(),
+                });
+            }
+
+            config_key.push(key);
+            if let Some(cv) = ws.gctx().get_cv_with_env(&config_key)? {
+                origin = Some(ProfileOrigin::Config {
+                    table: table_name,
+                    definition: cv.definition().clone(),
+                });
+            }
+        }
+        Ok(origin)
+    }
+
     /// Returns the profile maker for the given profile name.
     fn get_profile_maker(&self, name: &str) -> CargoResult<&ProfileMaker> {
         self.by_name
@@ -393,15 +627,157 @@ impl Profiles {
     }
 }
 
+/// The fields of [`Profile`] explained by [`Profiles::explain`], as the key in
+/// a `[profile]` table and the name in the serialized `Profile`.
+const PROFILE_FIELDS: &[(&str, &str)] = &[
+    ("opt-level", "opt_level"),
+    ("lto", "lto"),
+    ("codegen-backend", "codegen_backend"),
+    ("codegen-units", "codegen_units"),
+    ("debug", "debuginfo"),
+    ("split-debuginfo", "split_debuginfo"),
+    ("debug-assertions", "debug_assertions"),
+    ("overflow-checks", "overflow_checks"),
+    ("rpath", "rpath"),
+    ("incremental", "incremental"),
+    ("panic", "panic"),
+    ("strip", "strip"),
+    ("rustflags", "rustflags"),
+    ("trim-paths", "trim_paths"),
+    ("coverage", "coverage"),
+];
+
+/// A field of the [`Profile`] of a unit, from [`Profiles::explain`].
+#[derive(Clone, Debug)]
+pub struct ProfileField {
+    /// The key of the field in a `[profile]` table, like `opt-level`.
+    pub key: &'static str,
+    /// The value of the field, as it is serialized in `--unit-graph`.
+    pub value: serde_json::Value,
+    /// Where the value comes from.
+    pub origin: ProfileOrigin,
+}
+
+/// Where the value of a field of a [`Profile`] comes from.
+#[derive(Clone, Debug)]
+pub enum ProfileOrigin {
+    /// The built-in value of the profile, or a value Cargo picked for the
+    /// unit, like `opt-level = 0` for build scripts.
+    Default,
+    /// A table of a profile in the root manifest.
+    Manifest { table: String, path: PathBuf },
+    /// A table of a profile, or `build.incremental`, in config.
+    Config {
+        table: String,
+        definition: Definition,
+    },
+    /// An environment variable that overrides every profile.
+    Env(String),
+}
+
+impl fmt::Display for ProfileOrigin {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ProfileOrigin::Default => f.write_str("default"),
+            ProfileOrigin::Manifest { table, path } => {
+                write!(f, "`{table}` in {}", path.display())
+            }
+            ProfileOrigin::Config { table, definition } => write!(f, "`{table}` in {definition}"),
+            ProfileOrigin::Env(name) => write!(f, "environment variable `{name}`"),
+        }
+    }
+}
+
+/// What set a field of a [`Profile`] while it was made, see
+/// [`Profiles::explain`].
+#[derive(Clone, Copy, Debug)]
+enum FieldSource<'a> {
+    /// The profile table, or one of its override tables, with the `inherits`
+    /// chain and config merged into it.
+    Table(Option<OverrideTable<'a>>),
+    /// The global override of `incremental`, see [`incremental_origin`].
+    Incremental,
+}
+
+/// The [`FieldSource`] of each field of a [`Profile`], by its key in a
+/// `[profile]` table. A field that isn't in here has the built-in value of
+/// the profile, or one Cargo picked for the unit.
+type FieldSources<'a> = HashMap<&'static str, FieldSource<'a>>;
+
+/// Records that Cargo picked the value of the given field for the unit.
+fn picked_by_cargo(sources: &mut Option<&mut FieldSources<'_>>, key: &str) {
+    if let Some(sources) = sources {
+        sources.remove(key);
+    }
+}
+
+/// Records the fields [`merge_profile`] sets from the given table.
+fn record_table<'a>(
+    sources: &mut Option<&mut FieldSources<'a>>,
+    table: Option<OverrideTable<'a>>,
+    toml: &TomlProfile,
+) {
+    let Some(sources) = sources else {
+        return;
+    };
+    for &(key, _) in PROFILE_FIELDS {
+        if is_set(toml, key) {
+            sources.insert(key, FieldSource::Table(table));
+        } else if key == "strip" {
+            // Merging a table without `strip` resets it.
+            sources.remove(key);
+        }
+    }
+}
+
+/// Whether the given field of a `[profile]` table is set.
+fn is_set(toml: &TomlProfile, key: &str) -> bool {
+    match key {
+        "opt-level" => toml.opt_level.is_some(),
+        "lto" => toml.lto.is_some(),
+        "codegen-backend" => toml.codegen_backend.is_some(),
+        "codegen-units" => toml.codegen_units.is_some(),
+        "debug" => toml.debug.is_some(),
+        "split-debuginfo" => toml.split_debuginfo.is_some(),
+        "debug-assertions" => toml.debug_assertions.is_some(),
+        "overflow-checks" => toml.overflow_checks.is_some(),
+        "rpath" => toml.rpath.is_some(),
+        "incremental" => toml.incremental.is_some(),
+        "panic" => toml.panic.is_some(),
+        "strip" => toml.strip.is_some(),
+        "rustflags" => toml.rustflags.is_some(),
+        "trim-paths" => toml.trim_paths.is_some(),
+        "coverage" => toml.coverage.is_some(),
+        _ => unreachable!("unknown profile field `{key}`"),
+    }
+}
+
+/// Where the global override of `incremental` in [`Profiles::new`] comes from.
+fn incremental_origin(gctx: &GlobalContext) -> CargoResult<ProfileOrigin> {
+    if gctx.get_env_os("CARGO_INCREMENTAL").is_some() {
+        return Ok(ProfileOrigin::Env("CARGO_INCREMENTAL".to_string()));
+    }
+    let key = ConfigKey::from_str("build.incremental");
+    let cv = gctx
+        .get_cv_with_env(&key)?
+        .expect("`build.incremental` is set if `incremental` is overridden");
+    Ok(ProfileOrigin::Config {
+        table: "build".to_string(),
+        definition: cv.definition().clone(),
+    })
+}
+
 /// An object used for handling the profile hierarchy.
 ///
 /// The precedence of profiles are (first one wins):
 ///
 /// - Profiles in `.cargo/config` files (using same order as below).
 /// - `[profile.dev.package.name]` -- a named package.
+/// - `[profile.dev.package."aws-*"]` -- a glob of package names.
 /// - `[profile.dev.package."*"]` -- this cannot apply to workspace members.
 /// - `[profile.dev.build-override]` -- this can only apply to `build.rs` scripts
 ///   and their dependencies.
+/// - `[profile.dev.bin]` -- a kind of target, see [`target_kind_override`].
 /// - `[profile.dev]`
 /// - Default (hard-coded) values.
 #[derive(Debug, Clone)]
@@ -414,14 +790,21 @@ struct ProfileMaker {
     /// `default` is used. Note that the built-in defaults for test/bench/doc
     /// always set this since they need to declare the `inherits` value.
     toml: Option<TomlProfile>,
+    /// The names of the profiles merged into `toml`, from the root profile
+    /// `dev` or `release` to this one.
+    chain: Vec<InternedString>,
 }
 
 impl ProfileMaker {
     /// Creates a new `ProfileMaker`.
     ///
     /// Note that this does not process `inherits`, the caller is responsible for that.
-    fn new(default: Profile, toml: Option<TomlProfile>) -> ProfileMaker {
-        ProfileMaker { default, toml }
+    fn new(name: InternedString, default: Profile, toml: Option<TomlProfile>) -> ProfileMaker {
+        ProfileMaker {
+            default,
+            toml,
+            chain: vec![name],
+        }
     }
 
     /// Generates a new `Profile`.
@@ -430,6 +813,20 @@ impl ProfileMaker {
         pkg_id: Option<PackageId>,
         is_member: bool,
         is_for_host: bool,
+        target_kind: Option<&str>,
+    ) -> Profile {
+        self.make_profile(pkg_id, is_member, is_for_host, target_kind, None)
+    }
+
+    /// Generates a new `Profile`, recording what set each of its fields in
+    /// `sources`.
+    fn make_profile<'a>(
+        &'a self,
+        pkg_id: Option<PackageId>,
+        is_member: bool,
+        is_for_host: bool,
+        target_kind: Option<&str>,
+        mut sources: Option<&mut FieldSources<'a>>,
     ) -> Profile {
         let mut profile = self.default.clone();
 
@@ -437,6 +834,7 @@ impl ProfileMaker {
         // `[profile.release]`
         if let Some(toml) = &self.toml {
             merge_profile(&mut profile, toml);
+            record_table(&mut sources, None, toml);
         }
 
         // Next start overriding those settings. First comes build dependencies
@@ -452,6 +850,8 @@ impl ProfileMaker {
             // well as enabling parallelism by not constraining codegen units.
             profile.opt_level = InternedString::new("0");
             profile.codegen_units = None;
+            picked_by_cargo(&mut sources, "opt-level");
+            picked_by_cargo(&mut sources, "codegen-units");
 
             // For build dependencies, we usually don't need debuginfo, and
             // removing it will compile faster. However, that can conflict with
@@ -466,64 +866,146 @@ impl ProfileMaker {
             profile.debuginfo = DebugInfo::Deferred(profile.debuginfo.into_inner());
         }
         // ... and next comes any other sorts of overrides specified in
-        // profiles, such as `[profile.release.build-override]` or
-        // `[profile.release.package.foo]`
+        // profiles, such as `[profile.release.bin]`,
+        // `[profile.release.build-override]` or `[profile.release.package.foo]`
         if let Some(toml) = &self.toml {
-            merge_toml_overrides(pkg_id, is_member, is_for_host, &mut profile, toml);
+            for (table, toml) in override_tables(pkg_id, is_member, is_for_host, target_kind, toml)
+            {
+                merge_profile(&mut profile, toml);
+                record_table(&mut sources, Some(table), toml);
+            }
         }
         profile
     }
 }
 
-/// Merge package and build overrides from the given TOML profile into the given `Profile`.
-fn merge_toml_overrides(
+/// A table of a profile that overrides its settings for some units.
+#[derive(Clone, Copy, Debug)]
+enum OverrideTable<'a> {
+    /// `[profile.dev.bin]` and the other kinds of targets.
+    TargetKind(&'static str),
+    /// `[profile.dev.build-override]`.
+    BuildOverride,
+    /// `[profile.dev.package.<spec>]`.
+    Package(&'a ProfilePackageSpec),
+}
+
+impl OverrideTable<'_> {
+    /// Returns this table of the given profile, if it has one.
+    fn get<'t>(&self, toml: &'t TomlProfile) -> Option<&'t TomlProfile> {
+        match self {
+            OverrideTable::TargetKind(kind) => toml
+                .target_kind_overrides()
+                .find(|(k, _)| k == kind)
+                .map(|(_, table)| table),
+            OverrideTable::BuildOverride => toml.build_override.as_deref(),
+            OverrideTable::Package(spec) => toml.package.as_ref()?.get(*spec),
+        }
+    }
+
+    /// The keys of this table below the profile, like `["package", "foo"]`.
+    fn keys(&self) -> Vec<String> {
+        match self {
+            OverrideTable::TargetKind(kind) => vec![kind.to_string()],
+            OverrideTable::BuildOverride => vec!["build-override".to_string()],
+            OverrideTable::Package(spec) => vec!["package".to_string(), spec.to_string()],
+        }
+    }
+}
+
+/// Returns the override tables of the given TOML profile that apply to a
+/// unit, in the order they are merged.
+fn override_tables<'a>(
     pkg_id: Option<PackageId>,
     is_member: bool,
     is_for_host: bool,
-    profile: &mut Profile,
-    toml: &TomlProfile,
-) {
+    target_kind: Option<&str>,
+    toml: &'a TomlProfile,
+) -> Vec<(OverrideTable<'a>, &'a TomlProfile)> {
+    let mut tables = Vec::new();
+    if let Some(target_kind) = target_kind {
+        let kind_profile = toml
+            .target_kind_overrides()
+            .find(|(kind, _)| *kind == target_kind);
+        if let Some((kind, kind_profile)) = kind_profile {
+            tables.push((OverrideTable::TargetKind(kind), kind_profile));
+        }
+    }
     if is_for_host {
         if let Some(build_override) = &toml.build_override {
-            merge_profile(profile, build_override);
+            tables.push((OverrideTable::BuildOverride, &**build_override));
         }
     }
     if let Some(overrides) = toml.package.as_ref() {
         if !is_member {
-            if let Some(all) = overrides.get(&ProfilePackageSpec::All) {
-                merge_profile(profile, all);
+            if let Some((all, all_profile)) = overrides.get_key_value(&ProfilePackageSpec::All) {
+                tables.push((OverrideTable::Package(all), all_profile));
             }
         }
         if let Some(pkg_id) = pkg_id {
-            let mut matches = overrides
-                .iter()
-                .filter_map(|(key, spec_profile)| match *key {
-                    ProfilePackageSpec::All => None,
-                    ProfilePackageSpec::Spec(ref s) => {
-                        if s.matches(pkg_id) {
-                            Some(spec_profile)
-                        } else {
-                            None
-                        }
-                    }
-                });
-            if let Some(spec_profile) = matches.next() {
-                merge_profile(profile, spec_profile);
-                // `validate_packages` should ensure that there are
-                // no additional matches.
-                assert!(
-                    matches.next().is_none(),
-                    "package `{}` matched multiple package profile overrides",
-                    pkg_id
-                );
+            // Globs come first, so a spec naming the package wins over them.
+            for is_glob in [true, false] {
+                let mut matches = overrides
+                    .iter()
+                    .filter(|(key, _)| key_is_glob(key) == is_glob && key_matches(key, pkg_id));
+                if let Some((spec, spec_profile)) = matches.next() {
+                    tables.push((OverrideTable::Package(spec), spec_profile));
+                    // `validate_packages` should ensure that there are
+                    // no additional matches.
+                    assert!(
+                        matches.next().is_none(),
+                        "package `{}` matched multiple package profile overrides",
+                        pkg_id
+                    );
+                }
             }
         }
     }
+    tables
+}
+
+/// Returns the kind of target whose override, like `[profile.dev.bin]`, applies
+/// to a unit.
+///
+/// Every target built as a test or benchmark harness uses the `test` or
+/// `bench` override, including the unit tests of libraries and binaries.
+/// Build scripts have no override of their own, they use `build-override`.
+fn target_kind_override(target: &Target, mode: CompileMode) -> Option<&'static str> {
+    match mode {
+        CompileMode::Test | CompileMode::Check { test: true } => return Some("test"),
+        CompileMode::Bench => return Some("bench"),
+        _ => {}
+    }
+    match target.kind() {
+        TargetKind::Lib(_) => Some("lib"),
+        TargetKind::Bin => Some("bin"),
+        TargetKind::ExampleLib(_) | TargetKind::ExampleBin => Some("example"),
+        TargetKind::Test => Some("test"),
+        TargetKind::Bench => Some("bench"),
+        TargetKind::CustomBuild => None,
+    }
+}
+
+/// Whether the key of a package override is a glob of package names.
+fn key_is_glob(key: &ProfilePackageSpec) -> bool {
+    matches!(key, ProfilePackageSpec::Glob(_))
+}
+
+/// Whether the key of a package override names the given package. `"*"`
+/// never does, since it only applies to non-members.
+fn key_matches(key: &ProfilePackageSpec, pkg_id: PackageId) -> bool {
+    match key {
+        ProfilePackageSpec::All => false,
+        ProfilePackageSpec::Spec(spec) => spec.matches(pkg_id),
+        // Globs are validated along with the rest of the profile.
+        ProfilePackageSpec::Glob(glob) => glob::Pattern::new(glob)
+            .map_or(false, |pattern| pattern.matches(pkg_id.name().as_str())),
+    }
 }
 
 /// Merge the given TOML profile into the given `Profile`.
 ///
-/// Does not merge overrides (see `merge_toml_overrides`).
+/// Does not merge overrides (see `override_tables`).
 fn merge_profile(profile: &mut Profile, toml: &TomlProfile) {
     if let Some(ref opt_level) = toml.opt_level {
         profile.opt_level = InternedString::new(&opt_level.0);
@@ -572,6 +1054,9 @@ fn merge_profile(profile: &mut Profile, toml: &TomlProfile) {
     if let Some(trim_paths) = &toml.trim_paths {
         profile.trim_paths = Some(trim_paths.clone());
     }
+    if let Some(coverage) = toml.coverage {
+        profile.coverage = coverage;
+    }
     profile.strip = match toml.strip {
         Some(StringOrBool::Bool(true)) => {
             Strip::Resolved(StripInner::Named(InternedString::new("symbols")))
@@ -625,6 +1110,9 @@ pub struct Profile {
     // remove when `-Ztrim-paths` is stablized
     #[serde(skip_serializing_if = "Option::is_none")]
     pub trim_paths: Option<TomlTrimPaths>,
+    // remove when `-Zcoverage` is stablized
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub coverage: bool,
 }
 
 impl Default for Profile {
@@ -646,6 +1134,7 @@ impl Default for Profile {
             strip: Strip::Deferred(StripInner::None),
             rustflags: vec![],
             trim_paths: None,
+            coverage: false,
         }
     }
 }
@@ -675,6 +1164,7 @@ compact_debug! {
                 strip
                 rustflags
                 trim_paths
+                coverage
             )]
         }
     }
@@ -743,7 +1233,7 @@ impl Profile {
             self.rpath,
             (self.incremental, self.panic, self.strip),
             &self.rustflags,
-            &self.trim_paths,
+            (&self.trim_paths, self.coverage),
         )
     }
 }
@@ -1191,8 +1681,13 @@ impl UnitFor {
     }
 
     /// Set the artifact compile target for use in features using the given `artifact`.
+    ///
+    /// An artifact without a `target` is built like its parent, so it keeps
+    /// the parent's artifact compile target, matching the feature resolver.
     pub(crate) fn with_artifact_features(mut self, artifact: &Artifact) -> UnitFor {
-        self.artifact_target_for_features = artifact.target().and_then(|t| t.to_compile_target());
+        if let Some(target) = artifact.target().and_then(|t| t.to_compile_target()) {
+            self.artifact_target_for_features = Some(target);
+        }
         self
     }
 
@@ -1228,16 +1723,21 @@ impl UnitFor {
     /// the artifact is built.
     /// If we are an artifact but don't specify a `target`, we assume the default
     /// compile kind that is suitable in this situation.
+    /// Otherwise normal dependencies use the features of the root compile kind.
     pub(crate) fn map_to_features_for(&self, dep_artifact: Option<&Artifact>) -> FeaturesFor {
-        FeaturesFor::from_for_host_or_artifact_target(
+        match FeaturesFor::from_for_host_or_artifact_target(
             self.is_for_host_features(),
             match dep_artifact {
                 Some(artifact) => artifact
                     .target()
-                    .and_then(|t| t.to_resolved_compile_target(self.root_compile_kind)),
+                    .and_then(|t| t.to_resolved_compile_target(self.root_compile_kind))
+                    .or(self.artifact_target_for_features),
                 None => self.artifact_target_for_features,
             },
-        )
+        ) {
+            FeaturesFor::NormalOrDev => FeaturesFor::normal_for(self.root_compile_kind),
+            features_for => features_for,
+        }
     }
 
     pub(crate) fn root_compile_kind(&self) -> CompileKind {
@@ -1331,28 +1831,20 @@ fn validate_packages_unique(
     resolve: &Resolve,
     name: &str,
     toml: &Option<TomlProfile>,
-) -> CargoResult<HashSet<PackageIdSpec>> {
+) -> CargoResult<HashSet<ProfilePackageSpec>> {
     let Some(toml) = toml else {
         return Ok(HashSet::new());
     };
     let Some(overrides) = toml.package.as_ref() else {
         return Ok(HashSet::new());
     };
-    // Verify that a package doesn't match multiple spec overrides.
+    // Verify that a package doesn't match multiple spec overrides, or
+    // multiple glob overrides.
     let mut found = HashSet::new();
-    for pkg_id in resolve.iter() {
-        let matches: Vec<&PackageIdSpec> = overrides
+    for (pkg_id, is_glob) in resolve.iter().flat_map(|id| [(id, false), (id, true)]) {
+        let matches: Vec<&ProfilePackageSpec> = overrides
             .keys()
-            .filter_map(|key| match *key {
-                ProfilePackageSpec::All => None,
-                ProfilePackageSpec::Spec(ref spec) => {
-                    if spec.matches(pkg_id) {
-                        Some(spec)
-                    } else {
-                        None
-                    }
-                }
-            })
+            .filter(|key| key_is_glob(key) == is_glob && key_matches(key, pkg_id))
             .collect();
         match matches.len() {
             0 => {}
@@ -1386,22 +1878,27 @@ fn validate_packages_unmatched(
     resolve: &Resolve,
     name: &str,
     toml: &TomlProfile,
-    found: &HashSet<PackageIdSpec>,
+    found: &HashSet<ProfilePackageSpec>,
 ) -> CargoResult<()> {
     let Some(overrides) = toml.package.as_ref() else {
         return Ok(());
     };
 
     // Verify every override matches at least one package.
-    let missing_specs = overrides.keys().filter_map(|key| {
-        if let ProfilePackageSpec::Spec(ref spec) = *key {
-            if !found.contains(spec) {
-                return Some(spec);
+    let missing = overrides
+        .keys()
+        .filter(|key| !matches!(key, ProfilePackageSpec::All) && !found.contains(key));
+    for key in missing {
+        let spec = match key {
+            ProfilePackageSpec::Spec(spec) => spec,
+            _ => {
+                shell.warn(format!(
+                    "profile package glob `{}` in profile `{}` did not match any packages",
+                    key, name
+                ))?;
+                continue;
             }
-        }
-        None
-    });
-    for spec in missing_specs {
+        };
         // See if there is an exact name match.
         let name_matches: Vec<String> = resolve
             .iter()
@@ -10,11 +10,13 @@
 //! while the latter involves operations on the registry Web API.
 
 use std::collections::{HashMap, HashSet};
+use std::rc::Rc;
 use std::task::{ready, Poll};
 
 use crate::core::PackageSet;
 use crate::core::{Dependency, PackageId, SourceId, Summary};
 use crate::sources::config::SourceConfigMap;
+use crate::sources::ownership::{OwnsPolicy, RegistryOwnership};
 use crate::sources::source::QueryKind;
 use crate::sources::source::Source;
 use crate::sources::source::SourceMap;
@@ -131,6 +133,11 @@ pub struct PackageRegistry<'gctx> {
     /// This is constructed during calls to [`PackageRegistry::patch`],
     /// along with the `patches` field, thoough these entries never get locked.
     patches_available: HashMap<CanonicalUrl, Vec<PackageId>>,
+    /// Package names claimed by registries, see [`RegistryOwnership`].
+    ownership: Option<Rc<RegistryOwnership>>,
+    /// Candidates that have already been warned about for coming from a
+    /// registry that doesn't own their name.
+    ownership_warned: HashSet<(InternedString, SourceId)>,
 }
 
 /// A map of all "locked packages" which is filled in when parsing a lock file
@@ -209,6 +216,8 @@ impl<'gctx> PackageRegistry<'gctx> {
             patches: HashMap::new(),
             patches_locked: false,
             patches_available: HashMap::new(),
+            ownership: RegistryOwnership::from_gctx(gctx)?.map(Rc::new),
+            ownership_warned: HashSet::new(),
         })
     }
 
@@ -639,8 +648,10 @@ https://doc.rust-lang.org/cargo/reference/overriding-dependencies.html
     }
 }
 
-impl<'gctx> Registry for PackageRegistry<'gctx> {
-    fn query(
+impl<'gctx> PackageRegistry<'gctx> {
+    /// Queries the sources for candidates of `dep`, taking overrides and
+    /// `[patch]` entries into account.
+    fn query_candidates(
         &mut self,
         dep: &Dependency,
         kind: QueryKind,
@@ -770,6 +781,61 @@ impl<'gctx> Registry for PackageRegistry<'gctx> {
 
         Poll::Ready(Ok(()))
     }
+}
+
+impl<'gctx> Registry for PackageRegistry<'gctx> {
+    fn query(
+        &mut self,
+        dep: &Dependency,
+        kind: QueryKind,
+        f: &mut dyn FnMut(IndexSummary),
+    ) -> Poll<CargoResult<()>> {
+        let Some(ownership) = self.ownership.clone() else {
+            return self.query_candidates(dep, kind, f);
+        };
+        let mut found = false;
+        let mut denied = None;
+        let mut warn = Vec::new();
+        let callback = &mut |summary: IndexSummary| {
+            let pkg_id = summary.package_id();
+            if let Some(violation) = ownership.violation(pkg_id) {
+                let msg = format!(
+                    "`{}` was found in {}, but names matching `{}` are owned by registry `{}`",
+                    pkg_id.name(),
+                    pkg_id.source_id(),
+                    violation.pattern,
+                    violation.registry,
+                );
+                if violation.policy == OwnsPolicy::Deny {
+                    denied.get_or_insert((msg, violation.registry.to_owned()));
+                    return;
+                }
+                warn.push((pkg_id, msg));
+            }
+            found = true;
+            f(summary);
+        };
+        ready!(self.query_candidates(dep, kind, callback))?;
+        for (pkg_id, msg) in warn {
+            if self
+                .ownership_warned
+                .insert((pkg_id.name(), pkg_id.source_id()))
+            {
+                self.gctx.shell().warn(msg)?;
+            }
+        }
+        // Only explain why nothing matched for real queries, not when looking
+        // for alternatives to suggest in an error message.
+        if let (false, Some((msg, registry)), QueryKind::Exact) = (found, denied, kind) {
+            return Poll::Ready(Err(anyhow::format_err!(
+                "{msg}\n\n\
+                 help: to use the package from `{registry}`, add `registry = \"{registry}\"` \
+                 to the dependency on `{}`",
+                dep.package_name()
+            )));
+        }
+        Poll::Ready(Ok(()))
+    }
 
     fn describe_source(&self, id: SourceId) -> String {
         match self.sources.get(id) {
@@ -80,6 +80,9 @@ pub struct FeatureOpts {
     decouple_dev_deps: bool,
     /// Targets that are not in use will not activate features.
     ignore_inactive_targets: bool,
+    /// Normal and dev dependencies will not share features across the
+    /// requested `--target`s, when there is more than one of them.
+    decouple_targets: bool,
     /// If enabled, compare against old resolver (for testing).
     compare: bool,
 }
@@ -119,13 +122,21 @@ pub enum FeaturesFor {
     ///
     /// That is, `dep = { …, artifact = <crate-type>, target = <triple> }`
     ArtifactDep(CompileTarget),
+    /// Normal or dev dependency built for one of several requested targets.
+    ///
+    /// This is used instead of [`FeaturesFor::NormalOrDev`] when more than
+    /// one `--target` is requested, so that each target has its own set of
+    /// features, just as if each was built by a separate invocation.
+    Target(CompileTarget),
 }
 
 impl std::fmt::Display for FeaturesFor {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             FeaturesFor::HostDep => f.write_str("host"),
-            FeaturesFor::ArtifactDep(target) => f.write_str(&target.rustc_target()),
+            FeaturesFor::ArtifactDep(target) | FeaturesFor::Target(target) => {
+                f.write_str(&target.rustc_target())
+            }
             FeaturesFor::NormalOrDev => Ok(()),
         }
     }
@@ -156,11 +167,21 @@ impl FeaturesFor {
         }
     }
 
+    /// Returns the key for a normal or dev dependency built for `kind`.
+    pub fn normal_for(kind: CompileKind) -> FeaturesFor {
+        match kind {
+            CompileKind::Host => FeaturesFor::NormalOrDev,
+            CompileKind::Target(target) => FeaturesFor::Target(target),
+        }
+    }
+
     fn apply_opts(self, opts: &FeatureOpts) -> Self {
-        if opts.decouple_host_deps {
-            self
-        } else {
-            FeaturesFor::default()
+        if !opts.decouple_host_deps {
+            return FeaturesFor::default();
+        }
+        match self {
+            FeaturesFor::Target(_) if !opts.decouple_targets => FeaturesFor::NormalOrDev,
+            fk => fk,
         }
     }
 }
@@ -179,10 +200,12 @@ impl FeatureOpts {
                     "build_dep" | "host_dep" => opts.decouple_host_deps = true,
                     "dev_dep" => opts.decouple_dev_deps = true,
                     "itarget" => opts.ignore_inactive_targets = true,
+                    "target" => opts.decouple_targets = true,
                     "all" => {
                         opts.decouple_host_deps = true;
                         opts.decouple_dev_deps = true;
                         opts.ignore_inactive_targets = true;
+                        opts.decouple_targets = true;
                     }
                     "compare" => opts.compare = true,
                     "ws" => unimplemented!(),
@@ -218,6 +241,7 @@ impl FeatureOpts {
                 decouple_host_deps: true,
                 decouple_dev_deps: has_dev_units == HasDevUnits::No,
                 ignore_inactive_targets: true,
+                decouple_targets: true,
                 compare: false,
             },
         }
@@ -453,8 +477,13 @@ impl<'a, 'gctx> FeatureResolver<'a, 'gctx> {
         cli_features: &CliFeatures,
         specs: &[PackageIdSpec],
         requested_targets: &[CompileKind],
-        opts: FeatureOpts,
+        mut opts: FeatureOpts,
     ) -> CargoResult<ResolvedFeatures> {
+        // With a single target there is nothing to decouple, keep using
+        // `FeaturesFor::NormalOrDev` for it.
+        if requested_targets.len() < 2 {
+            opts.decouple_targets = false;
+        }
         let track_for_host = opts.decouple_host_deps || opts.ignore_inactive_targets;
         let mut r = FeatureResolver {
             ws,
@@ -490,24 +519,39 @@ impl<'a, 'gctx> FeatureResolver<'a, 'gctx> {
         let member_features = self.ws.members_with_features(specs, cli_features)?;
         for (member, cli_features) in &member_features {
             let fvs = self.fvs_from_requested(member.package_id(), cli_features);
-            let fk = if self.track_for_host && self.has_any_proc_macro(member.package_id()) {
-                // Also activate for normal dependencies. This is needed if the
-                // proc-macro includes other targets (like binaries or tests),
-                // or running in `cargo test`. Note that in a workspace, if
-                // the proc-macro is selected on the command like (like with
-                // `--workspace`), this forces feature unification with normal
-                // dependencies. This is part of the bigger problem where
-                // features depend on which packages are built.
-                self.activate_pkg(member.package_id(), FeaturesFor::default(), &fvs)?;
-                FeaturesFor::HostDep
-            } else {
-                FeaturesFor::default()
-            };
-            self.activate_pkg(member.package_id(), fk, &fvs)?;
+            // Members are always activated for normal dependencies. For a
+            // proc-macro this is needed if it includes other targets (like
+            // binaries or tests), or running in `cargo test`. Note that in a
+            // workspace, if the proc-macro is selected on the command like
+            // (like with `--workspace`), this forces feature unification with
+            // normal dependencies. This is part of the bigger problem where
+            // features depend on which packages are built.
+            for fk in self.normal_fks(member.manifest().forced_kind()) {
+                self.activate_pkg(member.package_id(), fk, &fvs)?;
+            }
+            if self.track_for_host && self.has_any_proc_macro(member.package_id()) {
+                self.activate_pkg(member.package_id(), FeaturesFor::HostDep, &fvs)?;
+            }
         }
         Ok(())
     }
 
+    /// The feature keys to activate a workspace member with when it is built
+    /// for the requested targets, or for its `forced-target`.
+    fn normal_fks(&self, forced_kind: Option<CompileKind>) -> Vec<FeaturesFor> {
+        if !self.opts.decouple_targets {
+            return vec![FeaturesFor::default()];
+        }
+        match forced_kind {
+            Some(kind) => vec![FeaturesFor::normal_for(kind)],
+            None => self
+                .requested_targets
+                .iter()
+                .map(|kind| FeaturesFor::normal_for(*kind))
+                .collect(),
+        }
+    }
+
     /// Activates [`FeatureValue`]s on the given package.
     ///
     /// This is the main entrance into the recursion of feature activation
@@ -796,7 +840,7 @@ impl<'a, 'gctx> FeatureResolver<'a, 'gctx> {
                 (_, FeaturesFor::NormalOrDev) => requested_targets
                     .iter()
                     .any(|kind| target_data.dep_platform_activated(dep, *kind)),
-                (_, FeaturesFor::ArtifactDep(target)) => {
+                (_, FeaturesFor::ArtifactDep(target) | FeaturesFor::Target(target)) => {
                     target_data.dep_platform_activated(dep, CompileKind::Target(target))
                 }
             }
@@ -849,12 +893,13 @@ impl<'a, 'gctx> FeatureResolver<'a, 'gctx> {
                         // All this may result in a dependency being built multiple times
                         // for various targets which are either specified in the manifest
                         // or on the cargo command-line.
-                        let lib_fk = if fk == FeaturesFor::default() {
-                            (self.track_for_host && (dep.is_build() || self.has_proc_macro_lib(dep_id)))
-                                .then(|| FeaturesFor::HostDep)
-                                .unwrap_or_default()
-                        } else {
-                            fk
+                        let lib_fk = match fk {
+                            FeaturesFor::NormalOrDev | FeaturesFor::Target(_) => {
+                                (self.track_for_host && (dep.is_build() || self.has_proc_macro_lib(dep_id)))
+                                    .then(|| FeaturesFor::HostDep)
+                                    .unwrap_or(fk)
+                            }
+                            _ => fk,
                         };
 
                         // `artifact_target_keys` are produced to fulfil the needs of artifacts that have a target specification.
@@ -26,6 +26,7 @@ struct Inner {
     dependencies: Vec<Dependency>,
     features: Arc<FeatureMap>,
     checksum: Option<String>,
+    signature: Option<String>,
     links: Option<InternedString>,
     rust_version: Option<RustVersion>,
 }
@@ -87,6 +88,7 @@ impl Summary {
                 dependencies,
                 features: Arc::new(feature_map),
                 checksum: None,
+                signature: None,
                 links: links.map(|l| l.into()),
                 rust_version,
             }),
@@ -115,6 +117,9 @@ impl Summary {
     pub fn checksum(&self) -> Option<&str> {
         self.inner.checksum.as_deref()
     }
+    pub fn signature(&self) -> Option<&str> {
+        self.inner.signature.as_deref()
+    }
     pub fn links(&self) -> Option<InternedString> {
         self.inner.links
     }
@@ -132,6 +137,10 @@ impl Summary {
         Arc::make_mut(&mut self.inner).checksum = Some(cksum);
     }
 
+    pub fn set_signature(&mut self, sig: String) {
+        Arc::make_mut(&mut self.inner).signature = Some(sig);
+    }
+
     pub fn map_dependencies<F>(self, mut f: F) -> Summary
     where
         F: FnMut(Dependency) -> Dependency,
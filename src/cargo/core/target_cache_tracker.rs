@@ -0,0 +1,399 @@
+//! Support for tracking the last time units were built in a target directory
+//! to assist with cleaning up their artifacts if they haven't been used in a
+//! while.
+//!
+//! Every build directory (like `target/debug` or `target/$TRIPLE/release`)
+//! has its own sqlite database which contains the timestamp of the last build
+//! that used each unit. Units are keyed by the [`Metadata`] hash in the
+//! `$pkgname-$META` names of their files, so the database only stores the
+//! hash and not the files themselves. The files of a unit are found by
+//! looking for that hash in the `.fingerprint`, `build`, `deps` and
+//! `examples` directories.
+//!
+//! Incremental compilation data is not tracked, since rustc picks the names
+//! of those directories itself.
+//!
+//! The database is only ever accessed while holding the lock on its build
+//! directory (`.cargo-lock`), which a build holds for its whole duration. A
+//! build records its units with [`TargetCacheTracker::mark_units_used`] before
+//! compiling anything, all with the same timestamp.
+//!
+//! Cleaning is done by [`clean`] through the `cargo clean gc` command with
+//! the `--max-unit-age` and `--max-target-size` options. The units of the
+//! most recent build in each build directory are never deleted, so that
+//! cleaning doesn't force the next build to start over.
+//!
+//! As with the [`global_cache_tracker`], tracking is only done with `-Zgc`,
+//! and units found on disk that are missing from the database (for example
+//! because they were built without `-Zgc`) are treated as if they were just
+//! used.
+//!
+//! [`Metadata`]: crate::core::compiler::Metadata
+//! [`global_cache_tracker`]: crate::core::global_cache_tracker
+
+use crate::core::gc::GcOpts;
+use crate::core::global_cache_tracker;
+use crate::ops::CleanContext;
+use crate::util::sqlite::{self, basic_migration, Migration};
+use crate::util::{FileLock, Filesystem};
+use crate::{CargoResult, GlobalContext};
+use anyhow::Context as _;
+use rusqlite::{params, Connection};
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+use tracing::{debug, trace};
+
+/// The filename of the database, in each build directory.
+const TARGET_CACHE_FILENAME: &str = ".unit-last-use";
+
+/// The directories of a build directory that hold files named after units.
+const UNIT_DIRS: &[&str] = &[".fingerprint", "build", "deps", "deps/artifact", "examples"];
+
+/// Type for timestamps as stored in the database.
+///
+/// These are seconds since the Unix epoch.
+type Timestamp = u64;
+
+/// Migrations which initialize the database, and can be used to evolve it over time.
+///
+/// See [`Migration`] for more detail.
+///
+/// **Be sure to not change the order or entries here!**
+fn migrations() -> Vec<Migration> {
+    vec![
+        // unit tracks the last time each unit was used by a build.
+        basic_migration(
+            "CREATE TABLE unit (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                hash TEXT UNIQUE NOT NULL,
+                timestamp INTEGER NOT NULL
+            )",
+        ),
+        // last_build is the time of the most recent build, whose units are
+        // never deleted. It is kept separately since units that weren't
+        // tracked get a more recent timestamp when they are found.
+        basic_migration("CREATE TABLE target_data (last_build INTEGER NOT NULL)"),
+        basic_migration("INSERT INTO target_data (last_build) VALUES (0)"),
+    ]
+}
+
+/// Connection to the last-use database of a single build directory.
+///
+/// See the [`crate::core::target_cache_tracker`] module docs for details.
+#[derive(Debug)]
+pub struct TargetCacheTracker {
+    /// Connection to the SQLite database.
+    conn: Connection,
+}
+
+impl TargetCacheTracker {
+    /// Opens the database of the build directory `dest`.
+    ///
+    /// The caller is responsible for holding the lock of the build directory.
+    pub fn new(dest: &Path) -> CargoResult<TargetCacheTracker> {
+        let mut conn = Connection::open(dest.join(TARGET_CACHE_FILENAME))?;
+        sqlite::migrate(&mut conn, &migrations())?;
+        Ok(TargetCacheTracker { conn })
+    }
+
+    /// Records that the units with the given hashes are used by the current
+    /// build.
+    pub fn mark_units_used(&mut self, hashes: &[String]) -> CargoResult<()> {
+        let now = global_cache_tracker::now();
+        let tx = self.conn.transaction()?;
+        {
+            let mut stmt = tx.prepare_cached(
+                "INSERT INTO unit (hash, timestamp) VALUES (?1, ?2)
+                    ON CONFLICT DO UPDATE SET timestamp=excluded.timestamp",
+            )?;
+            for hash in hashes {
+                stmt.execute(params![hash, now])?;
+            }
+        }
+        tx.execute("UPDATE target_data SET last_build = ?1", [now])?;
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Returns the time of the most recent build, which is the last use of
+    /// all of its units.
+    fn last_build(&self) -> CargoResult<Timestamp> {
+        let last_build = self
+            .conn
+            .query_row("SELECT last_build FROM target_data", [], |row| row.get(0))?;
+        Ok(last_build)
+    }
+
+    /// Returns the last use of every tracked unit.
+    fn units(&self) -> CargoResult<HashMap<String, Timestamp>> {
+        let mut stmt = self
+            .conn
+            .prepare_cached("SELECT hash, timestamp FROM unit")?;
+        let units = stmt
+            .query_map([], |row| Ok((row.get_unwrap(0), row.get_unwrap(1))))?
+            .collect::<Result<_, _>>()?;
+        Ok(units)
+    }
+}
+
+/// A unit found in a build directory.
+struct UnitFiles {
+    /// The index of the build directory in [`clean_inner`].
+    dest: usize,
+    hash: String,
+    timestamp: Timestamp,
+    paths: Vec<PathBuf>,
+    /// The disk usage of `paths`, only computed for `--max-target-size`.
+    size: u64,
+}
+
+/// Deletes artifacts of units from the build directories in `target_dir`
+/// based on the given options.
+pub fn clean(
+    clean_ctx: &mut CleanContext<'_>,
+    target_dir: &Filesystem,
+    gc_opts: &GcOpts,
+) -> CargoResult<()> {
+    clean_inner(clean_ctx, target_dir, gc_opts)
+        .context("failed to clean entries from the target directory")
+}
+
+#[tracing::instrument(skip_all)]
+fn clean_inner(
+    clean_ctx: &mut CleanContext<'_>,
+    target_dir: &Filesystem,
+    gc_opts: &GcOpts,
+) -> CargoResult<()> {
+    let gctx = clean_ctx.gctx;
+    let now = global_cache_tracker::now();
+    trace!(target: "gc", "cleaning target dir {gc_opts:?}");
+
+    let mut locks = Vec::new();
+    let mut trackers = Vec::new();
+    let mut last_builds = Vec::new();
+    let mut units = Vec::new();
+    for dest in tracked_dests(target_dir.as_path_unlocked())? {
+        let (lock, mut tracker) = open_dest(gctx, &dest)?;
+        let index = trackers.len();
+        last_builds.push(tracker.last_build()?);
+        for (hash, (timestamp, paths)) in
+            sync_units_with_files(&mut tracker, &dest, now, clean_ctx.dry_run)?
+        {
+            // This can be an expensive operation, so only perform it if
+            // necessary.
+            let size = if gc_opts.max_target_size.is_some() {
+                paths
+                    .iter()
+                    .map(|path| cargo_util::du(path, &[]))
+                    .sum::<CargoResult<u64>>()?
+            } else {
+                0
+            };
+            units.push(UnitFiles {
+                dest: index,
+                hash,
+                timestamp,
+                paths,
+                size,
+            });
+        }
+        locks.push(lock);
+        trackers.push(tracker);
+    }
+
+    // The units of the most recent build in each directory are kept.
+    let (kept, mut candidates): (Vec<_>, Vec<_>) = units
+        .into_iter()
+        .partition(|unit| unit.timestamp == last_builds[unit.dest]);
+
+    let mut delete = Vec::new();
+    if let Some(max_age) = gc_opts.max_unit_age {
+        let max_age = now.saturating_sub(max_age.as_secs());
+        debug!(target: "gc", "cleaning units since {max_age:?}");
+        let (old, young): (Vec<_>, Vec<_>) = candidates
+            .into_iter()
+            .partition(|unit| unit.timestamp < max_age);
+        delete.extend(old);
+        candidates = young;
+    }
+    // Size collection must happen after date collection so that dates have
+    // precedence, just like the global cache.
+    if let Some(max_size) = gc_opts.max_target_size {
+        debug!(target: "gc", "cleaning units till under {max_size:?}");
+        let mut total_size: u64 = kept.iter().chain(&candidates).map(|unit| unit.size).sum();
+        // The hash is included mainly for test purposes so that units with
+        // the same timestamp are deleted in a deterministic order.
+        candidates.sort_by(|a, b| (a.timestamp, &a.hash).cmp(&(b.timestamp, &b.hash)));
+        for unit in candidates {
+            if total_size <= max_size {
+                break;
+            }
+            total_size -= unit.size;
+            delete.push(unit);
+        }
+    }
+
+    let mut delete_paths: Vec<_> = delete
+        .iter()
+        .flat_map(|unit| unit.paths.iter().cloned())
+        .collect();
+    delete_paths.sort();
+    clean_ctx.remove_paths(&delete_paths)?;
+
+    if !clean_ctx.dry_run {
+        let mut by_dest: BTreeMap<usize, Vec<&str>> = BTreeMap::new();
+        for unit in &delete {
+            by_dest.entry(unit.dest).or_default().push(&unit.hash);
+        }
+        for (dest, hashes) in by_dest {
+            let tx = trackers[dest].conn.transaction()?;
+            {
+                let mut stmt = tx.prepare_cached("DELETE FROM unit WHERE hash = ?1")?;
+                for hash in hashes {
+                    stmt.execute([hash])?;
+                }
+            }
+            tx.commit()?;
+        }
+    }
+    drop(locks);
+    Ok(())
+}
+
+/// Returns the build directories in `target_dir` that have a database, which
+/// are either directly in it or in a directory for a target triple.
+fn tracked_dests(target_dir: &Path) -> CargoResult<Vec<PathBuf>> {
+    let mut dests = Vec::new();
+    for entry in read_dir_sorted(target_dir)? {
+        if entry.join(TARGET_CACHE_FILENAME).exists() {
+            dests.push(entry);
+        } else if entry.is_dir() {
+            for entry in read_dir_sorted(&entry)? {
+                if entry.join(TARGET_CACHE_FILENAME).exists() {
+                    dests.push(entry);
+                }
+            }
+        }
+    }
+    Ok(dests)
+}
+
+/// Returns the sorted entries of the given directory, or nothing if it
+/// doesn't exist.
+fn read_dir_sorted(path: &Path) -> CargoResult<Vec<PathBuf>> {
+    let entries = match path.read_dir() {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => {
+            return Err(anyhow::Error::new(e)
+                .context(format!("failed to read directory `{}`", path.display())))
+        }
+    };
+    let mut paths = entries
+        .map(|entry| Ok(entry?.path()))
+        .collect::<CargoResult<Vec<_>>>()?;
+    paths.sort();
+    Ok(paths)
+}
+
+/// Locks the given build directory, like a build does, and opens its
+/// database.
+fn open_dest(gctx: &GlobalContext, dest: &Path) -> CargoResult<(FileLock, TargetCacheTracker)> {
+    let lock = Filesystem::new(dest.to_path_buf()).open_rw_exclusive_create(
+        ".cargo-lock",
+        gctx,
+        "build directory",
+    )?;
+    let tracker = TargetCacheTracker::new(dest)?;
+    Ok((lock, tracker))
+}
+
+/// Finds the files of every unit in the build directory `dest`, and updates
+/// the database for units that were added or removed without it knowing.
+///
+/// Returns the last use and the files of each unit.
+fn sync_units_with_files(
+    tracker: &mut TargetCacheTracker,
+    dest: &Path,
+    now: Timestamp,
+    dry_run: bool,
+) -> CargoResult<HashMap<String, (Timestamp, Vec<PathBuf>)>> {
+    let mut files: HashMap<String, Vec<PathBuf>> = HashMap::new();
+    for dir in UNIT_DIRS {
+        for path in read_dir_sorted(&dest.join(dir))? {
+            let Some(hash) = path
+                .file_name()
+                .and_then(|name| name.to_str())
+                .and_then(unit_hash_from_name)
+            else {
+                continue;
+            };
+            files.entry(hash.to_string()).or_default().push(path);
+        }
+    }
+
+    let mut units = tracker.units()?;
+    let tx = tracker.conn.transaction()?;
+    {
+        let removed: Vec<_> = units
+            .keys()
+            .filter(|hash| !files.contains_key(*hash))
+            .cloned()
+            .collect();
+        let mut delete_stmt = tx.prepare_cached("DELETE FROM unit WHERE hash = ?1")?;
+        for hash in removed {
+            trace!(target: "gc", "unit {hash} removed from disk");
+            delete_stmt.execute([&hash])?;
+            units.remove(&hash);
+        }
+        let mut insert_stmt =
+            tx.prepare_cached("INSERT INTO unit (hash, timestamp) VALUES (?1, ?2)")?;
+        for hash in files.keys() {
+            if !units.contains_key(hash) {
+                trace!(target: "gc", "unit {hash} not tracked, adding");
+                insert_stmt.execute(params![hash, now])?;
+                units.insert(hash.clone(), now);
+            }
+        }
+    }
+    if dry_run {
+        tx.rollback()?;
+    } else {
+        tx.commit()?;
+    }
+
+    Ok(files
+        .into_iter()
+        .map(|(hash, paths)| (hash.clone(), (units[&hash], paths)))
+        .collect())
+}
+
+/// Extracts the unit hash from the name of a file or directory like
+/// `libfoo-0123456789abcdef.rlib` or `foo-0123456789abcdef`.
+fn unit_hash_from_name(name: &str) -> Option<&str> {
+    let (_, rest) = name.rsplit_once('-')?;
+    let hash = rest.split('.').next()?;
+    (hash.len() == 16 && hash.bytes().all(|b| b.is_ascii_hexdigit())).then_some(hash)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unit_hashes() {
+        let hash = Some("0123456789abcdef");
+        assert_eq!(unit_hash_from_name("foo-0123456789abcdef"), hash);
+        assert_eq!(unit_hash_from_name("foo-bar-0123456789abcdef"), hash);
+        assert_eq!(unit_hash_from_name("libfoo-0123456789abcdef.rlib"), hash);
+        assert_eq!(unit_hash_from_name("foo-0123456789abcdef.d"), hash);
+        assert_eq!(unit_hash_from_name("foo-0123456789abcdef.exe"), hash);
+
+        assert_eq!(unit_hash_from_name("foo"), None);
+        assert_eq!(unit_hash_from_name("libfoo.rlib"), None);
+        assert_eq!(unit_hash_from_name("foo-bar"), None);
+        assert_eq!(unit_hash_from_name("foo-0123456789abcdeg"), None);
+        assert_eq!(unit_hash_from_name("foo-0123456789abcde"), None);
+        assert_eq!(unit_hash_from_name("build-script-build"), None);
+    }
+}
@@ -0,0 +1,233 @@
+//! Selects the workspace members affected by the changes since a git ref, for
+//! [`Packages::ChangedSince`].
+//!
+//! The working tree, including untracked files, is diffed against the ref.
+//! Each changed file belongs to the package with the innermost root
+//! containing it, and to every package whose last build listed it in its
+//! dep-info, like a file pulled in with `include_str!` from outside the
+//! package. A member is selected when it is one of those packages, or depends
+//! on one of them through the workspace's [`Resolve`]. Changing the root
+//! manifest, the lockfile, a `.cargo/config.toml` or a `rust-toolchain` file
+//! selects every member, since those can affect how any of them is built.
+//!
+//! [`Packages::ChangedSince`]: super::Packages::ChangedSince
+
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+use anyhow::Context as _;
+
+use crate::core::compiler::fingerprint::parse_dep_info;
+use crate::core::resolver::Resolve;
+use crate::core::{Package, PackageId, Workspace};
+use crate::ops;
+use crate::ops::lockfile::LOCKFILE_NAME;
+use crate::util::CargoResult;
+use crate::util::GitRepo;
+
+/// Returns the workspace members affected by the changes since `rev`.
+pub fn changed_members<'ws>(ws: &'ws Workspace<'_>, rev: &str) -> CargoResult<Vec<&'ws Package>> {
+    let changed_files = changed_files(ws, rev)?;
+    let lockfile = match ws.requested_lockfile_path() {
+        Some(path) => path.to_owned(),
+        None => ws.lock_root().as_path_unlocked().join(LOCKFILE_NAME),
+    };
+    if changed_files.contains(&canonical(ws.root_manifest()))
+        || changed_files.contains(&canonical(&lockfile))
+        || changed_files.iter().any(|file| affects_all(file))
+    {
+        return Ok(ws.members().collect());
+    }
+
+    // Only selecting packages shouldn't write the lockfile, the build does
+    // that when it resolves the workspace again.
+    let (_, resolve) = ops::resolve_ws(ws, true)?;
+    let roots = package_roots(&resolve);
+    let mut changed = HashSet::new();
+    for file in &changed_files {
+        let owner = roots
+            .iter()
+            .filter(|(_, root)| file.starts_with(root))
+            .max_by_key(|(_, root)| root.components().count());
+        if let Some((id, _)) = owner {
+            changed.insert(*id);
+        }
+    }
+    changed.extend(dep_info_owners(ws, &roots, &changed_files)?);
+
+    let affected = dependents(&resolve, changed);
+    Ok(ws
+        .members()
+        .filter(|pkg| affected.contains(&pkg.package_id()))
+        .collect())
+}
+
+/// Whether a changed file is a config or toolchain file, which aren't part of
+/// any package but can change how every package is built.
+fn affects_all(file: &Path) -> bool {
+    let Some(name) = file.file_name().and_then(|name| name.to_str()) else {
+        return false;
+    };
+    let in_cargo_dir = file
+        .parent()
+        .and_then(|dir| dir.file_name())
+        .map_or(false, |dir| dir == ".cargo");
+    (in_cargo_dir && matches!(name, "config" | "config.toml"))
+        || matches!(name, "rust-toolchain" | "rust-toolchain.toml")
+}
+
+/// Lists the files that differ between `rev` and the working tree.
+fn changed_files(ws: &Workspace<'_>, rev: &str) -> CargoResult<HashSet<PathBuf>> {
+    let repo = GitRepo::discover(ws.root(), ws.gctx().cwd()).with_context(|| {
+        format!(
+            "`--changed-since` requires a git repository, but none was found at or above `{}`",
+            ws.root().display()
+        )
+    })?;
+    let Some(workdir) = repo.workdir().map(canonical) else {
+        anyhow::bail!(
+            "did not expect repo at {} to be bare",
+            repo.path().display()
+        );
+    };
+    let tree = repo
+        .revparse_single(rev)
+        .and_then(|object| object.peel_to_tree())
+        .with_context(|| format!("failed to find git ref `{rev}`"))?;
+    let mut opts = git2::DiffOptions::new();
+    opts.include_untracked(true).recurse_untracked_dirs(true);
+    let diff = repo.diff_tree_to_workdir_with_index(Some(&tree), Some(&mut opts))?;
+    // The target directory may not be ignored by git, but its files never
+    // belong to a package.
+    let target_dir = canonical(ws.target_dir().as_path_unlocked());
+    let mut files = HashSet::new();
+    for delta in diff.deltas() {
+        for file in [delta.old_file(), delta.new_file()] {
+            if let Some(path) = file.path() {
+                let path = workdir.join(path);
+                if !path.starts_with(&target_dir) {
+                    files.insert(path);
+                }
+            }
+        }
+    }
+    tracing::debug!("{} files changed since `{rev}`", files.len());
+    Ok(files)
+}
+
+/// Returns the root directory of every package in `resolve` from a path
+/// source, the only packages whose files can change.
+fn package_roots(resolve: &Resolve) -> Vec<(PackageId, PathBuf)> {
+    resolve
+        .iter()
+        .filter(|id| id.source_id().is_path())
+        .filter_map(|id| {
+            let root = id.source_id().url().to_file_path().ok()?;
+            Some((id, canonical(&root)))
+        })
+        .collect()
+}
+
+/// Finds the packages whose dep-info, from any earlier build in the target
+/// directory, lists one of `changed_files`.
+///
+/// The dep-info files are in `.fingerprint/<name>-<hash>` directories, for
+/// the host in `target/<profile>` and for other targets in
+/// `target/<triple>/<profile>`.
+fn dep_info_owners(
+    ws: &Workspace<'_>,
+    roots: &[(PackageId, PathBuf)],
+    changed_files: &HashSet<PathBuf>,
+) -> CargoResult<HashSet<PackageId>> {
+    let target_root = ws.target_dir().into_path_unlocked();
+    let mut fingerprint_dirs = Vec::new();
+    for dir in subdirs(&target_root) {
+        fingerprint_dirs.push(dir.join(".fingerprint"));
+        fingerprint_dirs.extend(subdirs(&dir).map(|dir| dir.join(".fingerprint")));
+    }
+
+    let mut by_name: HashMap<&str, Vec<&(PackageId, PathBuf)>> = HashMap::new();
+    for root in roots {
+        by_name
+            .entry(root.0.name().as_str())
+            .or_default()
+            .push(root);
+    }
+
+    let mut owners = HashSet::new();
+    for unit_dir in fingerprint_dirs.iter().flat_map(|dir| subdirs(dir)) {
+        let Some((name, _hash)) = unit_dir
+            .file_name()
+            .and_then(|name| name.to_str())
+            .and_then(|name| name.rsplit_once('-'))
+        else {
+            continue;
+        };
+        let Some(candidates) = by_name.get(name) else {
+            continue;
+        };
+        let dep_infos = std::fs::read_dir(&unit_dir)
+            .into_iter()
+            .flatten()
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+            .filter(|path| {
+                path.file_name()
+                    .and_then(|name| name.to_str())
+                    .map_or(false, |name| name.starts_with("dep-"))
+            });
+        for dep_info in dep_infos {
+            for (id, root) in candidates {
+                if owners.contains(id) {
+                    continue;
+                }
+                let Some(info) = parse_dep_info(root, &target_root, &dep_info)? else {
+                    continue;
+                };
+                if info
+                    .files
+                    .keys()
+                    .any(|file| changed_files.contains(&canonical(file)))
+                {
+                    owners.insert(*id);
+                }
+            }
+        }
+    }
+    Ok(owners)
+}
+
+/// Extends `changed` with every package that transitively depends on one of
+/// them.
+fn dependents(resolve: &Resolve, changed: HashSet<PackageId>) -> HashSet<PackageId> {
+    let mut reverse: HashMap<PackageId, Vec<PackageId>> = HashMap::new();
+    for id in resolve.iter() {
+        for (dep, _) in resolve.deps(id) {
+            reverse.entry(dep).or_default().push(id);
+        }
+    }
+    let mut affected = HashSet::new();
+    let mut queue: Vec<_> = changed.into_iter().collect();
+    while let Some(id) = queue.pop() {
+        if affected.insert(id) {
+            queue.extend(reverse.get(&id).into_iter().flatten().copied());
+        }
+    }
+    affected
+}
+
+/// Lists the directories in `dir`, or nothing if it can't be read.
+fn subdirs(dir: &Path) -> impl Iterator<Item = PathBuf> {
+    std::fs::read_dir(dir)
+        .into_iter()
+        .flatten()
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.file_type().map_or(false, |ty| ty.is_dir()))
+        .map(|entry| entry.path())
+}
+
+/// Canonicalizes `path` if it exists, so paths from git, manifests and
+/// dep-info compare equal.
+fn canonical(path: &Path) -> PathBuf {
+    path.canonicalize().unwrap_or_else(|_| path.to_owned())
+}
@@ -62,6 +62,7 @@ pub use compile_filter::{CompileFilter, FilterRule, LibRule};
 mod unit_generator;
 use unit_generator::UnitGenerator;
 
+mod changed_since;
 mod packages;
 
 pub use packages::Packages;
@@ -250,12 +251,19 @@ pub fn create_bcx<'a, 'gctx>(
     let mut target_data = RustcTargetData::new(ws, &build_config.requested_kinds)?;
 
     let specs = spec.to_package_id_specs(ws)?;
+    // Only `--changed-since` can select no packages at all. The workspace is
+    // still resolved as a whole, and nothing is built.
+    let resolve_specs = if specs.is_empty() {
+        ops::Packages::All.to_package_id_specs(ws)?
+    } else {
+        specs.clone()
+    };
     let has_dev_units = {
         // Rustdoc itself doesn't need dev-dependencies. But to scrape examples from packages in the
         // workspace, if any of those packages need dev-dependencies, then we need include dev-dependencies
         // to scrape those packages.
         let any_pkg_has_scrape_enabled = ws
-            .members_with_features(&specs, cli_features)?
+            .members_with_features(&resolve_specs, cli_features)?
             .iter()
             .any(|(pkg, _)| {
                 pkg.targets()
@@ -277,7 +285,7 @@ pub fn create_bcx<'a, 'gctx>(
         &mut target_data,
         &build_config.requested_kinds,
         cli_features,
-        &specs,
+        &resolve_specs,
         has_dev_units,
         crate::core::resolver::features::ForceAllTargets::No,
         dry_run,
@@ -289,9 +297,41 @@ pub fn create_bcx<'a, 'gctx>(
         resolved_features,
     } = resolve;
 
-    let std_resolve_features = if let Some(crates) = &gctx.cli_unstable().build_std {
-        let (std_package_set, std_resolve, std_features) =
-            standard_lib::resolve_std(ws, &mut target_data, &build_config, crates)?;
+    let profiles = Profiles::new(ws, build_config.requested_profile)?;
+    profiles.validate_packages(
+        ws.profiles(),
+        &mut gctx.shell(),
+        workspace_resolve.as_ref().unwrap_or(&resolve),
+    )?;
+
+    // If `--target` has not been specified, then the unit graph is built
+    // assuming `--target $HOST` was specified. See
+    // `rebuild_unit_graph_shared` for more on why this is done.
+    let explicit_host_kind = CompileKind::Target(CompileTarget::new(&target_data.rustc.host)?);
+    let explicit_host_kinds: Vec<_> = build_config
+        .requested_kinds
+        .iter()
+        .map(|kind| match kind {
+            CompileKind::Host => explicit_host_kind,
+            CompileKind::Target(t) => CompileKind::Target(*t),
+        })
+        .collect();
+
+    let std_crates = standard_lib::std_crates(
+        ws,
+        Some(&profiles),
+        &target_data,
+        &explicit_host_kinds,
+        None,
+    )?;
+    let std_resolve_features = if !std_crates.is_empty() {
+        let (std_package_set, std_resolve, std_features) = standard_lib::resolve_std(
+            ws,
+            &mut target_data,
+            &build_config,
+            Some(&profiles),
+            &std_crates,
+        )?;
         pkg_set.add_set(std_package_set);
         Some((std_resolve, std_features))
     } else {
@@ -340,26 +380,6 @@ pub fn create_bcx<'a, 'gctx>(
         );
     }
 
-    let profiles = Profiles::new(ws, build_config.requested_profile)?;
-    profiles.validate_packages(
-        ws.profiles(),
-        &mut gctx.shell(),
-        workspace_resolve.as_ref().unwrap_or(&resolve),
-    )?;
-
-    // If `--target` has not been specified, then the unit graph is built
-    // assuming `--target $HOST` was specified. See
-    // `rebuild_unit_graph_shared` for more on why this is done.
-    let explicit_host_kind = CompileKind::Target(CompileTarget::new(&target_data.rustc.host)?);
-    let explicit_host_kinds: Vec<_> = build_config
-        .requested_kinds
-        .iter()
-        .map(|kind| match kind {
-            CompileKind::Host => explicit_host_kind,
-            CompileKind::Target(t) => CompileKind::Target(*t),
-        })
-        .collect();
-
     // Passing `build_config.requested_kinds` instead of
     // `explicit_host_kinds` here so that `generate_root_units` can do
     // its own special handling of `CompileKind::Host`. It will
@@ -398,13 +418,18 @@ pub fn create_bcx<'a, 'gctx>(
         Vec::new()
     };
 
-    let std_roots = if let Some(crates) = standard_lib::std_crates(gctx, Some(&units)) {
-        let (std_resolve, std_features) = std_resolve_features.as_ref().unwrap();
+    let std_roots = if let Some((std_resolve, std_features)) = &std_resolve_features {
+        let crates = standard_lib::std_crates(
+            ws,
+            Some(&profiles),
+            &target_data,
+            &explicit_host_kinds,
+            Some(&units),
+        )?;
         standard_lib::generate_std_roots(
             &crates,
             std_resolve,
             std_features,
-            &explicit_host_kinds,
             &pkg_set,
             interner,
             &profiles,
@@ -924,14 +949,19 @@ fn override_rustc_crate_types(
 ///
 /// Dependencies are added as `dep_name/feat_name` because `required-features`
 /// wants to support that syntax.
+///
+/// `kind` is the kind the package is built for, as features may differ
+/// between targets.
 pub fn resolve_all_features(
     resolve_with_overrides: &Resolve,
     resolved_features: &features::ResolvedFeatures,
     package_set: &PackageSet<'_>,
     package_id: PackageId,
+    kind: CompileKind,
 ) -> HashSet<String> {
+    let features_for = FeaturesFor::normal_for(kind);
     let mut features: HashSet<String> = resolved_features
-        .activated_features(package_id, FeaturesFor::NormalOrDev)
+        .activated_features(package_id, features_for)
         .iter()
         .map(|s| s.to_string())
         .collect();
@@ -944,9 +974,13 @@ pub fn resolve_all_features(
             .expect("packages downloaded")
             .proc_macro();
         for dep in deps {
-            let features_for = FeaturesFor::from_for_host(is_proc_macro || dep.is_build());
+            let dep_features_for = if is_proc_macro || dep.is_build() {
+                FeaturesFor::HostDep
+            } else {
+                features_for
+            };
             for feature in resolved_features
-                .activated_features_unverified(dep_id, features_for)
+                .activated_features_unverified(dep_id, dep_features_for)
                 .unwrap_or_default()
             {
                 features.insert(format!("{}/{}", dep.name_in_toml(), feature));
@@ -2,7 +2,8 @@
 
 use std::collections::BTreeSet;
 
-use crate::core::{Package, PackageIdSpecQuery};
+use super::changed_since;
+use crate::core::{Package, PackageId, PackageIdSpecQuery};
 use crate::core::{PackageIdSpec, Workspace};
 use crate::util::restricted_names::is_glob_pattern;
 use crate::util::CargoResult;
@@ -27,6 +28,9 @@ pub enum Packages {
     OptOut(Vec<String>),
     /// A sequence of hand-picked packages that will be built. Normally done by `-p` flag.
     Packages(Vec<String>),
+    /// Workspace members affected by the changes since a git ref. Done by
+    /// `--changed-since` flag, see [`Packages::changed_since`].
+    ChangedSince(Vec<PackageId>),
 }
 
 impl Packages {
@@ -41,6 +45,26 @@ impl Packages {
         })
     }
 
+    /// Creates a [`Packages::ChangedSince`] with the workspace members
+    /// affected by the changes since `rev`.
+    ///
+    /// The members are selected once here, since that diffs the working tree
+    /// and resolves the workspace.
+    pub fn changed_since(ws: &Workspace<'_>, rev: &str) -> CargoResult<Self> {
+        let ids: Vec<_> = changed_since::changed_members(ws, rev)?
+            .into_iter()
+            .map(Package::package_id)
+            .collect();
+        if ids.is_empty() {
+            // Nothing to do is a success, so that CI jobs using this don't
+            // fail just because a change didn't touch any code.
+            ws.gctx()
+                .shell()
+                .note(format!("no packages changed since `{rev}`"))?;
+        }
+        Ok(Packages::ChangedSince(ids))
+    }
+
     /// Converts selected packages to [`PackageIdSpec`]s.
     pub fn to_package_id_specs(&self, ws: &Workspace<'_>) -> CargoResult<Vec<PackageIdSpec>> {
         let specs = match self {
@@ -93,6 +117,9 @@ impl Packages {
                 .map(Package::package_id)
                 .map(|id| id.to_spec())
                 .collect(),
+            // No changed packages is reported by `Packages::changed_since`.
+            Packages::ChangedSince(ids) if ids.is_empty() => return Ok(Vec::new()),
+            Packages::ChangedSince(ids) => ids.iter().map(|id| id.to_spec()).collect(),
         };
         if specs.is_empty() {
             if ws.is_virtual() {
@@ -152,6 +179,10 @@ impl Packages {
                 emit_pattern_not_found(ws, patterns, false)?;
                 packages
             }
+            Packages::ChangedSince(ids) => ws
+                .members()
+                .filter(|pkg| ids.contains(&pkg.package_id()))
+                .collect(),
         };
         Ok(packages)
     }
@@ -164,6 +195,7 @@ impl Packages {
             Packages::All => ws.members().count() > 1,
             Packages::Packages(_) => true,
             Packages::OptOut(_) => true,
+            Packages::ChangedSince(_) => true,
         }
     }
 }
@@ -100,12 +100,6 @@ impl<'a> UnitGenerator<'a, '_> {
 
         let is_local = pkg.package_id().source_id().is_path();
 
-        // No need to worry about build-dependencies, roots are never build dependencies.
-        let features_for = FeaturesFor::from_for_host(target.proc_macro());
-        let features = self
-            .resolved_features
-            .activated_features(pkg.package_id(), features_for);
-
         // If `--target` has not been specified, then the unit
         // graph is built almost like if `--target $HOST` was
         // specified. See `rebuild_unit_graph_shared` for more on
@@ -162,7 +156,18 @@ impl<'a> UnitGenerator<'a, '_> {
                     is_local,
                     unit_for,
                     kind,
+                    target,
+                    target_mode,
                 );
+                // No need to worry about build-dependencies, roots are never build dependencies.
+                let features_for = if target.proc_macro() {
+                    FeaturesFor::HostDep
+                } else {
+                    FeaturesFor::normal_for(kind)
+                };
+                let features = self
+                    .resolved_features
+                    .activated_features(pkg.package_id(), features_for);
                 let kind = kind.for_target(target);
                 self.interner.intern(
                     pkg,
@@ -170,7 +175,7 @@ impl<'a> UnitGenerator<'a, '_> {
                     profile,
                     kind,
                     target_mode,
-                    features.clone(),
+                    features,
                     self.target_data.info(kind).rustflags.clone(),
                     self.target_data.info(kind).rustdocflags.clone(),
                     self.target_data.target_config(kind).links_overrides.clone(),
@@ -640,7 +645,7 @@ Rustdoc did not scrape the following examples because they require dev-dependenc
         // Only include targets that are libraries or have all required
         // features available.
         //
-        // `features_map` is a map of &Package -> enabled_features
+        // `features_map` is a map of (&Package, CompileKind) -> enabled_features
         // It is computed by the set of enabled features for the package plus
         // every enabled feature of every enabled dependency.
         let mut features_map = HashMap::new();
@@ -655,38 +660,43 @@ Rustdoc did not scrape the following examples because they require dev-dependenc
             mode,
         } in proposals
         {
-            let unavailable_features = match target.required_features() {
-                Some(rf) => {
-                    self.validate_required_features(target.name(), rf, pkg.summary())?;
-
-                    let features = features_map.entry(pkg).or_insert_with(|| {
-                        super::resolve_all_features(
-                            self.resolve,
-                            self.resolved_features,
-                            self.package_set,
-                            pkg.package_id(),
-                        )
-                    });
-                    rf.iter().filter(|f| !features.contains(*f)).collect()
-                }
-                None => Vec::new(),
-            };
-            if target.is_lib() || unavailable_features.is_empty() {
-                units.extend(self.new_units(pkg, target, mode));
-            } else if requires_features {
-                let required_features = target.required_features().unwrap();
-                let quoted_required_features: Vec<String> = required_features
-                    .iter()
-                    .map(|s| format!("`{}`", s))
-                    .collect();
-                anyhow::bail!(
-                    "target `{}` in package `{}` requires the features: {}\n\
+            if let Some(rf) = target.required_features() {
+                self.validate_required_features(target.name(), rf, pkg.summary())?;
+            }
+            for unit in self.new_units(pkg, target, mode) {
+                let unavailable_features = match target.required_features() {
+                    Some(rf) if !target.is_lib() => {
+                        // Features may differ between the requested targets.
+                        let features = features_map.entry((pkg, unit.kind)).or_insert_with(|| {
+                            super::resolve_all_features(
+                                self.resolve,
+                                self.resolved_features,
+                                self.package_set,
+                                pkg.package_id(),
+                                unit.kind,
+                            )
+                        });
+                        rf.iter().filter(|f| !features.contains(*f)).collect()
+                    }
+                    _ => Vec::new(),
+                };
+                if unavailable_features.is_empty() {
+                    units.insert(unit);
+                } else if requires_features {
+                    let required_features = target.required_features().unwrap();
+                    let quoted_required_features: Vec<String> = required_features
+                        .iter()
+                        .map(|s| format!("`{}`", s))
+                        .collect();
+                    anyhow::bail!(
+                        "target `{}` in package `{}` requires the features: {}\n\
                Consider enabling them by passing, e.g., `--features=\"{}\"`",
-                    target.name(),
-                    pkg.name(),
-                    quoted_required_features.join(", "),
-                    required_features.join(" ")
-                );
+                        target.name(),
+                        pkg.name(),
+                        quoted_required_features.join(", "),
+                        required_features.join(" ")
+                    );
+                }
             }
             // else, silently skip target.
         }
@@ -1,7 +1,11 @@
 //! Implementation of `cargo config` subcommand.
 
+use crate::core::profiles::Profiles;
+use crate::core::{PackageIdSpec, PackageIdSpecQuery, Workspace};
+use crate::ops;
 use crate::util::context::{ConfigKey, ConfigValue as CV, Definition, GlobalContext};
 use crate::util::errors::CargoResult;
+use crate::util::interning::InternedString;
 use crate::{drop_eprintln, drop_println};
 use anyhow::{bail, format_err, Error};
 use serde_json::json;
@@ -88,6 +92,64 @@ pub fn get(gctx: &GlobalContext, opts: &GetOptions<'_>) -> CargoResult<()> {
     Ok(())
 }
 
+/// Options for `cargo config get --effective`.
+pub struct EffectiveOptions<'a> {
+    /// The package whose profile to display.
+    pub package: Option<&'a str>,
+    /// The kind of target whose profile to display, like `bin`.
+    pub target_kind: Option<&'a str>,
+}
+
+/// Displays a profile merged with the profiles of the workspace, see
+/// [`Profiles::effective_toml`].
+pub fn get_effective(
+    ws: &Workspace<'_>,
+    opts: &GetOptions<'_>,
+    effective: &EffectiveOptions<'_>,
+) -> CargoResult<()> {
+    let gctx = ws.gctx();
+    if opts.show_origin {
+        bail!("--effective does not support --show-origin");
+    }
+    if !opts.merged {
+        bail!("--effective does not support --merged=no");
+    }
+    let key = match opts.key {
+        Some(key) => ConfigKey::from_str(key),
+        None => ConfigKey::new(),
+    };
+    let mut parts = key.parts();
+    let (Some("profile"), Some(name)) = (parts.next(), parts.next()) else {
+        bail!("--effective requires the key of a profile, like `profile.release`");
+    };
+    let profiles = Profiles::new(ws, InternedString::new(name))?;
+    let pkg = match effective.package {
+        Some(spec) => {
+            let spec = PackageIdSpec::parse(spec)?;
+            let (_, resolve) = ops::resolve_ws(ws, true)?;
+            let pkg_id = spec.query(resolve.iter())?;
+            Some((pkg_id, ws.members().any(|pkg| pkg.package_id() == pkg_id)))
+        }
+        None => None,
+    };
+    let mut value = toml::Value::try_from(profiles.effective_toml(pkg, effective.target_kind))?;
+    for part in parts {
+        value = match value {
+            toml::Value::Table(mut table) => table.remove(part),
+            _ => None,
+        }
+        .ok_or_else(|| format_err!("config value `{}` is not set", key))?;
+    }
+    match opts.format {
+        ConfigFormat::Toml => print_toml_value(gctx, &key, &value),
+        ConfigFormat::Json => print_json_value(gctx, &key, serde_json::to_value(&value)?, true),
+        ConfigFormat::JsonValue => {
+            print_json_value(gctx, &key, serde_json::to_value(&value)?, false)
+        }
+    }
+    Ok(())
+}
+
 /// Checks for environment variables that might be used.
 fn maybe_env<'gctx>(
     gctx: &'gctx GlobalContext,
@@ -161,6 +223,20 @@ fn print_toml(gctx: &GlobalContext, opts: &GetOptions<'_>, key: &ConfigKey, cv:
     }
 }
 
+/// Like [`print_toml`], for a value that isn't from config.
+fn print_toml_value(gctx: &GlobalContext, key: &ConfigKey, value: &toml::Value) {
+    match value {
+        toml::Value::Table(table) => {
+            for (table_key, val) in table {
+                let mut subkey = key.clone();
+                subkey.push(table_key);
+                print_toml_value(gctx, &subkey, val);
+            }
+        }
+        _ => drop_println!(gctx, "{} = {}", key, value),
+    }
+}
+
 fn print_toml_env(gctx: &GlobalContext, env: &[(&str, &str)]) {
     drop_println!(
         gctx,
@@ -184,22 +260,7 @@ fn print_json_env(gctx: &GlobalContext, env: &[(&str, &str)]) {
 }
 
 fn print_json(gctx: &GlobalContext, key: &ConfigKey, cv: &CV, include_key: bool) {
-    let json_value = if key.is_root() || !include_key {
-        cv_to_json(cv)
-    } else {
-        let mut parts: Vec<_> = key.parts().collect();
-        let last_part = parts.pop().unwrap();
-        let mut root_table = json!({});
-        // Create a JSON object with nested keys up to the value being displayed.
-        let mut table = &mut root_table;
-        for part in parts {
-            table[part] = json!({});
-            table = table.get_mut(part).unwrap();
-        }
-        table[last_part] = cv_to_json(cv);
-        root_table
-    };
-    drop_println!(gctx, "{}", serde_json::to_string(&json_value).unwrap());
+    print_json_value(gctx, key, cv_to_json(cv), include_key);
 
     // Helper for recursively converting a CV to JSON.
     fn cv_to_json(cv: &CV) -> serde_json::Value {
@@ -222,6 +283,32 @@ fn print_json(gctx: &GlobalContext, key: &ConfigKey, cv: &CV, include_key: bool)
     }
 }
 
+/// Prints a JSON value, nested in objects for the parts of `key` when
+/// `include_key` is set.
+fn print_json_value(
+    gctx: &GlobalContext,
+    key: &ConfigKey,
+    value: serde_json::Value,
+    include_key: bool,
+) {
+    let json_value = if key.is_root() || !include_key {
+        value
+    } else {
+        let mut parts: Vec<_> = key.parts().collect();
+        let last_part = parts.pop().unwrap();
+        let mut root_table = json!({});
+        // Create a JSON object with nested keys up to the value being displayed.
+        let mut table = &mut root_table;
+        for part in parts {
+            table[part] = json!({});
+            table = table.get_mut(part).unwrap();
+        }
+        table[last_part] = value;
+        root_table
+    };
+    drop_println!(gctx, "{}", serde_json::to_string(&json_value).unwrap());
+}
+
 fn print_toml_unmerged(
     gctx: &GlobalContext,
     opts: &GetOptions<'_>,
@@ -63,11 +63,13 @@ pub fn fetch<'a>(
         deps_to_fetch.extend(deps);
     }
 
-    // If -Zbuild-std was passed, download dependencies for the standard library.
+    // If the standard library is built, download its dependencies.
     // We don't know ahead of time what jobs we'll be running, so tell `std_crates` that.
-    if let Some(crates) = standard_lib::std_crates(gctx, None) {
+    let std_crates =
+        standard_lib::std_crates(ws, None, &data, &build_config.requested_kinds, None)?;
+    if !std_crates.is_empty() {
         let (std_package_set, _, _) =
-            standard_lib::resolve_std(ws, &mut data, &build_config, &crates)?;
+            standard_lib::resolve_std(ws, &mut data, &build_config, None, &std_crates)?;
         packages.add_set(std_package_set);
     }
 
@@ -89,8 +89,8 @@ struct MetadataResolveNode {
 
 #[derive(Serialize)]
 struct Dep {
-    // TODO(bindeps): after -Zbindeps gets stabilized,
-    // mark this field as deprecated in the help manual of cargo-metadata
+    /// Superseded by [`DepKindInfo::extern_name`], which is per dependency
+    /// kind. Kept for backward compatibility.
     name: InternedString,
     pkg: PackageIdSpec,
     #[serde(skip)]
@@ -103,11 +103,11 @@ struct DepKindInfo {
     kind: DepKind,
     target: Option<Platform>,
 
-    // vvvvv The fields below are introduced for `-Z bindeps`.
     /// What the manifest calls the crate.
     ///
     /// A renamed dependency will show the rename instead of original name.
-    // TODO(bindeps): Remove `Option` after -Zbindeps get stabilized.
+    /// Only absent for artifact dependencies whose target cannot be
+    /// referenced as an extern crate.
     #[serde(skip_serializing_if = "Option::is_none")]
     extern_name: Option<InternedString>,
     /// Artifact's crate type, e.g. staticlib, cdylib, bin...
@@ -122,7 +122,6 @@ struct DepKindInfo {
     /// Executable name for an artifact binary dependency.
     #[serde(skip_serializing_if = "Option::is_none")]
     bin_name: Option<String>,
-    // ^^^^^ The fields above are introduced for `-Z bindeps`.
 }
 
 /// Builds the resolve graph as it will be displayed to the user.
@@ -255,19 +254,11 @@ fn build_resolve_graph_r(
                         Some(a) if a.is_lib() => true,
                         _ => false,
                     };
-                    // TODO(bindeps): Cargo shouldn't have `extern_name` field
-                    // if the user is not using -Zbindeps.
-                    // Remove this condition ` after -Zbindeps gets stabilized.
-                    let extern_name = if dep.artifact().is_some() {
-                        Some(extern_name(target)?)
-                    } else {
-                        None
-                    };
                     if included {
                         dep_kinds.push(DepKindInfo {
                             kind: dep.kind(),
                             target: dep.platform().cloned(),
-                            extern_name,
+                            extern_name: Some(extern_name(target)?),
                             artifact: None,
                             compile_target: None,
                             bin_name: None,
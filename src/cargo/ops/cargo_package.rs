@@ -1368,6 +1368,12 @@ impl<'a> TmpRegistry<'a> {
                 lib: dep.lib,
             })
             .collect();
+        // Artifact dependencies are only understood as of schema version 3.
+        let v = if deps.iter().any(RegistryDependency::is_artifact) {
+            3
+        } else {
+            2
+        };
 
         let index_line = serde_json::to_string(&IndexPackage {
             name: new_crate.name.into(),
@@ -1383,7 +1389,8 @@ impl<'a> TmpRegistry<'a> {
             yanked: None,
             links: new_crate.links.map(|x| x.into()),
             rust_version: None,
-            v: Some(2),
+            sig: None,
+            v: Some(v),
         })?;
 
         let file = cargo_util::registry::make_dep_path(package.name().as_str(), false);
@@ -0,0 +1,199 @@
+//! Implementation of `cargo profile` subcommand.
+
+use crate::core::compiler::{CompileKind, Unit, UnitInterner};
+use crate::core::profiles::{ProfileField, ProfileOrigin};
+use crate::core::Workspace;
+use crate::drop_println;
+use crate::ops::{self, CompileOptions};
+use crate::util::context::Definition;
+use crate::util::errors::CargoResult;
+use anyhow::{bail, Error};
+use serde_json::json;
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+
+pub enum ProfileFormat {
+    Text,
+    Json,
+}
+
+impl ProfileFormat {
+    /// For clap.
+    pub const POSSIBLE_VALUES: [&'static str; 2] = ["text", "json"];
+}
+
+impl FromStr for ProfileFormat {
+    type Err = Error;
+    fn from_str(s: &str) -> CargoResult<Self> {
+        match s {
+            "text" => Ok(ProfileFormat::Text),
+            "json" => Ok(ProfileFormat::Json),
+            f => bail!("unknown profile format `{}`", f),
+        }
+    }
+}
+
+impl fmt::Display for ProfileFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            ProfileFormat::Text => write!(f, "text"),
+            ProfileFormat::Json => write!(f, "json"),
+        }
+    }
+}
+
+/// Options for `cargo profile show`.
+pub struct ShowOptions {
+    pub compile_opts: CompileOptions,
+    pub format: ProfileFormat,
+}
+
+/// The version of the JSON output of `cargo profile show`.
+const VERSION: u32 = 1;
+
+/// Prints the profile of every unit the build would compile, with where the
+/// value of each field comes from.
+pub fn show(ws: &Workspace<'_>, opts: &ShowOptions) -> CargoResult<()> {
+    let interner = UnitInterner::new();
+    let bcx = ops::create_bcx(ws, &opts.compile_opts, &interner)?;
+
+    // Units don't record whether they are built for the host, but their
+    // dependents do. Roots are never build dependencies.
+    let mut is_for_host: HashMap<&Unit, bool> = bcx
+        .roots
+        .iter()
+        .map(|root| (root, root.target.for_host()))
+        .collect();
+    for deps in bcx.unit_graph.values() {
+        for dep in deps {
+            is_for_host
+                .entry(&dep.unit)
+                .or_insert(dep.unit_for.is_for_host());
+        }
+    }
+
+    // Running a build script doesn't compile anything, its profile is only
+    // used for a few environment variables.
+    let mut units: Vec<&Unit> = bcx
+        .unit_graph
+        .keys()
+        .filter(|unit| !unit.mode.is_run_custom_build())
+        .collect();
+    units.sort_unstable();
+
+    let mut explained = Vec::new();
+    for unit in units {
+        let is_for_host = is_for_host[unit];
+        let fields = bcx.profiles.explain(ws, unit, is_for_host)?;
+        explained.push((unit, is_for_host, fields));
+    }
+
+    match opts.format {
+        ProfileFormat::Text => print_text(ws, &explained),
+        ProfileFormat::Json => print_json(ws, &explained)?,
+    }
+    Ok(())
+}
+
+fn print_text(ws: &Workspace<'_>, explained: &[(&Unit, bool, Vec<ProfileField>)]) {
+    let gctx = ws.gctx();
+    for (i, (unit, is_for_host, fields)) in explained.iter().enumerate() {
+        if i > 0 {
+            drop_println!(gctx);
+        }
+        let mut details = vec![mode_name(unit)];
+        if let CompileKind::Target(target) = unit.kind {
+            details.push(target.short_name().to_string());
+        }
+        if *is_for_host {
+            details.push("for host".to_string());
+        }
+        drop_println!(
+            gctx,
+            "{} {} ({})",
+            unit.pkg.package_id(),
+            unit.target.description_named(),
+            details.join(", ")
+        );
+        drop_println!(gctx, "  profile = {:?}", unit.profile.name.as_str());
+        for field in fields {
+            drop_println!(gctx, "  {} = {} # {}", field.key, field.value, field.origin);
+        }
+    }
+}
+
+fn print_json(
+    ws: &Workspace<'_>,
+    explained: &[(&Unit, bool, Vec<ProfileField>)],
+) -> CargoResult<()> {
+    let units: Vec<_> = explained
+        .iter()
+        .map(|(unit, is_for_host, fields)| {
+            let fields: Vec<_> = fields
+                .iter()
+                .map(|field| {
+                    json!({
+                        "key": field.key,
+                        "value": field.value,
+                        "origin": origin_json(&field.origin),
+                    })
+                })
+                .collect();
+            json!({
+                "pkg_id": unit.pkg.package_id(),
+                "target": &unit.target,
+                "platform": unit.kind,
+                "mode": unit.mode,
+                "for_host": is_for_host,
+                "profile": unit.profile.name,
+                "fields": fields,
+            })
+        })
+        .collect();
+    let output = json!({
+        "version": VERSION,
+        "units": units,
+    });
+    drop_println!(ws.gctx(), "{}", serde_json::to_string(&output)?);
+    Ok(())
+}
+
+fn origin_json(origin: &ProfileOrigin) -> serde_json::Value {
+    match origin {
+        ProfileOrigin::Default => json!({ "kind": "default" }),
+        ProfileOrigin::Manifest { table, path } => json!({
+            "kind": "manifest",
+            "table": table,
+            "path": path,
+        }),
+        ProfileOrigin::Config { table, definition } => match definition {
+            Definition::Path(path) | Definition::Cli(Some(path)) => json!({
+                "kind": "config",
+                "table": table,
+                "path": path,
+            }),
+            Definition::Environment(name) => json!({
+                "kind": "env",
+                "table": table,
+                "name": name,
+            }),
+            Definition::Cli(None) => json!({
+                "kind": "cli",
+                "table": table,
+            }),
+        },
+        ProfileOrigin::Env(name) => json!({
+            "kind": "env",
+            "name": name,
+        }),
+    }
+}
+
+/// The name of the mode of a unit, as in `--unit-graph`.
+fn mode_name(unit: &Unit) -> String {
+    match serde_json::to_value(unit.mode) {
+        Ok(serde_json::Value::String(mode)) => mode,
+        _ => unreachable!("modes serialize to strings"),
+    }
+}
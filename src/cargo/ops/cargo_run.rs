@@ -5,6 +5,7 @@ use std::path::Path;
 use crate::core::compiler::UnitOutput;
 use crate::core::{TargetKind, Workspace};
 use crate::ops;
+use crate::util::context::RunnerKind;
 use crate::util::CargoResult;
 
 pub fn run(
@@ -92,7 +93,7 @@ pub fn run(
         Err(_) => path.to_path_buf(),
     };
     let pkg = bins[0].0;
-    let mut process = compile.target_process(exe, unit.kind, pkg, *script_meta)?;
+    let mut process = compile.target_process(exe, unit.kind, RunnerKind::Run, pkg, *script_meta)?;
 
     // Sets the working directory of the child process to the current working
     // directory of the parent process.
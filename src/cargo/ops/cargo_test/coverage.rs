@@ -0,0 +1,157 @@
+//! Collects the coverage of tests built with the `coverage` profile setting.
+//!
+//! Every test executable writes its profile data to its own directory in
+//! `profraw` under the output directory of the profile, like
+//! `target/coverage/profraw/foo-1a2b3c4d`. Data of earlier runs is removed
+//! before the first test executable of an output directory runs, which with
+//! `-Zpipelined-tests` is during the build. Once all tests ran, the data is merged with the tools of
+//! the `llvm-tools` component into `lcov.info` and `coverage.json` in the
+//! output directory.
+
+use std::collections::{BTreeMap, HashSet};
+use std::env::consts::EXE_SUFFIX;
+use std::path::{Path, PathBuf};
+
+use anyhow::Context as _;
+use cargo_util::{paths, ProcessBuilder};
+
+use crate::core::compiler::{Compilation, CompileKind, Unit, UnitOutput};
+use crate::core::Workspace;
+use crate::util::errors::CargoResult;
+
+/// Returns the `LLVM_PROFILE_FILE` for a test executable, if its unit is
+/// instrumented.
+pub(super) fn profile_file(
+    compilation: &Compilation<'_>,
+    unit: &Unit,
+    exe: &Path,
+) -> Option<PathBuf> {
+    if !unit.profile.coverage {
+        return None;
+    }
+    let dir = profraw_dir(compilation, unit.kind, exe)?;
+    Some(dir.join("%p-%m.profraw"))
+}
+
+/// Removes the profile data of earlier runs from the output directory of
+/// `unit` the first time one of its tests is about to run, so the reports
+/// only cover the tests of this run. `cleaned` holds the directories that
+/// were already cleaned.
+pub(super) fn clean(
+    compilation: &Compilation<'_>,
+    unit: &Unit,
+    cleaned: &mut HashSet<PathBuf>,
+) -> CargoResult<()> {
+    if !unit.profile.coverage {
+        return Ok(());
+    }
+    let profraw = compilation.root_output[&unit.kind].join("profraw");
+    if cleaned.insert(profraw.clone()) && profraw.exists() {
+        paths::remove_dir_all(&profraw)?;
+    }
+    Ok(())
+}
+
+/// Merges the profile data of all test executables into reports, if the
+/// `llvm-tools` component is installed.
+pub(super) fn report(ws: &Workspace<'_>, compilation: &Compilation<'_>) -> CargoResult<()> {
+    let mut by_root = output_dirs(compilation);
+    by_root.retain(|_, exes| !exes.is_empty());
+    if by_root.is_empty() {
+        return Ok(());
+    }
+
+    let gctx = ws.gctx();
+    let tools = compilation.host_llvm_tools_dir();
+    let llvm_profdata = tools.join(format!("llvm-profdata{EXE_SUFFIX}"));
+    let llvm_cov = tools.join(format!("llvm-cov{EXE_SUFFIX}"));
+    if !llvm_profdata.exists() || !llvm_cov.exists() {
+        for root in by_root.keys() {
+            gctx.shell().warn(format!(
+                "the coverage data of the tests is in `{}`, but it can't be merged into \
+                 reports without the `llvm-tools` component\n\
+                 help: install it with `rustup component add llvm-tools`",
+                display(ws, &root.join("profraw")),
+            ))?;
+        }
+        return Ok(());
+    }
+
+    for (root, exes) in by_root {
+        let mut profraws = Vec::new();
+        for (_, dir) in &exes {
+            let entries = std::fs::read_dir(dir)
+                .with_context(|| format!("failed to read `{}`", dir.display()))?;
+            for entry in entries {
+                let path = entry?.path();
+                if path.extension().map_or(false, |ext| ext == "profraw") {
+                    profraws.push(path);
+                }
+            }
+        }
+        if profraws.is_empty() {
+            continue;
+        }
+        profraws.sort();
+
+        let profdata = root.join("coverage.profdata");
+        ProcessBuilder::new(&llvm_profdata)
+            .arg("merge")
+            .arg("-sparse")
+            .args(&profraws)
+            .arg("-o")
+            .arg(&profdata)
+            .exec_with_output()?;
+
+        for (format, file) in [("lcov", "lcov.info"), ("text", "coverage.json")] {
+            let mut cmd = ProcessBuilder::new(&llvm_cov);
+            cmd.arg("export")
+                .arg(format!("-format={format}"))
+                .arg(format!("-instr-profile={}", profdata.display()));
+            for (i, (exe, _)) in exes.iter().enumerate() {
+                if i > 0 {
+                    cmd.arg("-object");
+                }
+                cmd.arg(exe);
+            }
+            let output = cmd.exec_with_output()?;
+            let path = root.join(file);
+            paths::write(&path, &output.stdout)?;
+            gctx.shell().status("Generated", display(ws, &path))?;
+        }
+    }
+    Ok(())
+}
+
+/// Groups the instrumented test executables that wrote profile data, with the
+/// directory of their data, by the output directory of their kind.
+fn output_dirs(compilation: &Compilation<'_>) -> BTreeMap<PathBuf, Vec<(PathBuf, PathBuf)>> {
+    let mut by_root: BTreeMap<_, Vec<_>> = BTreeMap::new();
+    for UnitOutput { unit, path, .. } in &compilation.tests {
+        if !unit.profile.coverage {
+            continue;
+        }
+        let exes = by_root
+            .entry(compilation.root_output[&unit.kind].clone())
+            .or_default();
+        if let Some(dir) = profraw_dir(compilation, unit.kind, path) {
+            if dir.exists() {
+                exes.push((path.clone(), dir));
+            }
+        }
+    }
+    by_root
+}
+
+/// The directory an executable writes its profile data to.
+fn profraw_dir(compilation: &Compilation<'_>, kind: CompileKind, exe: &Path) -> Option<PathBuf> {
+    let name = exe.file_stem()?;
+    Some(compilation.root_output[&kind].join("profraw").join(name))
+}
+
+fn display(ws: &Workspace<'_>, path: &Path) -> String {
+    path.strip_prefix(ws.gctx().cwd())
+        .unwrap_or(path)
+        .display()
+        .to_string()
+}
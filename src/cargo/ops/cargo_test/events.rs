@@ -0,0 +1,637 @@
+//! Results of every test, for messages with `cargo test
+//! --message-format=json` enabled by `-Zjson-test-events`, and for reports
+//! with `--report`.
+//!
+//! Test harnesses only print their results in a human-readable format on
+//! stable toolchains, so Cargo runs them with `--format pretty` and parses
+//! that output. For messages, the output is replaced with [`Test`] messages
+//! on stdout, otherwise it's passed through. The output the harness captured
+//! for a test is only printed after all tests have run, so the results of an
+//! executable are held back until then.
+//!
+//! Executables with `harness = false` can't report their tests, so each of
+//! them is reported as a single test named after its target, with all of its
+//! stdout as the captured output.
+//!
+//! With `--retries`, the failed tests of an executable are run again with
+//! exact filters, and those that pass are reported as flaky. Tests in the
+//! `test.quarantine` list, scoped to their package and target, are reported
+//! as quarantined when they fail, which doesn't fail the run.
+//!
+//! Once all tests have run, a [`TestSummary`] adds up the results.
+
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+use cargo_util::ProcessError;
+
+use super::partition::test_target;
+use super::report::Report;
+use crate::core::compiler::Unit;
+use crate::util::machine_message::{Message, Test, TestEvent, TestSummary, TEST_MESSAGE_VERSION};
+use crate::util::GlobalContext;
+use crate::CargoResult;
+
+/// Runs test processes and collects their results.
+pub struct TestEvents {
+    /// Whether to print a message for every test instead of the output of
+    /// the test processes.
+    json: bool,
+    /// The report to add the results to.
+    report: Option<Report>,
+    /// How many times the failed tests of an executable are run again.
+    retries: u32,
+    /// The tests whose failures don't fail the run, as
+    /// `<package>::<target>::<test>`.
+    quarantine: HashSet<String>,
+    start: Instant,
+    passed: usize,
+    failed: usize,
+    ignored: usize,
+    flaky: usize,
+    quarantined: usize,
+    measured: usize,
+    filtered_out: usize,
+}
+
+/// The results of a test executable, or of the doctests of a unit, that is
+/// running, including the retries of its failed tests.
+pub struct Execution {
+    unit: Unit,
+    executable: Option<PathBuf>,
+    /// Whether failed tests can be run again on their own.
+    retryable: bool,
+    /// When the first process started.
+    start: Instant,
+    /// When the current process started.
+    run_start: Instant,
+    /// How many times the failed tests were run again so far.
+    retry: u32,
+    /// Parses the output of the current process.
+    parser: Parser,
+    /// The stdout of the current process, with `harness = false`.
+    stdout: String,
+    /// The results of the earlier processes of the current run, when its
+    /// tests are split across several processes.
+    earlier: Vec<(CargoResult<()>, bool, Vec<TestResult>)>,
+    /// The results of all tests, updated by the retries.
+    results: Vec<TestResult>,
+}
+
+impl Execution {
+    /// How many times the failed tests were run again so far.
+    pub fn retry(&self) -> u32 {
+        self.retry
+    }
+
+    /// The results of all tests, once they are done.
+    pub fn results(&self) -> &[TestResult] {
+        &self.results
+    }
+}
+
+/// What happens once a test process has exited.
+pub enum Outcome {
+    /// The given failed tests are run again.
+    Retry(Vec<String>),
+    /// All tests are done, with this result.
+    Done(CargoResult<()>),
+}
+
+/// The result of a test.
+#[derive(Clone)]
+pub struct TestResult {
+    pub name: String,
+    pub event: TestEvent,
+    /// How long the test took in seconds, if known.
+    pub duration: Option<f64>,
+    /// Why the test was ignored, if given.
+    pub message: Option<String>,
+    /// The output the harness captured for the test.
+    pub output: Option<String>,
+}
+
+impl TestEvents {
+    pub fn new(
+        json: bool,
+        report: Option<Report>,
+        retries: u32,
+        quarantine: HashSet<String>,
+    ) -> TestEvents {
+        TestEvents {
+            json,
+            report,
+            retries,
+            quarantine,
+            start: Instant::now(),
+            passed: 0,
+            failed: 0,
+            ignored: 0,
+            flaky: 0,
+            quarantined: 0,
+            measured: 0,
+            filtered_out: 0,
+        }
+    }
+
+    /// Whether a message is printed for every test.
+    pub fn json(&self) -> bool {
+        self.json
+    }
+
+    /// Starts collecting the results of a test process. `executable` is
+    /// `None` for doctests.
+    ///
+    /// `retryable` is whether failed tests can be run again on their own.
+    pub fn begin(
+        &self,
+        gctx: &GlobalContext,
+        unit: &Unit,
+        executable: Option<&Path>,
+        retryable: bool,
+    ) -> CargoResult<Execution> {
+        let execution = Execution {
+            unit: unit.clone(),
+            executable: executable.map(Path::to_path_buf),
+            retryable,
+            start: Instant::now(),
+            run_start: Instant::now(),
+            retry: 0,
+            parser: Parser::new(false),
+            stdout: String::new(),
+            earlier: Vec::new(),
+            results: Vec::new(),
+        };
+        if !unit.target.harness() {
+            Reporter::new(gctx, &execution, self.json).emit(
+                TestEvent::Started,
+                unit.target.name(),
+                None,
+                None,
+                None,
+            )?;
+        }
+        Ok(execution)
+    }
+
+    /// Handles a line of the stdout of a test process.
+    pub fn stdout(
+        &self,
+        gctx: &GlobalContext,
+        execution: &mut Execution,
+        line: &str,
+    ) -> CargoResult<()> {
+        if !self.json {
+            writeln!(gctx.shell().out(), "{line}")?;
+        }
+        if execution.unit.target.harness() {
+            let line = anstream::adapter::strip_str(line).to_string();
+            let mut parser = std::mem::take(&mut execution.parser);
+            let result = parser.line(
+                &Reporter::new(gctx, execution, self.json),
+                &self.quarantine,
+                &line,
+            );
+            execution.parser = parser;
+            result
+        } else {
+            execution.stdout.push_str(line);
+            execution.stdout.push('\n');
+            Ok(())
+        }
+    }
+
+    /// Called once a test process has exited that only ran some of the tests
+    /// of the current run, as the names of the others didn't fit on its
+    /// command line. Its results are combined with those of the following
+    /// processes of the run.
+    pub fn part_finished(
+        &mut self,
+        gctx: &GlobalContext,
+        execution: &mut Execution,
+        result: CargoResult<()>,
+    ) -> CargoResult<()> {
+        let ended = self.end_run(gctx, execution, result)?;
+        execution.earlier.push(ended);
+        execution.parser = Parser::new(execution.retry > 0);
+        Ok(())
+    }
+
+    /// Called once a test process has exited, with its result.
+    ///
+    /// When tests failed and can be retried, returns the names of the tests
+    /// to run again, whose output is then handled like the first run's.
+    pub fn finished(
+        &mut self,
+        gctx: &GlobalContext,
+        execution: &mut Execution,
+        result: CargoResult<()>,
+    ) -> CargoResult<Outcome> {
+        let (mut result, mut completed, mut reported) = self.end_run(gctx, execution, result)?;
+        for (earlier_result, earlier_completed, earlier_reported) in
+            execution.earlier.drain(..).rev()
+        {
+            if earlier_result.is_err() {
+                // Keep the error of the first process that failed.
+                result = earlier_result;
+            }
+            completed &= earlier_completed;
+            reported.splice(0..0, earlier_reported);
+        }
+        let latest: Vec<_> = reported.iter().map(|r| (r.name.clone(), r.event)).collect();
+        if execution.retry == 0 {
+            execution.results = reported;
+        } else {
+            for retried in reported {
+                let Some(first) = execution
+                    .results
+                    .iter_mut()
+                    .find(|r| r.name == retried.name)
+                else {
+                    continue;
+                };
+                if retried.event == TestEvent::Flaky {
+                    gctx.shell().warn(format!(
+                        "test `{}` is flaky, it passed on retry {}",
+                        retried.name, execution.retry
+                    ))?;
+                    // Keep the output of the failure.
+                    first.event = TestEvent::Flaky;
+                    first.duration = retried.duration;
+                } else {
+                    *first = retried;
+                }
+            }
+        }
+
+        if result.is_err() {
+            let failed: Vec<_> = latest
+                .iter()
+                .filter(|(_, event)| *event == TestEvent::Failed)
+                .map(|(name, _)| name.clone())
+                .collect();
+            if failed.is_empty() {
+                // Unless the process crashed, it failed because of the
+                // quarantined tests.
+                if completed
+                    && latest
+                        .iter()
+                        .any(|(_, event)| *event == TestEvent::Quarantined)
+                {
+                    result = Ok(());
+                }
+            } else if execution.retryable && execution.retry < self.retries {
+                execution.retry += 1;
+                execution.run_start = Instant::now();
+                execution.parser = Parser::new(true);
+                return Ok(Outcome::Retry(failed));
+            }
+        }
+
+        for result in &execution.results {
+            match result.event {
+                TestEvent::Passed => self.passed += 1,
+                TestEvent::Failed => self.failed += 1,
+                TestEvent::Ignored => self.ignored += 1,
+                TestEvent::Flaky => self.flaky += 1,
+                TestEvent::Quarantined => {
+                    self.quarantined += 1;
+                    gctx.shell()
+                        .warn(format!("test `{}` failed, but is quarantined", result.name))?;
+                }
+                TestEvent::Started => {}
+            }
+        }
+        if let Some(report) = &mut self.report {
+            // A test process can fail without any failing test, or crash
+            // after some tests failed.
+            let any_failed = execution
+                .results
+                .iter()
+                .any(|result| matches!(result.event, TestEvent::Failed | TestEvent::Quarantined));
+            let error = match &result {
+                Err(e) if !any_failed || !completed => Some(format!("{e:#}")),
+                _ => None,
+            };
+            let unit = &execution.unit;
+            report.add(
+                test_target(unit, unit.mode.is_doc_test()),
+                execution.start.elapsed().as_secs_f64(),
+                execution.results.clone(),
+                error,
+            );
+        }
+        Ok(Outcome::Done(result))
+    }
+
+    /// Finishes the results of a test process that exited, and returns its
+    /// result, whether it ran to completion, and the results of its tests.
+    ///
+    /// A harness has run to completion once it printed its final
+    /// `test result:` line. Executables with `harness = false` have when
+    /// they exited normally or with the status of a panic, 101, instead of
+    /// crashing.
+    fn end_run(
+        &mut self,
+        gctx: &GlobalContext,
+        execution: &mut Execution,
+        result: CargoResult<()>,
+    ) -> CargoResult<(CargoResult<()>, bool, Vec<TestResult>)> {
+        let mut parser = std::mem::take(&mut execution.parser);
+        let reporter = Reporter::new(gctx, execution, self.json);
+        let completed = if execution.unit.target.harness() {
+            parser.flush(&reporter)?;
+            parser.finished
+        } else {
+            let name = execution.unit.target.name();
+            let event = if result.is_ok() {
+                TestEvent::Passed
+            } else {
+                TestEvent::Failed
+            };
+            let result_of_target = TestResult {
+                name: name.to_string(),
+                event: parser.event(&self.quarantine, &execution.unit, event, name),
+                duration: Some(execution.run_start.elapsed().as_secs_f64()),
+                message: None,
+                output: Some(execution.stdout.clone()),
+            };
+            reporter.report(&result_of_target)?;
+            parser.reported.push(result_of_target);
+            match &result {
+                Ok(_) => true,
+                Err(e) => e
+                    .downcast_ref::<ProcessError>()
+                    .is_some_and(|e| e.code == Some(101)),
+            }
+        };
+
+        // A retry filters out all tests but the failed ones, and the first
+        // process of a run filtered out the tests of the others.
+        if !parser.retry {
+            self.measured += parser.measured;
+            if execution.earlier.is_empty() {
+                self.filtered_out += parser.filtered_out;
+            } else {
+                self.filtered_out = self.filtered_out.saturating_sub(parser.reported.len());
+            }
+        }
+        execution.stdout.clear();
+        Ok((result, completed, parser.reported))
+    }
+
+    /// Prints the summary of all tests that ran, and writes the report.
+    pub fn finish(&self, gctx: &GlobalContext, success: bool) -> CargoResult<()> {
+        if let Some(report) = &self.report {
+            report.write()?;
+        }
+        if self.json {
+            let msg = TestSummary {
+                version: TEST_MESSAGE_VERSION,
+                success: success && self.failed == 0,
+                passed: self.passed,
+                failed: self.failed,
+                ignored: self.ignored,
+                flaky: self.flaky,
+                quarantined: self.quarantined,
+                measured: self.measured,
+                filtered_out: self.filtered_out,
+                duration: self.start.elapsed().as_secs_f64(),
+            }
+            .to_json_string();
+            writeln!(gctx.shell().out(), "{msg}")?;
+        }
+        Ok(())
+    }
+
+    /// Adds the arguments a test harness needs for its output to be parsed.
+    pub fn add_args(&self, gctx: &GlobalContext, test_args: &[&str], mut add: impl FnMut(&str)) {
+        // `--quiet` switches to a terse format without test names, which an
+        // explicit format overrides.
+        if !test_args.iter().any(|arg| arg.starts_with("--format")) {
+            add("--format");
+            add("pretty");
+        }
+        if self.json {
+            if !test_args.contains(&"--show-output") {
+                add("--show-output");
+            }
+        } else if gctx.shell().out_supports_color()
+            && !test_args.iter().any(|arg| arg.starts_with("--color"))
+        {
+            // The output is passed through, but the harness can't tell that
+            // it ends up on a terminal.
+            add("--color");
+            add("always");
+        }
+    }
+}
+
+/// Prints the messages for the tests of one unit.
+struct Reporter<'a> {
+    gctx: &'a GlobalContext,
+    unit: &'a Unit,
+    executable: Option<&'a Path>,
+    /// Whether to print messages at all.
+    json: bool,
+}
+
+impl<'a> Reporter<'a> {
+    fn new(gctx: &'a GlobalContext, execution: &'a Execution, json: bool) -> Reporter<'a> {
+        Reporter {
+            gctx,
+            unit: &execution.unit,
+            executable: execution.executable.as_deref(),
+            json,
+        }
+    }
+
+    fn emit(
+        &self,
+        event: TestEvent,
+        name: &str,
+        duration: Option<f64>,
+        message: Option<&str>,
+        output: Option<&str>,
+    ) -> CargoResult<()> {
+        if !self.json {
+            return Ok(());
+        }
+        let msg = Test {
+            version: TEST_MESSAGE_VERSION,
+            package_id: self.unit.pkg.package_id().to_spec(),
+            manifest_path: self.unit.pkg.manifest_path(),
+            target: &self.unit.target,
+            mode: self.unit.mode,
+            executable: self.executable,
+            event,
+            name,
+            duration,
+            message,
+            output,
+        }
+        .to_json_string();
+        writeln!(self.gctx.shell().out(), "{msg}")?;
+        Ok(())
+    }
+
+    fn report(&self, result: &TestResult) -> CargoResult<()> {
+        self.emit(
+            result.event,
+            &result.name,
+            result.duration,
+            result.message.as_deref(),
+            result.output.as_deref(),
+        )
+    }
+}
+
+/// Parses the `--format pretty` output of a test harness.
+#[derive(Default)]
+struct Parser {
+    /// Whether the tests are run again after failing.
+    retry: bool,
+    /// Results waiting for their captured output.
+    results: Vec<TestResult>,
+    /// The captured output of each test, by name.
+    outputs: HashMap<String, String>,
+    /// The name and output of the test whose captured output is being read.
+    capture: Option<(String, String)>,
+    measured: usize,
+    filtered_out: usize,
+    /// Whether the harness printed its final `test result:` line.
+    finished: bool,
+    /// The results that were reported.
+    reported: Vec<TestResult>,
+}
+
+impl Parser {
+    fn new(retry: bool) -> Parser {
+        Parser {
+            retry,
+            ..Parser::default()
+        }
+    }
+
+    /// Returns the event to report for the result of a test of `unit`.
+    fn event(
+        &self,
+        quarantine: &HashSet<String>,
+        unit: &Unit,
+        event: TestEvent,
+        name: &str,
+    ) -> TestEvent {
+        match event {
+            TestEvent::Passed if self.retry => TestEvent::Flaky,
+            TestEvent::Failed
+                if quarantine.contains(&format!(
+                    "{}::{}::{name}",
+                    unit.pkg.name(),
+                    unit.target.name()
+                )) =>
+            {
+                TestEvent::Quarantined
+            }
+            event => event,
+        }
+    }
+
+    fn line(
+        &mut self,
+        reporter: &Reporter<'_>,
+        quarantine: &HashSet<String>,
+        line: &str,
+    ) -> CargoResult<()> {
+        if let Some(name) = line
+            .strip_prefix("---- ")
+            .and_then(|line| line.strip_suffix(" stdout ----"))
+        {
+            self.end_capture();
+            self.capture = Some((name.to_string(), String::new()));
+        } else if line == "failures:" || line == "successes:" {
+            // The list of names following the captured outputs.
+            self.end_capture();
+        } else if let Some((_, output)) = &mut self.capture {
+            output.push_str(line);
+            output.push('\n');
+        } else if let Some(summary) = line.strip_prefix("test result: ") {
+            for part in summary.split(';') {
+                let part = part.trim();
+                if let Some(n) = part.strip_suffix(" measured") {
+                    self.measured += n.parse::<usize>().unwrap_or(0);
+                } else if let Some(n) = part.strip_suffix(" filtered out") {
+                    self.filtered_out += n.parse::<usize>().unwrap_or(0);
+                }
+            }
+            self.finished = true;
+            self.flush(reporter)?;
+        } else if let Some((name, result)) = line
+            .strip_prefix("test ")
+            .and_then(|line| line.rsplit_once(" ... "))
+        {
+            let Some((event, duration, message)) = parse_result(result) else {
+                return Ok(());
+            };
+            let name = name.strip_suffix(" - should panic").unwrap_or(name);
+            let event = self.event(quarantine, reporter.unit, event, name);
+            reporter.emit(TestEvent::Started, name, None, None, None)?;
+            self.results.push(TestResult {
+                name: name.to_string(),
+                event,
+                duration,
+                message,
+                output: None,
+            });
+        }
+        Ok(())
+    }
+
+    fn end_capture(&mut self) {
+        if let Some((name, mut output)) = self.capture.take() {
+            // The harness separates the captured outputs with blank lines.
+            output.truncate(output.trim_end_matches('\n').len());
+            if !output.is_empty() {
+                output.push('\n');
+            }
+            self.outputs.insert(name, output);
+        }
+    }
+
+    /// Reports the results read so far, with their captured output.
+    fn flush(&mut self, reporter: &Reporter<'_>) -> CargoResult<()> {
+        self.end_capture();
+        for mut result in std::mem::take(&mut self.results) {
+            result.output = self.outputs.remove(&result.name);
+            reporter.report(&result)?;
+            self.reported.push(result);
+        }
+        Ok(())
+    }
+}
+
+/// Parses the result of a test, like `ok`, `FAILED` or `ignored, slow`.
+///
+/// `--report-time` adds the duration of the test, like `ok <0.012s>`.
+fn parse_result(result: &str) -> Option<(TestEvent, Option<f64>, Option<String>)> {
+    let mut duration = None;
+    let mut result = result;
+    if let Some((rest, time)) = result.rsplit_once(" <") {
+        if let Some(secs) = time.strip_suffix("s>").and_then(|t| t.parse().ok()) {
+            duration = Some(secs);
+            result = rest;
+        }
+    }
+    let (event, message) = if result == "ok" || result.starts_with("bench:") {
+        (TestEvent::Passed, None)
+    } else if result.starts_with("FAILED") {
+        (TestEvent::Failed, None)
+    } else if result == "ignored" {
+        (TestEvent::Ignored, None)
+    } else if let Some(message) = result.strip_prefix("ignored, ") {
+        (TestEvent::Ignored, Some(message.to_string()))
+    } else {
+        return None;
+    };
+    Some((event, duration, message))
+}
@@ -0,0 +1,1059 @@
+use crate::core::compiler::{
+    AfterTest, Compilation, CompileKind, Doctest, TestRunner, Unit, UnitHash, UnitOutput,
+};
+use crate::core::profiles::PanicStrategy;
+use crate::core::shell::ColorChoice;
+use crate::core::shell::Verbosity;
+use crate::core::{TargetKind, Workspace};
+use crate::ops;
+use crate::util::context::RunnerKind;
+use crate::util::errors::CargoResult;
+use crate::util::{add_path_args, CliError, CliResult, GlobalContext};
+use anyhow::format_err;
+use cargo_util::{ProcessBuilder, ProcessError};
+use std::cell::RefCell;
+use std::collections::{HashSet, VecDeque};
+use std::ffi::OsString;
+use std::fmt::Write;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+use std::time::Instant;
+
+use self::events::{Execution, Outcome, TestEvents};
+use self::partition::Shard;
+pub use self::partition::{Partition, PartitionStrategy};
+use self::report::Report;
+pub use self::report::TestReport;
+
+mod coverage;
+mod events;
+mod partition;
+mod report;
+
+pub struct TestOptions {
+    pub compile_opts: ops::CompileOptions,
+    pub no_run: bool,
+    pub no_fail_fast: bool,
+    /// Only run the tests of this partition, with `--partition`.
+    pub partition: Option<Partition>,
+    /// The unit history to balance `count` partitions with, from
+    /// `--partition-durations`.
+    pub partition_durations: Option<PathBuf>,
+    /// Write a report of the test results, with `--report`.
+    pub report: Option<TestReport>,
+    /// How many times to run failed tests again, with `--retries`.
+    pub retries: u32,
+}
+
+/// The kind of test.
+///
+/// This is needed because `Unit` does not track whether or not something is a
+/// benchmark.
+#[derive(Copy, Clone)]
+enum TestKind {
+    Test,
+    Bench,
+    Doctest,
+}
+
+impl TestKind {
+    /// The kind of `runner` to run the tests with.
+    fn runner(self) -> RunnerKind {
+        match self {
+            TestKind::Test => RunnerKind::Test,
+            TestKind::Bench => RunnerKind::Bench,
+            TestKind::Doctest => RunnerKind::Doctest,
+        }
+    }
+}
+
+/// A unit that failed to run.
+struct UnitTestError {
+    unit: Unit,
+    kind: TestKind,
+}
+
+impl UnitTestError {
+    /// Returns the CLI args needed to target this unit.
+    fn cli_args(&self, ws: &Workspace<'_>, opts: &ops::CompileOptions) -> String {
+        let mut args = if opts.spec.needs_spec_flag(ws) {
+            format!("-p {} ", self.unit.pkg.name())
+        } else {
+            String::new()
+        };
+        let mut add = |which| write!(args, "--{which} {}", self.unit.target.name()).unwrap();
+
+        match self.kind {
+            TestKind::Test | TestKind::Bench => match self.unit.target.kind() {
+                TargetKind::Lib(_) => args.push_str("--lib"),
+                TargetKind::Bin => add("bin"),
+                TargetKind::Test => add("test"),
+                TargetKind::Bench => add("bench"),
+                TargetKind::ExampleLib(_) | TargetKind::ExampleBin => add("example"),
+                TargetKind::CustomBuild => panic!("unexpected CustomBuild kind"),
+            },
+            TestKind::Doctest => args.push_str("--doc"),
+        }
+        args
+    }
+}
+
+/// Compiles and runs tests.
+///
+/// On error, the returned [`CliError`] will have the appropriate process exit
+/// code that Cargo should use.
+pub fn run_tests(ws: &Workspace<'_>, options: &TestOptions, test_args: &[&str]) -> CliResult {
+    let pipelined = ws.gctx().cli_unstable().pipelined_tests && !options.no_run;
+    let json_events =
+        ws.gctx().cli_unstable().json_test_events && options.compile_opts.build_config.emit_json();
+    let mut session = TestSession::new(ws, options, test_args, TestKind::Test, json_events)?;
+    let (compilation, mut errors) = if pipelined {
+        let (returned, result) = compile_and_run_tests(ws, options, test_args, session);
+        session = returned;
+        match result {
+            Ok(result) => result,
+            Err(e) => {
+                session.finish(ws, false)?;
+                return Err(e);
+            }
+        }
+    } else {
+        (compile_tests(ws, options)?, Vec::new())
+    };
+
+    if options.no_run {
+        if !options.compile_opts.build_config.emit_json() {
+            display_no_run_information(ws, test_args, &compilation, TestKind::Test)?;
+        }
+        return Ok(());
+    }
+
+    if let Some(shard) = &mut session.shard {
+        shard.select(ws.gctx(), test_args, &compilation)?;
+    }
+    let result = run_unit_tests(ws, options, test_args, &compilation, &mut session).and_then(
+        |unit_errors| {
+            errors.extend(unit_errors);
+            run_doc_tests(ws, options, test_args, &compilation, &mut session)
+        },
+    );
+    let success = errors.is_empty() && result.as_ref().is_ok_and(|errors| errors.is_empty());
+    session.finish(ws, success)?;
+    coverage::report(ws, &compilation)?;
+    errors.extend(result?);
+    no_fail_fast_err(ws, &options.compile_opts, &errors)
+}
+
+/// Compiles and runs benchmarks.
+///
+/// On error, the returned [`CliError`] will have the appropriate process exit
+/// code that Cargo should use.
+pub fn run_benches(ws: &Workspace<'_>, options: &TestOptions, args: &[&str]) -> CliResult {
+    let compilation = compile_tests(ws, options)?;
+
+    if options.no_run {
+        if !options.compile_opts.build_config.emit_json() {
+            display_no_run_information(ws, args, &compilation, TestKind::Bench)?;
+        }
+        return Ok(());
+    }
+
+    let mut args = args.to_vec();
+    args.push("--bench");
+
+    let mut session = TestSession::new(ws, options, &args, TestKind::Bench, false)?;
+    let result = run_unit_tests(ws, options, &args, &compilation, &mut session);
+    session.finish(ws, result.is_ok())?;
+    no_fail_fast_err(ws, &options.compile_opts, &result?)
+}
+
+/// Runs test executables and doctests, and keeps what's shared by all of
+/// them: the selected partition, and the results of every test with their
+/// retries.
+///
+/// With `-Zpipelined-tests`, test executables run during the build through
+/// [`PipelinedTests`], and the rest after it, in the same session.
+struct TestSession {
+    kind: TestKind,
+    shard: Option<Shard>,
+    /// Collects the results of every test, when they are needed for
+    /// messages, a report, retries or the quarantine.
+    events: Option<TestEvents>,
+    /// How many times failed tests are run again.
+    retries: u32,
+    /// Paths of the test executables that already ran, or were skipped.
+    started: HashSet<PathBuf>,
+    /// The `profraw` directories whose data of earlier runs was removed.
+    coverage_cleaned: HashSet<PathBuf>,
+}
+
+/// A test executable, or the doctests of a unit, that is running.
+struct RunningTest {
+    /// The test executable, or `None` for doctests.
+    test: Option<UnitOutput>,
+    unit: Unit,
+    exe_display: String,
+    /// The tests of the executable in the partition. Empty runs all of them.
+    filters: Vec<String>,
+    /// The filter arguments of the processes that still have to run the
+    /// rest of the tests of the current run.
+    remaining: VecDeque<Vec<String>>,
+    /// The error of an earlier process of the current run, when its results
+    /// aren't collected.
+    error: Option<anyhow::Error>,
+    start: Instant,
+    execution: Option<Execution>,
+}
+
+/// What happens once a test process has exited.
+enum Next {
+    /// The failed tests are run again with this process.
+    Rerun(ProcessBuilder),
+    /// The test executable is done, with this result.
+    Done(CargoResult<()>),
+}
+
+impl TestSession {
+    fn new(
+        ws: &Workspace<'_>,
+        options: &TestOptions,
+        test_args: &[&str],
+        kind: TestKind,
+        json: bool,
+    ) -> CargoResult<TestSession> {
+        let shard = match options.partition {
+            Some(partition) => Some(Shard::new(
+                ws,
+                partition,
+                options.partition_durations.as_deref(),
+            )?),
+            None => None,
+        };
+        let report = options.report.as_ref().map(Report::new);
+        let quarantine = quarantine(ws.gctx())?;
+        // With `--report-time`, the durations of single tests are recorded
+        // for balancing partitions.
+        let report_time = shard.is_some() && test_args.contains(&"--report-time");
+        let needed = json
+            || report.is_some()
+            || options.retries > 0
+            || !quarantine.is_empty()
+            || report_time;
+        let events = needed.then(|| TestEvents::new(json, report, options.retries, quarantine));
+        Ok(TestSession {
+            kind,
+            shard,
+            events,
+            retries: options.retries,
+            started: HashSet::new(),
+            coverage_cleaned: HashSet::new(),
+        })
+    }
+
+    /// Whether the output of the test processes is handled by
+    /// [`TestSession::stdout`], instead of going straight to the terminal.
+    fn captures_output(&self) -> bool {
+        self.events.is_some()
+    }
+
+    /// Returns the process that runs a test executable, unless it already
+    /// ran or has no tests in the partition.
+    fn start(
+        &mut self,
+        gctx: &GlobalContext,
+        test: &UnitOutput,
+        test_args: &[&str],
+        compilation: &Compilation<'_>,
+    ) -> CargoResult<Option<(RunningTest, ProcessBuilder)>> {
+        if !self.started.insert(test.path.clone()) {
+            return Ok(None);
+        }
+        coverage::clean(compilation, &test.unit, &mut self.coverage_cleaned)?;
+        let (filters, skipped) = match &mut self.shard {
+            Some(shard) => match shard.executable_tests(gctx, test_args, compilation, test)? {
+                Some(tests) => tests,
+                None => return Ok(None),
+            },
+            None => (Vec::new(), Vec::new()),
+        };
+        let mut remaining: VecDeque<_> =
+            partition::filter_args(test_args, &filters, Some(&skipped)).into();
+        let filter_args = remaining.pop_front().unwrap();
+        let (exe_display, cmd) = self.process(gctx, test, test_args, compilation, &filter_args)?;
+
+        gctx.shell()
+            .concise(|shell| shell.status("Running", &exe_display))?;
+        gctx.shell()
+            .verbose(|shell| shell.status("Running", &cmd))?;
+
+        let execution = match &self.events {
+            // Only the tests of a harness can be run on their own.
+            Some(events) => Some(events.begin(
+                gctx,
+                &test.unit,
+                Some(&test.path),
+                test.unit.target.harness(),
+            )?),
+            None => None,
+        };
+        let running = RunningTest {
+            test: Some(test.clone()),
+            unit: test.unit.clone(),
+            exe_display,
+            filters,
+            remaining,
+            error: None,
+            start: Instant::now(),
+            execution,
+        };
+        Ok(Some((running, cmd)))
+    }
+
+    /// Starts running the doctests of a unit, unless they don't belong to
+    /// the partition.
+    fn start_doctests(
+        &mut self,
+        gctx: &GlobalContext,
+        unit: &Unit,
+    ) -> CargoResult<Option<RunningTest>> {
+        if self
+            .shard
+            .as_ref()
+            .is_some_and(|shard| !shard.has_doctests(unit))
+        {
+            return Ok(None);
+        }
+        let execution = match &self.events {
+            // The name of a doctest can't be passed to `rustdoc` as an
+            // exact filter, so doctests aren't retried.
+            Some(events) => Some(events.begin(gctx, unit, None, false)?),
+            None => None,
+        };
+        Ok(Some(RunningTest {
+            test: None,
+            unit: unit.clone(),
+            exe_display: unit.target.name().to_string(),
+            filters: Vec::new(),
+            remaining: VecDeque::new(),
+            error: None,
+            start: Instant::now(),
+            execution,
+        }))
+    }
+
+    /// Creates the process that runs the tests of an executable selected by
+    /// `filter_args`, or all of them.
+    fn process(
+        &self,
+        gctx: &GlobalContext,
+        test: &UnitOutput,
+        test_args: &[&str],
+        compilation: &Compilation<'_>,
+        filter_args: &[String],
+    ) -> CargoResult<(String, ProcessBuilder)> {
+        let UnitOutput {
+            unit,
+            path,
+            script_meta,
+        } = test;
+        let (exe_display, mut cmd) = cmd_builds(
+            gctx,
+            gctx.cwd(),
+            unit,
+            path,
+            script_meta,
+            test_args,
+            compilation,
+            self.kind,
+            "unittests",
+        )?;
+        cmd.args(filter_args);
+        if let Some(events) = &self.events {
+            if unit.target.harness() {
+                events.add_args(gctx, test_args, |arg| {
+                    cmd.arg(arg);
+                });
+            }
+        }
+        if gctx.extra_verbose() {
+            cmd.display_env_vars();
+        }
+        Ok((exe_display, cmd))
+    }
+
+    /// Handles a line of the stdout of a running test process.
+    fn stdout(
+        &self,
+        gctx: &GlobalContext,
+        running: &mut RunningTest,
+        line: &str,
+    ) -> CargoResult<()> {
+        match (&self.events, &mut running.execution) {
+            (Some(events), Some(execution)) => events.stdout(gctx, execution, line),
+            _ => {
+                writeln!(gctx.shell().out(), "{line}")?;
+                Ok(())
+            }
+        }
+    }
+
+    /// Handles a line of the stderr of a running test process.
+    fn stderr(&self, gctx: &GlobalContext, line: &str) -> CargoResult<()> {
+        writeln!(gctx.shell().err(), "{line}")?;
+        Ok(())
+    }
+
+    /// Called once a test process has exited, with its result.
+    fn finished(
+        &mut self,
+        gctx: &GlobalContext,
+        running: &mut RunningTest,
+        test_args: &[&str],
+        compilation: &Compilation<'_>,
+        result: CargoResult<()>,
+    ) -> CargoResult<Next> {
+        if let Some(filter_args) = running.remaining.pop_front() {
+            match (&mut self.events, &mut running.execution) {
+                (Some(events), Some(execution)) => events.part_finished(gctx, execution, result)?,
+                _ => {
+                    if let Err(e) = result {
+                        running.error.get_or_insert(e);
+                    }
+                }
+            }
+            let test = running.test.as_ref().unwrap();
+            let (_, cmd) = self.process(gctx, test, test_args, compilation, &filter_args)?;
+            gctx.shell()
+                .verbose(|shell| shell.status("Running", &cmd))?;
+            return Ok(Next::Rerun(cmd));
+        }
+        let result = match running.error.take() {
+            Some(e) => Err(e),
+            None => result,
+        };
+        let outcome = match (&mut self.events, &mut running.execution) {
+            (Some(events), Some(execution)) => events.finished(gctx, execution, result)?,
+            _ => Outcome::Done(result),
+        };
+        let result = match outcome {
+            Outcome::Retry(failed) => {
+                let test = running.test.as_ref().unwrap();
+                let retry = running.execution.as_ref().unwrap().retry();
+                running.remaining = partition::filter_args(test_args, &failed, None).into();
+                let filter_args = running.remaining.pop_front().unwrap();
+                let (_, cmd) = self.process(gctx, test, test_args, compilation, &filter_args)?;
+                let tests = if failed.len() == 1 { "test" } else { "tests" };
+                gctx.shell().status(
+                    "Retrying",
+                    format!(
+                        "{} failed {tests} of {}, retry {retry} of {}",
+                        failed.len(),
+                        running.exe_display,
+                        self.retries,
+                    ),
+                )?;
+                gctx.shell()
+                    .verbose(|shell| shell.status("Running", &cmd))?;
+                return Ok(Next::Rerun(cmd));
+            }
+            Outcome::Done(result) => result,
+        };
+        if let (Some(shard), Ok(())) = (&mut self.shard, &result) {
+            shard.record_duration(
+                &running.unit,
+                running.test.is_none(),
+                running.filters.len(),
+                running.start.elapsed(),
+                running
+                    .execution
+                    .as_ref()
+                    .map_or(&[], |execution| execution.results()),
+            );
+        }
+        Ok(Next::Done(result))
+    }
+
+    /// Runs a test process to the end, with the retries of its failed tests.
+    fn exec(
+        &mut self,
+        gctx: &GlobalContext,
+        running: &mut RunningTest,
+        mut cmd: ProcessBuilder,
+        test_args: &[&str],
+        compilation: &Compilation<'_>,
+    ) -> CargoResult<()> {
+        loop {
+            let result = if self.captures_output() {
+                cmd.exec_with_streaming(
+                    &mut |line| self.stdout(gctx, running, line),
+                    &mut |line| self.stderr(gctx, line),
+                    false,
+                )
+                .map(drop)
+            } else {
+                cmd.exec()
+            };
+            match self.finished(gctx, running, test_args, compilation, result)? {
+                Next::Rerun(next) => cmd = next,
+                Next::Done(result) => return result,
+            }
+        }
+    }
+
+    /// Saves the durations of the partition, and prints the summary of all
+    /// tests and writes the report.
+    fn finish(&self, ws: &Workspace<'_>, success: bool) -> CargoResult<()> {
+        if let Some(shard) = &self.shard {
+            shard.save(ws);
+        }
+        if let Some(events) = &self.events {
+            events.finish(ws.gctx(), success)?;
+        }
+        Ok(())
+    }
+}
+
+/// Returns the tests in the `test.quarantine` list, whose failures don't fail
+/// the run, as `<package>::<target>::<test>`.
+fn quarantine(gctx: &GlobalContext) -> CargoResult<HashSet<String>> {
+    if !gctx.cli_unstable().test_quarantine {
+        return Ok(HashSet::new());
+    }
+    let names: Option<Vec<String>> = gctx.get("test.quarantine")?;
+    let names = names.unwrap_or_default();
+    if let Some(name) = names.iter().find(|name| name.splitn(3, "::").count() < 3) {
+        anyhow::bail!(
+            "invalid entry `{name}` in `test.quarantine`, \
+             expected `<package>::<target>::<test>`"
+        );
+    }
+    Ok(names.into_iter().collect())
+}
+
+fn compile_tests<'a>(ws: &Workspace<'a>, options: &TestOptions) -> CargoResult<Compilation<'a>> {
+    let mut compilation = ops::compile(ws, &options.compile_opts)?;
+    compilation.tests.sort();
+    Ok(compilation)
+}
+
+/// Compiles tests, running each test executable as soon as it is built while
+/// the rest of the build continues. This is `-Zpipelined-tests`.
+///
+/// The tests run in `session`, which is handed back to skip them once the
+/// build is over. Failures are reported then, and the failed tests are
+/// returned when `--no-fail-fast` is used, like [`run_unit_tests`].
+fn compile_and_run_tests<'a>(
+    ws: &Workspace<'a>,
+    options: &TestOptions,
+    test_args: &[&str],
+    session: TestSession,
+) -> (
+    TestSession,
+    Result<(Compilation<'a>, Vec<UnitTestError>), CliError>,
+) {
+    let pipeline = Rc::new(RefCell::new(PipelinedTests {
+        test_args: test_args.iter().map(|arg| arg.to_string()).collect(),
+        no_fail_fast: options.no_fail_fast,
+        session,
+        running: None,
+        failures: Vec::new(),
+    }));
+    let mut compile_opts = options.compile_opts.clone();
+    compile_opts.build_config.test_runner = Some(pipeline.clone());
+    let result = ops::compile(ws, &compile_opts);
+    drop(compile_opts);
+    let PipelinedTests {
+        session, failures, ..
+    } = Rc::into_inner(pipeline)
+        .expect("the build is over")
+        .into_inner();
+
+    let mut errors = Vec::new();
+    for (unit, e) in failures {
+        let code = fail_fast_code(&e);
+        let unit_err = UnitTestError {
+            unit,
+            kind: TestKind::Test,
+        };
+        report_test_error(ws, test_args, &options.compile_opts, &unit_err, e);
+        errors.push(unit_err);
+        if !options.no_fail_fast {
+            // The build was stopped because of this, so its error doesn't
+            // matter.
+            return (session, Err(CliError::code(code)));
+        }
+    }
+
+    let result = result.map(|mut compilation| {
+        compilation.tests.sort();
+        (compilation, errors)
+    });
+    (session, result.map_err(CliError::from))
+}
+
+/// The [`TestRunner`] for `-Zpipelined-tests`.
+struct PipelinedTests {
+    test_args: Vec<String>,
+    no_fail_fast: bool,
+    session: TestSession,
+    running: Option<RunningTest>,
+    /// Tests that failed, to be reported after the build.
+    failures: Vec<(Unit, anyhow::Error)>,
+}
+
+impl std::fmt::Debug for PipelinedTests {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PipelinedTests")
+            .field("test_args", &self.test_args)
+            .field("no_fail_fast", &self.no_fail_fast)
+            .finish_non_exhaustive()
+    }
+}
+
+impl TestRunner for PipelinedTests {
+    fn start(
+        &mut self,
+        gctx: &GlobalContext,
+        test: &UnitOutput,
+        compilation: &Compilation<'_>,
+    ) -> CargoResult<Option<ProcessBuilder>> {
+        let test_args: Vec<&str> = self.test_args.iter().map(String::as_str).collect();
+        let Some((running, cmd)) = self.session.start(gctx, test, &test_args, compilation)? else {
+            return Ok(None);
+        };
+        self.running = Some(running);
+        Ok(Some(cmd))
+    }
+
+    fn captures_output(&self) -> bool {
+        self.session.captures_output()
+    }
+
+    fn stdout(&mut self, gctx: &GlobalContext, line: &str) -> CargoResult<()> {
+        let running = self.running.as_mut().unwrap();
+        self.session.stdout(gctx, running, line)
+    }
+
+    fn stderr(&mut self, gctx: &GlobalContext, line: &str) -> CargoResult<()> {
+        self.session.stderr(gctx, line)
+    }
+
+    fn finished(
+        &mut self,
+        gctx: &GlobalContext,
+        compilation: &Compilation<'_>,
+        result: CargoResult<()>,
+    ) -> CargoResult<AfterTest> {
+        let test_args: Vec<&str> = self.test_args.iter().map(String::as_str).collect();
+        let running = self.running.as_mut().unwrap();
+        match self
+            .session
+            .finished(gctx, running, &test_args, compilation, result)?
+        {
+            Next::Rerun(cmd) => Ok(AfterTest::Rerun(cmd)),
+            Next::Done(Ok(())) => {
+                self.running = None;
+                Ok(AfterTest::Continue)
+            }
+            Next::Done(Err(e)) => {
+                let running = self.running.take().unwrap();
+                self.failures.push((running.unit, e));
+                if self.no_fail_fast {
+                    Ok(AfterTest::Continue)
+                } else {
+                    Ok(AfterTest::Stop)
+                }
+            }
+        }
+    }
+}
+
+/// Runs the unit and integration tests of a package.
+///
+/// With `events`, the results of every test are collected.
+///
+/// Returns a `Vec` of tests that failed when `--no-fail-fast` is used.
+/// If `--no-fail-fast` is *not* used, then this returns an `Err`.
+fn run_unit_tests(
+    ws: &Workspace<'_>,
+    options: &TestOptions,
+    test_args: &[&str],
+    compilation: &Compilation<'_>,
+    session: &mut TestSession,
+) -> Result<Vec<UnitTestError>, CliError> {
+    let gctx = ws.gctx();
+    let mut errors = Vec::new();
+
+    for test in compilation.tests.iter() {
+        let Some((mut running, cmd)) = session.start(gctx, test, test_args, compilation)? else {
+            continue;
+        };
+        let result = session.exec(gctx, &mut running, cmd, test_args, compilation);
+        if let Err(e) = result {
+            let code = fail_fast_code(&e);
+            let unit_err = UnitTestError {
+                unit: test.unit.clone(),
+                kind: session.kind,
+            };
+            report_test_error(ws, test_args, &options.compile_opts, &unit_err, e);
+            errors.push(unit_err);
+            if !options.no_fail_fast {
+                return Err(CliError::code(code));
+            }
+        }
+    }
+    Ok(errors)
+}
+
+/// Runs doc tests.
+///
+/// Returns a `Vec` of tests that failed when `--no-fail-fast` is used.
+/// If `--no-fail-fast` is *not* used, then this returns an `Err`.
+fn run_doc_tests(
+    ws: &Workspace<'_>,
+    options: &TestOptions,
+    test_args: &[&str],
+    compilation: &Compilation<'_>,
+    session: &mut TestSession,
+) -> Result<Vec<UnitTestError>, CliError> {
+    let gctx = ws.gctx();
+    let mut errors = Vec::new();
+
+    for doctest_info in &compilation.to_doc_test {
+        let unit = &doctest_info.unit;
+        if doctest_skipped(gctx, compilation, unit) {
+            gctx.shell().verbose(|shell| {
+                shell.note(format!(
+                    "skipping doctests for {} ({}), \
+                     cross-compilation doctests are not yet supported\n\
+                     See https://doc.rust-lang.org/nightly/cargo/reference/unstable.html#doctest-xcompile \
+                     for more information.",
+                    unit.pkg,
+                    unit.target.description_named()
+                ))
+            })?;
+            continue;
+        }
+        let Some(mut running) = session.start_doctests(gctx, unit)? else {
+            continue;
+        };
+
+        gctx.shell().status("Doc-tests", unit.target.name())?;
+        let mut p = doctest_process(
+            ws,
+            test_args,
+            compilation,
+            doctest_info,
+            session.events.as_ref(),
+        )?;
+
+        if gctx.extra_verbose() {
+            p.display_env_vars();
+        }
+
+        gctx.shell()
+            .verbose(|shell| shell.status("Running", p.to_string()))?;
+
+        let result = session.exec(gctx, &mut running, p, test_args, compilation);
+        if let Err(e) = result {
+            let code = fail_fast_code(&e);
+            let unit_err = UnitTestError {
+                unit: unit.clone(),
+                kind: TestKind::Doctest,
+            };
+            report_test_error(ws, test_args, &options.compile_opts, &unit_err, e);
+            errors.push(unit_err);
+            if !options.no_fail_fast {
+                return Err(CliError::code(code));
+            }
+        }
+    }
+    Ok(errors)
+}
+
+/// Whether the doctests of a unit are skipped because they are
+/// cross-compiled, which needs `-Zdoctest-xcompile`.
+fn doctest_skipped(gctx: &GlobalContext, compilation: &Compilation<'_>, unit: &Unit) -> bool {
+    match unit.kind {
+        CompileKind::Host => false,
+        CompileKind::Target(target) => {
+            !gctx.cli_unstable().doctest_xcompile && target.short_name() != compilation.host
+        }
+    }
+}
+
+/// Creates a [`ProcessBuilder`] for running the doctests of a unit.
+///
+/// With `events`, the output of the doctests is set up to be parsed.
+fn doctest_process(
+    ws: &Workspace<'_>,
+    test_args: &[&str],
+    compilation: &Compilation<'_>,
+    doctest_info: &Doctest,
+    events: Option<&TestEvents>,
+) -> CargoResult<ProcessBuilder> {
+    let gctx = ws.gctx();
+    let doctest_xcompile = gctx.cli_unstable().doctest_xcompile;
+    let color = if events.is_some_and(TestEvents::json) {
+        ColorChoice::Never
+    } else {
+        gctx.shell().color_choice()
+    };
+    let Doctest {
+        args,
+        unstable_opts,
+        unit,
+        linker,
+        script_meta,
+        env,
+    } = doctest_info;
+
+    let mut p = compilation.rustdoc_process(unit, *script_meta)?;
+    for (var, value) in env {
+        p.env(var, value);
+    }
+
+    let color_arg = match color {
+        ColorChoice::Always => "always",
+        ColorChoice::Never => "never",
+        ColorChoice::CargoAuto => "auto",
+    };
+    p.arg("--color").arg(color_arg);
+
+    p.arg("--crate-name").arg(&unit.target.crate_name());
+    p.arg("--test");
+
+    add_path_args(ws, unit, &mut p);
+    p.arg("--test-run-directory")
+        .arg(unit.pkg.root().to_path_buf());
+
+    if let CompileKind::Target(target) = unit.kind {
+        // use `rustc_target()` to properly handle JSON target paths
+        p.arg("--target").arg(target.rustc_target());
+    }
+
+    let runtool = compilation.target_runner(unit.kind, RunnerKind::Doctest);
+    if doctest_xcompile || runtool.is_some() {
+        p.arg("-Zunstable-options");
+    }
+    if let Some((runtool, runtool_args)) = runtool {
+        p.arg("--runtool").arg(runtool);
+        for arg in runtool_args {
+            p.arg("--runtool-arg").arg(arg);
+        }
+    }
+    if doctest_xcompile {
+        p.arg("--enable-per-target-ignores");
+        if let Some(linker) = linker {
+            let mut joined = OsString::from("linker=");
+            joined.push(linker);
+            p.arg("-C").arg(joined);
+        }
+    }
+
+    if unit.profile.panic != PanicStrategy::Unwind {
+        p.arg("-C").arg(format!("panic={}", unit.profile.panic));
+    }
+
+    for &rust_dep in &[
+        &compilation.deps_output[&unit.kind],
+        &compilation.deps_output[&CompileKind::Host],
+    ] {
+        let mut arg = OsString::from("dependency=");
+        arg.push(rust_dep);
+        p.arg("-L").arg(arg);
+    }
+
+    for native_dep in compilation.native_dirs.iter() {
+        p.arg("-L").arg(native_dep);
+    }
+
+    for arg in test_args {
+        p.arg("--test-args").arg(arg);
+    }
+
+    if gctx.shell().verbosity() == Verbosity::Quiet {
+        p.arg("--test-args").arg("--quiet");
+    }
+
+    if let Some(events) = events {
+        events.add_args(gctx, test_args, |arg| {
+            p.arg("--test-args").arg(arg);
+        });
+    }
+
+    p.args(unit.pkg.manifest().lint_rustflags());
+
+    p.args(args);
+
+    if *unstable_opts {
+        p.arg("-Zunstable-options");
+    }
+
+    Ok(p)
+}
+
+/// Displays human-readable descriptions of the test executables.
+///
+/// This is used when `cargo test --no-run` is used.
+fn display_no_run_information(
+    ws: &Workspace<'_>,
+    test_args: &[&str],
+    compilation: &Compilation<'_>,
+    test_kind: TestKind,
+) -> CargoResult<()> {
+    let exec_type = match test_kind {
+        TestKind::Bench => "benches",
+        _ => "unittests",
+    };
+    let gctx = ws.gctx();
+    let cwd = gctx.cwd();
+    for UnitOutput {
+        unit,
+        path,
+        script_meta,
+    } in compilation.tests.iter()
+    {
+        let (exe_display, cmd) = cmd_builds(
+            gctx,
+            cwd,
+            unit,
+            path,
+            script_meta,
+            test_args,
+            compilation,
+            test_kind,
+            exec_type,
+        )?;
+        gctx.shell()
+            .concise(|shell| shell.status("Executable", &exe_display))?;
+        gctx.shell()
+            .verbose(|shell| shell.status("Executable", &cmd))?;
+    }
+
+    return Ok(());
+}
+
+/// Creates a [`ProcessBuilder`] for executing a single test.
+///
+/// Returns a tuple `(exe_display, process)` where `exe_display` is a string
+/// to display that describes the executable path in a human-readable form.
+/// `process` is the `ProcessBuilder` to use for executing the test.
+fn cmd_builds(
+    gctx: &GlobalContext,
+    cwd: &Path,
+    unit: &Unit,
+    path: &PathBuf,
+    script_meta: &Option<UnitHash>,
+    test_args: &[&str],
+    compilation: &Compilation<'_>,
+    test_kind: TestKind,
+    exec_type: &str,
+) -> CargoResult<(String, ProcessBuilder)> {
+    let test_path = unit.target.src_path().path().unwrap();
+    let short_test_path = test_path
+        .strip_prefix(unit.pkg.root())
+        .unwrap_or(test_path)
+        .display();
+
+    let exe_display = match unit.target.kind() {
+        TargetKind::Test | TargetKind::Bench => format!(
+            "{} ({})",
+            short_test_path,
+            path.strip_prefix(cwd).unwrap_or(path).display()
+        ),
+        _ => format!(
+            "{} {} ({})",
+            exec_type,
+            short_test_path,
+            path.strip_prefix(cwd).unwrap_or(path).display()
+        ),
+    };
+
+    let mut cmd =
+        compilation.target_process(path, unit.kind, test_kind.runner(), &unit.pkg, *script_meta)?;
+    cmd.args(test_args);
+    if unit.target.harness() && gctx.shell().verbosity() == Verbosity::Quiet {
+        cmd.arg("--quiet");
+    }
+    if let Some(profile_file) = coverage::profile_file(compilation, unit, path) {
+        cmd.env("LLVM_PROFILE_FILE", profile_file);
+    }
+
+    Ok((exe_display, cmd))
+}
+
+/// Returns the error code to use when *not* using `--no-fail-fast`.
+///
+/// Cargo will return the error code from the test process itself. If some
+/// other error happened (like a failure to launch the process), then it will
+/// return a standard 101 error code.
+///
+/// When using `--no-fail-fast`, Cargo always uses the 101 exit code (since
+/// there may not be just one process to report).
+fn fail_fast_code(error: &anyhow::Error) -> i32 {
+    if let Some(proc_err) = error.downcast_ref::<ProcessError>() {
+        if let Some(code) = proc_err.code {
+            return code;
+        }
+    }
+    101
+}
+
+/// Returns the `CliError` when using `--no-fail-fast` and there is at least
+/// one error.
+fn no_fail_fast_err(
+    ws: &Workspace<'_>,
+    opts: &ops::CompileOptions,
+    errors: &[UnitTestError],
+) -> CliResult {
+    // TODO: This could be improved by combining the flags on a single line when feasible.
+    let args: Vec<_> = errors
+        .iter()
+        .map(|unit_err| format!("    `{}`", unit_err.cli_args(ws, opts)))
+        .collect();
+    let message = match errors.len() {
+        0 => return Ok(()),
+        1 => format!("1 target failed:\n{}", args.join("\n")),
+        n => format!("{n} targets failed:\n{}", args.join("\n")),
+    };
+    Err(anyhow::Error::msg(message).into())
+}
+
+/// Displays an error on the console about a test failure.
+fn report_test_error(
+    ws: &Workspace<'_>,
+    test_args: &[&str],
+    opts: &ops::CompileOptions,
+    unit_err: &UnitTestError,
+    test_error: anyhow::Error,
+) {
+    let which = match unit_err.kind {
+        TestKind::Test => "test failed",
+        TestKind::Bench => "bench failed",
+        TestKind::Doctest => "doctest failed",
+    };
+
+    let mut err = format_err!("{}, to rerun pass `{}`", which, unit_err.cli_args(ws, opts));
+    // Don't show "process didn't exit successfully" for simple errors.
+    // libtest exits with 101 for normal errors.
+    let (is_simple, executed) = test_error
+        .downcast_ref::<ProcessError>()
+        .and_then(|proc_err| proc_err.code)
+        .map_or((false, false), |code| (code == 101, true));
+
+    if !is_simple {
+        err = test_error.context(err);
+    }
+
+    crate::display_error(&err, &mut ws.gctx().shell());
+
+    let harness: bool = unit_err.unit.target.harness();
+    let nocapture: bool = test_args.contains(&"--nocapture");
+
+    if !is_simple && executed && harness && !nocapture {
+        drop(ws.gctx().shell().note(
+            "test exited abnormally; to see the full output pass --nocapture to the harness.",
+        ));
+    }
+}
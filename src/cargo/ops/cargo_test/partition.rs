@@ -0,0 +1,521 @@
+//! Splitting the tests of a workspace into partitions with
+//! `cargo test --partition`, so they can be spread across several machines.
+//!
+//! Every test of every test executable is listed by running it with
+//! `--list`, and assigned to one of the partitions. Each executable is then
+//! only run with exact filters for the tests of the selected partition, or
+//! told to skip the other tests when that's shorter. Names that don't fit
+//! on a single command line are split across several runs of the
+//! executable.
+//!
+//! Executables that can't list their tests, like those with
+//! `harness = false`, are assigned as a whole. So are the doctests of each
+//! library, since `rustdoc` splits test arguments on whitespace and the
+//! name of a doctest can't be passed to it as an exact filter.
+//!
+//! With `-Zpipelined-tests`, `hash` partitions list each executable as soon
+//! as it has been built, as its tests are assigned regardless of the others.
+
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::time::Duration;
+
+use anyhow::{bail, Context as _};
+use cargo_util::ProcessBuilder;
+
+use super::events::TestResult;
+use super::{cmd_builds, doctest_skipped, TestKind};
+use crate::core::compiler::unit_history::UnitHistory;
+use crate::core::compiler::{Compilation, Unit, UnitOutput};
+use crate::core::Workspace;
+use crate::util::{hex, GlobalContext};
+use crate::CargoResult;
+
+/// How `--partition` assigns tests to partitions.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PartitionStrategy {
+    /// By a hash of the test's name, so that a test stays in the same
+    /// partition as other tests are added or removed.
+    Hash,
+    /// Evenly by the number of tests, or by how long they took in previous
+    /// runs when given with `--partition-durations`.
+    Count,
+}
+
+/// The `--partition` to run, e.g. `hash:2/8`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Partition {
+    pub strategy: PartitionStrategy,
+    /// The partition to run, starting at 1.
+    pub index: usize,
+    /// The total number of partitions.
+    pub total: usize,
+}
+
+impl FromStr for Partition {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> CargoResult<Partition> {
+        let Some((strategy, index_total)) = s.split_once(':') else {
+            bail!("invalid partition `{s}`, expected `hash:M/N` or `count:M/N`");
+        };
+        let strategy = match strategy {
+            "hash" => PartitionStrategy::Hash,
+            "count" => PartitionStrategy::Count,
+            _ => bail!("invalid partition strategy `{strategy}`, expected `hash` or `count`"),
+        };
+        let parsed = index_total
+            .split_once('/')
+            .and_then(|(index, total)| Some((index.parse().ok()?, total.parse().ok()?)));
+        let Some((index, total)) = parsed else {
+            bail!("invalid partition `{s}`, expected `hash:M/N` or `count:M/N`");
+        };
+        if index == 0 || index > total {
+            bail!("invalid partition `{s}`, `M` in `M/N` must be between 1 and N");
+        }
+        Ok(Partition {
+            strategy,
+            index,
+            total,
+        })
+    }
+}
+
+/// The tests that belong to the selected partition.
+pub struct Shard {
+    partition: Partition,
+    /// The durations to balance `count` partitions with.
+    durations: Option<UnitHistory>,
+    /// The test executables whose tests were assigned to partitions.
+    listed: HashSet<PathBuf>,
+    /// The tests to run from each test executable, by path. `None` runs the
+    /// whole executable. Executables without any tests in this partition
+    /// are missing.
+    executables: HashMap<PathBuf, Option<Vec<String>>>,
+    /// The tests of each test executable in the other partitions, by path.
+    skipped: HashMap<PathBuf, Vec<String>>,
+    /// The units whose doctests belong to this partition.
+    doctests: HashSet<Unit>,
+    /// Durations of the tests in previous runs, updated with this one.
+    history: UnitHistory,
+}
+
+/// A test, or a whole test executable, to assign to a partition.
+struct Item {
+    /// A description of the target the test belongs to.
+    test_target: String,
+    source: Source,
+    /// The name of the test, or `None` for a whole test executable or all
+    /// doctests of a unit.
+    name: Option<String>,
+}
+
+enum Source {
+    Executable(PathBuf),
+    Doctests(Unit),
+}
+
+impl Item {
+    /// A name for the test that's the same on every machine.
+    fn key(&self) -> String {
+        match &self.name {
+            Some(name) => format!("{} {name}", self.test_target),
+            None => self.test_target.clone(),
+        }
+    }
+}
+
+impl Shard {
+    /// Creates the shard of `partition`.
+    ///
+    /// `count` partitions are only balanced by the durations in
+    /// `durations_path`, never by the history in the target directory, as
+    /// every machine must assign the tests the same way.
+    pub fn new(
+        ws: &Workspace<'_>,
+        partition: Partition,
+        durations_path: Option<&Path>,
+    ) -> CargoResult<Shard> {
+        Ok(Shard {
+            partition,
+            durations: durations_path.map(UnitHistory::load_from).transpose()?,
+            listed: HashSet::new(),
+            executables: HashMap::new(),
+            skipped: HashMap::new(),
+            doctests: HashSet::new(),
+            history: UnitHistory::load(ws),
+        })
+    }
+
+    /// Lists the tests of the build that weren't listed yet, and picks those
+    /// in the partition.
+    ///
+    /// `count` partitions must list all tests at once, before any of them
+    /// runs, as the tests are balanced against each other.
+    pub fn select(
+        &mut self,
+        gctx: &GlobalContext,
+        test_args: &[&str],
+        compilation: &Compilation<'_>,
+    ) -> CargoResult<()> {
+        let mut items = Vec::new();
+        for test in compilation.tests.iter() {
+            if !self.listed.contains(&test.path) {
+                items.extend(list_executable(gctx, test_args, compilation, test)?);
+            }
+        }
+        for doctest in &compilation.to_doc_test {
+            if doctest_skipped(gctx, compilation, &doctest.unit) {
+                continue;
+            }
+            items.push(Item {
+                test_target: test_target(&doctest.unit, true),
+                source: Source::Doctests(doctest.unit.clone()),
+                name: None,
+            });
+        }
+        self.add(&items);
+        Ok(())
+    }
+
+    /// The tests to run from a test executable, and those of its tests to
+    /// skip, or `None` if it has no tests in this partition. An empty list
+    /// of tests to run runs the whole executable.
+    ///
+    /// An executable that wasn't listed yet is listed first, which only
+    /// `hash` partitions can do one executable at a time.
+    pub fn executable_tests(
+        &mut self,
+        gctx: &GlobalContext,
+        test_args: &[&str],
+        compilation: &Compilation<'_>,
+        test: &UnitOutput,
+    ) -> CargoResult<Option<(Vec<String>, Vec<String>)>> {
+        if !self.listed.contains(&test.path) {
+            assert_eq!(self.partition.strategy, PartitionStrategy::Hash);
+            let items = list_executable(gctx, test_args, compilation, test)?;
+            self.add(&items);
+        }
+        let Some(names) = self.executables.get(&test.path) else {
+            return Ok(None);
+        };
+        let skipped = self.skipped.get(&test.path).cloned().unwrap_or_default();
+        Ok(Some((names.clone().unwrap_or_default(), skipped)))
+    }
+
+    /// Adds the items of this partition.
+    fn add(&mut self, items: &[Item]) {
+        for item in items {
+            if let Source::Executable(path) = &item.source {
+                self.listed.insert(path.clone());
+            }
+        }
+        let assigned = assign(items, self.partition, self.durations.as_ref());
+        let keys: HashSet<_> = assigned.iter().map(|item| item.key()).collect();
+        for item in items {
+            if let (Source::Executable(path), Some(name)) = (&item.source, &item.name) {
+                if !keys.contains(&item.key()) {
+                    self.skipped
+                        .entry(path.clone())
+                        .or_default()
+                        .push(name.clone());
+                }
+            }
+        }
+        for item in assigned {
+            match (&item.source, &item.name) {
+                (Source::Executable(path), None) => {
+                    self.executables.insert(path.clone(), None);
+                }
+                (Source::Executable(path), Some(name)) => {
+                    self.executables
+                        .entry(path.clone())
+                        .or_insert_with(|| Some(Vec::new()))
+                        .as_mut()
+                        .unwrap()
+                        .push(name.clone());
+                }
+                (Source::Doctests(unit), _) => {
+                    self.doctests.insert(unit.clone());
+                }
+            }
+        }
+    }
+
+    /// Whether the doctests of a unit belong to this partition.
+    pub fn has_doctests(&self, unit: &Unit) -> bool {
+        self.doctests.contains(unit)
+    }
+
+    /// Records how long the tests of a test target took to run, and how long
+    /// each test took when the harness reported it in `results`.
+    pub fn record_duration(
+        &mut self,
+        unit: &Unit,
+        doctests: bool,
+        tests: usize,
+        elapsed: Duration,
+        results: &[TestResult],
+    ) {
+        let test_target = test_target(unit, doctests);
+        let per_test = elapsed.as_secs_f64() / tests.max(1) as f64;
+        self.history.record_test_duration(&test_target, per_test);
+        if doctests || !unit.target.harness() {
+            return;
+        }
+        for result in results {
+            if let Some(duration) = result.duration {
+                let key = format!("{test_target} {}", result.name);
+                self.history.record_test_duration(&key, duration);
+            }
+        }
+    }
+
+    /// Saves the recorded durations for the next runs.
+    pub fn save(&self, ws: &Workspace<'_>) {
+        self.history.save(ws);
+    }
+}
+
+/// How long the filters passed to a single test process can get. Windows
+/// limits the whole command line to 32767 characters.
+const MAX_FILTERS_LEN: usize = 16 * 1024;
+
+/// Returns the filter arguments of every test process it takes to run the
+/// given tests of an executable. Without any tests to run, a single process
+/// runs all of them.
+///
+/// `skipped` are the other tests of the executable, if they are known.
+/// Skipping them is used instead when it's shorter, unless `test_args` might
+/// already filter the tests, as skipping doesn't narrow the filters down.
+pub fn filter_args(
+    test_args: &[&str],
+    tests: &[String],
+    skipped: Option<&[String]>,
+) -> Vec<Vec<String>> {
+    if tests.is_empty() || skipped.is_some_and(|skipped| skipped.is_empty()) {
+        return vec![Vec::new()];
+    }
+    let exact = if test_args.contains(&"--exact") {
+        Vec::new()
+    } else {
+        vec!["--exact".to_string()]
+    };
+    let tests_len: usize = tests.iter().map(|name| name.len() + 1).sum();
+    if let Some(skipped) = skipped {
+        let skipped_len: usize = skipped
+            .iter()
+            .map(|name| name.len() + "--skip ".len() + 1)
+            .sum();
+        if skipped_len < tests_len
+            && skipped_len <= MAX_FILTERS_LEN
+            && test_args.iter().all(|arg| arg.starts_with('-'))
+        {
+            let mut args = exact;
+            for name in skipped {
+                args.push("--skip".to_string());
+                args.push(name.clone());
+            }
+            return vec![args];
+        }
+    }
+
+    let mut runs = Vec::new();
+    let mut args = exact.clone();
+    let mut len = 0;
+    for name in tests {
+        if len > 0 && len + name.len() + 1 > MAX_FILTERS_LEN {
+            runs.push(std::mem::replace(&mut args, exact.clone()));
+            len = 0;
+        }
+        args.push(name.clone());
+        len += name.len() + 1;
+    }
+    runs.push(args);
+    runs
+}
+
+/// Describes a test target the same way on every machine, for hashing and
+/// for looking up its durations.
+pub(super) fn test_target(unit: &Unit, doctests: bool) -> String {
+    let kind = if doctests {
+        "doctests".to_string()
+    } else {
+        unit.target.description_named()
+    };
+    format!("{} v{} {kind}", unit.pkg.name(), unit.pkg.version())
+}
+
+/// Returns the items of a test executable, which are its tests if it can
+/// list them.
+fn list_executable(
+    gctx: &GlobalContext,
+    test_args: &[&str],
+    compilation: &Compilation<'_>,
+    test: &UnitOutput,
+) -> CargoResult<Vec<Item>> {
+    let UnitOutput {
+        unit,
+        path,
+        script_meta,
+    } = test;
+    let test_target = test_target(unit, false);
+    if !unit.target.harness() {
+        return Ok(vec![Item {
+            test_target,
+            source: Source::Executable(path.clone()),
+            name: None,
+        }]);
+    }
+    let (exe_display, cmd) = cmd_builds(
+        gctx,
+        gctx.cwd(),
+        unit,
+        path,
+        script_meta,
+        test_args,
+        compilation,
+        TestKind::Test,
+        "unittests",
+    )?;
+    let names =
+        list_tests(cmd).with_context(|| format!("failed to list tests of {exe_display}"))?;
+    Ok(names
+        .into_iter()
+        .map(|name| Item {
+            test_target: test_target.clone(),
+            source: Source::Executable(path.clone()),
+            name: Some(name),
+        })
+        .collect())
+}
+
+/// Runs a test process with `--list` and returns the names of its tests.
+fn list_tests(mut cmd: ProcessBuilder) -> CargoResult<Vec<String>> {
+    let output = cmd.arg("--list").exec_with_output()?;
+    let stdout = String::from_utf8(output.stdout).context("test names are not UTF-8")?;
+    Ok(stdout
+        .lines()
+        .filter_map(|line| {
+            let (name, kind) = line.rsplit_once(": ")?;
+            matches!(kind, "test" | "benchmark").then(|| name.to_string())
+        })
+        .collect())
+}
+
+/// Returns the items that belong to `partition`.
+fn assign<'a>(
+    items: &'a [Item],
+    partition: Partition,
+    durations: Option<&UnitHistory>,
+) -> Vec<&'a Item> {
+    let index = partition.index - 1;
+    let total = partition.total as u64;
+    match partition.strategy {
+        PartitionStrategy::Hash => items
+            .iter()
+            .filter(|item| hex::hash_u64(item.key()) % total == index as u64)
+            .collect(),
+        PartitionStrategy::Count => {
+            // Greedily give the longest remaining test to the partition with
+            // the least work so far. Without any recorded durations all
+            // tests weigh the same, and they are dealt out in turn. A test
+            // without a duration of its own weighs the average of its target.
+            let duration = |item: &Item| {
+                let durations = durations?;
+                durations
+                    .test_duration(&item.key())
+                    .or_else(|| durations.test_duration(&item.test_target))
+            };
+            let recorded: Vec<f64> = items.iter().filter_map(duration).collect();
+            let default = if recorded.is_empty() {
+                1.0
+            } else {
+                recorded.iter().sum::<f64>() / recorded.len() as f64
+            };
+            let mut weighted: Vec<(f64, String, &Item)> = items
+                .iter()
+                .map(|item| {
+                    let weight = duration(item).unwrap_or(default);
+                    (weight, item.key(), item)
+                })
+                .collect();
+            weighted.sort_by(|a, b| b.0.total_cmp(&a.0).then_with(|| a.1.cmp(&b.1)));
+            let mut loads = vec![0.0f64; partition.total];
+            let mut selected = Vec::new();
+            for (weight, _, item) in weighted {
+                let lightest = (0..loads.len())
+                    .min_by(|&a, &b| loads[a].total_cmp(&loads[b]))
+                    .unwrap();
+                loads[lightest] += weight;
+                if lightest == index {
+                    selected.push(item);
+                }
+            }
+            selected
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_partition() {
+        let partition: Partition = "hash:2/8".parse().unwrap();
+        assert_eq!(partition.strategy, PartitionStrategy::Hash);
+        assert_eq!((partition.index, partition.total), (2, 8));
+        assert_eq!(
+            "count:1/1".parse::<Partition>().unwrap().strategy,
+            PartitionStrategy::Count
+        );
+
+        for invalid in [
+            "hash",
+            "hash:2",
+            "hash:0/2",
+            "hash:3/2",
+            "size:1/2",
+            "count:a/b",
+        ] {
+            assert!(invalid.parse::<Partition>().is_err(), "{invalid}");
+        }
+    }
+
+    #[test]
+    fn filters() {
+        let names = |names: &[&str]| {
+            names
+                .iter()
+                .map(|name| name.to_string())
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(filter_args(&[], &[], None), [names(&[])]);
+        assert_eq!(filter_args(&[], &names(&["a"]), Some(&[])), [names(&[])]);
+        assert_eq!(
+            filter_args(&[], &names(&["a"]), Some(&names(&["b"]))),
+            [names(&["--exact", "a"])]
+        );
+        assert_eq!(
+            filter_args(&["--exact"], &names(&["a_long_name"]), Some(&names(&["b"]))),
+            [names(&["--skip", "b"])]
+        );
+        // A filter in the test arguments would match fewer tests with
+        // `--exact`, and skipping doesn't make up for it.
+        assert_eq!(
+            filter_args(&["name"], &names(&["a_long_name"]), Some(&names(&["b"]))),
+            [names(&["--exact", "a_long_name"])]
+        );
+
+        let long: Vec<_> = (0..=MAX_FILTERS_LEN / 10)
+            .map(|i| format!("test_{i:04}"))
+            .collect();
+        let runs = filter_args(&[], &long, None);
+        assert_eq!(runs.len(), 2);
+        assert!(runs.iter().all(|run| run[0] == "--exact"));
+        assert_eq!(runs[0].len() + runs[1].len() - 2, long.len());
+    }
+}
@@ -0,0 +1,236 @@
+//! Reports of the test results with `--report`, for CI systems.
+//!
+//! The only format is JUnit XML, with one `<testsuite>` for every test
+//! executable and for the doctests of every library, in the order they ran.
+
+use std::fmt::Write as _;
+use std::path::PathBuf;
+use std::str::FromStr;
+
+use anyhow::{bail, Context as _};
+use cargo_util::paths;
+
+use super::events::TestResult;
+use crate::util::machine_message::TestEvent;
+use crate::CargoResult;
+
+/// The report to write with `--report`, e.g. `junit=report.xml`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TestReport {
+    /// A JUnit XML report at the given path.
+    Junit(PathBuf),
+}
+
+impl FromStr for TestReport {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> CargoResult<TestReport> {
+        let Some((format, path)) = s.split_once('=') else {
+            bail!("invalid report `{s}`, expected `junit=<path>`");
+        };
+        if path.is_empty() {
+            bail!("invalid report `{s}`, expected `junit=<path>`");
+        }
+        match format {
+            "junit" => Ok(TestReport::Junit(PathBuf::from(path))),
+            _ => bail!("unsupported report format `{format}`, expected `junit`"),
+        }
+    }
+}
+
+/// The results of all tests, to be written to a report.
+pub struct Report {
+    path: PathBuf,
+    suites: Vec<Suite>,
+}
+
+/// The results of one test executable, or the doctests of a library.
+struct Suite {
+    name: String,
+    /// How long the test process ran in seconds.
+    time: f64,
+    cases: Vec<TestResult>,
+    /// Why the test process failed, when that wasn't because of a test.
+    error: Option<String>,
+}
+
+impl Report {
+    pub fn new(report: &TestReport) -> Report {
+        let TestReport::Junit(path) = report;
+        Report {
+            path: path.clone(),
+            suites: Vec::new(),
+        }
+    }
+
+    /// Adds the results of a test process.
+    pub fn add(&mut self, name: String, time: f64, cases: Vec<TestResult>, error: Option<String>) {
+        self.suites.push(Suite {
+            name,
+            time,
+            cases,
+            error,
+        });
+    }
+
+    /// Writes the report to its path.
+    pub fn write(&self) -> CargoResult<()> {
+        if let Some(parent) = self.path.parent() {
+            paths::create_dir_all(parent)?;
+        }
+        paths::write(&self.path, self.to_junit())
+            .with_context(|| format!("failed to write report to `{}`", self.path.display()))
+    }
+
+    fn to_junit(&self) -> String {
+        let count = |suite: &Suite, events: &[TestEvent]| {
+            suite
+                .cases
+                .iter()
+                .filter(|case| events.contains(&case.event))
+                .count()
+        };
+        // Quarantined tests don't fail the run, like skipped ones.
+        let failures = |suite: &Suite| count(suite, &[TestEvent::Failed]);
+        let skipped = |suite: &Suite| count(suite, &[TestEvent::Ignored, TestEvent::Quarantined]);
+        let total = |f: &dyn Fn(&Suite) -> usize| self.suites.iter().map(f).sum::<usize>();
+        let errors = |suite: &Suite| usize::from(suite.error.is_some());
+        let tests = |suite: &Suite| suite.cases.len() + errors(suite);
+
+        let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+        writeln!(
+            xml,
+            "<testsuites tests=\"{}\" failures=\"{}\" errors=\"{}\" skipped=\"{}\" time=\"{:.3}\">",
+            total(&tests),
+            total(&failures),
+            total(&errors),
+            total(&skipped),
+            self.suites.iter().map(|suite| suite.time).sum::<f64>(),
+        )
+        .unwrap();
+        for suite in &self.suites {
+            let name = escape(&suite.name);
+            writeln!(
+                xml,
+                "  <testsuite name=\"{name}\" tests=\"{}\" failures=\"{}\" errors=\"{}\" skipped=\"{}\" time=\"{:.3}\">",
+                tests(suite),
+                failures(suite),
+                errors(suite),
+                skipped(suite),
+                suite.time,
+            )
+            .unwrap();
+            for case in &suite.cases {
+                write!(
+                    xml,
+                    "    <testcase name=\"{}\" classname=\"{name}\"",
+                    escape(&case.name)
+                )
+                .unwrap();
+                if let Some(duration) = case.duration {
+                    write!(xml, " time=\"{duration:.3}\"").unwrap();
+                }
+                let output = case.output.as_deref().filter(|output| !output.is_empty());
+                match (case.event, output) {
+                    (TestEvent::Failed, output) => {
+                        writeln!(
+                            xml,
+                            ">\n      <failure message=\"test failed\">{}</failure>\n    </testcase>",
+                            escape(output.unwrap_or_default())
+                        )
+                        .unwrap();
+                    }
+                    (TestEvent::Flaky, output) => {
+                        writeln!(
+                            xml,
+                            ">\n      <flakyFailure message=\"test failed\">{}</flakyFailure>\n    </testcase>",
+                            escape(output.unwrap_or_default())
+                        )
+                        .unwrap();
+                    }
+                    (TestEvent::Quarantined, output) => {
+                        xml.push_str(">\n      <skipped message=\"failed, but quarantined\"/>\n");
+                        if let Some(output) = output {
+                            writeln!(xml, "      <system-out>{}</system-out>", escape(output))
+                                .unwrap();
+                        }
+                        xml.push_str("    </testcase>\n");
+                    }
+                    (TestEvent::Ignored, _) => {
+                        let message = case.message.as_deref().unwrap_or("ignored");
+                        writeln!(
+                            xml,
+                            ">\n      <skipped message=\"{}\"/>\n    </testcase>",
+                            escape(message)
+                        )
+                        .unwrap();
+                    }
+                    (_, Some(output)) => {
+                        writeln!(
+                            xml,
+                            ">\n      <system-out>{}</system-out>\n    </testcase>",
+                            escape(output)
+                        )
+                        .unwrap();
+                    }
+                    (_, None) => xml.push_str("/>\n"),
+                }
+            }
+            if let Some(error) = &suite.error {
+                writeln!(
+                    xml,
+                    "    <testcase name=\"{name}\" classname=\"{name}\">\n      <error message=\"{}\"/>\n    </testcase>",
+                    escape(error)
+                )
+                .unwrap();
+            }
+            xml.push_str("  </testsuite>\n");
+        }
+        xml.push_str("</testsuites>\n");
+        xml
+    }
+}
+
+/// Escapes text for XML, dropping characters that XML can't contain, like
+/// the escape sequences of colored output.
+fn escape(s: &str) -> String {
+    let s = anstream::adapter::strip_str(s).to_string();
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            '\t' | '\n' | '\r' => escaped.push(c),
+            c if c < ' ' => {}
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_report() {
+        assert_eq!(
+            "junit=target/report.xml".parse::<TestReport>().unwrap(),
+            TestReport::Junit(PathBuf::from("target/report.xml"))
+        );
+        for invalid in ["junit", "junit=", "xml=report.xml"] {
+            assert!(invalid.parse::<TestReport>().is_err(), "{invalid}");
+        }
+    }
+
+    #[test]
+    fn escapes_xml() {
+        assert_eq!(
+            escape("<a href=\"x\">&'\x1b[31mred\x1b[0m\x07</a>\n"),
+            "&lt;a href=&quot;x&quot;&gt;&amp;&apos;red&lt;/a&gt;\n"
+        );
+    }
+}
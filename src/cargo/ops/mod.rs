@@ -14,7 +14,9 @@ pub use self::cargo_package::{check_yanked, package, PackageOpts};
 pub use self::cargo_pkgid::pkgid;
 pub use self::cargo_read_manifest::read_package;
 pub use self::cargo_run::run;
-pub use self::cargo_test::{run_benches, run_tests, TestOptions};
+pub use self::cargo_test::{
+    run_benches, run_tests, Partition, PartitionStrategy, TestOptions, TestReport,
+};
 pub use self::cargo_uninstall::uninstall;
 pub use self::cargo_update::generate_lockfile;
 pub use self::cargo_update::print_lockfile_changes;
@@ -53,6 +55,7 @@ mod cargo_new;
 mod cargo_output_metadata;
 mod cargo_package;
 mod cargo_pkgid;
+pub mod cargo_profile;
 mod cargo_read_manifest;
 pub mod cargo_remove;
 mod cargo_run;
@@ -9,7 +9,10 @@ use std::collections::HashSet;
 use std::fs::File;
 use std::io::Seek;
 use std::io::SeekFrom;
+use std::task::Poll;
 use std::time::Duration;
+use std::time::SystemTime;
+use std::time::UNIX_EPOCH;
 
 use anyhow::bail;
 use anyhow::Context as _;
@@ -44,6 +47,7 @@ use crate::util::auth;
 use crate::util::cache_lock::CacheLockMode;
 use crate::util::context::JobsConfig;
 use crate::util::toml::prepare_for_publish;
+use crate::util::FileLock;
 use crate::util::Graph;
 use crate::util::Progress;
 use crate::util::ProgressStyle;
@@ -127,16 +131,51 @@ pub fn publish(ws: &Workspace<'_>, opts: &PublishOpts<'_>) -> CargoResult<()> {
         Some(Operation::Read).filter(|_| !opts.dry_run),
     )?;
 
+    let mut already_published = Vec::new();
+    let supports_staging;
     {
         let _lock = opts
             .gctx
             .acquire_package_cache_lock(CacheLockMode::DownloadExclusive)?;
 
+        supports_staging =
+            multi_package_mode && !opts.dry_run && supports_staged_publish(&mut source)?;
+
         for (pkg, _) in &pkgs {
-            verify_unpublished(pkg, &mut source, &source_ids, opts.dry_run, opts.gctx)?;
+            if is_published(pkg, &mut source, &source_ids)? {
+                already_published.push(pkg.package_id());
+            }
             verify_dependencies(pkg, &registry, source_ids.original)?;
         }
     }
+    if multi_package_mode && already_published.len() < pkgs.len() {
+        // Resume a partially completed publish of several packages by
+        // skipping the ones that already made it to the registry.
+        for pkg_id in &already_published {
+            opts.gctx.shell().status(
+                "Skipping",
+                format!("{pkg_id} (already exists on {})", source.describe()),
+            )?;
+        }
+        pkgs.retain(|(pkg, _)| !already_published.contains(&pkg.package_id()));
+    } else {
+        for pkg_id in &already_published {
+            // Move the registry error earlier in the publish process.
+            // Since dry-run wouldn't talk to the registry to get the error, we downgrade it to a
+            // warning.
+            let msg = format!(
+                "crate {}@{} already exists on {}",
+                pkg_id.name(),
+                pkg_id.version(),
+                source.describe()
+            );
+            if opts.dry_run {
+                opts.gctx.shell().warn(msg)?;
+            } else {
+                bail!(msg);
+            }
+        }
+    }
 
     let pkg_dep_graph = ops::cargo_package::package_with_dep_graph(
         ws,
@@ -158,109 +197,290 @@ pub fn publish(ws: &Workspace<'_>, opts: &PublishOpts<'_>) -> CargoResult<()> {
         pkgs,
     )?;
 
-    let mut plan = PublishPlan::new(&pkg_dep_graph.graph);
-    // May contains packages from previous rounds as `wait_for_any_publish_confirmation` returns
-    // after it confirms any packages, not all packages, requiring us to handle the rest in the next
-    // iteration.
-    //
-    // As a side effect, any given package's "effective" timeout may be much larger.
-    let mut to_confirm = BTreeSet::new();
-
-    while !plan.is_empty() {
-        // There might not be any ready package, if the previous confirmations
-        // didn't unlock a new one. For example, if `c` depends on `a` and
-        // `b`, and we uploaded `a` and `b` but only confirmed `a`, then on
-        // the following pass through the outer loop nothing will be ready for
-        // upload.
-        for pkg_id in plan.take_ready() {
-            let (pkg, (_features, tarball)) = &pkg_dep_graph.packages[&pkg_id];
-            opts.gctx.shell().status("Uploading", pkg.package_id())?;
-
-            if !opts.dry_run {
-                let ver = pkg.version().to_string();
-
-                tarball.file().seek(SeekFrom::Start(0))?;
-                let hash = cargo_util::Sha256::new()
-                    .update_file(tarball.file())?
-                    .finish_hex();
-                let operation = Operation::Publish {
-                    name: pkg.name().as_str(),
-                    vers: &ver,
-                    cksum: &hash,
-                };
-                registry.set_token(Some(auth::auth_token(
-                    &opts.gctx,
-                    &source_ids.original,
-                    None,
-                    operation,
-                    vec![],
-                    false,
-                )?));
-            }
+    let mut publish = Publish {
+        opts,
+        ws,
+        pkg_dep_graph: &pkg_dep_graph,
+        registry: &mut registry,
+        source_ids: &source_ids,
+        uploaded: BTreeSet::new(),
+    };
+    let staged = supports_staging && pkg_dep_graph.packages.len() > 1;
+    let result = if staged {
+        publish.staged()
+    } else {
+        publish.sequential()
+    };
+    if let Err(e) = result {
+        if pkg_dep_graph.packages.len() > 1 && !opts.dry_run {
+            let not_uploaded: Vec<_> = pkg_dep_graph
+                .packages
+                .keys()
+                .filter(|id| !publish.uploaded.contains(id))
+                .copied()
+                .collect();
+            let msg = if publish.uploaded.is_empty() && staged {
+                String::from("no packages were published; the staged uploads were discarded")
+            } else if publish.uploaded.is_empty() {
+                String::from("no packages were published")
+            } else if not_uploaded.is_empty() {
+                format!(
+                    "published {}, but could not confirm that they are available",
+                    package_list(publish.uploaded.iter().copied(), "and"),
+                )
+            } else {
+                format!(
+                    "published {}, but not {}\n\
+                     Running `cargo publish` again will skip the published versions.",
+                    package_list(publish.uploaded.iter().copied(), "and"),
+                    package_list(not_uploaded, "or"),
+                )
+            };
+            opts.gctx.shell().note(msg)?;
+        }
+        return Err(e);
+    }
 
-            transmit(
-                opts.gctx,
-                ws,
-                pkg,
-                tarball.file(),
-                &mut registry,
-                source_ids.original,
-                opts.dry_run,
-            )?;
-            to_confirm.insert(pkg_id);
-
-            if !opts.dry_run {
-                // Short does not include the registry name.
-                let short_pkg_description = format!("{} v{}", pkg.name(), pkg.version());
-                let source_description = source_ids.original.to_string();
-                ws.gctx().shell().status(
-                    "Uploaded",
-                    format!("{short_pkg_description} to {source_description}"),
-                )?;
+    Ok(())
+}
+
+/// State for uploading the packages of a single `cargo publish` invocation.
+struct Publish<'a, 'gctx> {
+    opts: &'a PublishOpts<'gctx>,
+    ws: &'a Workspace<'gctx>,
+    pkg_dep_graph: &'a ops::cargo_package::LocalDependencies<(CliFeatures, FileLock)>,
+    registry: &'a mut Registry,
+    source_ids: &'a RegistrySourceIds,
+    /// Packages that have been published, or accepted by the registry and
+    /// are about to be.
+    uploaded: BTreeSet<PackageId>,
+}
+
+impl Publish<'_, '_> {
+    /// Uploads packages one at a time, waiting for each to be available
+    /// before uploading the packages that depend on it.
+    ///
+    /// If an upload fails, the packages uploaded before it stay published.
+    fn sequential(&mut self) -> CargoResult<()> {
+        let opts = self.opts;
+        let mut plan = PublishPlan::new(&self.pkg_dep_graph.graph);
+        // May contains packages from previous rounds as `wait_for_any_publish_confirmation` returns
+        // after it confirms any packages, not all packages, requiring us to handle the rest in the next
+        // iteration.
+        //
+        // As a side effect, any given package's "effective" timeout may be much larger.
+        let mut to_confirm = BTreeSet::new();
+
+        while !plan.is_empty() {
+            // There might not be any ready package, if the previous confirmations
+            // didn't unlock a new one. For example, if `c` depends on `a` and
+            // `b`, and we uploaded `a` and `b` but only confirmed `a`, then on
+            // the following pass through the outer loop nothing will be ready for
+            // upload.
+            for pkg_id in plan.take_ready() {
+                self.upload(pkg_id, None)?;
+                to_confirm.insert(pkg_id);
+
+                if !opts.dry_run {
+                    self.uploaded.insert(pkg_id);
+                    // Short does not include the registry name.
+                    let short_pkg_description = format!("{} v{}", pkg_id.name(), pkg_id.version());
+                    let source_description = self.source_ids.original.to_string();
+                    opts.gctx.shell().status(
+                        "Uploaded",
+                        format!("{short_pkg_description} to {source_description}"),
+                    )?;
+                }
             }
-        }
 
-        let confirmed = if opts.dry_run {
-            to_confirm.clone()
-        } else {
-            const DEFAULT_TIMEOUT: u64 = 60;
-            let timeout = if opts.gctx.cli_unstable().publish_timeout {
-                let timeout: Option<u64> = opts.gctx.get("publish.timeout")?;
-                timeout.unwrap_or(DEFAULT_TIMEOUT)
+            let confirmed = if opts.dry_run {
+                to_confirm.clone()
             } else {
-                DEFAULT_TIMEOUT
+                self.wait_for_any_publish_confirmation(&to_confirm)?
             };
-            if 0 < timeout {
-                let timeout = Duration::from_secs(timeout);
-                wait_for_any_publish_confirmation(
-                    opts.gctx,
-                    source_ids.original,
-                    &to_confirm,
-                    timeout,
-                )?
-            } else {
-                BTreeSet::new()
+            if confirmed.is_empty() {
+                // If nothing finished, it means we timed out while waiting for confirmation.
+                // We're going to exit, but first we need to check: have we uploaded everything?
+                if plan.is_empty() {
+                    // It's ok that we timed out, because nothing was waiting on dependencies to
+                    // be confirmed.
+                    break;
+                } else {
+                    let failed_list = package_list(plan.iter(), "and");
+                    bail!("unable to publish {failed_list} due to time out while waiting for published dependencies to be available.");
+                }
             }
-        };
-        if confirmed.is_empty() {
-            // If nothing finished, it means we timed out while waiting for confirmation.
-            // We're going to exit, but first we need to check: have we uploaded everything?
-            if plan.is_empty() {
-                // It's ok that we timed out, because nothing was waiting on dependencies to
-                // be confirmed.
+            for id in &confirmed {
+                to_confirm.remove(id);
+            }
+            plan.mark_confirmed(confirmed);
+        }
+
+        Ok(())
+    }
+
+    /// Uploads every package to a staging area of the registry, then asks
+    /// the registry to publish all of them at once.
+    ///
+    /// If any upload fails, the staged packages are discarded and nothing is
+    /// published.
+    fn staged(&mut self) -> CargoResult<()> {
+        let opts = self.opts;
+        let session = staging_session(self.pkg_dep_graph.packages.keys());
+        tracing::debug!("staging publish in session {session}");
+        let mut plan = PublishPlan::new(&self.pkg_dep_graph.graph);
+        let mut staged = BTreeSet::new();
+        let result = (|| {
+            while !plan.is_empty() {
+                let ready = plan.take_ready();
+                for pkg_id in &ready {
+                    self.upload(*pkg_id, Some(&session))?;
+                    staged.insert(*pkg_id);
+                }
+                // Staged packages are not visible to anyone until committed, so
+                // there is nothing to wait for before uploading their dependents.
+                plan.mark_confirmed(ready);
+            }
+            self.registry.commit_staged(&session).with_context(|| {
+                format!(
+                    "failed to publish the staged packages to registry at {}",
+                    self.registry.host()
+                )
+            })
+        })();
+        if let Err(e) = result {
+            if !staged.is_empty() {
+                if let Err(discard) = self.registry.discard_staged(&session) {
+                    tracing::debug!("failed to discard staged publish {session}: {discard}");
+                }
+            }
+            return Err(e);
+        }
+        self.uploaded = staged;
+
+        let short_pkg_description = self
+            .uploaded
+            .iter()
+            .map(|pkg| format!("{} v{}", pkg.name(), pkg.version()))
+            .sorted()
+            .join(", ");
+        let source_description = self.source_ids.original.to_string();
+        opts.gctx.shell().status(
+            "Uploaded",
+            format!("{short_pkg_description} to {source_description}"),
+        )?;
+
+        // Everything was published at once, so wait for all of it to show up.
+        let mut to_confirm = self.uploaded.clone();
+        while !to_confirm.is_empty() {
+            let confirmed = self.wait_for_any_publish_confirmation(&to_confirm)?;
+            if confirmed.is_empty() {
                 break;
-            } else {
-                let failed_list = package_list(plan.iter(), "and");
-                bail!("unable to publish {failed_list} due to time out while waiting for published dependencies to be available.");
+            }
+            for id in &confirmed {
+                to_confirm.remove(id);
             }
         }
-        for id in &confirmed {
-            to_confirm.remove(id);
+
+        Ok(())
+    }
+
+    /// Uploads a single package, either publishing it directly or adding it
+    /// to the staging `session`.
+    fn upload(&mut self, pkg_id: PackageId, session: Option<&str>) -> CargoResult<()> {
+        let opts = self.opts;
+        let (pkg, (_features, tarball)) = &self.pkg_dep_graph.packages[&pkg_id];
+        opts.gctx.shell().status("Uploading", pkg.package_id())?;
+
+        let mut signature = None;
+        if !opts.dry_run {
+            let ver = pkg.version().to_string();
+
+            tarball.file().seek(SeekFrom::Start(0))?;
+            let hash = cargo_util::Sha256::new()
+                .update_file(tarball.file())?
+                .finish_hex();
+            if sign_packages(opts.gctx, &self.source_ids.original)? {
+                let operation = Operation::Sign {
+                    name: pkg.name().as_str(),
+                    vers: &ver,
+                    cksum: &hash,
+                };
+                signature = Some(
+                    auth::sign(opts.gctx, &self.source_ids.original, operation)
+                        .with_context(|| format!("failed to sign `{}`", pkg.package_id()))?,
+                );
+            }
+            let operation = Operation::Publish {
+                name: pkg.name().as_str(),
+                vers: &ver,
+                cksum: &hash,
+            };
+            self.registry.set_token(Some(auth::auth_token(
+                &opts.gctx,
+                &self.source_ids.original,
+                None,
+                operation,
+                vec![],
+                false,
+            )?));
         }
-        plan.mark_confirmed(confirmed);
+
+        transmit(
+            opts.gctx,
+            self.ws,
+            pkg,
+            tarball.file(),
+            self.registry,
+            self.source_ids.original,
+            signature,
+            session,
+            opts.dry_run,
+        )
     }
 
-    Ok(())
+    fn wait_for_any_publish_confirmation(
+        &self,
+        to_confirm: &BTreeSet<PackageId>,
+    ) -> CargoResult<BTreeSet<PackageId>> {
+        const DEFAULT_TIMEOUT: u64 = 60;
+        let gctx = self.opts.gctx;
+        let timeout = if gctx.cli_unstable().publish_timeout {
+            let timeout: Option<u64> = gctx.get("publish.timeout")?;
+            timeout.unwrap_or(DEFAULT_TIMEOUT)
+        } else {
+            DEFAULT_TIMEOUT
+        };
+        if 0 < timeout {
+            let timeout = Duration::from_secs(timeout);
+            wait_for_any_publish_confirmation(gctx, self.source_ids.original, to_confirm, timeout)
+        } else {
+            Ok(BTreeSet::new())
+        }
+    }
+}
+
+/// Whether the registry advertises support for staged publishing in its
+/// `config.json`.
+fn supports_staged_publish(source: &mut RegistrySource<'_>) -> CargoResult<bool> {
+    let cfg = loop {
+        match source.config()? {
+            Poll::Pending => source.block_until_ready()?,
+            Poll::Ready(cfg) => break cfg,
+        }
+    };
+    Ok(cfg.map_or(false, |cfg| cfg.staged_publish))
+}
+
+/// Creates an identifier for the staging area of a publish, unique to this
+/// invocation.
+fn staging_session<'a>(pkgs: impl Iterator<Item = &'a PackageId>) -> String {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_nanos();
+    let pkgs: Vec<_> = pkgs.map(|id| id.to_string()).collect();
+    crate::util::hex::short_hash(&(pkgs, now, std::process::id()))
 }
 
 /// Poll the registry for any packages that are ready for use.
@@ -364,13 +584,12 @@ fn poll_one_package(
     Ok(!summaries.is_empty())
 }
 
-fn verify_unpublished(
+/// Checks whether this version of `pkg` already exists in the registry.
+fn is_published(
     pkg: &Package,
     source: &mut RegistrySource<'_>,
     source_ids: &RegistrySourceIds,
-    dry_run: bool,
-    gctx: &GlobalContext,
-) -> CargoResult<()> {
+) -> CargoResult<bool> {
     let query = Dependency::parse(
         pkg.name(),
         Some(&pkg.version().to_exact_req().to_string()),
@@ -384,28 +603,7 @@ fn verify_unpublished(
             std::task::Poll::Pending => source.block_until_ready()?,
         }
     };
-    if !duplicate_query.is_empty() {
-        // Move the registry error earlier in the publish process.
-        // Since dry-run wouldn't talk to the registry to get the error, we downgrade it to a
-        // warning.
-        if dry_run {
-            gctx.shell().warn(format!(
-                "crate {}@{} already exists on {}",
-                pkg.name(),
-                pkg.version(),
-                source.describe()
-            ))?;
-        } else {
-            bail!(
-                "crate {}@{} already exists on {}",
-                pkg.name(),
-                pkg.version(),
-                source.describe()
-            );
-        }
-    }
-
-    Ok(())
+    Ok(!duplicate_query.is_empty())
 }
 
 fn verify_dependencies(
@@ -565,9 +763,21 @@ pub(crate) fn prepare_transmit(
         badges: badges.clone(),
         links: links.clone(),
         rust_version,
+        sig: None,
     })
 }
 
+/// Whether packages published to `registry_id` should be signed, as set by
+/// `registries.<name>.sign-packages` (or `registry.sign-packages`).
+fn sign_packages(gctx: &GlobalContext, registry_id: &SourceId) -> CargoResult<bool> {
+    if !gctx.cli_unstable().package_signing {
+        return Ok(false);
+    }
+    Ok(auth::registry_credential_config_raw(gctx, registry_id)?
+        .and_then(|cfg| cfg.sign_packages)
+        .unwrap_or(false))
+}
+
 fn transmit(
     gctx: &GlobalContext,
     ws: &Workspace<'_>,
@@ -575,9 +785,12 @@ fn transmit(
     tarball: &File,
     registry: &mut Registry,
     registry_id: SourceId,
+    signature: Option<String>,
+    session: Option<&str>,
     dry_run: bool,
 ) -> CargoResult<()> {
-    let new_crate = prepare_transmit(gctx, ws, pkg, registry_id)?;
+    let mut new_crate = prepare_transmit(gctx, ws, pkg, registry_id)?;
+    new_crate.sig = signature;
 
     // Do not upload if performing a dry run
     if dry_run {
@@ -585,9 +798,11 @@ fn transmit(
         return Ok(());
     }
 
-    let warnings = registry
-        .publish(&new_crate, tarball)
-        .with_context(|| format!("failed to publish to registry at {}", registry.host()))?;
+    let warnings = match session {
+        Some(session) => registry.stage(session, &new_crate, tarball),
+        None => registry.publish(&new_crate, tarball),
+    }
+    .with_context(|| format!("failed to publish to registry at {}", registry.host()))?;
 
     if !warnings.invalid_categories.is_empty() {
         let msg = format!(
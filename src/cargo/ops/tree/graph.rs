@@ -2,7 +2,7 @@
 
 use super::TreeOptions;
 use crate::core::compiler::{CompileKind, RustcTargetData};
-use crate::core::dependency::DepKind;
+use crate::core::dependency::{Artifact, DepKind};
 use crate::core::resolver::features::{CliFeatures, FeaturesFor, ResolvedFeatures};
 use crate::core::resolver::Resolve;
 use crate::core::{FeatureMap, FeatureValue, Package, PackageId, PackageIdSpec, Workspace};
@@ -79,6 +79,11 @@ pub struct Graph<'a> {
     /// Key is the index of a package node, value is a map of `dep_name` to a
     /// set of `(pkg_node_index, is_optional)`.
     dep_name_map: HashMap<usize, HashMap<InternedString, HashSet<(usize, bool)>>>,
+    /// Map of artifact dependency edges to a description of the artifacts
+    /// they request, like `bin, target: wasm32-unknown-unknown`.
+    ///
+    /// Key is the edge kind and the `(from, to)` indexes of the package nodes.
+    artifact_edges: HashMap<(EdgeKind, usize, usize), String>,
 }
 
 impl<'a> Graph<'a> {
@@ -90,6 +95,7 @@ impl<'a> Graph<'a> {
             package_map,
             cli_features: HashSet::new(),
             dep_name_map: HashMap::new(),
+            artifact_edges: HashMap::new(),
         }
     }
 
@@ -116,6 +122,14 @@ impl<'a> Graph<'a> {
         }
     }
 
+    /// Returns the description of the artifacts requested by the edge from
+    /// `from` to `to`, if it is an artifact dependency.
+    pub fn artifact_edge(&self, kind: &EdgeKind, from: usize, to: usize) -> Option<&str> {
+        self.artifact_edges
+            .get(&(*kind, from, to))
+            .map(String::as_str)
+    }
+
     /// Returns `true` if the given node has any outgoing edges.
     pub fn has_outgoing_edges(&self, index: usize) -> bool {
         !self.edges[index].0.is_empty()
@@ -212,6 +226,10 @@ impl<'a> Graph<'a> {
             }
         }
         self.edges = new_edges;
+        self.artifact_edges = std::mem::take(&mut self.artifact_edges)
+            .into_iter()
+            .map(|((kind, from, to), desc)| ((kind, to, from), desc))
+            .collect();
     }
 
     /// Returns a list of nodes that are considered "duplicates" (same package
@@ -283,8 +301,12 @@ pub fn build<'a>(
     members_with_features.sort_unstable_by_key(|e| e.0.package_id());
     for (member, cli_features) in members_with_features {
         let member_id = member.package_id();
-        let features_for = FeaturesFor::from_for_host(member.proc_macro());
         for kind in requested_kinds {
+            let features_for = if member.proc_macro() {
+                FeaturesFor::HostDep
+            } else {
+                FeaturesFor::normal_for(member.manifest().forced_kind().unwrap_or(*kind))
+            };
             let member_index = add_pkg(
                 &mut graph,
                 resolve,
@@ -307,6 +329,24 @@ pub fn build<'a>(
     Ok(graph)
 }
 
+/// Describes the artifacts requested by an artifact dependency, for display.
+fn describe_artifact(artifact: &Artifact) -> String {
+    let mut desc = artifact
+        .kinds()
+        .iter()
+        .map(|kind| kind.as_str())
+        .collect::<Vec<_>>()
+        .join(", ");
+    if artifact.is_lib() {
+        desc.push_str(", lib");
+    }
+    if let Some(target) = artifact.target() {
+        desc.push_str("; target: ");
+        desc.push_str(target.as_str());
+    }
+    desc
+}
+
 /// Adds a single package node (if it does not already exist).
 ///
 /// This will also recursively add all of its dependencies.
@@ -326,7 +366,7 @@ fn add_pkg(
     let node_kind = match features_for {
         FeaturesFor::HostDep => CompileKind::Host,
         FeaturesFor::ArtifactDep(target) => CompileKind::Target(target),
-        FeaturesFor::NormalOrDev => requested_kind,
+        FeaturesFor::NormalOrDev | FeaturesFor::Target(_) => requested_kind,
     };
     let node = Node::Package {
         package_id,
@@ -405,7 +445,13 @@ fn add_pkg(
                 // * a host dep,
                 //
                 // its transitive deps, including build-deps, need to be built on that target.
-                None if features_for != FeaturesFor::default() => features_for,
+                None if matches!(
+                    features_for,
+                    FeaturesFor::HostDep | FeaturesFor::ArtifactDep(_)
+                ) =>
+                {
+                    features_for
+                }
                 // Dependent crate is a normal dep, then back to old rules:
                 //
                 // * normal deps, dev-deps -> inherited target
@@ -428,6 +474,12 @@ fn add_pkg(
                 requested_kind,
                 opts,
             );
+            if let Some(artifact) = dep.artifact() {
+                graph.artifact_edges.insert(
+                    (EdgeKind::Dep(dep.kind()), from_index, dep_index),
+                    describe_artifact(artifact),
+                );
+            }
             if opts.graph_features {
                 // Add the dependency node with feature nodes in-between.
                 dep_name_map
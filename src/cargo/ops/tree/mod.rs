@@ -245,6 +245,7 @@ fn print(
             gctx,
             graph,
             root_index,
+            None,
             &format,
             symbols,
             pkgs_to_prune,
@@ -265,6 +266,7 @@ fn print_node<'a>(
     gctx: &GlobalContext,
     graph: &'a Graph<'_>,
     node_index: usize,
+    artifact: Option<&str>,
     format: &Pattern,
     symbols: &Symbols,
     pkgs_to_prune: &[PackageIdSpec],
@@ -307,7 +309,17 @@ fn print_node<'a>(
     } else {
         " (*)"
     };
-    drop_println!(gctx, "{}{}", format.display(graph, node_index), star);
+    let artifact = match artifact {
+        Some(artifact) => format!(" (artifact: {artifact})"),
+        None => String::new(),
+    };
+    drop_println!(
+        gctx,
+        "{}{}{}",
+        format.display(graph, node_index),
+        artifact,
+        star
+    );
 
     if !new || in_cycle {
         return;
@@ -402,6 +414,7 @@ fn print_dependencies<'a>(
             gctx,
             graph,
             *dependency,
+            graph.artifact_edge(kind, node_index, *dependency),
             format,
             symbols,
             pkgs_to_prune,
@@ -0,0 +1,217 @@
+//! Signature verification for git dependencies.
+//!
+//! When `-Zgit-verify-signature` is enabled and the `git.verify-signature`
+//! config is set, a [`GitSource`] refuses to use a checkout unless the
+//! resolved commit, or the annotated tag it was resolved from, carries a
+//! signature made by a trusted key. Both settings can be overridden for a
+//! single repository in a `[git.repository."<url>"]` table.
+//!
+//! The verification itself is delegated to the `git` CLI (`git verify-commit`
+//! and `git verify-tag`), which is the same tool used to produce those
+//! signatures in the first place. This gives us support for both kinds of
+//! signatures git knows about:
+//!
+//! - SSH signatures are checked against an OpenSSH "allowed signers" file
+//!   given by `git.allowed-signers`. This is the same format as git's
+//!   `gpg.ssh.allowedSignersFile`, and complements the host key checking done
+//!   in [`super::known_hosts`].
+//! - OpenPGP signatures are checked against the user's GnuPG keyring.
+//!
+//! Verifying a signature requires spawning processes, so a successful result
+//! is recorded in the [`GlobalCacheTracker`] keyed by the git database, the
+//! revision and a fingerprint of the allowed signers. Subsequent builds skip
+//! the check until any of those change, or the git database is cleaned.
+//!
+//! [`GitSource`]: super::GitSource
+//! [`GlobalCacheTracker`]: crate::core::global_cache_tracker::GlobalCacheTracker
+
+use crate::core::global_cache_tracker;
+use crate::core::GitReference;
+use crate::util::context::ConfigRelativePath;
+use crate::util::errors::CargoResult;
+use crate::util::hex::short_hash;
+use crate::util::interning::InternedString;
+use crate::util::CanonicalUrl;
+use crate::GlobalContext;
+use anyhow::Context as _;
+use cargo_util::{paths, ProcessBuilder};
+use git2::{ObjectType, Oid};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use url::Url;
+
+/// The `[git]` config table.
+#[derive(Deserialize, Default)]
+#[serde(rename_all = "kebab-case")]
+struct GitSignatureConfig {
+    /// Whether every git dependency must be signed.
+    verify_signature: Option<bool>,
+    /// Path to an OpenSSH allowed signers file.
+    allowed_signers: Option<ConfigRelativePath>,
+    /// Overrides of the above for individual repositories, keyed by URL.
+    repository: Option<BTreeMap<String, GitRepositorySignatureConfig>>,
+}
+
+/// A `[git.repository."<url>"]` config table.
+#[derive(Deserialize)]
+#[serde(rename_all = "kebab-case")]
+struct GitRepositorySignatureConfig {
+    verify_signature: Option<bool>,
+    allowed_signers: Option<ConfigRelativePath>,
+}
+
+/// What a git checkout must satisfy before it can be used.
+#[derive(Debug)]
+pub struct SignaturePolicy {
+    /// Path to an OpenSSH allowed signers file, if configured, and the config
+    /// key it came from.
+    allowed_signers: Option<(PathBuf, String)>,
+    /// Identifies the set of trusted keys, so that cached results are
+    /// invalidated when the allowed signers change.
+    fingerprint: InternedString,
+}
+
+impl SignaturePolicy {
+    /// Loads the policy for the git repository at `url` from config.
+    ///
+    /// Returns `None` if signature verification is not enabled for it.
+    pub fn from_gctx(gctx: &GlobalContext, url: &Url) -> CargoResult<Option<SignaturePolicy>> {
+        if !gctx.cli_unstable().git_verify_signature {
+            return Ok(None);
+        }
+        let config = gctx
+            .get::<Option<GitSignatureConfig>>("git")?
+            .unwrap_or_default();
+        let mut verify_signature = config.verify_signature;
+        let mut allowed_signers = config
+            .allowed_signers
+            .map(|p| (p, String::from("git.allowed-signers")));
+        let canonical_url = CanonicalUrl::new(url)?;
+        for (key, repository) in config.repository.into_iter().flatten() {
+            let key_url = Url::parse(&key)
+                .with_context(|| format!("invalid URL `{key}` in `git.repository`"))?;
+            if CanonicalUrl::new(&key_url)? != canonical_url {
+                continue;
+            }
+            verify_signature = repository.verify_signature.or(verify_signature);
+            if let Some(path) = repository.allowed_signers {
+                allowed_signers = Some((path, format!("git.repository.\"{key}\".allowed-signers")));
+            }
+        }
+        if !verify_signature.unwrap_or(false) {
+            return Ok(None);
+        }
+        let allowed_signers = allowed_signers.map(|(p, key)| (p.resolve_path(gctx), key));
+        let fingerprint = match &allowed_signers {
+            Some((path, key)) => {
+                let contents =
+                    paths::read_bytes(path).with_context(|| format!("failed to read `{key}`"))?;
+                format!("ssh-{}", short_hash(&contents))
+            }
+            None => String::from("gpg"),
+        };
+        Ok(Some(SignaturePolicy {
+            allowed_signers,
+            fingerprint: fingerprint.into(),
+        }))
+    }
+
+    /// Verifies that `rev` in the git database `repo` is signed by a
+    /// trusted key.
+    ///
+    /// If `reference` is a tag that points at `rev` and is an annotated tag,
+    /// the tag signature is checked instead of the commit signature.
+    pub fn verify(
+        &self,
+        repo: &git2::Repository,
+        url: &Url,
+        encoded_git_name: InternedString,
+        rev: Oid,
+        reference: &GitReference,
+        gctx: &GlobalContext,
+    ) -> CargoResult<()> {
+        let key = global_cache_tracker::GitSignature {
+            encoded_git_name,
+            rev: rev.to_string().into(),
+            signers: self.fingerprint,
+        };
+        if gctx.global_cache_tracker()?.git_signature_verified(&key)? {
+            tracing::debug!("signature of {rev} for {url} already verified");
+            gctx.deferred_global_last_use()?
+                .mark_git_signature_verified(key);
+            return Ok(());
+        }
+
+        let (kind, object) = match signed_tag(repo, rev, reference) {
+            Some(tag) => ("tag", tag),
+            None => ("commit", rev),
+        };
+        let mut cmd = ProcessBuilder::new("git");
+        if let Some((allowed_signers, _)) = &self.allowed_signers {
+            cmd.arg("-c").arg(format!(
+                "gpg.ssh.allowedSignersFile={}",
+                allowed_signers.display()
+            ));
+        }
+        cmd.arg(format!("verify-{kind}"))
+            .arg(object.to_string())
+            // See `fetch_with_cli` for why these are removed.
+            .env_remove("GIT_DIR")
+            .env_remove("GIT_WORK_TREE")
+            .env_remove("GIT_INDEX_FILE")
+            .env_remove("GIT_OBJECT_DIRECTORY")
+            .env_remove("GIT_ALTERNATE_OBJECT_DIRECTORIES")
+            .cwd(repo.path());
+        gctx.shell()
+            .verbose(|s| s.status("Running", &cmd.to_string()))?;
+        let output = cmd.output()?;
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            let mut msg = format!(
+                "failed to verify the signature of {kind} `{object}` in git repository `{url}`"
+            );
+            if !stderr.trim().is_empty() {
+                msg.push_str("\n\n");
+                msg.push_str(stderr.trim_end());
+            }
+            msg.push_str("\n\n");
+            msg.push_str(&help(self.allowed_signers.as_ref()));
+            anyhow::bail!(msg);
+        }
+        gctx.shell().verbose(|s| {
+            s.status(
+                "Verified",
+                format!("signature of {kind} `{object}` in `{url}`"),
+            )
+        })?;
+        gctx.deferred_global_last_use()?
+            .mark_git_signature_verified(key);
+        Ok(())
+    }
+}
+
+/// Returns the annotated tag object `reference` was resolved from, if any.
+fn signed_tag(repo: &git2::Repository, rev: Oid, reference: &GitReference) -> Option<Oid> {
+    let GitReference::Tag(name) = reference else {
+        return None;
+    };
+    let refname = format!("refs/remotes/origin/tags/{name}");
+    let id = repo.refname_to_id(&refname).ok()?;
+    let tag = repo.find_object(id, Some(ObjectType::Tag)).ok()?;
+    let commit = tag.peel(ObjectType::Commit).ok()?;
+    (commit.id() == rev).then_some(id)
+}
+
+fn help(allowed_signers: Option<&(PathBuf, String)>) -> String {
+    match allowed_signers {
+        Some((path, key)) => format!(
+            "help: the signing key must be listed in `{}` (from `{key}`)",
+            path.display()
+        ),
+        None => String::from(
+            "help: SSH signatures require `git.allowed-signers` to be set; \
+             GPG signatures are checked against your GnuPG keyring",
+        ),
+    }
+}
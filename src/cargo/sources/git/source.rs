@@ -4,6 +4,7 @@ use crate::core::global_cache_tracker;
 use crate::core::GitReference;
 use crate::core::SourceId;
 use crate::core::{Dependency, Package, PackageId};
+use crate::sources::git::signature::SignaturePolicy;
 use crate::sources::git::utils::rev_to_oid;
 use crate::sources::git::utils::GitRemote;
 use crate::sources::source::MaybePackage;
@@ -333,6 +334,13 @@ impl<'gctx> Source for GitSource<'gctx> {
             }
         };
 
+        // Refuse to use anything that isn't signed by a trusted key before it
+        // ever gets checked out.
+        if let Some(policy) = SignaturePolicy::from_gctx(self.gctx, self.remote.url())? {
+            let reference = self.source_id.git_reference().unwrap();
+            db.verify_signature(&policy, self.ident, actual_rev, reference, self.gctx)?;
+        }
+
         // Don’t use the full hash, in order to contribute less to reaching the
         // path length limit on Windows. See
         // <https://github.com/servo/servo/pull/14397>.
@@ -5,7 +5,9 @@ use crate::core::{GitReference, Verbosity};
 use crate::sources::git::fetch::RemoteKind;
 use crate::sources::git::oxide;
 use crate::sources::git::oxide::cargo_config_to_gitoxide_overrides;
+use crate::sources::git::signature::SignaturePolicy;
 use crate::util::errors::CargoResult;
+use crate::util::interning::InternedString;
 use crate::util::{
     human_readable_bytes, network, GlobalContext, IntoUrl, MetricsCounter, Progress,
 };
@@ -192,6 +194,26 @@ impl GitDatabase {
         Ok(checkout)
     }
 
+    /// Verifies that `revision`, or the annotated tag `reference` resolved
+    /// to, is signed by a key trusted by `policy`.
+    pub fn verify_signature(
+        &self,
+        policy: &SignaturePolicy,
+        encoded_git_name: InternedString,
+        revision: git2::Oid,
+        reference: &GitReference,
+        gctx: &GlobalContext,
+    ) -> CargoResult<()> {
+        policy.verify(
+            &self.repo,
+            self.remote.url(),
+            encoded_git_name,
+            revision,
+            reference,
+            gctx,
+        )
+    }
+
     /// Get a short OID for a `revision`, usually 7 chars or more if ambiguous.
     pub fn to_short_id(&self, revision: git2::Oid) -> CargoResult<GitShortID> {
         let obj = self.repo.find_object(revision, None)?;
@@ -0,0 +1,138 @@
+//! Registry ownership of package names, for `-Zregistry-owns`.
+//!
+//! A package name that is only published on a private registry can usually
+//! be registered by anyone on crates.io. If a dependency is ever resolved
+//! against the public registry, for example because someone forgot the
+//! `registry` key or the lockfile was edited, Cargo would happily use the
+//! impostor. This is known as a dependency confusion attack.
+//!
+//! To prevent it, a registry can claim names in its configuration:
+//!
+//! ```toml
+//! [registries.internal]
+//! index = "sparse+https://internal.example.com/index/"
+//! owns = ["acme-*", "widgets"]
+//! ```
+//!
+//! Candidates for a claimed name that come from any other registry are then
+//! rejected by [`PackageRegistry`], or reported with a warning when
+//! `owns-policy = "warn"`. Path and git dependencies are not affected, since
+//! they always name their source explicitly.
+//!
+//! [`PackageRegistry`]: crate::core::registry::PackageRegistry
+
+use anyhow::Context as _;
+use serde::Deserialize;
+
+use crate::core::{PackageId, SourceId};
+use crate::util::errors::CargoResult;
+use crate::GlobalContext;
+
+/// What to do with a candidate from a registry that doesn't own its name.
+#[derive(Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum OwnsPolicy {
+    /// Ignore the candidate.
+    Deny,
+    /// Use the candidate, but warn about it.
+    Warn,
+}
+
+/// A single entry of a registry's `owns` list.
+#[derive(Debug)]
+struct Claim {
+    pattern: glob::Pattern,
+    registry: String,
+    source_id: SourceId,
+    policy: OwnsPolicy,
+}
+
+/// The names claimed by every configured registry.
+#[derive(Debug)]
+pub struct RegistryOwnership {
+    claims: Vec<Claim>,
+}
+
+/// A candidate from a registry that doesn't own its name.
+pub struct Violation<'a> {
+    /// The name of the registry that owns the name.
+    pub registry: &'a str,
+    /// The entry of the `owns` list that matched.
+    pub pattern: &'a str,
+    pub policy: OwnsPolicy,
+}
+
+impl RegistryOwnership {
+    /// Loads the `owns` lists of all `[registries]`.
+    ///
+    /// Returns `None` if no registry claims any names.
+    pub fn from_gctx(gctx: &GlobalContext) -> CargoResult<Option<RegistryOwnership>> {
+        if !gctx.cli_unstable().registry_owns {
+            return Ok(None);
+        }
+        let Some(registries) = gctx.values()?.get("registries") else {
+            return Ok(None);
+        };
+        let (registries, _) = registries.table("registries")?;
+        let mut claims = Vec::new();
+        for registry in registries.keys() {
+            let Some(owns) =
+                gctx.get::<Option<Vec<String>>>(&format!("registries.{registry}.owns"))?
+            else {
+                continue;
+            };
+            let source_id = SourceId::alt_registry(gctx, registry)?;
+            let policy = gctx
+                .get::<Option<OwnsPolicy>>(&format!("registries.{registry}.owns-policy"))?
+                .unwrap_or(OwnsPolicy::Deny);
+            for name in owns {
+                let pattern = glob::Pattern::new(&normalize(&name)).with_context(|| {
+                    format!("invalid pattern `{name}` in `registries.{registry}.owns`")
+                })?;
+                claims.push(Claim {
+                    pattern,
+                    registry: registry.clone(),
+                    source_id,
+                    policy,
+                });
+            }
+        }
+        if claims.is_empty() {
+            return Ok(None);
+        }
+        // Keep messages stable regardless of the order of the config table.
+        claims.sort_by(|a, b| a.registry.cmp(&b.registry));
+        Ok(Some(RegistryOwnership { claims }))
+    }
+
+    /// Checks whether `pkg` comes from a registry that is allowed to provide
+    /// its name.
+    ///
+    /// A name may be claimed by several registries, in which case any of them
+    /// may provide it.
+    pub fn violation(&self, pkg: PackageId) -> Option<Violation<'_>> {
+        let source_id = pkg.source_id();
+        if !source_id.is_registry() {
+            return None;
+        }
+        let name = normalize(&pkg.name());
+        let mut violation = None;
+        for claim in self.claims.iter().filter(|c| c.pattern.matches(&name)) {
+            if claim.source_id.canonical_url() == source_id.canonical_url() {
+                return None;
+            }
+            violation.get_or_insert(Violation {
+                registry: &claim.registry,
+                pattern: claim.pattern.as_str(),
+                policy: claim.policy,
+            });
+        }
+        violation
+    }
+}
+
+/// Registries like crates.io consider `-` and `_` to be the same, and names
+/// to be case insensitive, so claims must too.
+fn normalize(name: &str) -> String {
+    name.to_lowercase().replace('_', "-")
+}
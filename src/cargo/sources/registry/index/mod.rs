@@ -28,11 +28,7 @@ use crate::util::interning::InternedString;
 use crate::util::IntoUrl;
 use crate::util::{internal, CargoResult, Filesystem, GlobalContext, OptVersionReq};
 use cargo_util::registry::make_dep_path;
-use cargo_util_schemas::manifest::RustVersion;
 use semver::Version;
-use serde::{Deserialize, Serialize};
-use std::borrow::Cow;
-use std::collections::BTreeMap;
 use std::collections::HashMap;
 use std::path::Path;
 use std::str;
@@ -43,9 +39,11 @@ mod cache;
 use self::cache::CacheManager;
 use self::cache::SummariesCache;
 
+pub use cargo_util_schemas::index::{IndexPackage, RegistryDependency};
+
 /// The maximum schema version of the `v` field in the index this version of
 /// cargo understands. See [`IndexPackage::v`] for the detail.
-const INDEX_V_MAX: u32 = 2;
+const INDEX_V_MAX: u32 = 3;
 
 /// Manager for handling the on-disk index.
 ///
@@ -194,112 +192,6 @@ impl IndexSummary {
     }
 }
 
-/// A single line in the index representing a single version of a package.
-#[derive(Deserialize, Serialize)]
-pub struct IndexPackage<'a> {
-    /// Name of the package.
-    pub name: InternedString,
-    /// The version of this dependency.
-    pub vers: Version,
-    /// All kinds of direct dependencies of the package, including dev and
-    /// build dependencies.
-    #[serde(borrow)]
-    pub deps: Vec<RegistryDependency<'a>>,
-    /// Set of features defined for the package, i.e., `[features]` table.
-    #[serde(default)]
-    pub features: BTreeMap<InternedString, Vec<InternedString>>,
-    /// This field contains features with new, extended syntax. Specifically,
-    /// namespaced features (`dep:`) and weak dependencies (`pkg?/feat`).
-    ///
-    /// This is separated from `features` because versions older than 1.19
-    /// will fail to load due to not being able to parse the new syntax, even
-    /// with a `Cargo.lock` file.
-    pub features2: Option<BTreeMap<InternedString, Vec<InternedString>>>,
-    /// Checksum for verifying the integrity of the corresponding downloaded package.
-    pub cksum: String,
-    /// If `true`, Cargo will skip this version when resolving.
-    ///
-    /// This was added in 2014. Everything in the crates.io index has this set
-    /// now, so this probably doesn't need to be an option anymore.
-    pub yanked: Option<bool>,
-    /// Native library name this package links to.
-    ///
-    /// Added early 2018 (see <https://github.com/rust-lang/cargo/pull/4978>),
-    /// can be `None` if published before then.
-    pub links: Option<InternedString>,
-    /// Required version of rust
-    ///
-    /// Corresponds to `package.rust-version`.
-    ///
-    /// Added in 2023 (see <https://github.com/rust-lang/crates.io/pull/6267>),
-    /// can be `None` if published before then or if not set in the manifest.
-    pub rust_version: Option<RustVersion>,
-    /// The schema version for this entry.
-    ///
-    /// If this is None, it defaults to version `1`. Entries with unknown
-    /// versions are ignored.
-    ///
-    /// Version `2` schema adds the `features2` field.
-    ///
-    /// Version `3` schema adds `artifact`, `bindep_targes`, and `lib` for
-    /// artifact dependencies support.
-    ///
-    /// This provides a method to safely introduce changes to index entries
-    /// and allow older versions of cargo to ignore newer entries it doesn't
-    /// understand. This is honored as of 1.51, so unfortunately older
-    /// versions will ignore it, and potentially misinterpret version 2 and
-    /// newer entries.
-    ///
-    /// The intent is that versions older than 1.51 will work with a
-    /// pre-existing `Cargo.lock`, but they may not correctly process `cargo
-    /// update` or build a lock from scratch. In that case, cargo may
-    /// incorrectly select a new package that uses a new index schema. A
-    /// workaround is to downgrade any packages that are incompatible with the
-    /// `--precise` flag of `cargo update`.
-    pub v: Option<u32>,
-}
-
-/// A dependency as encoded in the [`IndexPackage`] index JSON.
-#[derive(Deserialize, Serialize)]
-pub struct RegistryDependency<'a> {
-    /// Name of the dependency. If the dependency is renamed, the original
-    /// would be stored in [`RegistryDependency::package`].
-    pub name: InternedString,
-    /// The SemVer requirement for this dependency.
-    #[serde(borrow)]
-    pub req: Cow<'a, str>,
-    /// Set of features enabled for this dependency.
-    #[serde(default)]
-    pub features: Vec<InternedString>,
-    /// Whether or not this is an optional dependency.
-    #[serde(default)]
-    pub optional: bool,
-    /// Whether or not default features are enabled.
-    #[serde(default = "default_true")]
-    pub default_features: bool,
-    /// The target platform for this dependency.
-    pub target: Option<Cow<'a, str>>,
-    /// The dependency kind. "dev", "build", and "normal".
-    pub kind: Option<Cow<'a, str>>,
-    // The URL of the index of the registry where this dependency is from.
-    // `None` if it is from the same index.
-    pub registry: Option<Cow<'a, str>>,
-    /// The original name if the dependency is renamed.
-    pub package: Option<InternedString>,
-    /// Whether or not this is a public dependency. Unstable. See [RFC 1977].
-    ///
-    /// [RFC 1977]: https://rust-lang.github.io/rfcs/1977-public-private-dependencies.html
-    pub public: Option<bool>,
-    pub artifact: Option<Vec<Cow<'a, str>>>,
-    pub bindep_target: Option<Cow<'a, str>>,
-    #[serde(default)]
-    pub lib: bool,
-}
-
-fn default_true() -> bool {
-    true
-}
-
 impl<'gctx> RegistryIndex<'gctx> {
     /// Creates an empty registry index at `path`.
     pub fn new(
@@ -330,6 +222,23 @@ impl<'gctx> RegistryIndex<'gctx> {
             .ok_or_else(|| internal(format!("no hash listed for {}", pkg)))?))
     }
 
+    /// Returns the signature listed for a specified `PackageId`, if any.
+    /// Used together with [`RegistryIndex::hash`] to check that a downloaded
+    /// package was published by a trusted key.
+    pub fn signature(
+        &mut self,
+        pkg: PackageId,
+        load: &mut dyn RegistryData,
+    ) -> Poll<CargoResult<Option<&str>>> {
+        let req = OptVersionReq::lock_to_exact(pkg.version());
+        let summary = self.summaries(pkg.name(), &req, load)?;
+        let summary = ready!(summary).next();
+        Poll::Ready(Ok(summary
+            .ok_or_else(|| internal(format!("no signature listed for {}", pkg)))?
+            .as_summary()
+            .signature()))
+    }
+
     /// Load a list of summaries for `name` package in this registry which
     /// match `req`.
     ///
@@ -350,8 +259,6 @@ impl<'gctx> RegistryIndex<'gctx> {
     where
         'a: 'b,
     {
-        let bindeps = self.gctx.cli_unstable().bindeps;
-
         let source_id = self.source_id;
 
         // First up parse what summaries we have available.
@@ -368,27 +275,23 @@ impl<'gctx> RegistryIndex<'gctx> {
             .versions
             .iter_mut()
             .filter_map(move |(k, v)| if req.matches(k) { Some(v) } else { None })
-            .filter_map(move |maybe| {
-                match maybe.parse(raw_data, source_id, bindeps) {
-                    Ok(sum @ IndexSummary::Candidate(_) | sum @ IndexSummary::Yanked(_)) => {
-                        Some(sum)
-                    }
-                    Ok(IndexSummary::Unsupported(summary, v)) => {
-                        debug!(
-                            "unsupported schema version {} ({} {})",
-                            v,
-                            summary.name(),
-                            summary.version()
-                        );
-                        None
-                    }
-                    Ok(IndexSummary::Offline(_)) => {
-                        unreachable!("We do not check for off-line until later")
-                    }
-                    Err(e) => {
-                        info!("failed to parse `{}` registry package: {}", name, e);
-                        None
-                    }
+            .filter_map(move |maybe| match maybe.parse(raw_data, source_id) {
+                Ok(sum @ IndexSummary::Candidate(_) | sum @ IndexSummary::Yanked(_)) => Some(sum),
+                Ok(IndexSummary::Unsupported(summary, v)) => {
+                    debug!(
+                        "unsupported schema version {} ({} {})",
+                        v,
+                        summary.name(),
+                        summary.version()
+                    );
+                    None
+                }
+                Ok(IndexSummary::Offline(_)) => {
+                    unreachable!("We do not check for off-line until later")
+                }
+                Err(e) => {
+                    info!("failed to parse `{}` registry package: {}", name, e);
+                    None
                 }
             })))
     }
@@ -429,7 +332,6 @@ impl<'gctx> RegistryIndex<'gctx> {
             &name,
             self.source_id,
             load,
-            self.gctx.cli_unstable().bindeps,
             &self.cache_manager,
         ))?
         .unwrap_or_default();
@@ -543,13 +445,11 @@ impl Summaries {
     ///   to create summaries.
     /// * `load` --- the actual index implementation which may be very slow to
     ///   call. We avoid this if we can.
-    /// * `bindeps` --- whether the `-Zbindeps` unstable flag is enabled
     pub fn parse(
         root: &Path,
         name: &str,
         source_id: SourceId,
         load: &mut dyn RegistryData,
-        bindeps: bool,
         cache_manager: &CacheManager<'_>,
     ) -> Poll<CargoResult<Option<Summaries>>> {
         // This is the file we're loading from cache or the index data.
@@ -599,7 +499,7 @@ impl Summaries {
                     // allow future cargo implementations to break the
                     // interpretation of each line here and older cargo will simply
                     // ignore the new lines.
-                    let summary = match IndexSummary::parse(line, source_id, bindeps) {
+                    let summary = match IndexSummary::parse(line, source_id) {
                         Ok(summary) => summary,
                         Err(e) => {
                             // This should only happen when there is an index
@@ -682,17 +582,12 @@ impl MaybeIndexSummary {
     /// Does nothing if this is already `Parsed`, and otherwise the `raw_data`
     /// passed in is sliced with the bounds in `Unparsed` and then actually
     /// parsed.
-    fn parse(
-        &mut self,
-        raw_data: &[u8],
-        source_id: SourceId,
-        bindeps: bool,
-    ) -> CargoResult<&IndexSummary> {
+    fn parse(&mut self, raw_data: &[u8], source_id: SourceId) -> CargoResult<&IndexSummary> {
         let (start, end) = match self {
             MaybeIndexSummary::Unparsed { start, end } => (*start, *end),
             MaybeIndexSummary::Parsed(summary) => return Ok(summary),
         };
-        let summary = IndexSummary::parse(&raw_data[start..end], source_id, bindeps)?;
+        let summary = IndexSummary::parse(&raw_data[start..end], source_id)?;
         *self = MaybeIndexSummary::Parsed(summary);
         match self {
             MaybeIndexSummary::Unparsed { .. } => unreachable!(),
@@ -713,7 +608,7 @@ impl IndexSummary {
     ///
     /// The `line` provided is expected to be valid JSON. It is supposed to be
     /// a [`IndexPackage`].
-    fn parse(line: &[u8], source_id: SourceId, bindeps: bool) -> CargoResult<IndexSummary> {
+    fn parse(line: &[u8], source_id: SourceId) -> CargoResult<IndexSummary> {
         // ****CAUTION**** Please be extremely careful with returning errors
         // from this function. Entries that error are not included in the
         // index cache, and can cause cargo to get confused when switching
@@ -730,30 +625,38 @@ impl IndexSummary {
             yanked,
             links,
             rust_version,
+            sig,
             v,
         } = serde_json::from_slice(line)?;
         let v = v.unwrap_or(1);
         tracing::trace!("json parsed registry {}/{}", name, vers);
-        let pkgid = PackageId::new(name.into(), vers.clone(), source_id);
+        let pkgid = PackageId::new(name.as_ref().into(), vers.clone(), source_id);
         let deps = deps
             .into_iter()
-            .map(|dep| dep.into_dep(source_id))
+            .map(|dep| registry_dependency_into_dep(dep, source_id))
             .collect::<CargoResult<Vec<_>>>()?;
         if let Some(features2) = features2 {
             for (name, values) in features2 {
                 features.entry(name).or_default().extend(values);
             }
         }
+        let features = features
+            .into_iter()
+            .map(|(name, values)| {
+                (
+                    InternedString::new(&name),
+                    values.iter().map(|v| InternedString::new(v)).collect(),
+                )
+            })
+            .collect();
+        let links = links.map(|l| InternedString::new(&l));
         let mut summary = Summary::new(pkgid, deps, &features, links, rust_version)?;
         summary.set_checksum(cksum);
+        if let Some(sig) = sig {
+            summary.set_signature(sig);
+        }
 
-        let v_max = if bindeps {
-            INDEX_V_MAX + 1
-        } else {
-            INDEX_V_MAX
-        };
-
-        if v_max < v {
+        if INDEX_V_MAX < v {
             Ok(IndexSummary::Unsupported(summary, v))
         } else if yanked.unwrap_or(false) {
             Ok(IndexSummary::Yanked(summary))
@@ -763,76 +666,77 @@ impl IndexSummary {
     }
 }
 
-impl<'a> RegistryDependency<'a> {
-    /// Converts an encoded dependency in the registry to a cargo dependency
-    pub fn into_dep(self, default: SourceId) -> CargoResult<Dependency> {
-        let RegistryDependency {
-            name,
-            req,
-            mut features,
-            optional,
-            default_features,
-            target,
-            kind,
-            registry,
-            package,
-            public,
-            artifact,
-            bindep_target,
-            lib,
-        } = self;
-
-        let id = if let Some(registry) = &registry {
-            SourceId::for_registry(&registry.into_url()?)?
-        } else {
-            default
-        };
-
-        let mut dep = Dependency::parse(package.unwrap_or(name), Some(&req), id)?;
-        if package.is_some() {
-            dep.set_explicit_name_in_toml(name);
-        }
-        let kind = match kind.as_deref().unwrap_or("") {
-            "dev" => DepKind::Development,
-            "build" => DepKind::Build,
-            _ => DepKind::Normal,
-        };
-
-        let platform = match target {
-            Some(target) => Some(target.parse()?),
-            None => None,
-        };
-
-        // All dependencies are private by default
-        let public = public.unwrap_or(false);
-
-        // Unfortunately older versions of cargo and/or the registry ended up
-        // publishing lots of entries where the features array contained the
-        // empty feature, "", inside. This confuses the resolution process much
-        // later on and these features aren't actually valid, so filter them all
-        // out here.
-        features.retain(|s| !s.is_empty());
-
-        // In index, "registry" is null if it is from the same index.
-        // In Cargo.toml, "registry" is None if it is from the default
-        if !id.is_crates_io() {
-            dep.set_registry_id(id);
-        }
+/// Converts an encoded dependency in the registry to a cargo dependency
+fn registry_dependency_into_dep(
+    dep: RegistryDependency<'_>,
+    default: SourceId,
+) -> CargoResult<Dependency> {
+    let RegistryDependency {
+        name,
+        req,
+        mut features,
+        optional,
+        default_features,
+        target,
+        kind,
+        registry,
+        package,
+        public,
+        artifact,
+        bindep_target,
+        lib,
+    } = dep;
+
+    let id = if let Some(registry) = &registry {
+        SourceId::for_registry(&registry.into_url()?)?
+    } else {
+        default
+    };
+
+    let mut dep = Dependency::parse(package.as_deref().unwrap_or(&name), Some(&req), id)?;
+    if package.is_some() {
+        dep.set_explicit_name_in_toml(name.as_ref());
+    }
+    let kind = match kind.as_deref().unwrap_or("") {
+        "dev" => DepKind::Development,
+        "build" => DepKind::Build,
+        _ => DepKind::Normal,
+    };
+
+    let platform = match target {
+        Some(target) => Some(target.parse()?),
+        None => None,
+    };
+
+    // All dependencies are private by default
+    let public = public.unwrap_or(false);
+
+    // Unfortunately older versions of cargo and/or the registry ended up
+    // publishing lots of entries where the features array contained the
+    // empty feature, "", inside. This confuses the resolution process much
+    // later on and these features aren't actually valid, so filter them all
+    // out here.
+    features.retain(|s| !s.is_empty());
+
+    // In index, "registry" is null if it is from the same index.
+    // In Cargo.toml, "registry" is None if it is from the default
+    if !id.is_crates_io() {
+        dep.set_registry_id(id);
+    }
 
-        if let Some(artifacts) = artifact {
-            let artifact = Artifact::parse(&artifacts, lib, bindep_target.as_deref())?;
-            dep.set_artifact(artifact);
-        }
+    if let Some(artifacts) = artifact {
+        let artifact = Artifact::parse(&artifacts, lib, bindep_target.as_deref())?;
+        dep.set_artifact(artifact);
+    }
 
-        dep.set_optional(optional)
-            .set_default_features(default_features)
-            .set_features(features)
-            .set_platform(platform)
-            .set_kind(kind)
-            .set_public(public);
+    dep.set_optional(optional)
+        .set_default_features(default_features)
+        .set_features(features.iter().map(|f| f.as_ref()))
+        .set_platform(platform)
+        .set_kind(kind)
+        .set_public(public);
 
-        Ok(dep)
-    }
+    Ok(dep)
 }
 
 /// Like [`slice::split`] but is optimized by [`memchr`].
@@ -179,6 +179,14 @@
 //!     src/
 //!         registry1-<hash>/<pkg>-<version>/...
 //!         ...
+//!
+//!     # With `-Zshared-registry-src`, tarballs are instead unpacked once per
+//!     # checksum, and the directories in `src/` are symlinks to this store.
+//!     # The same package reached through a mirror or `replace-with` source
+//!     # is then only on disk once.
+//!     src-store/
+//!         <checksum>/<pkg>-<version>/...
+//!         ...
 //! ```
 //!
 //! [`IndexPackage`]: index::IndexPackage
@@ -317,6 +325,11 @@ pub struct RegistryConfig {
     /// [RFC 3139]: https://rust-lang.github.io/rfcs/3139-cargo-alternative-registry-auth.html
     #[serde(default)]
     pub auth_required: bool,
+
+    /// Whether the registry supports staging several crates and publishing
+    /// them together, used by `cargo publish` for workspaces.
+    #[serde(default)]
+    pub staged_publish: bool,
 }
 
 /// Result from loading data from a registry.
@@ -449,6 +462,7 @@ pub(crate) mod index;
 pub use index::IndexSummary;
 mod local;
 mod remote;
+mod signature;
 
 /// Generates a unique name for [`SourceId`] to have a unique path to put their
 /// index files.
@@ -596,7 +610,7 @@ impl<'gctx> RegistrySource<'gctx> {
     /// `.cargo-ok` file is found.
     ///
     /// [CVE-2022-36113]: https://blog.rust-lang.org/2022/09/14/cargo-cves.html#arbitrary-file-corruption-cve-2022-36113
-    fn unpack_package(&self, pkg: PackageId, tarball: &File) -> CargoResult<PathBuf> {
+    fn unpack_package(&self, pkg: PackageId, tarball: &File, cksum: &str) -> CargoResult<PathBuf> {
         let package_dir = format!("{}-{}", pkg.name(), pkg.version());
         let dst = self.src_path.join(&package_dir);
         let path = dst.join(PACKAGE_SOURCE_LOCK);
@@ -604,110 +618,108 @@ impl<'gctx> RegistrySource<'gctx> {
             .gctx
             .assert_package_cache_locked(CacheLockMode::DownloadExclusive, &path);
         let unpack_dir = path.parent().unwrap();
-        match fs::read_to_string(path) {
-            Ok(ok) => match serde_json::from_str::<LockMetadata>(&ok) {
-                Ok(lock_meta) if lock_meta.v == 1 => {
-                    self.gctx
-                        .deferred_global_last_use()?
-                        .mark_registry_src_used(global_cache_tracker::RegistrySrc {
-                            encoded_registry_name: self.name,
-                            package_dir: package_dir.into(),
-                            size: None,
-                        });
-                    return Ok(unpack_dir.to_path_buf());
-                }
-                _ => {
-                    if ok == "ok" {
-                        tracing::debug!("old `ok` content found, clearing cache");
-                    } else {
-                        tracing::warn!("unrecognized .cargo-ok content, clearing cache: {ok}");
-                    }
-                    // See comment of `unpack_package` about why removing all stuff.
-                    paths::remove_dir_all(dst.as_path_unlocked())?;
-                }
-            },
-            Err(e) if e.kind() == io::ErrorKind::NotFound => {}
-            Err(e) => anyhow::bail!("unable to read .cargo-ok file at {path:?}: {e}"),
-        }
-        dst.create_dir()?;
-        let mut tar = {
-            let size_limit = max_unpack_size(self.gctx, tarball.metadata()?.len());
-            let gz = GzDecoder::new(tarball);
-            let gz = LimitErrorReader::new(gz, size_limit);
-            let mut tar = Archive::new(gz);
-            set_mask(&mut tar);
-            tar
+        let registry_src = |size| global_cache_tracker::RegistrySrc {
+            encoded_registry_name: self.name,
+            package_dir: package_dir.as_str().into(),
+            size,
         };
-        let mut bytes_written = 0;
-        let prefix = unpack_dir.file_name().unwrap();
-        let parent = unpack_dir.parent().unwrap();
-        for entry in tar.entries()? {
-            let mut entry = entry.context("failed to iterate over archive")?;
-            let entry_path = entry
-                .path()
-                .context("failed to read entry path")?
-                .into_owned();
-
-            // We're going to unpack this tarball into the global source
-            // directory, but we want to make sure that it doesn't accidentally
-            // (or maliciously) overwrite source code from other crates. Cargo
-            // itself should never generate a tarball that hits this error, and
-            // crates.io should also block uploads with these sorts of tarballs,
-            // but be extra sure by adding a check here as well.
-            if !entry_path.starts_with(prefix) {
-                anyhow::bail!(
-                    "invalid tarball downloaded, contains \
-                     a file at {:?} which isn't under {:?}",
-                    entry_path,
-                    prefix
-                )
-            }
-            // Prevent unpacking the lockfile from the crate itself.
-            if entry_path
-                .file_name()
-                .map_or(false, |p| p == PACKAGE_SOURCE_LOCK)
-            {
-                continue;
-            }
-            // Unpacking failed
-            bytes_written += entry.size();
-            let mut result = entry.unpack_in(parent).map_err(anyhow::Error::from);
-            if cfg!(windows) && restricted_names::is_windows_reserved_path(&entry_path) {
-                result = result.with_context(|| {
-                    format!(
-                        "`{}` appears to contain a reserved Windows path, \
-                        it cannot be extracted on Windows",
-                        entry_path.display()
-                    )
-                });
-            }
-            result
-                .with_context(|| format!("failed to unpack entry at `{}`", entry_path.display()))?;
+        if is_unpacked(path)? {
+            self.gctx
+                .deferred_global_last_use()?
+                .mark_registry_src_used(registry_src(None));
+            return Ok(unpack_dir.to_path_buf());
+        }
+        // See comment of `unpack_package` about why removing all stuff.
+        // This may also be a symlink to a store entry that was removed.
+        if unpack_dir.symlink_metadata().is_ok() {
+            paths::remove_dir_all(unpack_dir)?;
         }
 
-        // Now that we've finished unpacking, create and write to the lock file to indicate that
-        // unpacking was successful.
-        let mut ok = OpenOptions::new()
-            .create_new(true)
-            .read(true)
-            .write(true)
-            .open(&path)
-            .with_context(|| format!("failed to open `{}`", path.display()))?;
-
-        let lock_meta = LockMetadata { v: 1 };
-        write!(ok, "{}", serde_json::to_string(&lock_meta).unwrap())?;
+        if self.gctx.cli_unstable().shared_registry_src {
+            if let Some(size) = self.link_to_store(&package_dir, unpack_dir, tarball, cksum)? {
+                self.gctx
+                    .deferred_global_last_use()?
+                    .mark_registry_src_linked(registry_src(Some(size)), Some(cksum.into()));
+                return Ok(unpack_dir.to_path_buf());
+            }
+        }
 
+        dst.create_dir()?;
+        let bytes_written = unpack_tarball(self.gctx, tarball, unpack_dir)?;
         self.gctx
             .deferred_global_last_use()?
-            .mark_registry_src_used(global_cache_tracker::RegistrySrc {
-                encoded_registry_name: self.name,
-                package_dir: package_dir.into(),
-                size: Some(bytes_written),
-            });
+            .mark_registry_src_linked(registry_src(Some(bytes_written)), None);
 
         Ok(unpack_dir.to_path_buf())
     }
 
+    /// Makes `unpack_dir` a symlink to the store entry for the `.crate` file
+    /// with checksum `cksum`, unpacking the tarball into the store first if
+    /// no other registry has done so yet.
+    ///
+    /// Returns the size to record for `unpack_dir`. The size of the store
+    /// entry is attributed to the registry that created it, so that the sizes
+    /// of `src` directories still add up to what is on disk, and is zero for
+    /// any later links.
+    ///
+    /// Returns `None` if the symlink can't be created, for example on Windows
+    /// without the required privilege, in which case the caller should unpack
+    /// the package as usual.
+    fn link_to_store(
+        &self,
+        package_dir: &str,
+        unpack_dir: &Path,
+        tarball: &File,
+        cksum: &str,
+    ) -> CargoResult<Option<u64>> {
+        let store_dir = self
+            .gctx
+            .registry_src_store_path()
+            .join(cksum)
+            .join(package_dir);
+        let store_dir = self
+            .gctx
+            .assert_package_cache_locked(CacheLockMode::DownloadExclusive, &store_dir);
+        paths::create_dir_all(unpack_dir.parent().unwrap())?;
+        // Relative, so that the cache keeps working if `CARGO_HOME` is moved.
+        let target = Path::new("../../src-store").join(cksum).join(package_dir);
+        if let Err(e) = symlink_dir(&target, unpack_dir) {
+            debug!("failed to link {unpack_dir:?} to the shared store, unpacking instead: {e}");
+            return Ok(None);
+        }
+
+        if is_unpacked(&store_dir.join(PACKAGE_SOURCE_LOCK))? {
+            return Ok(Some(0));
+        }
+        if store_dir.exists() {
+            paths::remove_dir_all(store_dir)?;
+        }
+        paths::create_dir_all(store_dir)?;
+        let bytes_written = unpack_tarball(self.gctx, tarball, store_dir)?;
+        Ok(Some(bytes_written))
+    }
+
+    /// Checks the signature of `package` against the keys trusted for this
+    /// registry, if any. This must happen before the package is unpacked.
+    fn verify_signature(&mut self, package: PackageId) -> CargoResult<()> {
+        let Some(keys) = signature::TrustedKeys::from_gctx(self.gctx, self.source_id)? else {
+            return Ok(());
+        };
+        let hash = loop {
+            match self.index.hash(package, &mut *self.ops)? {
+                Poll::Pending => self.block_until_ready()?,
+                Poll::Ready(hash) => break hash.to_string(),
+            }
+        };
+        let sig = loop {
+            match self.index.signature(package, &mut *self.ops)? {
+                Poll::Pending => self.block_until_ready()?,
+                Poll::Ready(sig) => break sig,
+            }
+        };
+        keys.verify(package, &hash, sig)
+    }
+
     /// Turns the downloaded `.crate` tarball file into a [`Package`].
     ///
     /// This unconditionally sets checksum for the returned package, so it
@@ -715,8 +727,14 @@ impl<'gctx> RegistrySource<'gctx> {
     /// you need to call either [`RegistryData::download`] or
     /// [`RegistryData::finish_download`] before calling this method.
     fn get_pkg(&mut self, package: PackageId, path: &File) -> CargoResult<Package> {
+        let cksum = self
+            .index
+            .hash(package, &mut *self.ops)
+            .expect("a downloaded dep now pending!?")
+            .expect("summary not found")
+            .to_string();
         let path = self
-            .unpack_package(package, path)
+            .unpack_package(package, path, &cksum)
             .with_context(|| format!("failed to unpack package `{}`", package))?;
         let mut src = PathSource::new(&path, self.source_id, self.gctx);
         src.load()?;
@@ -727,14 +745,7 @@ impl<'gctx> RegistrySource<'gctx> {
 
         // After we've loaded the package configure its summary's `checksum`
         // field with the checksum we know for this `PackageId`.
-        let cksum = self
-            .index
-            .hash(package, &mut *self.ops)
-            .expect("a downloaded dep now pending!?")
-            .expect("summary not found");
-        pkg.manifest_mut()
-            .summary_mut()
-            .set_checksum(cksum.to_string());
+        pkg.manifest_mut().summary_mut().set_checksum(cksum);
 
         Ok(pkg)
     }
@@ -897,7 +908,10 @@ impl<'gctx> Source for RegistrySource<'gctx> {
             }
         };
         match self.ops.download(package, hash)? {
-            MaybeLock::Ready(file) => self.get_pkg(package, &file).map(MaybePackage::Ready),
+            MaybeLock::Ready(file) => {
+                self.verify_signature(package)?;
+                self.get_pkg(package, &file).map(MaybePackage::Ready)
+            }
             MaybeLock::Download {
                 url,
                 descriptor,
@@ -918,6 +932,7 @@ impl<'gctx> Source for RegistrySource<'gctx> {
             }
         };
         let file = self.ops.finish_download(package, hash, &data)?;
+        self.verify_signature(package)?;
         self.get_pkg(package, &file)
     }
 
@@ -962,6 +977,118 @@ impl RegistryConfig {
     const NAME: &'static str = "config.json";
 }
 
+/// Checks the `.cargo-ok` file at `path` to see whether a package was
+/// completely unpacked by a compatible version of Cargo.
+///
+/// See [`RegistrySource::unpack_package`] for the history of this file.
+fn is_unpacked(path: &Path) -> CargoResult<bool> {
+    match fs::read_to_string(path) {
+        Ok(ok) => match serde_json::from_str::<LockMetadata>(&ok) {
+            Ok(lock_meta) if lock_meta.v == 1 => Ok(true),
+            _ => {
+                if ok == "ok" {
+                    tracing::debug!("old `ok` content found, clearing cache");
+                } else {
+                    tracing::warn!("unrecognized .cargo-ok content, clearing cache: {ok}");
+                }
+                Ok(false)
+            }
+        },
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(false),
+        Err(e) => anyhow::bail!("unable to read .cargo-ok file at {path:?}: {e}"),
+    }
+}
+
+/// Unpacks `tarball` into `unpack_dir`, which must already exist, and
+/// writes the `.cargo-ok` file once done.
+///
+/// Returns the number of bytes written.
+fn unpack_tarball(gctx: &GlobalContext, tarball: &File, unpack_dir: &Path) -> CargoResult<u64> {
+    let mut tar = {
+        let size_limit = max_unpack_size(gctx, tarball.metadata()?.len());
+        let gz = GzDecoder::new(tarball);
+        let gz = LimitErrorReader::new(gz, size_limit);
+        let mut tar = Archive::new(gz);
+        set_mask(&mut tar);
+        tar
+    };
+    let mut bytes_written = 0;
+    let prefix = unpack_dir.file_name().unwrap();
+    let parent = unpack_dir.parent().unwrap();
+    for entry in tar.entries()? {
+        let mut entry = entry.context("failed to iterate over archive")?;
+        let entry_path = entry
+            .path()
+            .context("failed to read entry path")?
+            .into_owned();
+
+        // We're going to unpack this tarball into the global source
+        // directory, but we want to make sure that it doesn't accidentally
+        // (or maliciously) overwrite source code from other crates. Cargo
+        // itself should never generate a tarball that hits this error, and
+        // crates.io should also block uploads with these sorts of tarballs,
+        // but be extra sure by adding a check here as well.
+        if !entry_path.starts_with(prefix) {
+            anyhow::bail!(
+                "invalid tarball downloaded, contains \
+                 a file at {:?} which isn't under {:?}",
+                entry_path,
+                prefix
+            )
+        }
+        // Prevent unpacking the lockfile from the crate itself.
+        if entry_path
+            .file_name()
+            .map_or(false, |p| p == PACKAGE_SOURCE_LOCK)
+        {
+            continue;
+        }
+        // Unpacking failed
+        bytes_written += entry.size();
+        let mut result = entry.unpack_in(parent).map_err(anyhow::Error::from);
+        if cfg!(windows) && restricted_names::is_windows_reserved_path(&entry_path) {
+            result = result.with_context(|| {
+                format!(
+                    "`{}` appears to contain a reserved Windows path, \
+                    it cannot be extracted on Windows",
+                    entry_path.display()
+                )
+            });
+        }
+        result.with_context(|| format!("failed to unpack entry at `{}`", entry_path.display()))?;
+    }
+
+    // Now that we've finished unpacking, create and write to the lock file to indicate that
+    // unpacking was successful.
+    let path = unpack_dir.join(PACKAGE_SOURCE_LOCK);
+    let mut ok = OpenOptions::new()
+        .create_new(true)
+        .read(true)
+        .write(true)
+        .open(&path)
+        .with_context(|| format!("failed to open `{}`", path.display()))?;
+
+    let lock_meta = LockMetadata { v: 1 };
+    write!(ok, "{}", serde_json::to_string(&lock_meta).unwrap())?;
+
+    Ok(bytes_written)
+}
+
+#[cfg(unix)]
+fn symlink_dir(original: &Path, link: &Path) -> io::Result<()> {
+    std::os::unix::fs::symlink(original, link)
+}
+
+#[cfg(windows)]
+fn symlink_dir(original: &Path, link: &Path) -> io::Result<()> {
+    std::os::windows::fs::symlink_dir(original, link)
+}
+
+#[cfg(not(any(unix, windows)))]
+fn symlink_dir(_original: &Path, _link: &Path) -> io::Result<()> {
+    Err(io::ErrorKind::Unsupported.into())
+}
+
 /// Get the maximum unpack size that Cargo permits
 /// based on a given `size` of your compressed file.
 ///
@@ -0,0 +1,143 @@
+//! Verification of package signatures for `-Zpackage-signing`.
+//!
+//! The checksum in the index protects against corrupted downloads, but not
+//! against a compromised index, since whoever can change the checksum can
+//! also change the `.crate` file. A signature is made by the publisher with a
+//! key that the registry never sees, so it survives a compromise of the
+//! registry itself.
+//!
+//! Signatures are produced by the credential provider when publishing, via
+//! [`Operation::Sign`]. They are PASETO `v3.public` tokens, the same format as
+//! [asymmetric tokens], whose claims are:
+//!
+//! ```json
+//! {"iat":"...","mutation":"sign","name":"foo","vers":"1.0.0","cksum":"..."}
+//! ```
+//!
+//! and whose footer identifies the key that made it:
+//!
+//! ```json
+//! {"url":"...","kip":"k3.pid...."}
+//! ```
+//!
+//! The registry stores the token in the `sig` field of the [index entry].
+//! When a registry has `trusted-keys` configured, every package downloaded
+//! from it must carry a signature made by one of those keys, over the same
+//! name, version and checksum as the index entry. This is checked before
+//! the package is unpacked.
+//!
+//! [`Operation::Sign`]: cargo_credential::Operation::Sign
+//! [asymmetric tokens]: https://doc.rust-lang.org/nightly/cargo/reference/unstable.html#asymmetric-token
+//! [index entry]: super::index::IndexPackage
+
+use anyhow::{bail, Context as _};
+use pasetors::keys::AsymmetricPublicKey;
+use pasetors::paserk::FormatAsPaserk;
+use pasetors::token::UntrustedToken;
+use pasetors::version3::V3;
+use serde::Deserialize;
+
+use crate::core::{PackageId, SourceId};
+use crate::util::auth::registry_credential_config_raw;
+use crate::util::errors::CargoResult;
+use crate::GlobalContext;
+
+/// The public keys a registry's packages must be signed with.
+pub struct TrustedKeys {
+    /// Each key along with its PASERK id, as found in the token footer.
+    keys: Vec<(String, AsymmetricPublicKey<V3>)>,
+}
+
+/// The claims of a package signature.
+#[derive(Deserialize)]
+struct Message<'a> {
+    mutation: Option<&'a str>,
+    name: Option<&'a str>,
+    vers: Option<&'a str>,
+    cksum: Option<&'a str>,
+}
+
+/// The footer of a package signature.
+#[derive(Deserialize)]
+struct Footer<'a> {
+    kip: &'a str,
+}
+
+impl TrustedKeys {
+    /// Loads the keys from `registries.<name>.trusted-keys`, or
+    /// `registry.trusted-keys` for crates.io.
+    ///
+    /// Returns `None` if signatures shouldn't be checked for this registry.
+    pub fn from_gctx(gctx: &GlobalContext, sid: SourceId) -> CargoResult<Option<TrustedKeys>> {
+        if !gctx.cli_unstable().package_signing || !sid.is_remote_registry() {
+            return Ok(None);
+        }
+        let Some(trusted_keys) =
+            registry_credential_config_raw(gctx, &sid)?.and_then(|cfg| cfg.trusted_keys)
+        else {
+            return Ok(None);
+        };
+        if trusted_keys.is_empty() {
+            return Ok(None);
+        }
+        let keys = trusted_keys
+            .iter()
+            .map(|paserk| {
+                let key = AsymmetricPublicKey::<V3>::try_from(paserk.as_str())
+                    .map_err(|e| anyhow::format_err!("{e}"))
+                    .with_context(|| {
+                        format!(
+                            "failed to parse trusted key `{paserk}` for `{}`",
+                            sid.display_registry_name()
+                        )
+                    })?;
+                let mut kip = String::new();
+                FormatAsPaserk::fmt(&pasetors::paserk::Id::from(&key), &mut kip).unwrap();
+                Ok((kip, key))
+            })
+            .collect::<CargoResult<_>>()?;
+        Ok(Some(TrustedKeys { keys }))
+    }
+
+    /// Verifies that `sig` is a signature of `pkg` with checksum `cksum`,
+    /// made by one of the trusted keys.
+    pub fn verify(&self, pkg: PackageId, cksum: &str, sig: Option<&str>) -> CargoResult<()> {
+        self.verify_inner(pkg, cksum, sig).with_context(|| {
+            format!(
+                "failed to verify the signature of `{pkg}`\n\n\
+                 help: packages from `{}` must be signed by one of its `trusted-keys`",
+                pkg.source_id().display_registry_name()
+            )
+        })
+    }
+
+    fn verify_inner(&self, pkg: PackageId, cksum: &str, sig: Option<&str>) -> CargoResult<()> {
+        let Some(sig) = sig else {
+            bail!("the package is not signed");
+        };
+        let untrusted = UntrustedToken::<pasetors::Public, V3>::try_from(sig)
+            .map_err(|e| anyhow::format_err!("{e}"))
+            .context("the signature is not a PASETO `v3.public` token")?;
+        let footer: Footer<'_> = serde_json::from_slice(untrusted.untrusted_footer())
+            .context("the signature does not identify its key")?;
+        let Some((_, key)) = self.keys.iter().find(|(kip, _)| kip == footer.kip) else {
+            bail!("the package is signed by an untrusted key `{}`", footer.kip);
+        };
+        let trusted = pasetors::version3::PublicToken::verify(key, &untrusted, None, None)
+            .map_err(|e| anyhow::format_err!("{e}"))
+            .context("the signature is invalid")?;
+        let message: Message<'_> =
+            serde_json::from_str(trusted.payload()).context("the signature is malformed")?;
+        let vers = pkg.version().to_string();
+        if message.mutation != Some("sign")
+            || message.name != Some(pkg.name().as_str())
+            || message.vers != Some(vers.as_str())
+        {
+            bail!("the signature was made for a different package");
+        }
+        if message.cksum != Some(cksum) {
+            bail!("the signature was made for a different checksum");
+        }
+        Ok(())
+    }
+}
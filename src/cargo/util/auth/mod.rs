@@ -38,8 +38,14 @@ pub struct RegistryConfig {
     pub credential_provider: Option<PathAndArgs>,
     pub secret_key: OptValue<Secret<String>>,
     pub secret_key_subject: Option<String>,
+    pub sign_packages: Option<bool>,
+    pub trusted_keys: Option<Vec<String>>,
     #[serde(rename = "protocol")]
     _protocol: Option<String>,
+    #[serde(rename = "owns")]
+    _owns: Option<Vec<String>>,
+    #[serde(rename = "owns-policy")]
+    _owns_policy: Option<String>,
 }
 
 /// The `[registry]` table, which more keys than the `[registries.NAME]` tables.
@@ -54,6 +60,8 @@ pub struct RegistryConfigExtended {
     pub credential_provider: Option<PathAndArgs>,
     pub secret_key: OptValue<Secret<String>>,
     pub secret_key_subject: Option<String>,
+    pub sign_packages: Option<bool>,
+    pub trusted_keys: Option<Vec<String>>,
     #[serde(rename = "default")]
     _default: Option<String>,
     #[serde(rename = "global-credential-providers")]
@@ -68,7 +76,11 @@ impl RegistryConfigExtended {
             credential_provider: self.credential_provider,
             secret_key: self.secret_key,
             secret_key_subject: self.secret_key_subject,
+            sign_packages: self.sign_packages,
+            trusted_keys: self.trusted_keys,
             _protocol: None,
+            _owns: None,
+            _owns_policy: None,
         }
     }
 }
@@ -665,6 +677,38 @@ fn auth_token_optional(
     Ok(Some(token))
 }
 
+/// Returns a signature over a crate file for the given registry.
+///
+/// Unlike [`auth_token`], the result is never cached, and it is rejected
+/// unless it is an asymmetric token, so a secret token can never end up
+/// published in the index as a signature.
+pub fn sign(gctx: &GlobalContext, sid: &SourceId, operation: Operation<'_>) -> CargoResult<String> {
+    let no_signature = || {
+        anyhow::format_err!(
+            "the credential providers for `{}` did not produce a signature\n\n\
+             help: signing packages requires an asymmetric key, such as a `secret-key` \
+             used by the `cargo:paseto` credential provider",
+            sid.display_registry_name()
+        )
+    };
+    let credential_response =
+        credential_action(gctx, sid, Action::Get(operation), vec![], &[], false);
+    if let Some(e) = credential_response.as_ref().err() {
+        if let Some(cargo_credential::Error::NotFound) = e.downcast_ref() {
+            return Err(no_signature());
+        }
+    }
+    let credential_response = credential_response?;
+    let CredentialResponse::Get { token, .. } = credential_response else {
+        bail!("credential provider produced unexpected response for `get` request: {credential_response:?}")
+    };
+    let token = token.expose();
+    if !token.starts_with("v3.public.") {
+        return Err(no_signature());
+    }
+    Ok(token)
+}
+
 /// Log out from the given registry.
 pub fn logout(gctx: &GlobalContext, sid: &SourceId) -> CargoResult<()> {
     let credential_response = credential_action(gctx, sid, Action::Logout, vec![], &[], false);
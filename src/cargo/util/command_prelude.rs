@@ -94,6 +94,18 @@ pub trait CommandExt: Sized {
         )
     }
 
+    fn arg_changed_since(self) -> Self {
+        self._arg(
+            opt(
+                "changed-since",
+                "Only packages affected by the changes since a git ref (unstable)",
+            )
+            .value_name("REF")
+            .conflicts_with_all(["package", "workspace", "exclude", "all"])
+            .help_heading(heading::PACKAGE_SELECTION),
+        )
+    }
+
     fn arg_package(self, package: &'static str) -> Self {
         self._arg(
             optional_opt("package", package)
@@ -355,6 +367,16 @@ pub trait CommandExt: Sized {
         )
     }
 
+    fn arg_test_report(self) -> Self {
+        self._arg(
+            opt(
+                "report",
+                "Write a report of the results, e.g. `junit=report.xml` (unstable)",
+            )
+            .value_name("FORMAT=PATH"),
+        )
+    }
+
     fn arg_new_opts(self) -> Self {
         self._arg(
             opt(
@@ -628,6 +650,16 @@ Run `{cmd}` to see possible targets."
         Ok(self._values_of("target"))
     }
 
+    fn test_report(&self, gctx: &GlobalContext) -> CargoResult<Option<ops::TestReport>> {
+        let Some(report) = self._value_of("report") else {
+            return Ok(None);
+        };
+        gctx.cli_unstable().fail_if_stable_opt("--report", None)?;
+        Ok(Some(match report.parse()? {
+            ops::TestReport::Junit(path) => ops::TestReport::Junit(gctx.cwd().join(path)),
+        }))
+    }
+
     fn get_profile_name(
         &self,
         default: &str,
@@ -688,7 +720,15 @@ Run `{cmd}` to see possible targets."
         workspace: Option<&Workspace<'_>>,
         profile_checking: ProfileChecking,
     ) -> CargoResult<CompileOptions> {
-        let spec = self.packages_from_flags()?;
+        let spec = match (self._value_of("changed-since"), workspace) {
+            (Some(rev), Some(ws)) => {
+                gctx.cli_unstable()
+                    .fail_if_stable_opt("--changed-since", None)?;
+                Packages::changed_since(ws, rev)?
+            }
+            (Some(_), None) => bail!("`--changed-since` requires a workspace"),
+            (None, _) => self.packages_from_flags()?,
+        };
         let mut message_format = None;
         let default_json = MessageFormat::Json {
             short: false,
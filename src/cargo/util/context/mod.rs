@@ -106,7 +106,7 @@ mod path;
 pub use path::{ConfigRelativePath, PathAndArgs};
 
 mod target;
-pub use target::{TargetCfgConfig, TargetConfig};
+pub use target::{RunnerKind, TargetCfgConfig, TargetConfig, TargetRunner};
 
 mod environment;
 use environment::Env;
@@ -400,6 +400,12 @@ impl GlobalContext {
         self.registry_base_path().join("src")
     }
 
+    /// Gets the shared store of extracted registry sources
+    /// (`<cargo_home>/registry/src-store`), used by `-Zshared-registry-src`.
+    pub fn registry_src_store_path(&self) -> Filesystem {
+        self.registry_base_path().join("src-store")
+    }
+
     /// Gets the default Cargo registry.
     pub fn default_registry(&self) -> CargoResult<Option<String>> {
         Ok(self
@@ -1919,6 +1925,11 @@ impl GlobalContext {
         target::load_target_triple(self, target)
     }
 
+    /// Returns the `runner` at the given key, like `target.<triple>.runner`.
+    pub fn target_runner(&self, key: &str) -> CargoResult<OptValue<TargetRunner>> {
+        target::load_runner(self, &ConfigKey::from_str(key))
+    }
+
     /// Returns the cached [`SourceId`] corresponding to the main repository.
     ///
     /// This is the main cargo registry by default, but it can be overridden in
@@ -2643,6 +2654,24 @@ pub struct CargoBuildConfig {
     pub out_dir: Option<ConfigRelativePath>,
     pub artifact_dir: Option<ConfigRelativePath>,
     pub warnings: Option<WarningHandling>,
+    /// Unstable feature `-Zbuild-sandbox`.
+    pub sandbox: Option<BuildSandboxConfig>,
+    /// Unstable feature `-Zbuild-memory-limit`.
+    pub memory_limit: Option<String>,
+}
+
+/// Configuration for `build.sandbox`.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct BuildSandboxConfig {
+    /// Whether build scripts are run in a sandbox.
+    pub enable: Option<bool>,
+    /// Packages whose build scripts may access the network.
+    pub allow_net: Option<Vec<String>>,
+    /// Extra paths build scripts may read.
+    pub allow_read: Option<Vec<ConfigRelativePath>>,
+    /// Packages whose build scripts are not sandboxed at all.
+    pub exclude: Option<Vec<String>>,
 }
 
 /// Whether warnings should warn, be allowed, or cause an error.
@@ -1,4 +1,7 @@
-use super::{ConfigKey, ConfigRelativePath, GlobalContext, OptValue, PathAndArgs, StringList, CV};
+use super::de::Deserializer;
+use super::{
+    ConfigKey, ConfigRelativePath, GlobalContext, OptValue, PathAndArgs, StringList, Value, CV,
+};
 use crate::core::compiler::{BuildOutput, LinkArgTarget};
 use crate::util::CargoResult;
 use serde::Deserialize;
@@ -11,7 +14,9 @@ use std::rc::Rc;
 /// This is a subset of `TargetConfig`.
 #[derive(Debug, Deserialize)]
 pub struct TargetCfgConfig {
-    pub runner: OptValue<PathAndArgs>,
+    /// Loaded on its own by [`load_runner`].
+    #[serde(skip)]
+    pub runner: OptValue<TargetRunner>,
     pub rustflags: OptValue<StringList>,
     pub linker: OptValue<ConfigRelativePath>,
     // This is here just to ignore fields from normal `TargetConfig` because
@@ -25,7 +30,7 @@ pub struct TargetCfgConfig {
 #[derive(Debug, Clone, Default)]
 pub struct TargetConfig {
     /// Process to run as a wrapper for `cargo run`, `test`, and `bench` commands.
-    pub runner: OptValue<PathAndArgs>,
+    pub runner: OptValue<TargetRunner>,
     /// Additional rustc flags to pass.
     pub rustflags: OptValue<StringList>,
     /// Additional rustdoc flags to pass.
@@ -40,6 +45,133 @@ pub struct TargetConfig {
     pub links_overrides: Rc<BTreeMap<String, BuildOutput>>,
 }
 
+/// The kinds of executables that can have their own `runner`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RunnerKind {
+    /// Tests run by `cargo test`.
+    Test,
+    /// Benchmarks run by `cargo bench`.
+    Bench,
+    /// The binary run by `cargo run`.
+    Run,
+    /// Doctests, which rustdoc runs with `--runtool`.
+    Doctest,
+}
+
+impl RunnerKind {
+    pub const ALL: [RunnerKind; 4] = [
+        RunnerKind::Test,
+        RunnerKind::Bench,
+        RunnerKind::Run,
+        RunnerKind::Doctest,
+    ];
+
+    /// The key of the runner in a `runner` table.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RunnerKind::Test => "test",
+            RunnerKind::Bench => "bench",
+            RunnerKind::Run => "run",
+            RunnerKind::Doctest => "doctest",
+        }
+    }
+}
+
+/// Config definition of the `runner` of a `[target]` table.
+///
+/// This is either a program that runs every kind of executable, or a table
+/// with a program for each kind, like:
+///
+/// ```toml
+/// [target.'cfg(target_arch = "aarch64")'.runner]
+/// test = "qemu-aarch64"
+/// bench = ["valgrind", "--tool=callgrind"]
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub enum TargetRunner {
+    All(PathAndArgs),
+    /// Runners keyed by [`RunnerKind::as_str`].
+    ByKind(BTreeMap<String, PathAndArgs>),
+}
+
+impl TargetRunner {
+    /// Returns the runner for the given kind of executable, if any.
+    pub fn get(&self, kind: RunnerKind) -> Option<&PathAndArgs> {
+        match self {
+            TargetRunner::All(runner) => Some(runner),
+            TargetRunner::ByKind(runners) => runners.get(kind.as_str()),
+        }
+    }
+}
+
+/// Loads the `runner` at the given key.
+///
+/// A table is loaded one kind at a time, so that each runner can also come
+/// from an environment variable like `CARGO_TARGET_<triple>_RUNNER_TEST`.
+pub(super) fn load_runner(
+    gctx: &GlobalContext,
+    key: &ConfigKey,
+) -> CargoResult<OptValue<TargetRunner>> {
+    let table = match gctx.get_cv(key)? {
+        Some(CV::Table(table, definition)) => Some(Value {
+            val: table,
+            definition,
+        }),
+        _ => None,
+    };
+    let env_prefix = format!("{}_", key.as_env_key());
+    let is_table = !gctx.env.contains_key(key.as_env_key())
+        && (table.is_some() || gctx.env_keys().any(|k| k.starts_with(&env_prefix)));
+    if !is_table {
+        let runner: OptValue<PathAndArgs> = get(gctx, key)?;
+        return Ok(runner.map(|runner| Value {
+            val: TargetRunner::All(runner.val),
+            definition: runner.definition,
+        }));
+    }
+
+    if let Some(table) = &table {
+        if let Some(name) = table
+            .val
+            .keys()
+            .find(|name| RunnerKind::ALL.iter().all(|kind| kind.as_str() != *name))
+        {
+            anyhow::bail!(
+                "unknown kind of runner `{name}` in `{key}`, \
+                 expected one of `test`, `bench`, `run`, `doctest`\n\
+                 defined in {}",
+                table.definition
+            );
+        }
+    }
+    let mut definition = table.map(|table| table.definition);
+    let mut runners = BTreeMap::new();
+    for kind in RunnerKind::ALL {
+        let mut kind_key = key.clone();
+        kind_key.push(kind.as_str());
+        let Some(runner) = get::<OptValue<PathAndArgs>>(gctx, &kind_key)? else {
+            continue;
+        };
+        definition.get_or_insert(runner.definition);
+        runners.insert(kind.as_str().to_string(), runner.val);
+    }
+    Ok(definition.map(|definition| Value {
+        val: TargetRunner::ByKind(runners),
+        definition,
+    }))
+}
+
+/// Like [`GlobalContext::get`], for a key whose parts may contain dots, like
+/// the ones of `target.'cfg(…)'` tables.
+fn get<'de, T: Deserialize<'de>>(gctx: &GlobalContext, key: &ConfigKey) -> CargoResult<T> {
+    let d = Deserializer {
+        gctx,
+        key: key.clone(),
+        env_prefix_ok: true,
+    };
+    T::deserialize(d).map_err(|e| e.into())
+}
+
 /// Loads all of the `target.'cfg()'` tables.
 pub(super) fn load_target_cfgs(
     gctx: &GlobalContext,
@@ -52,8 +184,9 @@ pub(super) fn load_target_cfgs(
     // ordering via the order keys were defined in files perhaps.
     let target: BTreeMap<String, TargetCfgConfig> = gctx.get("target")?;
     tracing::debug!("Got all targets {:#?}", target);
-    for (key, cfg) in target {
+    for (key, mut cfg) in target {
         if key.starts_with("cfg(") {
+            cfg.other.remove("runner");
             // Unfortunately this is not able to display the location of the
             // unused key. Using config::Value<toml::Value> doesn't work. One
             // solution might be to create a special "Any" type, but I think
@@ -64,6 +197,10 @@ pub(super) fn load_target_cfgs(
                     other_key, key
                 ))?;
             }
+            let mut runner_key = ConfigKey::from_str("target");
+            runner_key.push(&key);
+            runner_key.push("runner");
+            cfg.runner = load_runner(gctx, &runner_key)?;
             result.push((key, cfg));
         }
     }
@@ -114,7 +251,7 @@ fn load_config_table(gctx: &GlobalContext, prefix: &str) -> CargoResult<TargetCo
     // because it causes serde to use `deserialize_map` which means the config
     // deserializer does not know which keys to deserialize, which means
     // environment variables would not work.
-    let runner: OptValue<PathAndArgs> = gctx.get(&format!("{prefix}.runner"))?;
+    let runner = load_runner(gctx, &ConfigKey::from_str(&format!("{prefix}.runner")))?;
     let rustflags: OptValue<StringList> = gctx.get(&format!("{prefix}.rustflags"))?;
     let rustdocflags: OptValue<StringList> = gctx.get(&format!("{prefix}.rustdocflags"))?;
     let linker: OptValue<ConfigRelativePath> = gctx.get(&format!("{prefix}.linker"))?;
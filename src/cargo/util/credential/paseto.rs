@@ -113,23 +113,28 @@ impl<'a> Credential for PasetoCredential<'a> {
                         Operation::Yank { .. } => Some("yank"),
                         Operation::Unyank { .. } => Some("unyank"),
                         Operation::Owners { .. } => Some("owners"),
+                        Operation::Sign { .. } => Some("sign"),
                         _ => None,
                     },
                     name: match operation {
                         Operation::Publish { name, .. }
                         | Operation::Yank { name, .. }
                         | Operation::Unyank { name, .. }
-                        | Operation::Owners { name, .. } => Some(name),
+                        | Operation::Owners { name, .. }
+                        | Operation::Sign { name, .. } => Some(name),
                         _ => None,
                     },
                     vers: match operation {
                         Operation::Publish { vers, .. }
                         | Operation::Yank { vers, .. }
-                        | Operation::Unyank { vers, .. } => Some(vers),
+                        | Operation::Unyank { vers, .. }
+                        | Operation::Sign { vers, .. } => Some(vers),
                         _ => None,
                     },
                     cksum: match operation {
-                        Operation::Publish { cksum, .. } => Some(cksum),
+                        Operation::Publish { cksum, .. } | Operation::Sign { cksum, .. } => {
+                            Some(cksum)
+                        }
                         _ => None,
                     },
                     challenge: None, // todo: PASETO with challenges
@@ -1,7 +1,9 @@
 //! Credential provider that uses plaintext tokens in Cargo's config.
 
 use anyhow::Context as _;
-use cargo_credential::{Action, CacheControl, Credential, CredentialResponse, Error, RegistryInfo};
+use cargo_credential::{
+    Action, CacheControl, Credential, CredentialResponse, Error, Operation, RegistryInfo,
+};
 use url::Url;
 
 use crate::{
@@ -37,6 +39,9 @@ impl<'a> Credential for TokenCredential<'a> {
         let previous_token = registry_credential_config_raw(self.gctx, &sid)?.and_then(|c| c.token);
 
         match action {
+            // A plain token is a secret, so it must never be handed out as a
+            // signature that would be published in the index.
+            Action::Get(Operation::Sign { .. }) => Err(Error::NotFound),
             Action::Get(_) => {
                 let token = previous_token.ok_or_else(|| Error::NotFound)?.val;
                 Ok(CredentialResponse::Get {
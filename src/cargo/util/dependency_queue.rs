@@ -145,6 +145,52 @@ impl<N: Hash + Eq + Clone, E: Eq + Hash + Clone, V> DependencyQueue<N, E, V> {
         }
     }
 
+    /// Like [`DependencyQueue::queue_finished`], but the priority of each node
+    /// is the total cost of the most expensive chain of nodes depending on it,
+    /// including itself. Nodes on the critical path are thus dequeued first.
+    ///
+    /// This is a better fit than summing the cost of all dependents when the
+    /// costs are real estimates of how long each node takes.
+    pub fn queue_finished_by_critical_path(&mut self) {
+        let mut out = HashMap::new();
+        for key in self.dep_map.keys() {
+            critical_path(key, &self.reverse_dep_map, &self.cost, &mut out);
+        }
+        self.priority = out
+            .into_iter()
+            .map(|(n, path)| (n, path.expect("cycle in DependencyQueue")))
+            .collect();
+
+        /// Finds the total cost of the most expensive chain of nodes starting
+        /// from `key` and following reverse dependencies.
+        ///
+        /// `results` holds `None` while a node is being visited, to detect
+        /// cycles.
+        fn critical_path<N: Hash + Eq + Clone, E: Hash + Eq + Clone>(
+            key: &N,
+            map: &HashMap<N, HashMap<E, HashSet<N>>>,
+            cost: &HashMap<N, usize>,
+            results: &mut HashMap<N, Option<usize>>,
+        ) -> usize {
+            if let Some(path) = results.get(key) {
+                return path.expect("cycle in DependencyQueue");
+            }
+            results.insert(key.clone(), None);
+
+            let longest = map
+                .get(key)
+                .into_iter()
+                .flat_map(|it| it.values())
+                .flatten()
+                .map(|dep| critical_path(dep, map, cost, results))
+                .max()
+                .unwrap_or(0);
+            let path = cost[key] + longest;
+            results.insert(key.clone(), Some(path));
+            path
+        }
+    }
+
     /// Dequeues a package that is ready to be built.
     ///
     /// A package is ready to be built when it has 0 un-built dependencies. If
@@ -251,4 +297,29 @@ mod test {
         q.finish(&4, &());
         assert_eq!(q.dequeue(), None);
     }
+
+    #[test]
+    fn critical_path() {
+        let mut q = DependencyQueue::new();
+
+        // 1 has the most expensive dependents in total, but 3 starts the
+        // most expensive chain.
+        q.queue(1, (), vec![], 1);
+        q.queue(2, (), vec![(1, ())], 1);
+        q.queue(3, (), vec![], 2);
+        q.queue(4, (), vec![(1, ()), (3, ())], 5);
+        q.queue(5, (), vec![(1, ())], 3);
+        q.queue_finished_by_critical_path();
+
+        assert_eq!(q.dequeue(), Some((3, (), 7)));
+        assert_eq!(q.dequeue(), Some((1, (), 6)));
+        assert_eq!(q.dequeue(), None);
+        q.finish(&1, &());
+        assert_eq!(q.dequeue(), Some((5, (), 3)));
+        assert_eq!(q.dequeue(), Some((2, (), 1)));
+        assert_eq!(q.dequeue(), None);
+        q.finish(&3, &());
+        assert_eq!(q.dequeue(), Some((4, (), 5)));
+        assert_eq!(q.dequeue(), None);
+    }
 }
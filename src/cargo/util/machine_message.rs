@@ -79,6 +79,11 @@ pub struct BuildScript<'a> {
     pub cfgs: &'a [String],
     pub env: &'a [(String, String)],
     pub out_dir: &'a Path,
+    /// Only set with `-Zbuild-script-instructions`.
+    #[serde(skip_serializing_if = "<[PathBuf]>::is_empty")]
+    pub input_files: &'a [PathBuf],
+    #[serde(skip_serializing_if = "<[PathBuf]>::is_empty")]
+    pub output_files: &'a [PathBuf],
 }
 
 impl<'a> Message for BuildScript<'a> {
@@ -95,6 +100,8 @@ pub struct TimingInfo<'a> {
     pub duration: f64,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub rmeta_time: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub peak_rss: Option<u64>,
 }
 
 impl<'a> Message for TimingInfo<'a> {
@@ -113,3 +120,64 @@ impl Message for BuildFinished {
         "build-finished"
     }
 }
+
+/// The version of the [`Test`] and [`TestSummary`] messages, increased
+/// whenever they change in a way that isn't backwards compatible.
+pub const TEST_MESSAGE_VERSION: u32 = 2;
+
+#[derive(Serialize)]
+pub struct Test<'a> {
+    pub version: u32,
+    pub package_id: PackageIdSpec,
+    pub manifest_path: &'a Path,
+    pub target: &'a Target,
+    pub mode: CompileMode,
+    pub executable: Option<&'a Path>,
+    pub event: TestEvent,
+    pub name: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub duration: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub output: Option<&'a str>,
+}
+
+impl<'a> Message for Test<'a> {
+    fn reason(&self) -> &str {
+        "test"
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum TestEvent {
+    Started,
+    Passed,
+    Failed,
+    Ignored,
+    /// Passed when it was retried after failing.
+    Flaky,
+    /// Failed, but it's in the quarantine list.
+    Quarantined,
+}
+
+#[derive(Serialize)]
+pub struct TestSummary {
+    pub version: u32,
+    pub success: bool,
+    pub passed: usize,
+    pub failed: usize,
+    pub ignored: usize,
+    pub flaky: usize,
+    pub quarantined: usize,
+    pub measured: usize,
+    pub filtered_out: usize,
+    pub duration: f64,
+}
+
+impl Message for TestSummary {
+    fn reason(&self) -> &str {
+        "test-summary"
+    }
+}
@@ -471,6 +471,7 @@ fn normalize_toml(
             normalized_target.insert(
                 name.clone(),
                 manifest::TomlPlatform {
+                    build_std: platform.build_std.clone(),
                     dependencies: normalized_dependencies,
                     build_dependencies: normalized_build_dependencies,
                     build_dependencies2: None,
@@ -1331,6 +1332,9 @@ pub fn to_real_manifest(
             Some(DepKind::Development),
             warnings,
         )?;
+        if platform.build_std.is_some() {
+            features.require(Feature::build_std())?;
+        }
     }
 
     // Collect the dependencies.
@@ -2064,20 +2068,16 @@ fn detailed_dep_to_dependency<P: ResolveToPath + Clone>(
         orig.lib.unwrap_or(false),
         orig.target.as_deref(),
     ) {
-        if manifest_ctx.gctx.cli_unstable().bindeps {
-            let artifact = Artifact::parse(&artifact.0, is_lib, target)?;
-            if dep.kind() != DepKind::Build
-                && artifact.target() == Some(ArtifactTarget::BuildDependencyAssumeTarget)
-            {
-                bail!(
-                    r#"`target = "target"` in normal- or dev-dependencies has no effect ({})"#,
-                    name_in_toml
-                );
-            }
-            dep.set_artifact(artifact)
-        } else {
-            bail!("`artifact = …` requires `-Z bindeps` ({})", name_in_toml);
+        let artifact = Artifact::parse(&artifact.0, is_lib, target)?;
+        if dep.kind() != DepKind::Build
+            && artifact.target() == Some(ArtifactTarget::BuildDependencyAssumeTarget)
+        {
+            bail!(
+                r#"`target = "target"` in normal- or dev-dependencies has no effect ({})"#,
+                name_in_toml
+            );
         }
+        dep.set_artifact(artifact)
     } else if orig.lib.is_some() || orig.target.is_some() {
         for (is_set, specifier) in [
             (orig.lib.is_some(), "lib"),
@@ -2287,6 +2287,9 @@ pub fn validate_profile(
     }
     if let Some(ref packages) = root.package {
         for (override_name, profile) in packages {
+            if let manifest::ProfilePackageSpec::Glob(glob) = override_name {
+                validate_profile_package_glob(glob, name)?;
+            }
             validate_profile_override(profile, "package")?;
             validate_profile_layer(
                 profile,
@@ -2296,6 +2299,10 @@ pub fn validate_profile(
             )?;
         }
     }
+    for (kind, profile) in root.target_kind_overrides() {
+        validate_profile_override(profile, kind)?;
+        validate_profile_layer(profile, &format!("{name}.{kind}"), cli_unstable, features)?;
+    }
 
     if let Some(dir_name) = &root.dir_name {
         // This is disabled for now, as we would like to stabilize named
@@ -2397,6 +2404,41 @@ fn validate_profile_layer(
             _ => {}
         }
     }
+    if profile.target_kind_overrides().next().is_some() {
+        match (
+            features.require(Feature::profile_target_overrides()),
+            cli_unstable.profile_target_overrides,
+        ) {
+            (Err(e), false) => return Err(e),
+            _ => {}
+        }
+    }
+    let has_glob = profile.package.as_ref().map_or(false, |packages| {
+        packages
+            .keys()
+            .any(|spec| matches!(spec, manifest::ProfilePackageSpec::Glob(_)))
+    });
+    if has_glob {
+        match (
+            features.require(Feature::profile_package_globs()),
+            cli_unstable.profile_package_globs,
+        ) {
+            (Err(e), false) => return Err(e),
+            _ => {}
+        }
+    }
+    if profile.coverage.is_some() && !cli_unstable.coverage {
+        bail!("`profile.{name}.coverage` is unstable, pass `-Zcoverage` to enable it");
+    }
+    if profile.build_std.is_some() || profile.build_std_features.is_some() {
+        match (
+            features.require(Feature::build_std()),
+            cli_unstable.build_std.is_some(),
+        ) {
+            (Err(e), false) => return Err(e),
+            _ => {}
+        }
+    }
     Ok(())
 }
 
@@ -2408,6 +2450,9 @@ fn validate_profile_override(profile: &manifest::TomlProfile, which: &str) -> Ca
     if profile.build_override.is_some() {
         bail!("build-override profiles cannot be nested");
     }
+    if profile.target_kind_overrides().next().is_some() {
+        bail!("target-kind profiles cannot be nested");
+    }
     if profile.panic.is_some() {
         bail!("`panic` may not be specified in a `{}` profile", which)
     }
@@ -2417,6 +2462,36 @@ fn validate_profile_override(profile: &manifest::TomlProfile, which: &str) -> Ca
     if profile.rpath.is_some() {
         bail!("`rpath` may not be specified in a `{}` profile", which)
     }
+    if profile.build_std.is_some() {
+        bail!("`build-std` may not be specified in a `{}` profile", which)
+    }
+    if profile.coverage.is_some() {
+        bail!("`coverage` may not be specified in a `{}` profile", which)
+    }
+    if profile.build_std_features.is_some() {
+        bail!(
+            "`build-std-features` may not be specified in a `{}` profile",
+            which
+        )
+    }
+    Ok(())
+}
+
+/// Validates a glob of package names in `[profile.<name>.package]`.
+fn validate_profile_package_glob(glob: &str, name: &str) -> CargoResult<()> {
+    let invalid = |reason: &str| {
+        anyhow::format_err!(
+            "invalid package glob `{glob}` in profile `{name}`: {reason}\n\
+             help: a package glob like `aws-*` matches the names of packages"
+        )
+    };
+    if let Some(c) = glob
+        .chars()
+        .find(|c| !(c.is_alphanumeric() || matches!(c, '-' | '_' | '*' | '?' | '[' | ']' | '!')))
+    {
+        return Err(invalid(&format!("`{c}` is not allowed")));
+    }
+    glob::Pattern::new(glob).map_err(|e| invalid(&e.to_string()))?;
     Ok(())
 }
 
@@ -2842,6 +2917,7 @@ fn prepare_toml_for_publish(
                     Ok((
                         k.clone(),
                         manifest::TomlPlatform {
+                            build_std: v.build_std.clone(),
                             dependencies: map_deps(gctx, v.dependencies.as_ref(), all)?,
                             dev_dependencies: map_deps(
                                 gctx,
@@ -1267,6 +1267,7 @@ fn alt_reg_metadata() {
           {
             "dep_kinds": [
               {
+                "extern_name": "bar",
                 "kind": null,
                 "target": null
               }
@@ -1299,6 +1300,7 @@ fn alt_reg_metadata() {
           {
             "dep_kinds": [
               {
+                "extern_name": "altdep",
                 "kind": null,
                 "target": null
               }
@@ -1309,6 +1311,7 @@ fn alt_reg_metadata() {
           {
             "dep_kinds": [
               {
+                "extern_name": "iodep",
                 "kind": null,
                 "target": null
               }
@@ -1328,6 +1331,7 @@ fn alt_reg_metadata() {
           {
             "dep_kinds": [
               {
+                "extern_name": "altdep2",
                 "kind": null,
                 "target": null
               }
@@ -1565,6 +1569,7 @@ fn unknown_registry() {
           {
             "dep_kinds": [
               {
+                "extern_name": "baz",
                 "kind": null,
                 "target": null
               }
@@ -1590,6 +1595,7 @@ fn unknown_registry() {
           {
             "dep_kinds": [
               {
+                "extern_name": "bar",
                 "kind": null,
                 "target": null
               }
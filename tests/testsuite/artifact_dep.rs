@@ -32,8 +32,7 @@ fn check_with_invalid_artifact_dependency() {
         .file("bar/Cargo.toml", &basic_manifest("bar", "0.0.1"))
         .file("bar/src/lib.rs", "")
         .build();
-    p.cargo("check -Z bindeps")
-        .masquerade_as_nightly_cargo(&["bindeps"])
+    p.cargo("check")
         .with_stderr_data(str![[r#"
 [ERROR] failed to parse manifest at `[ROOT]/foo/Cargo.toml`
 
@@ -44,18 +43,6 @@ Caused by:
         .with_status(101)
         .run();
 
-    fn run_cargo_with_and_without_bindeps_feature(
-        p: &Project,
-        cmd: &str,
-        assert: &dyn Fn(&mut cargo_test_support::Execs),
-    ) {
-        assert(
-            p.cargo(&format!("{} -Z bindeps", cmd))
-                .masquerade_as_nightly_cargo(&["bindeps"]),
-        );
-        assert(&mut p.cargo(cmd));
-    }
-
     // lib specified without artifact
     let p = project()
         .file(
@@ -75,18 +62,16 @@ Caused by:
         .file("bar/Cargo.toml", &basic_manifest("bar", "0.0.1"))
         .file("bar/src/lib.rs", "")
         .build();
-    run_cargo_with_and_without_bindeps_feature(&p, "check", &|cargo| {
-        cargo
-            .with_stderr_data(str![[r#"
+    p.cargo("check")
+        .with_stderr_data(str![[r#"
 [ERROR] failed to parse manifest at `[ROOT]/foo/Cargo.toml`
 
 Caused by:
   'lib' specifier cannot be used without an 'artifact = …' value (bar)
 
 "#]])
-            .with_status(101)
-            .run();
-    });
+        .with_status(101)
+        .run();
 
     // target specified without artifact
     let p = project()
@@ -107,18 +92,16 @@ Caused by:
         .file("bar/Cargo.toml", &basic_manifest("bar", "0.0.1"))
         .file("bar/src/lib.rs", "")
         .build();
-    run_cargo_with_and_without_bindeps_feature(&p, "check", &|cargo| {
-        cargo
-            .with_stderr_data(str![[r#"
+    p.cargo("check")
+        .with_stderr_data(str![[r#"
 [ERROR] failed to parse manifest at `[ROOT]/foo/Cargo.toml`
 
 Caused by:
   'target' specifier cannot be used without an 'artifact = …' value (bar)
 
 "#]])
-            .with_status(101)
-            .run();
-    })
+        .with_status(101)
+        .run();
 }
 
 #[cargo_test]
@@ -143,8 +126,7 @@ fn check_with_invalid_target_triple() {
         .file("bar/Cargo.toml", &basic_manifest("bar", "0.0.1"))
         .file("bar/src/main.rs", "fn main() {}")
         .build();
-    p.cargo("check -Z bindeps")
-        .masquerade_as_nightly_cargo(&["bindeps"])
+    p.cargo("check")
         .with_stderr_data(str![[r#"
 [ERROR] failed to run `rustc` to learn about target-specific information
 
@@ -160,7 +142,7 @@ Caused by:
 }
 
 #[cargo_test]
-fn build_without_nightly_aborts_with_error() {
+fn bindeps_flag_is_stabilized() {
     let p = project()
         .file(
             "Cargo.toml",
@@ -176,17 +158,20 @@ fn build_without_nightly_aborts_with_error() {
                 bar = { path = "bar/", artifact = "bin" }
             "#,
         )
-        .file("src/lib.rs", "extern crate bar;")
+        .file("src/lib.rs", "")
         .file("bar/Cargo.toml", &basic_manifest("bar", "0.0.1"))
-        .file("bar/src/lib.rs", "")
+        .file("bar/src/main.rs", "fn main() {}")
         .build();
-    p.cargo("check")
-        .with_status(101)
+    p.cargo("check -Zbindeps")
+        .masquerade_as_nightly_cargo(&["bindeps"])
         .with_stderr_data(str![[r#"
-[ERROR] failed to parse manifest at `[ROOT]/foo/Cargo.toml`
+[WARNING] flag `-Z bindeps` has been stabilized in the 1.85 release, and is no longer necessary
+  Artifact dependencies (`artifact = …` in dependencies) are now always available.
 
-Caused by:
-  `artifact = …` requires `-Z bindeps` (bar)
+[LOCKING] 1 package to latest compatible version
+[COMPILING] bar v0.0.1 ([ROOT]/foo/bar)
+[CHECKING] foo v0.0.0 ([ROOT]/foo)
+[FINISHED] `dev` profile [unoptimized + debuginfo] target(s) in [ELAPSED]s
 
 "#]])
         .run();
@@ -214,8 +199,7 @@ fn disallow_artifact_and_no_artifact_dep_to_same_package_within_the_same_dep_cat
         .file("bar/Cargo.toml", &basic_bin_manifest("bar"))
         .file("bar/src/main.rs", "fn main() {}")
         .build();
-    p.cargo("check -Z bindeps")
-        .masquerade_as_nightly_cargo(&["bindeps"])
+    p.cargo("check")
         .with_status(101)
         .with_stderr_data(str![[r#"
 [LOCKING] 1 package to latest compatible version
@@ -325,8 +309,7 @@ fn features_are_unified_among_lib_and_bin_dep_of_same_target() {
         )
         .build();
 
-    p.cargo("build -Z bindeps")
-        .masquerade_as_nightly_cargo(&["bindeps"])
+    p.cargo("build")
         .with_stderr_data(str![[r#"
 [LOCKING] 2 packages to latest compatible versions
 [COMPILING] d2 v0.0.1 ([ROOT]/foo/d2)
@@ -435,8 +418,7 @@ fn features_are_not_unified_among_lib_and_bin_dep_of_different_target() {
         )
         .build();
 
-    p.cargo("build -Z bindeps")
-        .masquerade_as_nightly_cargo(&["bindeps"])
+    p.cargo("build")
         .with_status(101)
         .with_stderr_data(str![[r#"
 [LOCKING] 2 packages to latest compatible versions
@@ -529,9 +511,7 @@ fn feature_resolution_works_for_cfg_target_specification() {
         .file("d2/src/lib.rs", "pub fn f() {}")
         .build();
 
-    p.cargo("test -Z bindeps")
-        .masquerade_as_nightly_cargo(&["bindeps"])
-        .run();
+    p.cargo("test").run();
 }
 
 #[cargo_test]
@@ -597,8 +577,7 @@ fn build_script_with_bin_artifacts() {
         .file("bar/src/bin/baz.rs", "fn main() {}")
         .file("bar/src/lib.rs", "")
         .build();
-    p.cargo("build -Z bindeps")
-        .masquerade_as_nightly_cargo(&["bindeps"])
+    p.cargo("build")
         .with_stderr_data(
             str![[r#"
 [LOCKING] 1 package to latest compatible version
@@ -687,8 +666,7 @@ fn build_script_with_bin_artifact_and_lib_false() {
         )
         .build();
 
-    p.cargo("build -Z bindeps")
-        .masquerade_as_nightly_cargo(&["bindeps"])
+    p.cargo("build")
         .with_status(101)
         .with_stderr_does_not_contain("[..]sentinel[..]")
         .run();
@@ -730,8 +708,7 @@ fn lib_with_bin_artifact_and_lib_false() {
         )
         .build();
 
-    p.cargo("build -Z bindeps")
-        .masquerade_as_nightly_cargo(&["bindeps"])
+    p.cargo("build")
         .with_status(101)
         .with_stderr_does_not_contain("[..]sentinel[..]")
         .run();
@@ -790,8 +767,7 @@ fn build_script_with_selected_dashed_bin_artifact_and_lib_true() {
             }
         "#)
         .build();
-    p.cargo("build -Z bindeps")
-        .masquerade_as_nightly_cargo(&["bindeps"])
+    p.cargo("build")
         .with_stderr_data(str![[r#"
 [LOCKING] 1 package to latest compatible version
 [COMPILING] bar-baz v0.5.0 ([ROOT]/foo/bar)
@@ -886,8 +862,7 @@ fn lib_with_selected_dashed_bin_artifact_and_lib_true() {
         .file("bar/src/main.rs", "fn main() {}")
         .file("bar/src/lib.rs", "pub fn exists() {}")
         .build();
-    p.cargo("build -Z bindeps")
-        .masquerade_as_nightly_cargo(&["bindeps"])
+    p.cargo("build")
         .with_stderr_data(str![[r#"
 [LOCKING] 1 package to latest compatible version
 [COMPILING] bar-baz v0.5.0 ([ROOT]/foo/bar)
@@ -937,8 +912,7 @@ fn allow_artifact_and_no_artifact_dep_to_same_package_within_different_dep_categ
         .file("bar/src/main.rs", "fn main() {}")
         .file("bar/src/lib.rs", "")
         .build();
-    p.cargo("test -Z bindeps")
-        .masquerade_as_nightly_cargo(&["bindeps"])
+    p.cargo("test")
         .with_stderr_data(str![[r#"
 [LOCKING] 1 package to latest compatible version
 [COMPILING] bar v0.5.0 ([ROOT]/foo/bar)
@@ -984,9 +958,7 @@ fn normal_build_deps_are_picked_up_in_presence_of_an_artifact_build_dep_to_the_s
         .file("bar/src/main.rs", "fn main() {}")
         .file("bar/src/lib.rs", "pub fn f() {}")
         .build();
-    p.cargo("check -Z bindeps")
-        .masquerade_as_nightly_cargo(&["bindeps"])
-        .run();
+    p.cargo("check").run();
 }
 
 #[cargo_test]
@@ -1011,8 +983,7 @@ fn disallow_using_example_binaries_as_artifacts() {
         .file("bar/src/main.rs", "fn main() {}")
         .file("bar/examples/one-example.rs", "fn main() {}")
         .build();
-    p.cargo("build -Z bindeps")
-        .masquerade_as_nightly_cargo(&["bindeps"])
+    p.cargo("build")
         .with_status(101)
         .with_stderr_data(str![[r#"
 [LOCKING] 1 package to latest compatible version
@@ -1065,8 +1036,7 @@ fn allow_artifact_and_non_artifact_dependency_to_same_crate() {
             .file("bar/src/lib.rs", "pub fn doit() {}")
         .build();
 
-    p.cargo("check -Z bindeps")
-        .masquerade_as_nightly_cargo(&["bindeps"])
+    p.cargo("check")
         .with_stderr_data(str![[r#"
 [LOCKING] 1 package to latest compatible version
 [COMPILING] bar v0.5.0 ([ROOT]/foo/bar)
@@ -1115,8 +1085,7 @@ fn build_script_deps_adopt_specified_target_unconditionally() {
         .file("bar/src/lib.rs", "pub fn doit() {}")
         .build();
 
-    p.cargo("check -v -Z bindeps")
-        .masquerade_as_nightly_cargo(&["bindeps"])
+    p.cargo("check -v")
         .with_stderr_does_not_contain(
             "[RUNNING] `rustc --crate-name build_script_build --edition=2015 build.rs [..]--target [ALT_TARGET] [..]",
         )
@@ -1183,8 +1152,7 @@ fn build_script_deps_adopt_do_not_allow_multiple_targets_under_different_name_an
         .file("bar/src/main.rs", "fn main() {}")
         .build();
 
-    p.cargo("check -v -Z bindeps")
-        .masquerade_as_nightly_cargo(&["bindeps"])
+    p.cargo("check -v")
         .with_status(101)
         .with_stderr_data(str![[r#"
 [LOCKING] 1 package to latest compatible version
@@ -1230,8 +1198,7 @@ fn non_build_script_deps_adopt_specified_target_unconditionally() {
         .file("bar/src/lib.rs", "pub fn doit() {}")
         .build();
 
-    p.cargo("check -v -Z bindeps")
-        .masquerade_as_nightly_cargo(&["bindeps"])
+    p.cargo("check -v")
         .with_stderr_contains(
             "[RUNNING] `rustc --crate-name bar --edition=2015 bar/src/lib.rs [..]--target [ALT_TARGET] [..]",
         )
@@ -1285,9 +1252,8 @@ fn no_cross_doctests_works_with_artifacts() {
         .build();
 
     let target = rustc_host();
-    p.cargo("test -Z bindeps --target")
+    p.cargo("test --target")
         .arg(&target)
-        .masquerade_as_nightly_cargo(&["bindeps"])
         .with_stderr_data(str![[r#"
 [LOCKING] 1 package to latest compatible version
 [COMPILING] bar v0.5.0 ([ROOT]/foo/bar)
@@ -1304,9 +1270,8 @@ fn no_cross_doctests_works_with_artifacts() {
 
     // This will build the library, but does not build or run doc tests.
     // This should probably be a warning or error.
-    p.cargo("test -Z bindeps -v --doc --target")
+    p.cargo("test -v --doc --target")
         .arg(&target)
-        .masquerade_as_nightly_cargo(&["bindeps"])
         .with_stderr_data(str![[r#"
 [COMPILING] bar v0.5.0 ([ROOT]/foo/bar)
 [RUNNING] `rustc --crate-name bar --edition=2015 bar/src/lib.rs [..]--target [ALT_TARGET] [..]
@@ -1325,9 +1290,8 @@ See https://doc.rust-lang.org/nightly/cargo/reference/unstable.html#doctest-xcom
     }
 
     // This tests the library, but does not run the doc tests.
-    p.cargo("test -Z bindeps -v --target")
+    p.cargo("test -v --target")
         .arg(&target)
-        .masquerade_as_nightly_cargo(&["bindeps"])
         .with_stderr_data(str![[r#"
 [FRESH] bar v0.5.0 ([ROOT]/foo/bar)
 [COMPILING] foo v0.0.1 ([ROOT]/foo)
@@ -1374,9 +1338,8 @@ fn build_script_deps_adopts_target_platform_if_target_equals_target() {
         .build();
 
     let alternate_target = cross_compile::alternate();
-    p.cargo("check -v -Z bindeps --target")
+    p.cargo("check -v --target")
         .arg(alternate_target)
-        .masquerade_as_nightly_cargo(&["bindeps"])
         .with_stderr_does_not_contain(
             "[RUNNING] `rustc --crate-name build_script_build --edition=2015 build.rs [..]--target [ALT_TARGET] [..]",
         )
@@ -1393,6 +1356,44 @@ fn build_script_deps_adopts_target_platform_if_target_equals_target() {
         .run();
 }
 
+#[cargo_test]
+fn build_script_deps_with_target_equals_target_build_for_host() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.0.0"
+                edition = "2015"
+                authors = []
+                resolver = "2"
+
+                [build-dependencies]
+                bar = { path = "bar/", artifact = "bin", target = "target" }
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .file("build.rs", r#"
+                fn main() {
+                    let bar: std::path::PathBuf = std::env::var("CARGO_BIN_FILE_BAR").expect("CARGO_BIN_FILE_BAR").into();
+                    assert!(&bar.is_file());
+                }"#)
+        .file("bar/Cargo.toml", &basic_bin_manifest("bar"))
+        .file("bar/src/main.rs", "fn main() {}")
+        .build();
+
+    p.cargo("build")
+        .with_stderr_data(str![[r#"
+[LOCKING] 1 package to latest compatible version
+[COMPILING] bar v0.5.0 ([ROOT]/foo/bar)
+[COMPILING] foo v0.0.0 ([ROOT]/foo)
+[FINISHED] `dev` profile [unoptimized + debuginfo] target(s) in [ELAPSED]s
+
+"#]])
+        .run();
+}
+
 #[cargo_test]
 // TODO(ST): rename bar (dependency) to something else and un-ignore this with RFC-3176
 #[cfg_attr(target_env = "msvc", ignore = "msvc not working")]
@@ -1427,8 +1428,7 @@ fn profile_override_basic() {
         .file("bar/src/lib.rs", "pub fn bar() {}")
         .build();
 
-    p.cargo("build -v -Z bindeps")
-        .masquerade_as_nightly_cargo(&["bindeps"])
+    p.cargo("build -v")
         .with_stderr_data(
             str![[r#"
 [LOCKING] 1 package to latest compatible version
@@ -1495,17 +1495,14 @@ fn dependencies_of_dependencies_work_in_artifacts() {
         .file("bar/src/lib.rs", r#"pub fn bar() {baz::baz()}"#)
         .file("bar/src/main.rs", r#"fn main() {bar::bar()}"#)
         .build();
-    p.cargo("build -Z bindeps")
-        .masquerade_as_nightly_cargo(&["bindeps"])
-        .run();
+    p.cargo("build").run();
 
     // cargo tree sees artifacts as the dependency kind they are in and doesn't do anything special with it.
-    p.cargo("tree -Z bindeps")
-        .masquerade_as_nightly_cargo(&["bindeps"])
+    p.cargo("tree")
         .with_stdout_data(str![[r#"
 foo v0.0.0 ([ROOT]/foo)
 [build-dependencies]
-└── bar v0.5.0 ([ROOT]/foo/bar)
+└── bar v0.5.0 ([ROOT]/foo/bar) (artifact: bin)
     └── baz v1.0.0
 
 "#]])
@@ -1540,8 +1537,7 @@ fn artifact_dep_target_specified() {
         .file("bindep/src/main.rs", "fn main() {}")
         .build();
 
-    p.cargo("check -Z bindeps")
-        .masquerade_as_nightly_cargo(&["bindeps"])
+    p.cargo("check")
         .with_stderr_data(str![[r#"
 [LOCKING] 1 package to latest compatible version
 [COMPILING] bindep v0.0.0 ([ROOT]/foo/bindep)
@@ -1552,11 +1548,10 @@ fn artifact_dep_target_specified() {
         .with_status(0)
         .run();
 
-    p.cargo("tree -Z bindeps")
-        .masquerade_as_nightly_cargo(&["bindeps"])
+    p.cargo("tree")
         .with_stdout_data(str![[r#"
 foo v0.0.0 ([ROOT]/foo)
-└── bindep v0.0.0 ([ROOT]/foo/bindep)
+└── bindep v0.0.0 ([ROOT]/foo/bindep) (artifact: bin; target: [ALT_TARGET])
 
 "#]])
         .with_status(0)
@@ -1617,8 +1612,7 @@ fn dep_of_artifact_dep_same_target_specified() {
         .file("baz/src/lib.rs", "")
         .build();
 
-    p.cargo("check -Z bindeps")
-        .masquerade_as_nightly_cargo(&["bindeps"])
+    p.cargo("check")
         .with_stderr_data(str![[r#"
 [LOCKING] 2 packages to latest compatible versions
 [COMPILING] baz v0.1.0 ([ROOT]/foo/baz)
@@ -1630,12 +1624,11 @@ fn dep_of_artifact_dep_same_target_specified() {
         .with_status(0)
         .run();
 
-    p.cargo("tree -Z bindeps")
-        .masquerade_as_nightly_cargo(&["bindeps"])
+    p.cargo("tree")
         .with_stdout_data(
             r#"...
 foo v0.1.0 ([ROOT]/foo)
-└── bar v0.1.0 ([ROOT]/foo/bar)
+└── bar v0.1.0 ([ROOT]/foo/bar) (artifact: bin; target: [ALT_TARGET])
     └── baz v0.1.0 ([ROOT]/foo/baz)
 "#,
         )
@@ -1684,17 +1677,12 @@ fn targets_are_picked_up_from_non_workspace_artifact_deps() {
         )
         .build();
 
-    p.cargo("build -Z bindeps")
-        .masquerade_as_nightly_cargo(&["bindeps"])
-        .run();
+    p.cargo("build").run();
 }
 
 #[cargo_test]
 fn index_version_filtering() {
-    if cross_compile::disabled() {
-        return;
-    }
-    let target = cross_compile::alternate();
+    let target = rustc_host();
 
     Package::new("artifact", "1.0.0")
         .file("src/main.rs", r#"fn main() {}"#)
@@ -1708,8 +1696,13 @@ fn index_version_filtering() {
         .schema_version(3)
         .add_dep(dep.artifact("bin", Some(target.to_string())))
         .publish();
+    Package::new("bar", "1.0.2")
+        .schema_version(4)
+        .add_dep(dep.artifact("bin", Some(target.to_string())))
+        .publish();
 
-    // Verify that without `-Zbindeps` that it does not use 1.0.1.
+    // Verify that it uses 1.0.1 with the artifact dependency, but skips
+    // 1.0.2 since its schema version is not understood.
     let p = project()
         .file(
             "Cargo.toml",
@@ -1729,33 +1722,8 @@ fn index_version_filtering() {
     p.cargo("tree")
         .with_stdout_data(str![[r#"
 foo v0.1.0 ([ROOT]/foo)
-└── bar v1.0.0
-
-"#]])
-        .run();
-
-    // And with -Zbindeps it can use 1.0.1.
-    p.cargo("update -Zbindeps")
-        .masquerade_as_nightly_cargo(&["bindeps"])
-        .with_stderr_data(str![[r#"
-[UPDATING] `dummy-registry` index
-[LOCKING] 2 packages to latest compatible versions
-[ADDING] artifact v1.0.0
-[UPDATING] bar v1.0.0 -> v1.0.1
-
-"#]])
-        .run();
-
-    // And without -Zbindeps, now that 1.0.1 is in Cargo.lock, it should fail.
-    p.cargo("check")
-        .with_status(101)
-        .with_stderr_data(str![[r#"
-[UPDATING] `dummy-registry` index
-[ERROR] failed to select a version for the requirement `bar = "^1.0"` (locked to 1.0.1)
-candidate versions found which didn't match: 1.0.0
-location searched: `dummy-registry` index (which is replacing registry `crates-io`)
-required by package `foo v0.1.0 ([ROOT]/foo)`
-perhaps a crate was updated and forgotten to be re-vendored?
+└── bar v1.0.1
+    └── artifact v1.0.0 (artifact: bin; target: [HOST_TARGET])
 
 "#]])
         .run();
@@ -1809,8 +1777,7 @@ fn proc_macro_in_artifact_dep() {
         .file("src/lib.rs", "")
         .build();
 
-    p.cargo("check -Z bindeps")
-        .masquerade_as_nightly_cargo(&["bindeps"])
+    p.cargo("check")
         .with_stderr_data(
             r#"...
 [UPDATING] `dummy-registry` index
@@ -1861,8 +1828,7 @@ fn allow_dep_renames_with_multiple_versions() {
         .file("bar/Cargo.toml", &basic_bin_manifest("bar"))
         .file("bar/src/main.rs", r#"fn main() {println!("0.5.0")}"#)
         .build();
-    p.cargo("check -Z bindeps")
-        .masquerade_as_nightly_cargo(&["bindeps"])
+    p.cargo("check")
         .with_stderr_data(
             str![[r#"
 [UPDATING] `dummy-registry` index
@@ -1925,8 +1891,7 @@ fn allow_artifact_and_non_artifact_dependency_to_same_crate_if_these_are_not_the
         .file("bar/src/lib.rs", "pub fn doit() {}")
         .file("bar/src/main.rs", "fn main() {}")
         .build();
-    p.cargo("build -Z bindeps")
-        .masquerade_as_nightly_cargo(&["bindeps"])
+    p.cargo("build")
         .with_stderr_data(str![[r#"
 [LOCKING] 1 package to latest compatible version
 [COMPILING] bar v0.0.1 ([ROOT]/foo/bar)
@@ -1961,8 +1926,7 @@ fn prevent_no_lib_warning_with_artifact_dependencies() {
         .file("bar/Cargo.toml", &basic_bin_manifest("bar"))
         .file("bar/src/main.rs", "fn main() {}")
         .build();
-    p.cargo("check -Z bindeps")
-        .masquerade_as_nightly_cargo(&["bindeps"])
+    p.cargo("check")
         .with_stderr_data(str![[r#"
 [LOCKING] 1 package to latest compatible version
 [COMPILING] bar v0.5.0 ([ROOT]/foo/bar)
@@ -1998,8 +1962,7 @@ fn show_no_lib_warning_with_artifact_dependencies_that_have_no_lib_but_lib_true(
         .file("bar/Cargo.toml", &basic_bin_manifest("bar"))
         .file("bar/src/main.rs", "fn main() {}")
         .build();
-    p.cargo("check -Z bindeps")
-        .masquerade_as_nightly_cargo(&["bindeps"])
+    p.cargo("check")
         .with_stderr_data(str![[r#"
 [LOCKING] 1 package to latest compatible version
 [WARNING] foo v0.0.0 ([ROOT]/foo) ignoring invalid dependency `bar` which is missing a lib target
@@ -2036,9 +1999,7 @@ fn resolver_2_build_dep_without_lib() {
         .file("bar/Cargo.toml", &basic_bin_manifest("bar"))
         .file("bar/src/main.rs", "fn main() {}")
         .build();
-    p.cargo("check -Z bindeps")
-        .masquerade_as_nightly_cargo(&["bindeps"])
-        .run();
+    p.cargo("check").run();
 }
 
 #[cargo_test]
@@ -2065,8 +2026,7 @@ fn check_missing_crate_type_in_package_fails() {
             .file("bar/Cargo.toml", &basic_manifest("bar", "0.0.1")) //no bin, just rlib
             .file("bar/src/lib.rs", "")
             .build();
-        p.cargo("check -Z bindeps")
-            .masquerade_as_nightly_cargo(&["bindeps"])
+        p.cargo("check")
             .with_status(101)
             .with_stderr_data(str![[r#"
 [LOCKING] 1 package to latest compatible version
@@ -2098,8 +2058,7 @@ fn check_target_equals_target_in_non_build_dependency_errors() {
         .file("bar/Cargo.toml", &basic_manifest("bar", "0.0.1"))
         .file("bar/src/main.rs", "fn main() {}")
         .build();
-    p.cargo("check -Z bindeps")
-        .masquerade_as_nightly_cargo(&["bindeps"])
+    p.cargo("check")
         .with_status(101)
         .with_stderr_data(str![[r#"
 [ERROR] failed to parse manifest at `[ROOT]/foo/Cargo.toml`
@@ -2217,8 +2176,7 @@ fn env_vars_and_build_products_for_various_build_targets() {
         .file("bar/src/lib.rs", r#"pub extern "C" fn c() {}"#)
         .file("bar/src/main.rs", "fn main() {}")
         .build();
-    p.cargo("test -Z bindeps")
-        .masquerade_as_nightly_cargo(&["bindeps"])
+    p.cargo("test")
         .with_stderr_data(str![[r#"
 [LOCKING] 1 package to latest compatible version
 [COMPILING] bar v0.5.0 ([ROOT]/foo/bar)
@@ -2265,9 +2223,8 @@ fn publish_artifact_dep() {
         .file("src/lib.rs", "")
         .build();
 
-    p.cargo("publish -Z bindeps --no-verify")
+    p.cargo("publish --no-verify")
         .replace_crates_io(registry.index_url())
-        .masquerade_as_nightly_cargo(&["bindeps"])
         .with_stderr_data(str![[r#"
 [UPDATING] crates.io index
 [PACKAGING] foo v0.1.0 ([ROOT]/foo)
@@ -2414,8 +2371,7 @@ fn doc_lib_true() {
         .file("bar/src/main.rs", "fn main() {}")
         .build();
 
-    p.cargo("doc -Z bindeps")
-        .masquerade_as_nightly_cargo(&["bindeps"])
+    p.cargo("doc")
         .with_stderr_data(str![[r#"
 [LOCKING] 1 package to latest compatible version
 [COMPILING] bar v0.0.1 ([ROOT]/foo/bar)
@@ -2435,8 +2391,7 @@ fn doc_lib_true() {
     assert_eq!(p.glob("target/debug/artifact/*.rlib").count(), 0);
     assert_eq!(p.glob("target/debug/deps/libbar-*.rmeta").count(), 2);
 
-    p.cargo("doc -Z bindeps")
-        .masquerade_as_nightly_cargo(&["bindeps"])
+    p.cargo("doc")
         .with_stderr_data(str![[r#"
 [FINISHED] `dev` profile [unoptimized + debuginfo] target(s) in [ELAPSED]s
 [GENERATED] [ROOT]/foo/target/doc/foo/index.html
@@ -2496,8 +2451,7 @@ fn rustdoc_works_on_libs_with_artifacts_and_lib_false() {
         .file("bar/src/main.rs", "fn main() {}")
         .build();
 
-    p.cargo("doc -Z bindeps")
-        .masquerade_as_nightly_cargo(&["bindeps"])
+    p.cargo("doc")
         .with_stderr_data(str![[r#"
 [LOCKING] 1 package to latest compatible version
 [COMPILING] bar v0.5.0 ([ROOT]/foo/bar)
@@ -2670,9 +2624,7 @@ fn build_script_features_for_shared_dependency() {
         )
         .build();
 
-    p.cargo("build -Z bindeps -v")
-        .masquerade_as_nightly_cargo(&["bindeps"])
-        .run();
+    p.cargo("build -v").run();
 }
 
 #[cargo_test]
@@ -2703,8 +2655,7 @@ fn calc_bin_artifact_fingerprint() {
         .file("bar/Cargo.toml", &basic_bin_manifest("bar"))
         .file("bar/src/main.rs", r#"fn main() { println!("foo") }"#)
         .build();
-    p.cargo("check -Z bindeps")
-        .masquerade_as_nightly_cargo(&["bindeps"])
+    p.cargo("check")
         .with_stderr_data(str![[r#"
 [LOCKING] 1 package to latest compatible version
 [COMPILING] bar v0.5.0 ([ROOT]/foo/bar)
@@ -2716,8 +2667,7 @@ fn calc_bin_artifact_fingerprint() {
 
     p.change_file("bar/src/main.rs", r#"fn main() { println!("bar") }"#);
     // Change in artifact bin dep `bar` propagates to `foo`, triggering recompile.
-    p.cargo("check -v -Z bindeps")
-        .masquerade_as_nightly_cargo(&["bindeps"])
+    p.cargo("check -v")
         .with_stderr_data(str![[r#"
 [DIRTY] bar v0.5.0 ([ROOT]/foo/bar): the file `bar/src/main.rs` has changed ([..])
 [COMPILING] bar v0.5.0 ([ROOT]/foo/bar)
@@ -2731,8 +2681,7 @@ fn calc_bin_artifact_fingerprint() {
         .run();
 
     // All units are fresh. No recompile.
-    p.cargo("check -v -Z bindeps")
-        .masquerade_as_nightly_cargo(&["bindeps"])
+    p.cargo("check -v")
         .with_stderr_data(str![[r#"
 [FRESH] bar v0.5.0 ([ROOT]/foo/bar)
 [FRESH] foo v0.1.0 ([ROOT]/foo)
@@ -2782,8 +2731,7 @@ fn with_target_and_optional() {
         .file("d1/src/main.rs", "fn main() {}")
         .build();
 
-    p.cargo("check -Z bindeps -F d1 -v")
-        .masquerade_as_nightly_cargo(&["bindeps"])
+    p.cargo("check -F d1 -v")
         .with_stderr_data(str![[r#"
 [LOCKING] 1 package to latest compatible version
 [COMPILING] d1 v0.0.1 ([ROOT]/foo/d1)
@@ -2831,8 +2779,7 @@ fn with_assumed_host_target_and_optional_build_dep() {
         .file("d1/src/main.rs", "fn main() {}")
         .build();
 
-    p.cargo("check -Z bindeps -F d1 -v")
-        .masquerade_as_nightly_cargo(&["bindeps"])
+    p.cargo("check -F d1 -v")
         .with_stderr_data(
             str![[r#"
 [LOCKING] 1 package to latest compatible version
@@ -2961,8 +2908,7 @@ fn decouple_same_target_transitive_dep_from_artifact_dep() {
             "#,
         )
         .build();
-    p.cargo("build -Z bindeps")
-        .masquerade_as_nightly_cargo(&["bindeps"])
+    p.cargo("build")
         .with_stderr_data(str![[r#"
 [LOCKING] 4 packages to latest compatible versions
 [COMPILING] c v0.1.0 ([ROOT]/foo/c)
@@ -3064,8 +3010,7 @@ fn decouple_same_target_transitive_dep_from_artifact_dep_lib() {
             "#,
         )
         .build();
-    p.cargo("build -Z bindeps")
-        .masquerade_as_nightly_cargo(&["bindeps"])
+    p.cargo("build")
         .with_stderr_data(str![[r#"
 [LOCKING] 3 packages to latest compatible versions
 [COMPILING] b v0.1.0 ([ROOT]/foo/b)
@@ -3190,8 +3135,7 @@ fn decouple_same_target_transitive_dep_from_artifact_dep_and_proc_macro() {
         .file("d/src/lib.rs", "pub struct D;")
         .build();
 
-    p.cargo("build -Z bindeps")
-        .masquerade_as_nightly_cargo(&["bindeps"])
+    p.cargo("build")
         .with_stderr_data(
             str![[r#"
 [LOCKING] 5 packages to latest compatible versions
@@ -3258,8 +3202,7 @@ fn same_target_artifact_dep_sharing() {
         )
         .file("a/src/lib.rs", "")
         .build();
-    p.cargo(&format!("build -Z bindeps --target {target}"))
-        .masquerade_as_nightly_cargo(&["bindeps"])
+    p.cargo(&format!("build --target {target}"))
         .with_stderr_data(str![[r#"
 [LOCKING] 2 packages to latest compatible versions
 [COMPILING] a v0.1.0 ([ROOT]/foo/a)
@@ -3314,8 +3257,7 @@ fn check_transitive_artifact_dependency_with_different_target() {
         .file("bar/baz/src/main.rs", "fn main() {}")
         .build();
 
-    p.cargo("check -Z bindeps")
-        .masquerade_as_nightly_cargo(&["bindeps"])
+    p.cargo("check")
         .with_stderr_data(str![[r#"
 [LOCKING] 2 packages to latest compatible versions
 [ERROR] failed to determine target information for target `custom-target`.
@@ -3381,10 +3323,7 @@ fn build_only_specified_artifact_library() {
     };
 
     let cdylib = create_project("cdylib");
-    cdylib
-        .cargo("build -Z bindeps")
-        .masquerade_as_nightly_cargo(&["bindeps"])
-        .run();
+    cdylib.cargo("build").run();
     assert_e2e().eq(
         &build_script_output_string(&cdylib, "foo"),
         str![[r#"
@@ -3395,10 +3334,7 @@ staticlib present: false
     );
 
     let staticlib = create_project("staticlib");
-    staticlib
-        .cargo("build -Z bindeps")
-        .masquerade_as_nightly_cargo(&["bindeps"])
-        .run();
+    staticlib.cargo("build").run();
     assert_e2e().eq(
         &build_script_output_string(&staticlib, "foo"),
         str![[r#"
@@ -3408,3 +3344,153 @@ staticlib present: true
 "#]],
     );
 }
+
+#[cargo_test]
+fn nested_artifact_dep_without_target_uses_parent_artifact_target_features() {
+    let target = rustc_host();
+    let p = project()
+        .file(
+            "Cargo.toml",
+            &format!(
+                r#"
+                [package]
+                name = "foo"
+                version = "0.1.0"
+                edition = "2021"
+
+                [dependencies]
+                bar = {{ path = "bar", artifact = "bin", target = "{target}" }}
+                baz = {{ path = "baz" }}
+            "#
+            ),
+        )
+        .file(
+            "src/lib.rs",
+            r#"
+            pub fn foo() -> bool {
+                baz::enabled()
+            }
+            "#,
+        )
+        .file(
+            "bar/Cargo.toml",
+            r#"
+            [package]
+            name = "bar"
+            version = "0.1.0"
+            edition = "2021"
+
+            [dependencies]
+            baz = { path = "../baz", artifact = "bin", features = ["enabled"] }
+            "#,
+        )
+        .file(
+            "bar/src/main.rs",
+            r#"
+            fn main() {
+                let _b = env!("CARGO_BIN_FILE_BAZ");
+            }
+            "#,
+        )
+        .file(
+            "baz/Cargo.toml",
+            r#"
+            [package]
+            name = "baz"
+            version = "0.1.0"
+            edition = "2021"
+
+            [features]
+            enabled = []
+            "#,
+        )
+        .file(
+            "baz/src/lib.rs",
+            r#"
+            pub fn enabled() -> bool {
+                cfg!(feature = "enabled")
+            }
+            "#,
+        )
+        .file(
+            "baz/src/main.rs",
+            r#"
+            #[cfg(not(feature = "enabled"))]
+            compile_error!("`enabled` feature must be set for the artifact");
+            fn main() {}
+            "#,
+        )
+        .build();
+
+    p.cargo("check")
+        .with_stderr_data(str![[r#"
+[LOCKING] 2 packages to latest compatible versions
+[COMPILING] baz v0.1.0 ([ROOT]/foo/baz)
+[COMPILING] bar v0.1.0 ([ROOT]/foo/bar)
+[CHECKING] foo v0.1.0 ([ROOT]/foo)
+[FINISHED] `dev` profile [unoptimized + debuginfo] target(s) in [ELAPSED]s
+
+"#]])
+        .run();
+}
+
+#[cargo_test]
+fn tree_shows_artifact_edges() {
+    let target = rustc_host();
+    let p = project()
+        .file(
+            "Cargo.toml",
+            &format!(
+                r#"
+                [package]
+                name = "foo"
+                version = "0.1.0"
+                edition = "2021"
+
+                [dependencies]
+                bar = {{ path = "bar", artifact = ["bin", "cdylib"], lib = true }}
+
+                [build-dependencies]
+                baz = {{ path = "baz", artifact = "bin", target = "{target}" }}
+            "#
+            ),
+        )
+        .file("src/lib.rs", "")
+        .file("build.rs", "fn main() {}")
+        .file(
+            "bar/Cargo.toml",
+            r#"
+            [package]
+            name = "bar"
+            version = "0.1.0"
+            edition = "2021"
+
+            [lib]
+            crate-type = ["cdylib", "rlib"]
+            "#,
+        )
+        .file("bar/src/lib.rs", "")
+        .file("bar/src/main.rs", "fn main() {}")
+        .file("baz/Cargo.toml", &basic_bin_manifest("baz"))
+        .file("baz/src/main.rs", "fn main() {}")
+        .build();
+
+    p.cargo("tree")
+        .with_stdout_data(str![[r#"
+foo v0.1.0 ([ROOT]/foo)
+└── bar v0.1.0 ([ROOT]/foo/bar) (artifact: bin, cdylib, lib)
+[build-dependencies]
+└── baz v0.5.0 ([ROOT]/foo/baz) (artifact: bin; target: [HOST_TARGET])
+
+"#]])
+        .run();
+
+    p.cargo("tree --invert baz")
+        .with_stdout_data(str![[r#"
+baz v0.5.0 ([ROOT]/foo/baz)
+[build-dependencies]
+└── foo v0.1.0 ([ROOT]/foo) (artifact: bin; target: [HOST_TARGET])
+
+"#]])
+        .run();
+}
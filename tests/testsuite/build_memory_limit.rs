@@ -0,0 +1,192 @@
+//! Tests for `-Zbuild-memory-limit`.
+
+use cargo_test_support::prelude::*;
+use cargo_test_support::registry::Package;
+use cargo_test_support::{project, str, Project};
+
+/// Whether the peak memory usage of processes can be measured here.
+fn peak_rss_supported() -> bool {
+    cfg!(any(
+        target_os = "linux",
+        target_os = "macos",
+        target_os = "freebsd",
+        windows
+    ))
+}
+
+fn history(p: &Project) -> serde_json::Value {
+    let contents = std::fs::read_to_string(p.root().join("target/.unit-history.json")).unwrap();
+    serde_json::from_str(&contents).unwrap()
+}
+
+#[cargo_test]
+fn gated() {
+    let p = project()
+        .file("src/lib.rs", "")
+        .file(
+            ".cargo/config.toml",
+            r#"
+                [build]
+                memory-limit = "not a size"
+            "#,
+        )
+        .build();
+
+    p.cargo("check").run();
+    assert!(!p.root().join("target/.unit-history.json").exists());
+}
+
+#[cargo_test]
+fn records_peak_memory() {
+    if !peak_rss_supported() {
+        return;
+    }
+    Package::new("bar", "1.0.0").publish();
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.0.1"
+                edition = "2015"
+
+                [dependencies]
+                bar = "1.0"
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .file("build.rs", "fn main() {}")
+        .build();
+
+    p.cargo("check -Zbuild-memory-limit")
+        .masquerade_as_nightly_cargo(&["build-memory-limit"])
+        .run();
+
+    // bar, foo's build script, running it, and foo itself.
+    let history = history(&p);
+    let units = history["units"].as_object().unwrap();
+    assert_eq!(units.len(), 4, "{history:#}");
+    for record in units.values() {
+        assert!(record["peak-rss"].as_u64().unwrap() > 0, "{history:#}");
+    }
+}
+
+#[cargo_test]
+fn builds_with_limit_below_every_unit() {
+    if !peak_rss_supported() {
+        return;
+    }
+    Package::new("dep1", "1.0.0").publish();
+    Package::new("dep2", "1.0.0").publish();
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.0.1"
+                edition = "2015"
+
+                [dependencies]
+                dep1 = "1.0"
+                dep2 = "1.0"
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .file("build.rs", "fn main() {}")
+        .file(
+            ".cargo/config.toml",
+            r#"
+                [build]
+                memory-limit = "1B"
+            "#,
+        )
+        .build();
+
+    p.cargo("check -Zbuild-memory-limit")
+        .masquerade_as_nightly_cargo(&["build-memory-limit"])
+        .run();
+
+    // Every unit now has a measurement above the limit, so each job can only
+    // start once nothing else is running.
+    std::fs::remove_dir_all(p.root().join("target/debug")).unwrap();
+    p.cargo("check -Zbuild-memory-limit -j4")
+        .masquerade_as_nightly_cargo(&["build-memory-limit"])
+        .with_stderr_data(
+            str![[r#"
+[CHECKING] dep1 v1.0.0
+[CHECKING] dep2 v1.0.0
+[COMPILING] foo v0.0.1 ([ROOT]/foo)
+[FINISHED] `dev` profile [unoptimized + debuginfo] target(s) in [ELAPSED]s
+
+"#]]
+            .unordered(),
+        )
+        .run();
+}
+
+#[cargo_test]
+fn invalid_limit() {
+    let p = project()
+        .file("src/lib.rs", "")
+        .file(
+            ".cargo/config.toml",
+            r#"
+                [build]
+                memory-limit = "lots"
+            "#,
+        )
+        .build();
+
+    p.cargo("check -Zbuild-memory-limit")
+        .masquerade_as_nightly_cargo(&["build-memory-limit"])
+        .with_status(101)
+        .with_stderr_data(str![[r#"
+...
+[ERROR] failed to parse `build.memory-limit`
+
+Caused by:
+  invalid size `lots`, expected a number with an optional B, kB, MB, GB, kiB, MiB, or GiB suffix
+
+"#]])
+        .run();
+}
+
+#[cargo_test]
+fn peak_memory_in_timings() {
+    if !peak_rss_supported() {
+        return;
+    }
+    Package::new("bar", "1.0.0").publish();
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.0.1"
+                edition = "2015"
+
+                [dependencies]
+                bar = "1.0"
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+
+    let output = p
+        .cargo("check --timings=json -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["timings=json"])
+        .run();
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let timings: Vec<serde_json::Value> = stdout
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .filter(|msg: &serde_json::Value| msg["reason"] == "timing-info")
+        .collect();
+    assert_eq!(timings.len(), 2, "{stdout}");
+    for msg in timings {
+        assert!(msg["peak_rss"].as_u64().unwrap() > 0, "{msg:#}");
+    }
+}
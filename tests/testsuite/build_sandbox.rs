@@ -0,0 +1,227 @@
+//! Tests for `-Zbuild-sandbox`.
+
+use cargo_test_support::prelude::*;
+use cargo_test_support::{paths, project, str, Project};
+
+/// Whether this system supports Landlock and unprivileged user namespaces.
+#[cfg(target_os = "linux")]
+fn sandbox_supported() -> bool {
+    // SAFETY: querying the Landlock ABI version takes no attributes.
+    let landlock = unsafe {
+        libc::syscall(
+            libc::SYS_landlock_create_ruleset,
+            std::ptr::null::<u64>(),
+            0,
+            1,
+        )
+    } >= 1;
+    let userns = std::process::Command::new("unshare")
+        .args(["--user", "--net", "true"])
+        .status()
+        .map(|s| s.success())
+        .unwrap_or(false);
+    landlock && userns
+}
+
+#[cfg(not(target_os = "linux"))]
+fn sandbox_supported() -> bool {
+    false
+}
+
+/// A project whose build script does whatever `main` does.
+fn sandboxed_project(main: &str, config: &str) -> Project {
+    project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.0.1"
+                edition = "2015"
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .file("build.rs", &format!("fn main() {{ {main} }}"))
+        .file(
+            ".cargo/config.toml",
+            &format!(
+                r#"
+                    [build.sandbox]
+                    enable = true
+                    {config}
+                "#
+            ),
+        )
+        .build()
+}
+
+#[cargo_test]
+fn allowed_access() {
+    if !sandbox_supported() {
+        return;
+    }
+    let p = sandboxed_project(
+        r#"
+            let out_dir = std::path::PathBuf::from(std::env::var("OUT_DIR").unwrap());
+            std::fs::write(out_dir.join("generated.rs"), "").unwrap();
+            std::fs::read_to_string("Cargo.toml").unwrap();
+            let rustc = std::env::var("RUSTC").unwrap();
+            assert!(std::process::Command::new(rustc).arg("-V").status().unwrap().success());
+        "#,
+        "",
+    );
+
+    p.cargo("check -Zbuild-sandbox")
+        .masquerade_as_nightly_cargo(&["build-sandbox"])
+        .with_stderr_data(str![[r#"
+[COMPILING] foo v0.0.1 ([ROOT]/foo)
+[FINISHED] `dev` profile [unoptimized + debuginfo] target(s) in [ELAPSED]s
+
+"#]])
+        .run();
+}
+
+#[cargo_test]
+fn denied_write() {
+    if !sandbox_supported() {
+        return;
+    }
+    let p = sandboxed_project(r#"std::fs::write("../outside", "").unwrap();"#, "");
+
+    p.cargo("check -Zbuild-sandbox")
+        .masquerade_as_nightly_cargo(&["build-sandbox"])
+        .with_status(101)
+        .with_stderr_data(str![[r#"
+[COMPILING] foo v0.0.1 ([ROOT]/foo)
+[ERROR] failed to run custom build command for `foo v0.0.1 ([ROOT]/foo)`
+[NOTE] the build script of `foo` was run in a sandbox, which may have caused this failure
+[HELP] to allow network access, add `foo` to `build.sandbox.allow-net`
+[HELP] to allow reading files outside of the package, add them to `build.sandbox.allow-read`
+[HELP] to run it without a sandbox, add `foo` to `build.sandbox.exclude`
+
+Caused by:
+  process didn't exit successfully: `[..]/cargo[EXE] [ROOT]/foo/target/debug/build/foo-[HASH]/build-script-build` ([EXIT_STATUS]: 101)
+  --- stderr
+...
+  called `Result::unwrap()` on an `Err` value: Os { code: 13, kind: PermissionDenied, message: "Permission denied" }
+...
+"#]])
+        .run();
+    assert!(!p.root().join("../outside").exists());
+
+    // The same build script can write outside the package when it isn't
+    // sandboxed.
+    p.cargo("check").run();
+    assert!(p.root().join("../outside").exists());
+}
+
+#[cargo_test]
+fn denied_dev_write() {
+    if !sandbox_supported() || !std::path::Path::new("/dev/shm").is_dir() {
+        return;
+    }
+    let p = sandboxed_project(
+        r#"
+            std::fs::write("/dev/null", "").unwrap();
+            assert!(std::fs::write("/dev/shm/cargo-sandbox-test", "").is_err());
+        "#,
+        "",
+    );
+
+    p.cargo("check -Zbuild-sandbox")
+        .masquerade_as_nightly_cargo(&["build-sandbox"])
+        .run();
+    assert!(!std::path::Path::new("/dev/shm/cargo-sandbox-test").exists());
+}
+
+#[cargo_test]
+fn denied_read() {
+    if !sandbox_supported() {
+        return;
+    }
+    let secret = paths::home().join("secret");
+    std::fs::write(&secret, "").unwrap();
+    let p = sandboxed_project(
+        &format!(
+            r#"assert!(std::fs::read_to_string({:?}).is_err());"#,
+            secret.display().to_string()
+        ),
+        "",
+    );
+    p.cargo("check -Zbuild-sandbox")
+        .masquerade_as_nightly_cargo(&["build-sandbox"])
+        .run();
+
+    let p = sandboxed_project(
+        &format!(
+            r#"std::fs::read_to_string({:?}).unwrap();"#,
+            secret.display().to_string()
+        ),
+        &format!(r#"allow-read = [{:?}]"#, secret.display().to_string()),
+    );
+    p.cargo("check -Zbuild-sandbox")
+        .masquerade_as_nightly_cargo(&["build-sandbox"])
+        .run();
+}
+
+#[cargo_test]
+fn denied_network() {
+    if !sandbox_supported() {
+        return;
+    }
+    // A build script without network access runs in its own network
+    // namespace, which has nothing but an unconfigured loopback interface.
+    let main = r#"
+        let netns = std::fs::read_link("/proc/self/ns/net").unwrap();
+        let host = std::env::var_os("HOST_NETNS").unwrap();
+        assert_eq!(netns == host, std::env::var("CARGO_FEATURE_NET").is_ok());
+    "#;
+    let host_netns = std::fs::read_link("/proc/self/ns/net").unwrap();
+
+    let p = sandboxed_project(main, "");
+    p.change_file(
+        "Cargo.toml",
+        r#"
+            [package]
+            name = "foo"
+            version = "0.0.1"
+            edition = "2015"
+
+            [features]
+            net = []
+        "#,
+    );
+    p.cargo("check -Zbuild-sandbox")
+        .masquerade_as_nightly_cargo(&["build-sandbox"])
+        .env("HOST_NETNS", &host_netns)
+        .run();
+
+    p.change_file(
+        ".cargo/config.toml",
+        r#"
+            [build.sandbox]
+            enable = true
+            allow-net = ["foo"]
+        "#,
+    );
+    p.cargo("check -Zbuild-sandbox --features net")
+        .masquerade_as_nightly_cargo(&["build-sandbox"])
+        .env("HOST_NETNS", &host_netns)
+        .run();
+}
+
+#[cargo_test]
+fn exclude() {
+    if !sandbox_supported() {
+        return;
+    }
+    let p = sandboxed_project(
+        r#"std::fs::write("../outside", "").unwrap();"#,
+        r#"exclude = ["foo"]"#,
+    );
+
+    p.cargo("check -Zbuild-sandbox")
+        .masquerade_as_nightly_cargo(&["build-sandbox"])
+        .run();
+    assert!(p.root().join("../outside").exists());
+}
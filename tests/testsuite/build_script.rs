@@ -1330,6 +1330,126 @@ fn links_passes_env_vars() {
     p.cargo("build -v").run();
 }
 
+#[cargo_test]
+fn links_passes_metadata_json() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.5.0"
+                edition = "2015"
+
+                [dependencies.a-sys]
+                path = "a"
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .file(
+            "build.rs",
+            r##"
+                use std::env;
+                use std::fs;
+                use std::path::Path;
+                fn main() {
+                    let dir = env::var("CARGO_DEP_METADATA_DIR").unwrap();
+                    let dir = Path::new(&dir).join("a-sys");
+                    assert_eq!(
+                        fs::read_to_string(dir.join("include-paths.json")).unwrap(),
+                        r#"["/usr/include/a", "/opt/a/include"]"#,
+                    );
+                    assert_eq!(
+                        fs::read_to_string(dir.join("Version.json")).unwrap(),
+                        r#"{"major": 1, "minor": 2}"#,
+                    );
+                }
+            "##,
+        )
+        .file(
+            "a/Cargo.toml",
+            r#"
+                [package]
+                name = "a-sys"
+                version = "0.5.0"
+                edition = "2015"
+                links = "a"
+            "#,
+        )
+        .file("a/src/lib.rs", "")
+        .file(
+            "a/build.rs",
+            r##"
+                fn main() {
+                    println!(r#"cargo::metadata-json=include-paths=["/usr/include/a", "/opt/a/include"]"#);
+                    println!(r#"cargo::metadata-json=Version={{"major": 1, "minor": 2}}"#);
+                }
+            "##,
+        )
+        .build();
+
+    p.cargo("check -Zbuild-script-instructions")
+        .masquerade_as_nightly_cargo(&["build-script-instructions"])
+        .run();
+}
+
+#[cargo_test]
+fn metadata_json_invalid() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.5.0"
+                edition = "2015"
+                links = "foo"
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .file(
+            "build.rs",
+            r#"
+                fn main() {
+                    println!("cargo::metadata-json=paths=[/usr/include]");
+                }
+            "#,
+        )
+        .build();
+
+    p.cargo("check -Zbuild-script-instructions")
+        .masquerade_as_nightly_cargo(&["build-script-instructions"])
+        .with_status(101)
+        .with_stderr_data(str![[r#"
+[COMPILING] foo v0.5.0 ([ROOT]/foo)
+[ERROR] invalid output in build script of `foo v0.5.0 ([ROOT]/foo)`: `cargo::metadata-json=paths=[/usr/include]`
+Invalid JSON: expected value at line 1 column 2.
+See https://doc.rust-lang.org/cargo/reference/build-scripts.html#outputs-of-the-build-script for more information about build script outputs.
+
+"#]])
+        .run();
+
+    p.change_file(
+        "build.rs",
+        r#"
+            fn main() {
+                println!("cargo::metadata-json=a/b=1");
+            }
+        "#,
+    );
+    p.cargo("check -Zbuild-script-instructions")
+        .masquerade_as_nightly_cargo(&["build-script-instructions"])
+        .with_status(101)
+        .with_stderr_data(str![[r#"
+[COMPILING] foo v0.5.0 ([ROOT]/foo)
+[ERROR] invalid output in build script of `foo v0.5.0 ([ROOT]/foo)`: `cargo::metadata-json=a/b=1`
+Metadata keys may only contain ASCII letters, digits, `-` and `_`.
+See https://doc.rust-lang.org/cargo/reference/build-scripts.html#outputs-of-the-build-script for more information about build script outputs.
+
+"#]])
+        .run();
+}
+
 #[cargo_test]
 fn only_rerun_build_script() {
     let p = project()
@@ -5374,6 +5494,292 @@ fn rerun_if_directory() {
     fresh();
 }
 
+#[cargo_test]
+fn rerun_if_changed_glob() {
+    // rerun-if-changed-glob should rerun if any matching file changes, or
+    // if files start or stop matching.
+    let p = project()
+        .file("Cargo.toml", &basic_manifest("foo", "0.1.0"))
+        .file("src/lib.rs", "")
+        .file(
+            "build.rs",
+            r#"
+                fn main() {
+                    println!("cargo::rerun-if-changed-glob=proto/**/*.proto");
+                }
+            "#,
+        )
+        .file("proto/a.proto", "")
+        .file("proto/nested/b.proto", "")
+        .file("proto/README.md", "")
+        .build();
+
+    let dirty = |expected| {
+        p.cargo("check -v -Zbuild-script-instructions")
+            .masquerade_as_nightly_cargo(&["build-script-instructions"])
+            .with_stderr_data(expected)
+            .run();
+    };
+
+    let fresh = || {
+        p.cargo("check -Zbuild-script-instructions")
+            .masquerade_as_nightly_cargo(&["build-script-instructions"])
+            .with_stderr_data(
+                "\
+[FINISHED] [..]
+",
+            )
+            .run();
+    };
+
+    p.cargo("check -Zbuild-script-instructions")
+        .masquerade_as_nightly_cargo(&["build-script-instructions"])
+        .run();
+    fresh();
+
+    if is_coarse_mtime() {
+        sleep_ms(1000);
+    }
+
+    // Modify a nested file.
+    p.change_file("proto/nested/b.proto", "syntax = \"proto3\";");
+    dirty(str![[r#"
+[DIRTY] foo v0.1.0 ([ROOT]/foo): the file `proto/nested/b.proto` has changed ([TIME_DIFF_AFTER_LAST_BUILD])
+[COMPILING] foo v0.1.0 ([ROOT]/foo)
+[RUNNING] `[ROOT]/foo/target/debug/build/foo-[HASH]/build-script-build`
+[RUNNING] `rustc --crate-name foo [..]`
+[FINISHED] `dev` profile [unoptimized + debuginfo] target(s) in [ELAPSED]s
+
+"#]]);
+    fresh();
+
+    // Files that don't match are ignored.
+    p.change_file("proto/README.md", "docs");
+    fresh();
+
+    // Add a file.
+    p.change_file("proto/nested/c.proto", "");
+    dirty(str![[r#"
+[DIRTY] foo v0.1.0 ([ROOT]/foo): the file `proto/nested/c.proto` started matching rerun-if-changed-glob
+[COMPILING] foo v0.1.0 ([ROOT]/foo)
+[RUNNING] `[ROOT]/foo/target/debug/build/foo-[HASH]/build-script-build`
+[RUNNING] `rustc --crate-name foo [..]`
+[FINISHED] `dev` profile [unoptimized + debuginfo] target(s) in [ELAPSED]s
+
+"#]]);
+    fresh();
+
+    // Remove a file.
+    fs::remove_file(p.root().join("proto/a.proto")).unwrap();
+    dirty(str![[r#"
+[DIRTY] foo v0.1.0 ([ROOT]/foo): the file `proto/a.proto` stopped matching rerun-if-changed-glob
+[COMPILING] foo v0.1.0 ([ROOT]/foo)
+[RUNNING] `[ROOT]/foo/target/debug/build/foo-[HASH]/build-script-build`
+[RUNNING] `rustc --crate-name foo [..]`
+[FINISHED] `dev` profile [unoptimized + debuginfo] target(s) in [ELAPSED]s
+
+"#]]);
+    fresh();
+}
+
+#[cargo_test]
+fn rerun_if_changed_glob_invalid() {
+    let p = project()
+        .file("Cargo.toml", &basic_manifest("foo", "0.1.0"))
+        .file("src/lib.rs", "")
+        .file(
+            "build.rs",
+            r#"
+                fn main() {
+                    println!("cargo::rerun-if-changed-glob=proto/[*.proto");
+                }
+            "#,
+        )
+        .build();
+
+    p.cargo("check -Zbuild-script-instructions")
+        .masquerade_as_nightly_cargo(&["build-script-instructions"])
+        .with_status(101)
+        .with_stderr_data(str![[r#"
+[COMPILING] foo v0.1.0 ([ROOT]/foo)
+[ERROR] invalid output in build script of `foo v0.1.0 ([ROOT]/foo)`: `cargo::rerun-if-changed-glob=proto/[*.proto`
+Invalid glob pattern: [..].
+See https://doc.rust-lang.org/cargo/reference/build-scripts.html#outputs-of-the-build-script for more information about build script outputs.
+
+"#]])
+        .run();
+}
+
+#[cargo_test]
+fn unstable_instructions_gated() {
+    let p = project()
+        .file("Cargo.toml", &basic_manifest("foo", "0.1.0"))
+        .file("src/lib.rs", "")
+        .file(
+            "build.rs",
+            r#"
+                fn main() {
+                    println!("cargo::rerun-if-changed-glob=proto/[*.proto");
+                    println!("cargo::input-file=missing.txt");
+                    println!("cargo::output-file=../../src/lib.rs");
+                    println!("cargo::metadata-json=a/b=1");
+                }
+            "#,
+        )
+        .build();
+
+    // The instructions are ignored without the flag, so the invalid values
+    // aren't errors.
+    p.cargo("check")
+        .with_stderr_data(str![[r#"
+[COMPILING] foo v0.1.0 ([ROOT]/foo)
+[WARNING] foo@0.1.0: `cargo::rerun-if-changed-glob` is unstable and was ignored, pass `-Zbuild-script-instructions` to enable it
+[WARNING] foo@0.1.0: `cargo::input-file` is unstable and was ignored, pass `-Zbuild-script-instructions` to enable it
+[WARNING] foo@0.1.0: `cargo::output-file` is unstable and was ignored, pass `-Zbuild-script-instructions` to enable it
+[WARNING] foo@0.1.0: `cargo::metadata-json` is unstable and was ignored, pass `-Zbuild-script-instructions` to enable it
+[FINISHED] `dev` profile [unoptimized + debuginfo] target(s) in [ELAPSED]s
+
+"#]])
+        .run();
+}
+
+#[cargo_test]
+fn input_and_output_files() {
+    let p = project()
+        .file("Cargo.toml", &basic_manifest("foo", "0.1.0"))
+        .file(
+            "src/lib.rs",
+            r#"include!(concat!(env!("OUT_DIR"), "/", env!("GENERATED")));"#,
+        )
+        .file("names.txt", "a.rs")
+        .file(
+            "build.rs",
+            r#"
+                fn main() {
+                    let out_dir = std::path::PathBuf::from(std::env::var("OUT_DIR").unwrap());
+                    let name = std::fs::read_to_string("names.txt").unwrap();
+                    std::fs::write(out_dir.join(&name), "").unwrap();
+                    println!("cargo::input-file=names.txt");
+                    println!("cargo::output-file={name}");
+                    println!("cargo::rustc-env=GENERATED={name}");
+                }
+            "#,
+        )
+        .build();
+
+    p.cargo("check --message-format=json -Zbuild-script-instructions")
+        .masquerade_as_nightly_cargo(&["build-script-instructions"])
+        .with_stdout_data(
+            str![[r#"
+[
+  "{...}",
+  {
+    "cfgs": [],
+    "env": [
+      [
+        "GENERATED",
+        "a.rs"
+      ]
+    ],
+    "input_files": [
+      "[ROOT]/foo/names.txt"
+    ],
+    "linked_libs": [],
+    "linked_paths": [],
+    "out_dir": "[ROOT]/foo/target/debug/build/foo-[HASH]/out",
+    "output_files": [
+      "[ROOT]/foo/target/debug/build/foo-[HASH]/out/a.rs"
+    ],
+    "package_id": "path+[ROOTURL]/foo#0.1.0",
+    "reason": "build-script-executed"
+  },
+  "{...}"
+]
+"#]]
+            .is_json()
+            .against_jsonlines(),
+        )
+        .run();
+    let out_dir = p
+        .glob("target/debug/build/foo-*/out")
+        .next()
+        .unwrap()
+        .unwrap();
+    assert!(out_dir.join("a.rs").exists());
+
+    // Changes to other files are ignored, since an input was declared.
+    p.change_file("src/other.rs", "");
+    p.cargo("check -v -Zbuild-script-instructions")
+        .masquerade_as_nightly_cargo(&["build-script-instructions"])
+        .with_stderr_data(str![[r#"
+[FRESH] foo v0.1.0 ([ROOT]/foo)
+[FINISHED] `dev` profile [unoptimized + debuginfo] target(s) in [ELAPSED]s
+
+"#]])
+        .run();
+
+    // A missing output file is generated again.
+    fs::remove_file(out_dir.join("a.rs")).unwrap();
+    p.cargo("check -v -Zbuild-script-instructions")
+        .masquerade_as_nightly_cargo(&["build-script-instructions"])
+        .with_stderr_data(str![[r#"
+[DIRTY] foo v0.1.0 ([ROOT]/foo): the file `target/debug/build/foo-[HASH]/out/a.rs` is missing
+[COMPILING] foo v0.1.0 ([ROOT]/foo)
+[RUNNING] `[ROOT]/foo/target/debug/build/foo-[HASH]/build-script-build`
+[RUNNING] `rustc --crate-name foo [..]`
+[FINISHED] `dev` profile [unoptimized + debuginfo] target(s) in [ELAPSED]s
+
+"#]])
+        .run();
+    assert!(out_dir.join("a.rs").exists());
+
+    // An output file that is no longer generated is removed.
+    sleep_ms(1000);
+    p.change_file("names.txt", "b.rs");
+    p.cargo("check -v -Zbuild-script-instructions")
+        .masquerade_as_nightly_cargo(&["build-script-instructions"])
+        .with_stderr_data(str![[r#"
+[DIRTY] foo v0.1.0 ([ROOT]/foo): the file `names.txt` has changed ([TIME_DIFF_AFTER_LAST_BUILD])
+[COMPILING] foo v0.1.0 ([ROOT]/foo)
+[RUNNING] `[ROOT]/foo/target/debug/build/foo-[HASH]/build-script-build`
+[RUNNING] `rustc --crate-name foo [..]`
+[FINISHED] `dev` profile [unoptimized + debuginfo] target(s) in [ELAPSED]s
+
+"#]])
+        .run();
+    assert!(!out_dir.join("a.rs").exists());
+    assert!(out_dir.join("b.rs").exists());
+}
+
+#[cargo_test]
+fn output_file_outside_out_dir() {
+    let p = project()
+        .file("Cargo.toml", &basic_manifest("foo", "0.1.0"))
+        .file("src/lib.rs", "")
+        .file(
+            "build.rs",
+            r#"
+                fn main() {
+                    println!("cargo::output-file=../../src/lib.rs");
+                    println!("cargo::metadata-json=a/b=1");
+                }
+            "#,
+        )
+        .build();
+
+    p.cargo("check -Zbuild-script-instructions")
+        .masquerade_as_nightly_cargo(&["build-script-instructions"])
+        .with_status(101)
+        .with_stderr_data(str![[r#"
+[COMPILING] foo v0.1.0 ([ROOT]/foo)
+[ERROR] invalid output in build script of `foo v0.1.0 ([ROOT]/foo)`: `cargo::output-file=../../src/lib.rs`
+Output files must be inside `OUT_DIR`.
+See https://doc.rust-lang.org/cargo/reference/build-scripts.html#outputs-of-the-build-script for more information about build script outputs.
+
+"#]])
+        .run();
+}
+
 #[cargo_test]
 fn rerun_if_published_directory() {
     // build script of a dependency contains a `rerun-if-changed` pointing to a directory
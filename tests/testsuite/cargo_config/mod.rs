@@ -4,6 +4,7 @@ use super::config::write_config_at;
 use cargo_test_support::paths;
 use cargo_test_support::prelude::*;
 use cargo_test_support::str;
+use cargo_test_support::{basic_manifest, project};
 use std::fs;
 use std::path::PathBuf;
 
@@ -578,3 +579,127 @@ target.'cfg(target_os = "linux")'.runner = "runme"
         .with_stderr_data(str![[r#""#]])
         .run();
 }
+
+#[cargo_test]
+fn effective_profile() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                cargo-features = ["profile-package-globs", "profile-target-overrides"]
+
+                [package]
+                name = "foo"
+                version = "0.0.1"
+                edition = "2015"
+
+                [dependencies]
+                aws-s3 = { path = "aws-s3" }
+
+                [profile.release]
+                debug = 1
+
+                [profile.release.bin]
+                opt-level = 2
+
+                [profile.release.package."aws-*"]
+                codegen-units = 4
+
+                [profile.custom]
+                inherits = "release"
+                lto = true
+            "#,
+        )
+        .file("src/main.rs", "fn main() {}")
+        .file("aws-s3/Cargo.toml", &basic_manifest("aws-s3", "0.5.0"))
+        .file("aws-s3/src/lib.rs", "")
+        .file(
+            ".cargo/config.toml",
+            r#"
+                [profile.release]
+                incremental = true
+            "#,
+        )
+        .build();
+
+    cargo_process("config get -Zunstable-options --effective profile.custom")
+        .cwd(p.root())
+        .masquerade_as_nightly_cargo(&[
+            "cargo-config",
+            "profile-package-globs",
+            "profile-target-overrides",
+        ])
+        .with_stdout_data(str![[r#"
+profile.custom.bin.opt-level = 2
+profile.custom.debug = 1
+profile.custom.incremental = true
+profile.custom.inherits = "release"
+profile.custom.lto = true
+profile.custom.package."aws-*".codegen-units = 4
+
+"#]])
+        .with_stderr_data(str![[r#""#]])
+        .run();
+
+    cargo_process(
+        "config get -Zunstable-options --effective profile.release --package aws-s3 --target-kind lib",
+    )
+    .cwd(p.root())
+    .masquerade_as_nightly_cargo(&[
+        "cargo-config",
+        "profile-package-globs",
+        "profile-target-overrides",
+    ])
+    .with_stdout_data(str![[r#"
+profile.release.codegen-units = 4
+profile.release.debug = 1
+profile.release.incremental = true
+
+"#]])
+    .with_stderr_data(str![[r#"
+[LOCKING] 1 package to latest compatible version
+
+"#]])
+    .run();
+
+    cargo_process(
+        "config get -Zunstable-options --effective profile.release --target-kind bin --format json",
+    )
+    .cwd(p.root())
+    .masquerade_as_nightly_cargo(&[
+        "cargo-config",
+        "profile-package-globs",
+        "profile-target-overrides",
+    ])
+    .with_stdout_data(
+        str![[r#"
+{
+  "profile": {
+    "release": {
+      "debug": 1,
+      "incremental": true,
+      "opt-level": 2
+    }
+  }
+}
+"#]]
+        .is_json(),
+    )
+    .with_stderr_data(str![[r#""#]])
+    .run();
+    assert!(!p.root().join("Cargo.lock").exists());
+
+    cargo_process("config get -Zunstable-options --effective build.jobs")
+        .cwd(p.root())
+        .masquerade_as_nightly_cargo(&[
+            "cargo-config",
+            "profile-package-globs",
+            "profile-target-overrides",
+        ])
+        .with_status(101)
+        .with_stderr_data(str![[r#"
+[ERROR] --effective requires the key of a profile, like `profile.release`
+
+"#]])
+        .run();
+}
@@ -0,0 +1,352 @@
+//! Tests for the `cargo profile` command.
+
+use cargo_test_support::prelude::*;
+use cargo_test_support::{basic_manifest, project, str, Project};
+
+mod help;
+
+fn profile_show(p: &Project, args: &str) -> cargo_test_support::Execs {
+    let mut e = p.cargo(&format!("profile show -Zunstable-options {args}"));
+    // Clear out the profile environment added by default so the origins
+    // don't depend on the platform.
+    e.masquerade_as_nightly_cargo(&["cargo-profile"])
+        .env_remove("CARGO_PROFILE_DEV_SPLIT_DEBUGINFO")
+        .env_remove("CARGO_PROFILE_TEST_SPLIT_DEBUGINFO")
+        .env_remove("CARGO_PROFILE_RELEASE_SPLIT_DEBUGINFO")
+        .env_remove("CARGO_PROFILE_BENCH_SPLIT_DEBUGINFO")
+        .env_remove("CARGO_INCREMENTAL");
+    e
+}
+
+#[cargo_test]
+fn gated() {
+    let p = project().file("src/lib.rs", "").build();
+    p.cargo("profile show")
+        .masquerade_as_nightly_cargo(&["cargo-profile"])
+        .with_status(101)
+        .with_stderr_data(str![[r#"
+[ERROR] the `cargo profile` command is unstable, pass `-Z unstable-options` to enable it
+
+"#]])
+        .run();
+}
+
+#[cargo_test]
+fn defaults() {
+    let p = project().file("src/lib.rs", "").build();
+    profile_show(&p, "")
+        .with_stdout_data(str![[r#"
+foo v0.0.1 ([ROOT]/foo) lib (build)
+  profile = "dev"
+  opt-level = "0" # default
+  lto = "false" # default
+  codegen-backend = null # default
+  codegen-units = null # default
+  debug = 2 # default
+  split-debuginfo = null # default
+  debug-assertions = true # default
+  overflow-checks = true # default
+  rpath = false # default
+  incremental = true # default
+  panic = "unwind" # default
+  strip = "none" # default
+
+"#]])
+        .with_stderr_data("")
+        .run();
+}
+
+#[cargo_test]
+fn origins() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.0.1"
+                edition = "2015"
+
+                [dependencies]
+                bar = { path = "bar" }
+
+                [build-dependencies]
+                bar = { path = "bar" }
+
+                [profile.dev]
+                opt-level = 1
+
+                [profile.dev.package.bar]
+                debug = 0
+
+                [profile.dev.build-override]
+                debug-assertions = false
+            "#,
+        )
+        .file("src/main.rs", "fn main() {}")
+        .file("build.rs", "fn main() {}")
+        .file("bar/Cargo.toml", &basic_manifest("bar", "0.5.0"))
+        .file("bar/src/lib.rs", "")
+        .file(
+            ".cargo/config.toml",
+            r#"
+                [profile.dev]
+                codegen-units = 4
+            "#,
+        )
+        .build();
+
+    profile_show(&p, "")
+        .env("CARGO_PROFILE_DEV_OVERFLOW_CHECKS", "false")
+        .env("CARGO_INCREMENTAL", "0")
+        .with_stdout_data(str![[r#"
+bar v0.5.0 ([ROOT]/foo/bar) lib (build, for host)
+  profile = "dev"
+  opt-level = "0" # default
+  lto = "false" # default
+  codegen-backend = null # default
+  codegen-units = null # default
+  debug = 0 # `profile.dev.package.bar` in [ROOT]/foo/Cargo.toml
+  split-debuginfo = null # default
+  debug-assertions = false # `profile.dev.build-override` in [ROOT]/foo/Cargo.toml
+  overflow-checks = false # `profile.dev` in environment variable `CARGO_PROFILE_DEV_OVERFLOW_CHECKS`
+  rpath = false # default
+  incremental = false # environment variable `CARGO_INCREMENTAL`
+  panic = "unwind" # default
+  strip = "debuginfo" # default
+
+bar v0.5.0 ([ROOT]/foo/bar) lib (build)
+  profile = "dev"
+  opt-level = "1" # `profile.dev` in [ROOT]/foo/Cargo.toml
+  lto = "false" # default
+  codegen-backend = null # default
+  codegen-units = 4 # `profile.dev` in [ROOT]/foo/.cargo/config.toml
+  debug = 0 # `profile.dev.package.bar` in [ROOT]/foo/Cargo.toml
+  split-debuginfo = null # default
+  debug-assertions = true # default
+  overflow-checks = false # `profile.dev` in environment variable `CARGO_PROFILE_DEV_OVERFLOW_CHECKS`
+  rpath = false # default
+  incremental = false # environment variable `CARGO_INCREMENTAL`
+  panic = "unwind" # default
+  strip = "debuginfo" # default
+
+foo v0.0.1 ([ROOT]/foo) bin "foo" (build)
+  profile = "dev"
+  opt-level = "1" # `profile.dev` in [ROOT]/foo/Cargo.toml
+  lto = "false" # default
+  codegen-backend = null # default
+  codegen-units = 4 # `profile.dev` in [ROOT]/foo/.cargo/config.toml
+  debug = 2 # default
+  split-debuginfo = null # default
+  debug-assertions = true # default
+  overflow-checks = false # `profile.dev` in environment variable `CARGO_PROFILE_DEV_OVERFLOW_CHECKS`
+  rpath = false # default
+  incremental = false # environment variable `CARGO_INCREMENTAL`
+  panic = "unwind" # default
+  strip = "none" # default
+
+foo v0.0.1 ([ROOT]/foo) build script (build, for host)
+  profile = "dev"
+  opt-level = "0" # default
+  lto = "false" # default
+  codegen-backend = null # default
+  codegen-units = null # default
+  debug = 0 # default
+  split-debuginfo = null # default
+  debug-assertions = false # `profile.dev.build-override` in [ROOT]/foo/Cargo.toml
+  overflow-checks = false # `profile.dev` in environment variable `CARGO_PROFILE_DEV_OVERFLOW_CHECKS`
+  rpath = false # default
+  incremental = false # environment variable `CARGO_INCREMENTAL`
+  panic = "unwind" # default
+  strip = "none" # default
+
+"#]])
+        .run();
+}
+
+#[cargo_test]
+fn inherits() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.0.1"
+                edition = "2015"
+
+                [profile.release]
+                lto = true
+
+                [profile.custom]
+                inherits = "release"
+                opt-level = 2
+            "#,
+        )
+        .file("src/main.rs", "fn main() {}")
+        .build();
+
+    profile_show(&p, "-p foo --bins --profile custom")
+        .with_stdout_data(str![[r#"
+...
+foo v0.0.1 ([ROOT]/foo) bin "foo" (build)
+  profile = "custom"
+  opt-level = "2" # `profile.custom` in [ROOT]/foo/Cargo.toml
+  lto = "true" # `profile.release` in [ROOT]/foo/Cargo.toml
+...
+"#]])
+        .run();
+}
+
+#[cargo_test]
+fn json() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.0.1"
+                edition = "2015"
+
+                [profile.release]
+                panic = "abort"
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+    p.change_file(
+        ".cargo/config.toml",
+        r#"
+            [profile.release]
+            codegen-units = 4
+        "#,
+    );
+
+    profile_show(&p, "--release --format json")
+        .env("CARGO_PROFILE_RELEASE_DEBUG", "1")
+        .with_stdout_data(
+            str![[r#"
+{
+  "units": [
+    {
+      "fields": [
+        {
+          "key": "opt-level",
+          "origin": {
+            "kind": "default"
+          },
+          "value": "3"
+        },
+        {
+          "key": "lto",
+          "origin": {
+            "kind": "default"
+          },
+          "value": "false"
+        },
+        {
+          "key": "codegen-backend",
+          "origin": {
+            "kind": "default"
+          },
+          "value": null
+        },
+        {
+          "key": "codegen-units",
+          "origin": {
+            "kind": "config",
+            "path": "[ROOT]/foo/.cargo/config.toml",
+            "table": "profile.release"
+          },
+          "value": 4
+        },
+        {
+          "key": "debug",
+          "origin": {
+            "kind": "env",
+            "name": "CARGO_PROFILE_RELEASE_DEBUG",
+            "table": "profile.release"
+          },
+          "value": 1
+        },
+        {
+          "key": "split-debuginfo",
+          "origin": {
+            "kind": "default"
+          },
+          "value": null
+        },
+        {
+          "key": "debug-assertions",
+          "origin": {
+            "kind": "default"
+          },
+          "value": false
+        },
+        {
+          "key": "overflow-checks",
+          "origin": {
+            "kind": "default"
+          },
+          "value": false
+        },
+        {
+          "key": "rpath",
+          "origin": {
+            "kind": "default"
+          },
+          "value": false
+        },
+        {
+          "key": "incremental",
+          "origin": {
+            "kind": "default"
+          },
+          "value": false
+        },
+        {
+          "key": "panic",
+          "origin": {
+            "kind": "manifest",
+            "path": "[ROOT]/foo/Cargo.toml",
+            "table": "profile.release"
+          },
+          "value": "abort"
+        },
+        {
+          "key": "strip",
+          "origin": {
+            "kind": "default"
+          },
+          "value": "none"
+        }
+      ],
+      "for_host": false,
+      "mode": "build",
+      "pkg_id": "foo 0.0.1 (path+[ROOTURL]/foo)",
+      "platform": null,
+      "profile": "release",
+      "target": {
+        "crate_types": [
+          "lib"
+        ],
+        "doc": true,
+        "doctest": true,
+        "edition": "2015",
+        "kind": [
+          "lib"
+        ],
+        "name": "foo",
+        "src_path": "[ROOT]/foo/src/lib.rs",
+        "test": true
+      }
+    }
+  ],
+  "version": 1
+}
+"#]]
+            .is_json(),
+        )
+        .run();
+}
@@ -0,0 +1,340 @@
+//! Tests for `--changed-since`.
+
+use cargo_test_support::prelude::*;
+use cargo_test_support::{basic_lib_manifest, git, project, str};
+
+#[cargo_test]
+fn gated() {
+    let p = project().file("src/lib.rs", "").build();
+
+    p.cargo("check --changed-since HEAD")
+        .with_status(101)
+        .with_stderr_data(str![[r#"
+[ERROR] the `--changed-since` flag is unstable, and only available on the nightly channel of Cargo, but this is the `stable` channel
+See https://doc.rust-lang.org/book/appendix-07-nightly-rust.html for more information about Rust release channels.
+
+"#]])
+        .run();
+}
+
+#[cargo_test]
+fn selects_dependents() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [workspace]
+                members = ["a", "b", "c"]
+                resolver = "2"
+            "#,
+        )
+        .file(".gitignore", "/target\n")
+        .file("a/Cargo.toml", &basic_lib_manifest("a"))
+        .file("a/src/lib.rs", "pub fn a() {}")
+        .file(
+            "b/Cargo.toml",
+            r#"
+                [package]
+                name = "b"
+                version = "0.5.0"
+                edition = "2015"
+
+                [dependencies]
+                a = { path = "../a" }
+            "#,
+        )
+        .file("b/src/lib.rs", "pub fn b() { a::a() }")
+        .file("c/Cargo.toml", &basic_lib_manifest("c"))
+        .file("c/src/lib.rs", "")
+        .build();
+    p.cargo("generate-lockfile").run();
+    let repo = git::init(&p.root());
+    git::add(&repo);
+    git::commit(&repo);
+    p.change_file("a/src/lib.rs", "pub fn a() { }");
+
+    p.cargo("check --changed-since HEAD -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .with_stderr_data(
+            str![[r#"
+[CHECKING] a v0.5.0 ([ROOT]/foo/a)
+[CHECKING] b v0.5.0 ([ROOT]/foo/b)
+[FINISHED] `dev` profile [unoptimized + debuginfo] target(s) in [ELAPSED]s
+
+"#]]
+            .unordered(),
+        )
+        .run();
+
+    p.cargo("test --changed-since HEAD -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .with_stderr_data(
+            str![[r#"
+[COMPILING] a v0.5.0 ([ROOT]/foo/a)
+[COMPILING] b v0.5.0 ([ROOT]/foo/b)
+[FINISHED] `test` profile [unoptimized + debuginfo] target(s) in [ELAPSED]s
+[RUNNING] unittests src/lib.rs (target/debug/deps/a-[HASH][EXE])
+[RUNNING] unittests src/lib.rs (target/debug/deps/b-[HASH][EXE])
+[DOCTEST] a
+[DOCTEST] b
+
+"#]]
+            .unordered(),
+        )
+        .run();
+}
+
+#[cargo_test]
+fn untracked_file() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [workspace]
+                members = ["a", "b"]
+                resolver = "2"
+            "#,
+        )
+        .file(".gitignore", "/target\n")
+        .file("a/Cargo.toml", &basic_lib_manifest("a"))
+        .file("a/src/lib.rs", "pub fn a() {}")
+        .file(
+            "b/Cargo.toml",
+            r#"
+                [package]
+                name = "b"
+                version = "0.5.0"
+                edition = "2015"
+
+                [dependencies]
+                a = { path = "../a" }
+            "#,
+        )
+        .file("b/src/lib.rs", "pub fn b() { a::a() }")
+        .build();
+    p.cargo("generate-lockfile").run();
+    let repo = git::init(&p.root());
+    git::add(&repo);
+    git::commit(&repo);
+    p.change_file("b/src/new.rs", "");
+
+    p.cargo("build --changed-since HEAD -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .with_stderr_data(str![[r#"
+[COMPILING] a v0.5.0 ([ROOT]/foo/a)
+[COMPILING] b v0.5.0 ([ROOT]/foo/b)
+[FINISHED] `dev` profile [unoptimized + debuginfo] target(s) in [ELAPSED]s
+
+"#]])
+        .run();
+}
+
+#[cargo_test]
+fn file_in_dep_info() {
+    // `c` includes a file from outside of its package.
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [workspace]
+                members = ["a", "c"]
+                resolver = "2"
+            "#,
+        )
+        .file(".gitignore", "/target\n")
+        .file("a/Cargo.toml", &basic_lib_manifest("a"))
+        .file("a/src/lib.rs", "pub fn a() {}")
+        .file("c/Cargo.toml", &basic_lib_manifest("c"))
+        .file(
+            "c/src/lib.rs",
+            r#"pub const DATA: &str = include_str!("../../shared/data.txt");"#,
+        )
+        .file("shared/data.txt", "data")
+        .build();
+    p.cargo("generate-lockfile").run();
+    let repo = git::init(&p.root());
+    git::add(&repo);
+    git::commit(&repo);
+    p.cargo("check").run();
+    p.change_file("shared/data.txt", "changed");
+
+    p.cargo("check --changed-since HEAD -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .with_stderr_data(str![[r#"
+[CHECKING] c v0.5.0 ([ROOT]/foo/c)
+[FINISHED] `dev` profile [unoptimized + debuginfo] target(s) in [ELAPSED]s
+
+"#]])
+        .run();
+}
+
+#[cargo_test]
+fn root_manifest_selects_all() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [workspace]
+                members = ["a", "c"]
+                resolver = "2"
+            "#,
+        )
+        .file(".gitignore", "/target\n")
+        .file("a/Cargo.toml", &basic_lib_manifest("a"))
+        .file("a/src/lib.rs", "pub fn a() {}")
+        .file("c/Cargo.toml", &basic_lib_manifest("c"))
+        .file("c/src/lib.rs", "")
+        .build();
+    p.cargo("generate-lockfile").run();
+    let repo = git::init(&p.root());
+    git::add(&repo);
+    git::commit(&repo);
+    p.change_file(
+        "Cargo.toml",
+        r#"
+            [workspace]
+            members = ["a", "c"]
+            resolver = "2"
+
+            [profile.dev]
+            debug = 1
+        "#,
+    );
+
+    p.cargo("check --changed-since HEAD -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .with_stderr_data(
+            str![[r#"
+[CHECKING] a v0.5.0 ([ROOT]/foo/a)
+[CHECKING] c v0.5.0 ([ROOT]/foo/c)
+[FINISHED] `dev` profile [unoptimized + debuginfo] target(s) in [ELAPSED]s
+
+"#]]
+            .unordered(),
+        )
+        .run();
+}
+
+#[cargo_test]
+fn config_and_toolchain_select_all() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [workspace]
+                members = ["a", "c"]
+                resolver = "2"
+            "#,
+        )
+        .file(".gitignore", "/target\n")
+        .file("a/Cargo.toml", &basic_lib_manifest("a"))
+        .file("a/src/lib.rs", "pub fn a() {}")
+        .file("c/Cargo.toml", &basic_lib_manifest("c"))
+        .file("c/src/lib.rs", "")
+        .build();
+    p.cargo("generate-lockfile").run();
+    let repo = git::init(&p.root());
+    git::add(&repo);
+    git::commit(&repo);
+    p.change_file(
+        ".cargo/config.toml",
+        r#"
+            [term]
+            verbose = false
+        "#,
+    );
+
+    p.cargo("check --changed-since HEAD -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .with_stderr_data(
+            str![[r#"
+[CHECKING] a v0.5.0 ([ROOT]/foo/a)
+[CHECKING] c v0.5.0 ([ROOT]/foo/c)
+[FINISHED] `dev` profile [unoptimized + debuginfo] target(s) in [ELAPSED]s
+
+"#]]
+            .unordered(),
+        )
+        .run();
+
+    std::fs::remove_dir_all(p.root().join(".cargo")).unwrap();
+    p.change_file(
+        "rust-toolchain.toml",
+        r#"
+            [toolchain]
+            channel = "stable"
+        "#,
+    );
+
+    p.cargo("build --changed-since HEAD -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .with_stderr_data(
+            str![[r#"
+[COMPILING] a v0.5.0 ([ROOT]/foo/a)
+[COMPILING] c v0.5.0 ([ROOT]/foo/c)
+[FINISHED] `dev` profile [unoptimized + debuginfo] target(s) in [ELAPSED]s
+
+"#]]
+            .unordered(),
+        )
+        .run();
+}
+
+#[cargo_test]
+fn no_changes() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [workspace]
+                members = ["a"]
+                resolver = "2"
+            "#,
+        )
+        .file(".gitignore", "/target\n")
+        .file("a/Cargo.toml", &basic_lib_manifest("a"))
+        .file("a/src/lib.rs", "pub fn a() {}")
+        .build();
+    p.cargo("generate-lockfile").run();
+    let repo = git::init(&p.root());
+    git::add(&repo);
+    git::commit(&repo);
+
+    p.cargo("check --changed-since HEAD -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .with_stderr_data(str![[r#"
+[NOTE] no packages changed since `HEAD`
+[FINISHED] `dev` profile [unoptimized + debuginfo] target(s) in [ELAPSED]s
+
+"#]])
+        .run();
+
+    p.cargo("test --changed-since HEAD -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .with_stderr_data(str![[r#"
+[NOTE] no packages changed since `HEAD`
+[FINISHED] `test` profile [unoptimized + debuginfo] target(s) in [ELAPSED]s
+
+"#]])
+        .run();
+}
+
+#[cargo_test]
+fn unknown_ref() {
+    let p = project().file("src/lib.rs", "").build();
+    let repo = git::init(&p.root());
+    git::add(&repo);
+    git::commit(&repo);
+
+    p.cargo("check --changed-since nope -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .with_status(101)
+        .with_stderr_data(str![[r#"
+[ERROR] failed to find git ref `nope`
+
+Caused by:
+  revspec 'nope' not found; class=Reference (4); code=NotFound (-3)
+
+"#]])
+        .run();
+}
@@ -1663,7 +1663,15 @@ fn all_profile_options() {
         strip: Some(cargo_toml::StringOrBool::String("symbols".to_string())),
         package: None,
         build_override: None,
+        lib: None,
+        bin: None,
+        example: None,
+        test: None,
+        bench: None,
         rustflags: None,
+        build_std: Some(vec!["std".to_string()]),
+        coverage: Some(true),
+        build_std_features: Some(vec!["panic-unwind".to_string()]),
         trim_paths: None,
     };
     let mut overrides = BTreeMap::new();
@@ -1672,6 +1680,7 @@ fn all_profile_options() {
     let profile = cargo_toml::TomlProfile {
         build_override: Some(Box::new(base_settings.clone())),
         package: Some(overrides),
+        bin: Some(Box::new(base_settings.clone())),
         ..base_settings
     };
     let profile_toml = toml::to_string(&profile).unwrap();
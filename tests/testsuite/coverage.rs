@@ -0,0 +1,233 @@
+//! Tests for the `coverage` profile setting and `cargo test --coverage`.
+
+use cargo_test_support::prelude::*;
+use cargo_test_support::{basic_manifest, project, str, Project};
+
+/// Lists the executables that wrote profile data to `dir`.
+fn profraw_executables(p: &Project, dir: &str) -> Vec<String> {
+    let mut exes: Vec<_> = p
+        .root()
+        .join(dir)
+        .read_dir()
+        .unwrap()
+        .map(|entry| {
+            let entry = entry.unwrap();
+            let has_data = entry
+                .path()
+                .read_dir()
+                .unwrap()
+                .any(|file| file.unwrap().path().extension().unwrap() == "profraw");
+            assert!(has_data, "no profile data in {}", entry.path().display());
+            let name = entry.file_name().into_string().unwrap();
+            name.rsplit_once('-').unwrap().0.to_string()
+        })
+        .collect();
+    exes.sort();
+    exes
+}
+
+#[cargo_test]
+fn gated() {
+    let p = project().file("src/lib.rs", "").build();
+
+    p.cargo("test --coverage")
+        .masquerade_as_nightly_cargo(&["coverage"])
+        .with_status(101)
+        .with_stderr_data(str![[r#"
+[ERROR] the `--coverage` flag is unstable, pass `-Z coverage` to enable it
+
+"#]])
+        .run();
+
+    p.change_file(
+        ".cargo/config.toml",
+        r#"
+            [profile.dev]
+            coverage = true
+        "#,
+    );
+    p.cargo("test")
+        .masquerade_as_nightly_cargo(&["coverage"])
+        .with_status(101)
+        .with_stderr_data(str![[r#"
+[ERROR] config profile `dev` is not valid (defined in `[ROOT]/foo/.cargo/config.toml`)
+
+Caused by:
+  `profile.dev.coverage` is unstable, pass `-Zcoverage` to enable it
+
+"#]])
+        .run();
+}
+
+#[cargo_test]
+fn instruments_members_only() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.1.0"
+                edition = "2015"
+
+                [dependencies]
+                bar = { path = "bar" }
+            "#,
+        )
+        .file(
+            "src/lib.rs",
+            r#"
+                pub fn foo() -> u32 {
+                    bar::bar()
+                }
+
+                #[test]
+                fn it_works() {
+                    assert_eq!(foo(), 1);
+                }
+            "#,
+        )
+        .file("bar/Cargo.toml", &basic_manifest("bar", "0.1.0"))
+        .file("bar/src/lib.rs", "pub fn bar() -> u32 { 1 }")
+        .build();
+
+    p.cargo("test --coverage -v -Zcoverage")
+        .masquerade_as_nightly_cargo(&["coverage"])
+        .with_stderr_line_without(
+            &["[RUNNING] `rustc --crate-name bar"],
+            &["-C instrument-coverage"],
+        )
+        .with_stderr_line_without(&["[RUNNING] `rustc --crate-name foo", "--test"], &[])
+        .with_stderr_data(str![[r#"
+...
+[RUNNING] `rustc --crate-name foo [..]-C instrument-coverage --test [..]`
+[FINISHED] `coverage` profile [unoptimized + debuginfo] target(s) in [ELAPSED]s
+[RUNNING] `[ROOT]/foo/target/coverage/deps/foo-[HASH][EXE]`
+...
+"#]])
+        .run();
+
+    assert_eq!(profraw_executables(&p, "target/coverage/profraw"), ["foo"]);
+}
+
+#[cargo_test]
+fn with_pipelined_tests() {
+    let p = project()
+        .file("Cargo.toml", &basic_manifest("foo", "0.1.0"))
+        .file("src/lib.rs", "#[test] fn lib_works() {}")
+        .file("tests/t.rs", "#[test] fn t_works() {}")
+        .build();
+
+    p.cargo("test --coverage -Zcoverage -Zpipelined-tests")
+        .masquerade_as_nightly_cargo(&["coverage", "pipelined-tests"])
+        .with_stderr_data(
+            str![[r#"
+[COMPILING] foo v0.1.0 ([ROOT]/foo)
+[RUNNING] tests/t.rs (target/coverage/deps/t-[HASH][EXE])
+[RUNNING] unittests src/lib.rs (target/coverage/deps/foo-[HASH][EXE])
+[FINISHED] `coverage` profile [unoptimized + debuginfo] target(s) in [ELAPSED]s
+[DOCTEST] foo
+...
+
+"#]]
+            .unordered(),
+        )
+        .run();
+
+    assert_eq!(
+        profraw_executables(&p, "target/coverage/profraw"),
+        ["foo", "t"]
+    );
+}
+
+#[cargo_test]
+fn named_profile() {
+    let p = project()
+        .file(
+            "src/lib.rs",
+            r#"
+                pub fn foo() {}
+
+                #[test]
+                fn it_works() {
+                    foo();
+                }
+            "#,
+        )
+        .file(
+            "tests/integration.rs",
+            "#[test] fn it_works() { foo::foo(); }",
+        )
+        .file(
+            ".cargo/config.toml",
+            r#"
+                [profile.ci]
+                inherits = "test"
+                coverage = true
+            "#,
+        )
+        .build();
+
+    p.cargo("test --profile ci -Zcoverage")
+        .masquerade_as_nightly_cargo(&["coverage"])
+        .run();
+
+    assert_eq!(
+        profraw_executables(&p, "target/ci/profraw"),
+        ["foo", "integration"]
+    );
+
+    // Data of earlier runs is removed.
+    p.cargo("test --profile ci --test integration -Zcoverage")
+        .masquerade_as_nightly_cargo(&["coverage"])
+        .run();
+
+    assert_eq!(
+        profraw_executables(&p, "target/ci/profraw"),
+        ["integration"]
+    );
+}
+
+#[cargo_test]
+fn not_in_package_profile() {
+    let p = project()
+        .file("src/lib.rs", "")
+        .file(
+            ".cargo/config.toml",
+            r#"
+                [profile.dev.package.bar]
+                coverage = true
+            "#,
+        )
+        .build();
+
+    p.cargo("test -Zcoverage")
+        .masquerade_as_nightly_cargo(&["coverage"])
+        .with_status(101)
+        .with_stderr_data(str![[r#"
+[ERROR] config profile `dev` is not valid (defined in `[ROOT]/foo/.cargo/config.toml`)
+
+Caused by:
+  `coverage` may not be specified in a `package` profile
+
+"#]])
+        .run();
+}
+
+#[cargo_test]
+fn conflicts_with_release() {
+    let p = project().file("src/lib.rs", "").build();
+
+    p.cargo("test --coverage --release -Zcoverage")
+        .masquerade_as_nightly_cargo(&["coverage"])
+        .with_status(1)
+        .with_stderr_data(str![[r#"
+[ERROR] the argument '--coverage' cannot be used with '--release'
+
+Usage: cargo[EXE] test --coverage -Z <FLAG> [TESTNAME] [-- [ARGS]...]
+
+For more information, try '--help'.
+
+"#]])
+        .run();
+}
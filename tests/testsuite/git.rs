@@ -3635,7 +3635,8 @@ fn metadata_master_consistency() {
                         "dep_kinds": [
                           {
                             "kind": null,
-                            "target": null
+                            "target": null,
+                            "extern_name": "bar"
                           }
                         ]
                       }
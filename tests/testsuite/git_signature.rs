@@ -0,0 +1,227 @@
+//! Tests for `-Zgit-verify-signature`.
+
+use cargo_test_support::prelude::*;
+use cargo_test_support::{basic_manifest, git, paths, project, str, Project};
+
+fn signed_dep(key: &std::path::Path) -> (Project, git2::Repository) {
+    let (dep, repo) = git::new_repo("dep", |project| {
+        project
+            .file("Cargo.toml", &basic_manifest("dep", "1.0.0"))
+            .file("src/lib.rs", "")
+    });
+    dep.change_file("src/lib.rs", "pub fn f() {}");
+    git::add(&repo);
+    git::commit_signed(&repo, key);
+    (dep, repo)
+}
+
+fn project_with(dep: &Project, extra: &str, allowed_signers: &std::path::Path) -> Project {
+    project()
+        .file(
+            "Cargo.toml",
+            &format!(
+                r#"
+                    [package]
+                    name = "foo"
+                    version = "0.1.0"
+                    edition = "2015"
+
+                    [dependencies]
+                    dep = {{ git = '{}'{extra} }}
+                "#,
+                dep.url()
+            ),
+        )
+        .file("src/lib.rs", "")
+        .file(
+            ".cargo/config.toml",
+            &format!(
+                r#"
+                    [git]
+                    verify-signature = true
+                    allowed-signers = '{}'
+                "#,
+                allowed_signers.display()
+            ),
+        )
+        .build()
+}
+
+#[cargo_test(requires_git, requires_ssh_keygen)]
+fn signed_commit() {
+    let (key, allowed_signers) = git::ssh_signing_key(&paths::home().join("keys"));
+    let (dep, _repo) = signed_dep(&key);
+    let p = project_with(&dep, "", &allowed_signers);
+
+    p.cargo("check -Zgit-verify-signature -v")
+        .masquerade_as_nightly_cargo(&["git-verify-signature"])
+        .with_stderr_data(str![[r#"
+[UPDATING] git repository `[ROOTURL]/dep`
+[RUNNING] `git -c gpg.ssh.allowedSignersFile=[ROOT]/home/keys/allowed_signers verify-commit [..]`
+[VERIFIED] signature of commit `[..]` in `[ROOTURL]/dep`
+[LOCKING] 1 package to latest compatible version
+[CHECKING] dep v1.0.0 ([ROOTURL]/dep#[..])
+[RUNNING] `rustc --crate-name dep [..]`
+[CHECKING] foo v0.1.0 ([ROOT]/foo)
+[RUNNING] `rustc --crate-name foo [..]`
+[FINISHED] `dev` profile [unoptimized + debuginfo] target(s) in [ELAPSED]s
+
+"#]])
+        .run();
+
+    // The result is cached, so `git` isn't run again.
+    p.cargo("check -Zgit-verify-signature -v")
+        .masquerade_as_nightly_cargo(&["git-verify-signature"])
+        .with_stderr_does_not_contain("[..]verify-commit[..]")
+        .run();
+}
+
+#[cargo_test(requires_git, requires_ssh_keygen)]
+fn unsigned_commit() {
+    let (key, allowed_signers) = git::ssh_signing_key(&paths::home().join("keys"));
+    let (dep, repo) = signed_dep(&key);
+    dep.change_file("src/lib.rs", "pub fn f() { }");
+    git::add(&repo);
+    git::commit(&repo);
+    let p = project_with(&dep, "", &allowed_signers);
+
+    p.cargo("check -Zgit-verify-signature")
+        .masquerade_as_nightly_cargo(&["git-verify-signature"])
+        .with_status(101)
+        .with_stderr_data(str![[r#"
+[UPDATING] git repository `[ROOTURL]/dep`
+[ERROR] failed to get `dep` as a dependency of package `foo v0.1.0 ([ROOT]/foo)`
+
+Caused by:
+  failed to load source for dependency `dep`
+
+Caused by:
+  Unable to update [ROOTURL]/dep
+
+Caused by:
+  failed to verify the signature of commit `[..]` in git repository `[ROOTURL]/dep`
+
+  [HELP] the signing key must be listed in `[ROOT]/home/keys/allowed_signers` (from `git.allowed-signers`)
+
+"#]])
+        .run();
+
+    // The unsigned commit is accepted as long as `-Zgit-verify-signature`
+    // isn't passed.
+    p.cargo("check").run();
+}
+
+#[cargo_test(requires_git, requires_ssh_keygen)]
+fn untrusted_key() {
+    let (key, _) = git::ssh_signing_key(&paths::home().join("keys"));
+    let (_, allowed_signers) = git::ssh_signing_key(&paths::home().join("other-keys"));
+    let (dep, _repo) = signed_dep(&key);
+    let p = project_with(&dep, "", &allowed_signers);
+
+    p.cargo("check -Zgit-verify-signature")
+        .masquerade_as_nightly_cargo(&["git-verify-signature"])
+        .with_status(101)
+        .with_stderr_data(str![[r#"
+[UPDATING] git repository `[ROOTURL]/dep`
+[ERROR] failed to get `dep` as a dependency of package `foo v0.1.0 ([ROOT]/foo)`
+
+Caused by:
+  failed to load source for dependency `dep`
+
+Caused by:
+  Unable to update [ROOTURL]/dep
+
+Caused by:
+  failed to verify the signature of commit `[..]` in git repository `[ROOTURL]/dep`
+
+  Good "git" signature with ED25519 key SHA256:[..]
+  No principal matched.
+
+  [HELP] the signing key must be listed in `[ROOT]/home/other-keys/allowed_signers` (from `git.allowed-signers`)
+
+"#]])
+        .run();
+}
+
+#[cargo_test(requires_git, requires_ssh_keygen)]
+fn signed_tag() {
+    let (key, allowed_signers) = git::ssh_signing_key(&paths::home().join("keys"));
+    let (dep, repo) = git::new_repo("dep", |project| {
+        project
+            .file("Cargo.toml", &basic_manifest("dep", "1.0.0"))
+            .file("src/lib.rs", "")
+    });
+    git::tag_signed(&repo, "v1.0.0", &key);
+    let p = project_with(&dep, ", tag = \"v1.0.0\"", &allowed_signers);
+
+    p.cargo("check -Zgit-verify-signature -v")
+        .masquerade_as_nightly_cargo(&["git-verify-signature"])
+        .with_stderr_contains("[RUNNING] `git [..] verify-tag [..]`")
+        .with_stderr_contains("[VERIFIED] signature of tag `[..]` in `[ROOTURL]/dep`")
+        .run();
+}
+
+#[cargo_test(requires_git, requires_ssh_keygen)]
+fn per_repository() {
+    let (key, allowed_signers) = git::ssh_signing_key(&paths::home().join("keys"));
+    let (dep, repo) = signed_dep(&key);
+    dep.change_file("src/lib.rs", "pub fn f() { }");
+    git::add(&repo);
+    git::commit(&repo);
+    let p = project_with(&dep, "", &allowed_signers);
+
+    // Only the repository that asks for it is verified.
+    p.change_file(
+        ".cargo/config.toml",
+        &format!(
+            r#"
+                [git.repository.'https://example.com/other']
+                verify-signature = false
+
+                [git.repository.'{}']
+                verify-signature = true
+                allowed-signers = '{}'
+            "#,
+            dep.url(),
+            allowed_signers.display()
+        ),
+    );
+    p.cargo("check -Zgit-verify-signature")
+        .masquerade_as_nightly_cargo(&["git-verify-signature"])
+        .with_status(101)
+        .with_stderr_data(str![[r#"
+[UPDATING] git repository `[ROOTURL]/dep`
+[ERROR] failed to get `dep` as a dependency of package `foo v0.1.0 ([ROOT]/foo)`
+
+Caused by:
+  failed to load source for dependency `dep`
+
+Caused by:
+  Unable to update [ROOTURL]/dep
+
+Caused by:
+  failed to verify the signature of commit `[..]` in git repository `[ROOTURL]/dep`
+
+  [HELP] the signing key must be listed in `[ROOT]/home/keys/allowed_signers` (from `git.repository."[ROOTURL]/dep".allowed-signers`)
+
+"#]])
+        .run();
+
+    // A repository can opt out of `git.verify-signature`.
+    p.change_file(
+        ".cargo/config.toml",
+        &format!(
+            r#"
+                [git]
+                verify-signature = true
+
+                [git.repository.'{}']
+                verify-signature = false
+            "#,
+            dep.url(),
+        ),
+    );
+    p.cargo("check -Zgit-verify-signature")
+        .masquerade_as_nightly_cargo(&["git-verify-signature"])
+        .run();
+}
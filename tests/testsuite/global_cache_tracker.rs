@@ -21,7 +21,7 @@ use cargo::GlobalContext;
 use cargo_test_support::compare::assert_e2e;
 use cargo_test_support::paths;
 use cargo_test_support::prelude::*;
-use cargo_test_support::registry::{Package, RegistryBuilder};
+use cargo_test_support::registry::{self, Package, RegistryBuilder};
 use cargo_test_support::{
     basic_manifest, cargo_process, execs, git, process, project, retry, sleep_ms, str,
     thread_wait_timeout, Execs, Project,
@@ -2091,3 +2091,118 @@ fn forward_compatible() {
     assert_eq!(cos.len(), 1);
     drop(lock);
 }
+
+#[cargo_test]
+fn shared_registry_src() {
+    // -Zshared-registry-src unpacks the same `.crate` from two registries
+    // only once, and gc keeps it until neither refers to it.
+    registry::alt_init();
+    Package::new("bar", "1.0.0").publish();
+    Package::new("bar", "1.0.0").alternative(true).publish();
+    let both = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.1.0"
+                edition = "2015"
+
+                [dependencies]
+                bar = "1.0"
+                alt-bar = { package = "bar", version = "1.0", registry = "alternative" }
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+    both.cargo("fetch -Zgc -Zshared-registry-src")
+        .masquerade_as_nightly_cargo(&["gc", "shared-registry-src"])
+        .env("__CARGO_TEST_LAST_USE_NOW", days_ago_unix(4))
+        .run();
+
+    assert_eq!(get_registry_names("src"), ["bar-1.0.0", "bar-1.0.0"]);
+    assert_eq!(get_registry_names("src-store"), ["bar-1.0.0"]);
+    let store = glob::glob(
+        paths::home()
+            .join(".cargo/registry/src-store/*")
+            .to_str()
+            .unwrap(),
+    )
+    .unwrap()
+    .next()
+    .unwrap()
+    .unwrap();
+    for src in glob::glob(
+        paths::home()
+            .join(".cargo/registry/src/*/*")
+            .to_str()
+            .unwrap(),
+    )
+    .unwrap()
+    {
+        let src = src.unwrap();
+        assert!(src.symlink_metadata().unwrap().is_symlink());
+        assert_eq!(
+            src.canonicalize().unwrap(),
+            store.join("bar-1.0.0").canonicalize().unwrap()
+        );
+        assert!(src.join("src/lib.rs").exists());
+    }
+
+    let alt_only = project()
+        .at("alt_only")
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "alt_only"
+                version = "0.1.0"
+                edition = "2015"
+
+                [dependencies]
+                bar = { version = "1.0", registry = "alternative" }
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+    alt_only
+        .cargo("check -Zgc -Zshared-registry-src")
+        .masquerade_as_nightly_cargo(&["gc", "shared-registry-src"])
+        .env("__CARGO_TEST_LAST_USE_NOW", days_ago_unix(2))
+        .run();
+
+    // The link from crates.io is removed, but the store entry is still in use.
+    alt_only
+        .cargo("clean gc -v -Zgc")
+        .arg("--max-src-age=3 days")
+        .masquerade_as_nightly_cargo(&["gc"])
+        .with_stderr_data(str![[r#"
+[REMOVING] [ROOT]/home/.cargo/registry/src/-[HASH]/bar-1.0.0
+[REMOVED] 1 file, [FILE_SIZE]B total
+
+"#]])
+        .run();
+    assert_eq!(get_registry_names("src"), ["bar-1.0.0"]);
+    assert_eq!(get_registry_names("src-store"), ["bar-1.0.0"]);
+
+    // Removing the last link also removes the store entry.
+    alt_only
+        .cargo("clean gc -v -Zgc")
+        .arg("--max-src-age=1 day")
+        .masquerade_as_nightly_cargo(&["gc"])
+        .with_stderr_data(str![[r#"
+[REMOVING] [ROOT]/home/.cargo/registry/src/-[HASH]/bar-1.0.0
+[REMOVING] [ROOT]/home/.cargo/registry/src-store/[..]
+[REMOVED] [FILE_NUM] files, [FILE_SIZE]B total
+
+"#]])
+        .run();
+    assert_eq!(get_registry_names("src"), Vec::<String>::new());
+    assert_eq!(get_registry_names("src-store"), Vec::<String>::new());
+
+    // The sources are unpacked again as needed.
+    both.cargo("check -Zgc -Zshared-registry-src")
+        .masquerade_as_nightly_cargo(&["gc", "shared-registry-src"])
+        .run();
+    assert_eq!(get_registry_names("src-store"), ["bar-1.0.0"]);
+}
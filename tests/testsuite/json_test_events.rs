@@ -0,0 +1,314 @@
+//! Tests for `-Zjson-test-events`.
+
+use cargo_test_support::prelude::*;
+use cargo_test_support::{project, str, Project};
+use serde_json::Value;
+
+/// Returns the `test` and `test-summary` messages printed by `cargo`.
+fn test_messages(p: &Project, args: &str, stderr: impl IntoData) -> Vec<Value> {
+    let output = p
+        .cargo(args)
+        .arg("--message-format=json")
+        .arg("-Zjson-test-events")
+        .masquerade_as_nightly_cargo(&["json-test-events"])
+        .with_status(101)
+        .with_stderr_data(stderr)
+        .run();
+    String::from_utf8(output.stdout)
+        .unwrap()
+        .lines()
+        .map(|line| serde_json::from_str::<Value>(line).unwrap())
+        .filter(|msg| matches!(msg["reason"].as_str(), Some("test" | "test-summary")))
+        .collect()
+}
+
+/// Describes a `test` message in a single line, without the parts that
+/// change between runs.
+fn describe(msg: &Value) -> String {
+    let mut line = format!(
+        "{} {} {} {}",
+        msg["mode"].as_str().unwrap(),
+        msg["target"]["name"].as_str().unwrap(),
+        msg["name"].as_str().unwrap(),
+        msg["event"].as_str().unwrap(),
+    );
+    if let Some(message) = msg["message"].as_str() {
+        line.push_str(&format!(" ({message})"));
+    }
+    line
+}
+
+#[cargo_test]
+fn events_for_each_test() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.1.0"
+                edition = "2018"
+
+                [[test]]
+                name = "custom"
+                harness = false
+            "#,
+        )
+        .file(
+            "src/lib.rs",
+            r#"
+                /// ```
+                /// foo::a();
+                /// ```
+                pub fn a() {}
+
+                #[test]
+                fn passes() {
+                    println!("passes output");
+                }
+
+                #[test]
+                fn fails() {
+                    println!("fails output");
+                    panic!("FALSE!");
+                }
+
+                #[test]
+                #[ignore = "too slow"]
+                fn ignored() {}
+            "#,
+        )
+        .file(
+            "tests/custom.rs",
+            r#"fn main() { println!("custom output"); }"#,
+        )
+        .build();
+
+    let messages = test_messages(
+        &p,
+        "test --no-fail-fast",
+        str![[r#"
+[COMPILING] foo v0.1.0 ([ROOT]/foo)
+[FINISHED] `test` profile [unoptimized + debuginfo] target(s) in [ELAPSED]s
+[RUNNING] unittests src/lib.rs (target/debug/deps/foo-[HASH][EXE])
+[ERROR] test failed, to rerun pass `--lib`
+[RUNNING] tests/custom.rs (target/debug/deps/custom-[HASH][EXE])
+[DOCTEST] foo
+[ERROR] 1 target failed:
+    `--lib`
+
+"#]],
+    );
+    let (summary, tests) = messages.split_last().unwrap();
+
+    let mut described: Vec<_> = tests.iter().map(describe).collect();
+    described.sort();
+    assert_eq!(
+        described,
+        [
+            "doctest foo src/lib.rs - a (line 2) passed",
+            "doctest foo src/lib.rs - a (line 2) started",
+            "test custom custom passed",
+            "test custom custom started",
+            "test foo fails failed",
+            "test foo fails started",
+            "test foo ignored ignored (too slow)",
+            "test foo ignored started",
+            "test foo passes passed",
+            "test foo passes started",
+        ]
+    );
+
+    for msg in tests {
+        assert_eq!(msg["version"], 2);
+        assert_eq!(msg["package_id"], format!("path+{}#0.1.0", p.url()));
+        assert_eq!(msg["executable"].is_null(), msg["mode"] == "doctest");
+        let output = msg["output"].as_str().unwrap_or_default();
+        match (
+            msg["name"].as_str().unwrap(),
+            msg["event"].as_str().unwrap(),
+        ) {
+            ("passes", "passed") => assert_eq!(output, "passes output\n"),
+            ("fails", "failed") => {
+                assert!(output.starts_with("fails output\n"), "{output}");
+                assert!(output.contains("FALSE!"), "{output}");
+            }
+            ("custom", "passed") => {
+                assert_eq!(output, "custom output\n");
+                assert!(msg["duration"].is_f64());
+            }
+            _ => assert!(msg.get("output").is_none(), "{msg}"),
+        }
+    }
+
+    assert_eq!(summary["reason"], "test-summary");
+    assert_eq!(summary["version"], 2);
+    assert_eq!(summary["success"], false);
+    assert_eq!(summary["passed"], 3);
+    assert_eq!(summary["failed"], 1);
+    assert_eq!(summary["ignored"], 1);
+    assert_eq!(summary["filtered_out"], 0);
+}
+
+#[cargo_test]
+fn fail_fast() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.1.0"
+                edition = "2018"
+
+                [[test]]
+                name = "custom"
+                harness = false
+            "#,
+        )
+        .file("src/lib.rs", "#[test] fn fails() { panic!() }")
+        .file("tests/custom.rs", "fn main() {}")
+        .build();
+
+    let messages = test_messages(
+        &p,
+        "test -q",
+        str![[r#"
+[ERROR] test failed, to rerun pass `--lib`
+
+"#]],
+    );
+    let described: Vec<_> = messages[..messages.len() - 1]
+        .iter()
+        .map(describe)
+        .collect();
+    assert!(
+        described.contains(&"test foo fails failed".to_string()),
+        "{described:?}"
+    );
+    assert!(
+        !described.iter().any(|line| line.starts_with("test custom")),
+        "{described:?}"
+    );
+
+    let summary = messages.last().unwrap();
+    assert_eq!(summary["reason"], "test-summary");
+    assert_eq!(summary["success"], false);
+    assert_eq!(summary["failed"], 1);
+}
+
+#[cargo_test]
+fn filtered_out() {
+    let p = project()
+        .file(
+            "src/lib.rs",
+            r#"
+                #[test] fn passes() {}
+                #[test] fn other() {}
+                #[test] fn another() {}
+            "#,
+        )
+        .build();
+
+    let output = p
+        .cargo("test --lib --message-format=json -Zjson-test-events -- passes")
+        .masquerade_as_nightly_cargo(&["json-test-events"])
+        .with_stderr_data(str![[r#"
+[COMPILING] foo v0.0.1 ([ROOT]/foo)
+[FINISHED] `test` profile [unoptimized + debuginfo] target(s) in [ELAPSED]s
+[RUNNING] unittests src/lib.rs (target/debug/deps/foo-[HASH][EXE])
+
+"#]])
+        .run();
+    let summary: Value = serde_json::from_str(
+        String::from_utf8(output.stdout)
+            .unwrap()
+            .lines()
+            .last()
+            .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(summary["reason"], "test-summary");
+    assert_eq!(summary["success"], true);
+    assert_eq!(summary["passed"], 1);
+    assert_eq!(summary["filtered_out"], 2);
+}
+
+#[cargo_test]
+fn human_output_without_flag() {
+    let p = project()
+        .file("src/lib.rs", "#[test] fn passes() {}")
+        .build();
+
+    p.cargo("test --lib --message-format=json")
+        .with_stdout_data(str![[r#"
+...
+test passes ... ok
+...
+"#]])
+        .run();
+}
+
+#[cargo_test]
+fn with_pipelined_tests() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.1.0"
+                edition = "2018"
+
+                [[test]]
+                name = "custom"
+                harness = false
+            "#,
+        )
+        .file(
+            "src/lib.rs",
+            r#"
+                #[test] fn passes() {}
+                #[test] fn fails() { panic!() }
+            "#,
+        )
+        .file("tests/custom.rs", "fn main() {}")
+        .build();
+
+    let messages = test_messages(
+        &p,
+        "test --no-fail-fast -Zpipelined-tests",
+        str![[r#"
+[COMPILING] foo v0.1.0 ([ROOT]/foo)
+[RUNNING] unittests src/lib.rs (target/debug/deps/foo-[HASH][EXE])
+[RUNNING] tests/custom.rs (target/debug/deps/custom-[HASH][EXE])
+[FINISHED] `test` profile [unoptimized + debuginfo] target(s) in [ELAPSED]s
+[ERROR] test failed, to rerun pass `--lib`
+[DOCTEST] foo
+[ERROR] 1 target failed:
+    `--lib`
+
+"#]]
+        .unordered(),
+    );
+    let (summary, tests) = messages.split_last().unwrap();
+
+    let mut described: Vec<_> = tests.iter().map(describe).collect();
+    described.sort();
+    assert_eq!(
+        described,
+        [
+            "test custom custom passed",
+            "test custom custom started",
+            "test foo fails failed",
+            "test foo fails started",
+            "test foo passes passed",
+            "test foo passes started",
+        ]
+    );
+
+    assert_eq!(summary["reason"], "test-summary");
+    assert_eq!(summary["success"], false);
+    assert_eq!(summary["passed"], 2);
+    assert_eq!(summary["failed"], 1);
+}
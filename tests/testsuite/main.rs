@@ -11,7 +11,9 @@ mod bad_manifest_path;
 mod bench;
 mod binary_name;
 mod build;
+mod build_memory_limit;
 mod build_plan;
+mod build_sandbox;
 mod build_script;
 mod build_script_env;
 mod build_script_extra_link_arg;
@@ -45,6 +47,7 @@ mod cargo_new;
 mod cargo_owner;
 mod cargo_package;
 mod cargo_pkgid;
+mod cargo_profile;
 mod cargo_publish;
 mod cargo_read_manifest;
 mod cargo_remove;
@@ -63,6 +66,7 @@ mod cargo_verify_project;
 mod cargo_version;
 mod cargo_yank;
 mod cfg;
+mod changed_since;
 mod check;
 mod check_cfg;
 mod clean;
@@ -72,6 +76,7 @@ mod config;
 mod config_cli;
 mod config_include;
 mod corrupt_git;
+mod coverage;
 mod credential_process;
 mod cross_compile;
 mod cross_publish;
@@ -99,6 +104,7 @@ mod git;
 mod git_auth;
 mod git_gc;
 mod git_shallow;
+mod git_signature;
 mod glob_targets;
 mod global_cache_tracker;
 mod help;
@@ -107,6 +113,7 @@ mod inheritable_workspace_fields;
 mod install;
 mod install_upgrade;
 mod jobserver;
+mod json_test_events;
 mod lints;
 mod lints_table;
 mod list_availables;
@@ -133,15 +140,18 @@ mod open_namespaces;
 mod owner;
 mod package;
 mod package_features;
+mod package_signing;
 mod patch;
 mod path;
 mod paths;
+mod pipelined_tests;
 mod pkgid;
 mod precise_pre_release;
 mod proc_macro;
 mod profile_config;
 mod profile_custom;
 mod profile_overrides;
+mod profile_target_overrides;
 mod profile_targets;
 mod profile_trim_paths;
 mod profiles;
@@ -153,10 +163,12 @@ mod read_manifest;
 mod registry;
 mod registry_auth;
 mod registry_overlay;
+mod registry_owns;
 mod rename_deps;
 mod replace;
 mod required_features;
 mod run;
+mod runner_kinds;
 mod rust_version;
 mod rustc;
 mod rustc_info_cache;
@@ -165,13 +177,18 @@ mod rustdoc_extern_html;
 mod rustdocflags;
 mod rustflags;
 mod rustup;
+mod scheduling_history;
 mod script;
 mod search;
 mod shell_quoting;
 mod source_replacement;
 mod ssh;
 mod standard_lib;
+mod target_cache_tracker;
 mod test;
+mod test_partition;
+mod test_report;
+mod test_retries;
 mod timings;
 mod tool_paths;
 mod tree;
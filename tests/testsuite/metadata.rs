@@ -555,6 +555,7 @@ fn cargo_metadata_with_deps_and_version() {
           {
             "dep_kinds": [
               {
+                "extern_name": "baz",
                 "kind": null,
                 "target": null
               }
@@ -581,6 +582,7 @@ fn cargo_metadata_with_deps_and_version() {
           {
             "dep_kinds": [
               {
+                "extern_name": "bar",
                 "kind": null,
                 "target": null
               }
@@ -591,6 +593,7 @@ fn cargo_metadata_with_deps_and_version() {
           {
             "dep_kinds": [
               {
+                "extern_name": "foobar",
                 "kind": "dev",
                 "target": null
               }
@@ -1237,8 +1240,7 @@ fn workspace_metadata_with_dependencies_no_deps() {
         .file("artifact/src/main.rs", "fn main() {}")
         .build();
 
-    p.cargo("metadata --no-deps -Z bindeps")
-        .masquerade_as_nightly_cargo(&["bindeps"])
+    p.cargo("metadata --no-deps")
         .with_stdout_data(
             str![[r#"
 {
@@ -1515,8 +1517,7 @@ fn workspace_metadata_with_dependencies_and_resolve() {
         .file("non-artifact/src/lib.rs", "")
         .build();
 
-    p.cargo("metadata -Z bindeps")
-        .masquerade_as_nightly_cargo(&["bindeps"])
+    p.cargo("metadata")
         .with_stdout_data(
             str![[r#"
 {
@@ -1947,6 +1948,7 @@ fn workspace_metadata_with_dependencies_and_resolve() {
                 "target": null
               },
               {
+                "extern_name": "artifact",
                 "kind": "dev",
                 "target": null
               },
@@ -2009,14 +2011,17 @@ fn workspace_metadata_with_dependencies_and_resolve() {
           {
             "dep_kinds": [
               {
+                "extern_name": "non_artifact",
                 "kind": null,
                 "target": null
               },
               {
+                "extern_name": "non_artifact",
                 "kind": "dev",
                 "target": null
               },
               {
+                "extern_name": "non_artifact",
                 "kind": "build",
                 "target": null
               }
@@ -2181,8 +2186,7 @@ fn cargo_metadata_with_invalid_artifact_deps() {
         .file("artifact/src/main.rs", "fn main() {}")
         .build();
 
-    p.cargo("metadata -Z bindeps")
-        .masquerade_as_nightly_cargo(&["bindeps"])
+    p.cargo("metadata")
         .with_status(101)
         .with_stderr_data(str![[r#"
 [WARNING] please specify `--format-version` flag explicitly to avoid compatibility problems
@@ -3259,6 +3263,7 @@ fn rename_dependency() {
           {
             "dep_kinds": [
               {
+                "extern_name": "bar",
                 "kind": null,
                 "target": null
               }
@@ -3269,6 +3274,7 @@ fn rename_dependency() {
           {
             "dep_kinds": [
               {
+                "extern_name": "baz",
                 "kind": null,
                 "target": null
               }
@@ -3649,6 +3655,7 @@ fn filter_platform() {
           {
             "dep_kinds": [
               {
+                "extern_name": "alt_dep",
                 "kind": null,
                 "target": "wasm32-unknown-unknown"
               }
@@ -3659,6 +3666,7 @@ fn filter_platform() {
           {
             "dep_kinds": [
               {
+                "extern_name": "cfg_dep",
                 "kind": null,
                 "target": "cfg(foobar)"
               }
@@ -3669,6 +3677,7 @@ fn filter_platform() {
           {
             "dep_kinds": [
               {
+                "extern_name": "host_dep",
                 "kind": null,
                 "target": "[HOST_TARGET]"
               }
@@ -3679,6 +3688,7 @@ fn filter_platform() {
           {
             "dep_kinds": [
               {
+                "extern_name": "normal_dep",
                 "kind": null,
                 "target": null
               }
@@ -3786,6 +3796,7 @@ fn filter_platform() {
           {
             "dep_kinds": [
               {
+                "extern_name": "alt_dep",
                 "kind": null,
                 "target": "wasm32-unknown-unknown"
               }
@@ -3796,6 +3807,7 @@ fn filter_platform() {
           {
             "dep_kinds": [
               {
+                "extern_name": "normal_dep",
                 "kind": null,
                 "target": null
               }
@@ -3890,6 +3902,7 @@ fn filter_platform() {
           {
             "dep_kinds": [
               {
+                "extern_name": "host_dep",
                 "kind": null,
                 "target": "[HOST_TARGET]"
               }
@@ -3900,6 +3913,7 @@ fn filter_platform() {
           {
             "dep_kinds": [
               {
+                "extern_name": "normal_dep",
                 "kind": null,
                 "target": null
               }
@@ -4014,6 +4028,7 @@ fn filter_platform() {
           {
             "dep_kinds": [
               {
+                "extern_name": "cfg_dep",
                 "kind": null,
                 "target": "cfg(foobar)"
               }
@@ -4024,6 +4039,7 @@ fn filter_platform() {
           {
             "dep_kinds": [
               {
+                "extern_name": "host_dep",
                 "kind": null,
                 "target": "[HOST_TARGET]"
               }
@@ -4034,6 +4050,7 @@ fn filter_platform() {
           {
             "dep_kinds": [
               {
+                "extern_name": "normal_dep",
                 "kind": null,
                 "target": null
               }
@@ -4121,14 +4138,17 @@ fn dep_kinds() {
           {
             "dep_kinds": [
               {
+                "extern_name": "bar",
                 "kind": null,
                 "target": null
               },
               {
+                "extern_name": "bar",
                 "kind": "dev",
                 "target": null
               },
               {
+                "extern_name": "bar",
                 "kind": "build",
                 "target": null
               }
@@ -4139,6 +4159,7 @@ fn dep_kinds() {
           {
             "dep_kinds": [
               {
+                "extern_name": "winapi",
                 "kind": null,
                 "target": "cfg(windows)"
               }
@@ -4235,6 +4256,7 @@ fn dep_kinds_workspace() {
           {
             "dep_kinds": [
               {
+                "extern_name": "foo",
                 "kind": null,
                 "target": null
               }
@@ -4260,6 +4282,7 @@ fn dep_kinds_workspace() {
           {
             "dep_kinds": [
               {
+                "extern_name": "dep",
                 "kind": null,
                 "target": null
               }
@@ -4357,8 +4380,7 @@ fn workspace_metadata_with_dependencies_no_deps_artifact() {
         .file("artifact/src/main.rs", "fn main() {}")
         .build();
 
-    p.cargo("metadata --no-deps -Z bindeps")
-        .masquerade_as_nightly_cargo(&["bindeps"])
+    p.cargo("metadata --no-deps")
         .with_stdout_data(
             str![[r#"
 {
@@ -4778,6 +4800,7 @@ fn versionless_packages() {
           {
             "dep_kinds": [
               {
+                "extern_name": "baz",
                 "kind": null,
                 "target": null
               }
@@ -4788,6 +4811,7 @@ fn versionless_packages() {
           {
             "dep_kinds": [
               {
+                "extern_name": "foobar",
                 "kind": null,
                 "target": null
               }
@@ -4807,6 +4831,7 @@ fn versionless_packages() {
           {
             "dep_kinds": [
               {
+                "extern_name": "foobar",
                 "kind": null,
                 "target": null
               }
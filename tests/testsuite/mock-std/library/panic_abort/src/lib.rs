@@ -0,0 +1,5 @@
+#![feature(panic_abort, panic_runtime)]
+#![panic_runtime]
+#![no_std]
+
+extern crate panic_abort;
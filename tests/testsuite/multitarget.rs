@@ -1,6 +1,7 @@
 //! Tests for multiple `--target` flags to subcommands
 
 use cargo_test_support::prelude::*;
+use cargo_test_support::registry::Package;
 use cargo_test_support::{basic_manifest, cross_compile, project, rustc_host, str};
 
 #[cargo_test]
@@ -268,3 +269,55 @@ fn works_with_env() {
 
     assert!(p.target_bin(t, "foo").is_file());
 }
+
+#[cargo_test]
+fn features_per_target() {
+    // Features requested for one target do not leak into another.
+    if cross_compile::disabled() {
+        return;
+    }
+    let t1 = cross_compile::alternate();
+    let t2 = rustc_host();
+    Package::new("common", "1.0.0")
+        .feature("host", &[])
+        .file(
+            "src/lib.rs",
+            &format!(
+                r#"
+                    #[cfg(all(feature = "host", not(target = "{t2}")))]
+                    compile_error!("`host` feature enabled for the wrong target");
+                "#
+            ),
+        )
+        .publish();
+    let p = project()
+        .file(
+            "Cargo.toml",
+            &format!(
+                r#"
+                    [package]
+                    name = "foo"
+                    version = "1.0.0"
+                    edition = "2021"
+
+                    [dependencies]
+                    common = "1.0"
+
+                    [target.'{t2}'.dependencies]
+                    common = {{ version = "1.0", features = ["host"] }}
+                "#
+            ),
+        )
+        .file("src/main.rs", "fn main() {}")
+        .build();
+
+    p.cargo("build")
+        .arg("--target")
+        .arg(&t1)
+        .arg("--target")
+        .arg(&t2)
+        .run();
+
+    assert!(p.target_bin(t1, "foo").is_file());
+    assert!(p.target_bin(t2, "foo").is_file());
+}
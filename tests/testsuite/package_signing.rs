@@ -0,0 +1,254 @@
+//! Tests for `-Zpackage-signing`.
+
+use cargo_test_support::prelude::*;
+use cargo_test_support::registry::{alt_registry_path, Package, RegistryBuilder, Token};
+use cargo_test_support::{project, str, Project};
+
+const SECRET_KEY: &str =
+    "k3.secret.fNYVuMvBgOlljt9TDohnaYLblghqaHoQquVZwgR6X12cBFHZLFsaU3q7X3k1Zn36";
+const PUBLIC_KEY: &str =
+    "k3.public.AmDwjlyf8jAV3gm5Z7Kz9xAOcsKslt_Vwp5v-emjFzBHLCtcANzTaVEghTNEMj9PkQ";
+const OTHER_SECRET_KEY: &str =
+    "k3.secret.9Vxr5hVlI_g_orBZN54vPz20bmB4O76wB_MVqUSuJJJqHFLwP8kdn_RY5g6J6pQG";
+
+fn trusting_project() -> Project {
+    project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.0.1"
+                edition = "2015"
+
+                [dependencies]
+                bar = { version = "1.0", registry = "alternative" }
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .file(
+            ".cargo/config.toml",
+            &format!(
+                r#"
+                    [registries.alternative]
+                    trusted-keys = ["{PUBLIC_KEY}"]
+                "#
+            ),
+        )
+        .build()
+}
+
+#[cargo_test]
+fn signed_download() {
+    let _registry = RegistryBuilder::new().alternative().http_index().build();
+    Package::new("bar", "1.0.0")
+        .alternative(true)
+        .signed_by(SECRET_KEY)
+        .publish();
+    let p = trusting_project();
+
+    p.cargo("check -Zpackage-signing")
+        .masquerade_as_nightly_cargo(&["package-signing"])
+        .with_stderr_data(str![[r#"
+[UPDATING] `alternative` index
+[LOCKING] 1 package to latest compatible version
+[DOWNLOADING] crates ...
+[DOWNLOADED] bar v1.0.0 (registry `alternative`)
+[CHECKING] bar v1.0.0 (registry `alternative`)
+[CHECKING] foo v0.0.1 ([ROOT]/foo)
+[FINISHED] `dev` profile [unoptimized + debuginfo] target(s) in [ELAPSED]s
+
+"#]])
+        .run();
+}
+
+#[cargo_test]
+fn unsigned_download() {
+    let _registry = RegistryBuilder::new().alternative().http_index().build();
+    Package::new("bar", "1.0.0").alternative(true).publish();
+    let p = trusting_project();
+
+    p.cargo("check -Zpackage-signing")
+        .masquerade_as_nightly_cargo(&["package-signing"])
+        .with_status(101)
+        .with_stderr_data(str![[r#"
+[UPDATING] `alternative` index
+[LOCKING] 1 package to latest compatible version
+[DOWNLOADING] crates ...
+[DOWNLOADED] bar v1.0.0 (registry `alternative`)
+[ERROR] failed to verify the signature of `bar v1.0.0 (registry `alternative`)`
+
+[HELP] packages from `alternative` must be signed by one of its `trusted-keys`
+
+Caused by:
+  the package is not signed
+
+"#]])
+        .run();
+
+    // Unsigned packages are still downloaded when `-Zpackage-signing` isn't
+    // passed, even though `alternative` has trusted keys.
+    p.cargo("check").run();
+}
+
+#[cargo_test]
+fn untrusted_key() {
+    let _registry = RegistryBuilder::new().alternative().http_index().build();
+    Package::new("bar", "1.0.0")
+        .alternative(true)
+        .signed_by(OTHER_SECRET_KEY)
+        .publish();
+    let p = trusting_project();
+
+    p.cargo("check -Zpackage-signing")
+        .masquerade_as_nightly_cargo(&["package-signing"])
+        .with_status(101)
+        .with_stderr_data(str![[r#"
+[UPDATING] `alternative` index
+[LOCKING] 1 package to latest compatible version
+[DOWNLOADING] crates ...
+[DOWNLOADED] bar v1.0.0 (registry `alternative`)
+[ERROR] failed to verify the signature of `bar v1.0.0 (registry `alternative`)`
+
+[HELP] packages from `alternative` must be signed by one of its `trusted-keys`
+
+Caused by:
+  the package is signed by an untrusted key `k3.pid.8unG61qYzBCGH7QzwS0qxca7U1uH3lLzn186EinyihVy`
+
+"#]])
+        .run();
+}
+
+#[cargo_test]
+fn publish_signed() {
+    let _registry = RegistryBuilder::new()
+        .http_api()
+        .http_index()
+        .alternative()
+        .token(Token::rfc_key())
+        .build();
+
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "bar"
+                version = "1.0.0"
+                edition = "2015"
+                license = "MIT"
+                description = "bar"
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .file(
+            ".cargo/config.toml",
+            r#"
+                [registries.alternative]
+                sign-packages = true
+            "#,
+        )
+        .build();
+
+    p.cargo("publish --no-verify -Zasymmetric-token -Zpackage-signing --registry alternative")
+        .masquerade_as_nightly_cargo(&["asymmetric-token", "package-signing"])
+        .with_stderr_data(str![[r#"
+[UPDATING] `alternative` index
+[WARNING] manifest has no documentation, homepage or repository.
+See https://doc.rust-lang.org/cargo/reference/manifest.html#package-metadata for more info.
+[PACKAGING] bar v1.0.0 ([ROOT]/foo)
+[PACKAGED] 4 files, [FILE_SIZE]B ([FILE_SIZE]B compressed)
+[UPLOADING] bar v1.0.0 ([ROOT]/foo)
+[UPLOADED] bar v1.0.0 to registry `alternative`
+[NOTE] waiting for `bar v1.0.0` to be available at registry `alternative`.
+You may press ctrl-c to skip waiting; the crate should be available shortly.
+[PUBLISHED] bar v1.0.0 at registry `alternative`
+
+"#]])
+        .run();
+
+    let index = std::fs::read_to_string(alt_registry_path().join("3/b/bar")).unwrap();
+    assert!(index.contains(r#""sig":"v3.public."#), "{index}");
+
+    // The signature is accepted by a consumer trusting the publisher's key.
+    let consumer = project()
+        .at("consumer")
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "consumer"
+                version = "0.0.1"
+                edition = "2015"
+
+                [dependencies]
+                bar = { version = "1.0", registry = "alternative" }
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .file(
+            ".cargo/config.toml",
+            &format!(
+                r#"
+                    [registries.alternative]
+                    trusted-keys = ["{PUBLIC_KEY}"]
+                "#
+            ),
+        )
+        .build();
+    consumer
+        .cargo("check -Zasymmetric-token -Zpackage-signing")
+        .masquerade_as_nightly_cargo(&["asymmetric-token", "package-signing"])
+        .run();
+}
+
+#[cargo_test]
+fn publish_with_plain_token() {
+    let _registry = RegistryBuilder::new()
+        .http_api()
+        .http_index()
+        .alternative()
+        .build();
+
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "bar"
+                version = "1.0.0"
+                edition = "2015"
+                license = "MIT"
+                description = "bar"
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .file(
+            ".cargo/config.toml",
+            r#"
+                [registries.alternative]
+                sign-packages = true
+            "#,
+        )
+        .build();
+
+    p.cargo("publish --no-verify -Zpackage-signing --registry alternative")
+        .masquerade_as_nightly_cargo(&["package-signing"])
+        .with_status(101)
+        .with_stderr_data(str![[r#"
+[UPDATING] `alternative` index
+[WARNING] manifest has no documentation, homepage or repository.
+See https://doc.rust-lang.org/cargo/reference/manifest.html#package-metadata for more info.
+[PACKAGING] bar v1.0.0 ([ROOT]/foo)
+[PACKAGED] 4 files, [FILE_SIZE]B ([FILE_SIZE]B compressed)
+[UPLOADING] bar v1.0.0 ([ROOT]/foo)
+[ERROR] failed to sign `bar v1.0.0 ([ROOT]/foo)`
+
+Caused by:
+  the credential providers for `alternative` did not produce a signature
+
+  [HELP] signing packages requires an asymmetric key, such as a `secret-key` used by the `cargo:paseto` credential provider
+
+"#]])
+        .run();
+}
@@ -0,0 +1,211 @@
+//! Tests for `-Zpipelined-tests`.
+
+use cargo_test_support::prelude::*;
+use cargo_test_support::{basic_manifest, project, str};
+
+#[cargo_test]
+fn runs_tests_during_build() {
+    // `bar` can't finish compiling until the test of `foo` has run, which only
+    // works when tests run while the build is still going.
+    let p = project();
+    let marker = p.root().join("foo-test-ran");
+    let p = p
+        .file(
+            "Cargo.toml",
+            r#"
+                [workspace]
+                members = ["foo", "bar", "pm"]
+                resolver = "2"
+            "#,
+        )
+        .file("foo/Cargo.toml", &basic_manifest("foo", "0.1.0"))
+        .file("foo/src/lib.rs", "")
+        .file(
+            "foo/tests/t.rs",
+            &format!(
+                r#"
+                    #[test]
+                    fn create_marker() {{
+                        std::fs::write({marker:?}, "").unwrap();
+                    }}
+                "#
+            ),
+        )
+        .file(
+            "pm/Cargo.toml",
+            r#"
+                [package]
+                name = "pm"
+                version = "0.1.0"
+                edition = "2015"
+
+                [lib]
+                proc-macro = true
+            "#,
+        )
+        .file(
+            "pm/src/lib.rs",
+            &format!(
+                r#"
+                    extern crate proc_macro;
+                    use proc_macro::TokenStream;
+
+                    #[proc_macro]
+                    pub fn wait_for_foo_test(_input: TokenStream) -> TokenStream {{
+                        for _ in 0..600 {{
+                            if std::path::Path::new({marker:?}).exists() {{
+                                return TokenStream::new();
+                            }}
+                            std::thread::sleep(std::time::Duration::from_millis(100));
+                        }}
+                        panic!("the test of foo didn't run");
+                    }}
+                "#
+            ),
+        )
+        .file(
+            "bar/Cargo.toml",
+            r#"
+                [package]
+                name = "bar"
+                version = "0.1.0"
+                edition = "2015"
+
+                [lib]
+                doctest = false
+
+                [dependencies]
+                pm = { path = "../pm" }
+            "#,
+        )
+        .file(
+            "bar/src/lib.rs",
+            r#"
+                #[macro_use]
+                extern crate pm;
+                wait_for_foo_test!();
+            "#,
+        )
+        .build();
+
+    p.cargo("test --workspace -Zpipelined-tests -j4")
+        .masquerade_as_nightly_cargo(&["pipelined-tests"])
+        .with_stderr_data(
+            str![[r#"
+[COMPILING] pm v0.1.0 ([ROOT]/foo/pm)
+[COMPILING] foo v0.1.0 ([ROOT]/foo/foo)
+[COMPILING] bar v0.1.0 ([ROOT]/foo/bar)
+[RUNNING] unittests src/lib.rs (target/debug/deps/foo-[HASH][EXE])
+[RUNNING] tests/t.rs (target/debug/deps/t-[HASH][EXE])
+[RUNNING] unittests src/lib.rs (target/debug/deps/pm-[HASH][EXE])
+[RUNNING] unittests src/lib.rs (target/debug/deps/bar-[HASH][EXE])
+[FINISHED] `test` profile [unoptimized + debuginfo] target(s) in [ELAPSED]s
+[DOCTEST] foo
+[DOCTEST] pm
+
+"#]]
+            .unordered(),
+        )
+        .run();
+    assert!(marker.exists());
+}
+
+#[cargo_test]
+fn build_script_env() {
+    let p = project()
+        .file("Cargo.toml", &basic_manifest("foo", "0.1.0"))
+        .file(
+            "build.rs",
+            r#"
+                fn main() {
+                    println!("cargo::rustc-env=FROM_BUILD_SCRIPT=hello");
+                }
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .file(
+            "tests/t.rs",
+            r#"
+                #[test]
+                fn env() {
+                    assert_eq!(std::env::var("FROM_BUILD_SCRIPT").unwrap(), "hello");
+                    assert!(std::env::var_os("OUT_DIR").is_some());
+                }
+            "#,
+        )
+        .build();
+
+    p.cargo("test --test t -Zpipelined-tests")
+        .masquerade_as_nightly_cargo(&["pipelined-tests"])
+        .with_stderr_data(str![[r#"
+[COMPILING] foo v0.1.0 ([ROOT]/foo)
+[RUNNING] tests/t.rs (target/debug/deps/t-[HASH][EXE])
+[FINISHED] `test` profile [unoptimized + debuginfo] target(s) in [ELAPSED]s
+
+"#]])
+        .with_stdout_data(str![[r#"
+...
+test env ... ok
+...
+"#]])
+        .run();
+}
+
+#[cargo_test]
+fn failing_test_stops_build() {
+    let p = project()
+        .file("Cargo.toml", &basic_manifest("foo", "0.1.0"))
+        .file("src/lib.rs", "")
+        .file(
+            "tests/t.rs",
+            r#"#[test] fn fails() { assert!(false, "FALSE!") }"#,
+        )
+        .build();
+
+    p.cargo("test --test t -Zpipelined-tests")
+        .masquerade_as_nightly_cargo(&["pipelined-tests"])
+        .with_status(101)
+        .with_stderr_data(str![[r#"
+[COMPILING] foo v0.1.0 ([ROOT]/foo)
+[RUNNING] tests/t.rs (target/debug/deps/t-[HASH][EXE])
+[ERROR] test failed, to rerun pass `--test t`
+
+"#]])
+        .with_stdout_data(str![[r#"
+...
+test fails ... FAILED
+...
+"#]])
+        .run();
+}
+
+#[cargo_test]
+fn no_fail_fast() {
+    let p = project()
+        .file("Cargo.toml", &basic_manifest("foo", "0.1.0"))
+        .file("src/lib.rs", "")
+        .file("tests/t1.rs", "#[test] fn fails() { panic!() }")
+        .file("tests/t2.rs", "#[test] fn fails() { panic!() }")
+        .build();
+
+    p.cargo("test --tests -Zpipelined-tests --no-fail-fast")
+        .masquerade_as_nightly_cargo(&["pipelined-tests"])
+        .with_status(101)
+        .with_stderr_data(
+            str![[r#"
+[COMPILING] foo v0.1.0 ([ROOT]/foo)
+[RUNNING] unittests src/lib.rs (target/debug/deps/foo-[HASH][EXE])
+[RUNNING] tests/t1.rs (target/debug/deps/t1-[HASH][EXE])
+[RUNNING] tests/t2.rs (target/debug/deps/t2-[HASH][EXE])
+[FINISHED] `test` profile [unoptimized + debuginfo] target(s) in [ELAPSED]s
+[ERROR] test failed, to rerun pass `--test t1`
+[ERROR] test failed, to rerun pass `--test t2`
+[ERROR] 2 targets failed:
+    `--test t1`
+    `--test t2`
+
+"#]]
+            .unordered(),
+        )
+        .run();
+}
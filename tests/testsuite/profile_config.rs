@@ -372,9 +372,9 @@ fn named_config_profile() {
     // foo -> middle -> bar -> dev
     // middle exists in Cargo.toml, the others in .cargo/config.toml
     use super::config::GlobalContextBuilder;
-    use cargo::core::compiler::CompileKind;
+    use cargo::core::compiler::{CompileKind, CompileMode, CrateType};
     use cargo::core::profiles::{Profiles, UnitFor};
-    use cargo::core::{PackageId, Workspace};
+    use cargo::core::{Edition, PackageId, Target, Workspace};
     use cargo::util::interning::InternedString;
     use std::fs;
     paths::root().join(".cargo").mkdir_p();
@@ -428,10 +428,25 @@ fn named_config_profile() {
     let crates_io = cargo::core::SourceId::crates_io(&gctx).unwrap();
     let a_pkg = PackageId::try_new("a", "0.1.0", crates_io).unwrap();
     let dep_pkg = PackageId::try_new("dep", "0.1.0", crates_io).unwrap();
+    let lib = Target::lib_target(
+        "a",
+        vec![CrateType::Lib],
+        paths::root().join("src/lib.rs"),
+        Edition::Edition2015,
+    );
+    let mode = CompileMode::Build;
 
     // normal package
     let kind = CompileKind::Host;
-    let p = profiles.get_profile(a_pkg, true, true, UnitFor::new_normal(kind), kind);
+    let p = profiles.get_profile(
+        a_pkg,
+        true,
+        true,
+        UnitFor::new_normal(kind),
+        kind,
+        &lib,
+        mode,
+    );
     assert_eq!(p.name, "foo");
     assert_eq!(p.codegen_units, Some(2)); // "foo" from config
     assert_eq!(p.opt_level, "1"); // "middle" from manifest
@@ -440,7 +455,15 @@ fn named_config_profile() {
     assert_eq!(p.overflow_checks, true); // "dev" built-in (ignore package override)
 
     // build-override
-    let bo = profiles.get_profile(a_pkg, true, true, UnitFor::new_host(false, kind), kind);
+    let bo = profiles.get_profile(
+        a_pkg,
+        true,
+        true,
+        UnitFor::new_host(false, kind),
+        kind,
+        &lib,
+        mode,
+    );
     assert_eq!(bo.name, "foo");
     assert_eq!(bo.codegen_units, Some(6)); // "foo" build override from config
     assert_eq!(bo.opt_level, "0"); // default to zero
@@ -449,7 +472,15 @@ fn named_config_profile() {
     assert_eq!(bo.overflow_checks, true); // SAME as normal
 
     // package overrides
-    let po = profiles.get_profile(dep_pkg, false, true, UnitFor::new_normal(kind), kind);
+    let po = profiles.get_profile(
+        dep_pkg,
+        false,
+        true,
+        UnitFor::new_normal(kind),
+        kind,
+        &lib,
+        mode,
+    );
     assert_eq!(po.name, "foo");
     assert_eq!(po.codegen_units, Some(7)); // "foo" package override from config
     assert_eq!(po.opt_level, "1"); // SAME as normal
@@ -609,3 +609,184 @@ fn build_override_shared() {
 
     p.cargo("run").run();
 }
+
+#[cargo_test]
+fn profile_override_glob() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+            cargo-features = ["profile-package-globs"]
+
+            [package]
+            name = "foo"
+            version = "0.0.1"
+            edition = "2015"
+
+            [dependencies]
+            aws-core = { path = "aws-core" }
+            aws-s3 = { path = "aws-s3" }
+
+            [profile.dev.package."aws-*"]
+            opt-level = 1
+
+            [profile.dev.package.aws-s3]
+            opt-level = 3
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .file("aws-core/Cargo.toml", &basic_manifest("aws-core", "0.5.0"))
+        .file("aws-core/src/lib.rs", "")
+        .file("aws-s3/Cargo.toml", &basic_manifest("aws-s3", "0.5.0"))
+        .file("aws-s3/src/lib.rs", "")
+        .build();
+
+    p.cargo("check -v")
+        .masquerade_as_nightly_cargo(&["profile-package-globs"])
+        .with_stderr_line_without(&["[RUNNING] `rustc --crate-name foo"], &["-C opt-level"])
+        .with_stderr_data(
+            str![[r#"
+[LOCKING] 2 packages to latest compatible versions
+[CHECKING] aws-core v0.5.0 ([ROOT]/foo/aws-core)
+[RUNNING] `rustc --crate-name aws_core [..] -C opt-level=1 [..]`
+[CHECKING] aws-s3 v0.5.0 ([ROOT]/foo/aws-s3)
+[RUNNING] `rustc --crate-name aws_s3 [..] -C opt-level=3 [..]`
+[CHECKING] foo v0.0.1 ([ROOT]/foo)
+[RUNNING] `rustc --crate-name foo [..]`
+[FINISHED] `dev` profile [unoptimized + debuginfo] target(s) in [ELAPSED]s
+
+"#]]
+            .unordered(),
+        )
+        .run();
+}
+
+#[cargo_test]
+fn profile_override_glob_gated() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+            [package]
+            name = "foo"
+            version = "0.0.1"
+            edition = "2015"
+
+            [profile.dev.package."aws-*"]
+            opt-level = 1
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+
+    p.cargo("check")
+        .masquerade_as_nightly_cargo(&["profile-package-globs"])
+        .with_status(101)
+        .with_stderr_data(str![[r#"
+[ERROR] failed to parse manifest at `[ROOT]/foo/Cargo.toml`
+
+Caused by:
+  feature `profile-package-globs` is required
+
+  The package requires the Cargo feature called `profile-package-globs`, but that feature is not stabilized in this version of Cargo ([..]).
+  Consider adding `cargo-features = ["profile-package-globs"]` to the top of Cargo.toml (above the [package] table) to tell Cargo you are opting in to use this unstable feature.
+  See https://doc.rust-lang.org/nightly/cargo/reference/unstable.html#profile-package-globs for more information about the status of this feature.
+
+"#]])
+        .run();
+}
+
+#[cargo_test]
+fn profile_override_glob_errors() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+            cargo-features = ["profile-package-globs"]
+
+            [package]
+            name = "foo"
+            version = "0.0.1"
+            edition = "2015"
+
+            [dependencies]
+            aws-core = { path = "aws-core" }
+
+            [profile.dev.package."aws-*"]
+            opt-level = 1
+
+            [profile.dev.package."*-core"]
+            opt-level = 3
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .file("aws-core/Cargo.toml", &basic_manifest("aws-core", "0.5.0"))
+        .file("aws-core/src/lib.rs", "")
+        .build();
+
+    p.cargo("check")
+        .masquerade_as_nightly_cargo(&["profile-package-globs"])
+        .with_status(101)
+        .with_stderr_data(str![[r#"
+[LOCKING] 1 package to latest compatible version
+[ERROR] multiple package overrides in profile `dev` match package `aws-core v0.5.0 ([ROOT]/foo/aws-core)`
+found package specs: *-core, aws-*
+
+"#]])
+        .run();
+
+    p.change_file(
+        "Cargo.toml",
+        r#"
+        cargo-features = ["profile-package-globs"]
+
+        [package]
+        name = "foo"
+        version = "0.0.1"
+        edition = "2015"
+
+        [dependencies]
+        aws-core = { path = "aws-core" }
+
+        [profile.dev.package."azure-*"]
+        opt-level = 1
+        "#,
+    );
+    p.cargo("check")
+        .masquerade_as_nightly_cargo(&["profile-package-globs"])
+        .with_stderr_data(str![[r#"
+[WARNING] profile package glob `azure-*` in profile `dev` did not match any packages
+[CHECKING] aws-core v0.5.0 ([ROOT]/foo/aws-core)
+[CHECKING] foo v0.0.1 ([ROOT]/foo)
+[FINISHED] `dev` profile [unoptimized + debuginfo] target(s) in [ELAPSED]s
+
+"#]])
+        .run();
+
+    p.change_file(
+        "Cargo.toml",
+        r#"
+        cargo-features = ["profile-package-globs"]
+
+        [package]
+        name = "foo"
+        version = "0.0.1"
+        edition = "2015"
+
+        [profile.dev.package."aws-*@1.0.0"]
+        opt-level = 1
+        "#,
+    );
+    p.cargo("check")
+        .masquerade_as_nightly_cargo(&["profile-package-globs"])
+        .with_status(101)
+        .with_stderr_data(str![[r#"
+[ERROR] failed to parse manifest at `[ROOT]/foo/Cargo.toml`
+
+Caused by:
+  invalid package glob `aws-*@1.0.0` in profile `dev`: `@` is not allowed
+  [HELP] a package glob like `aws-*` matches the names of packages
+
+"#]])
+        .run();
+}
@@ -0,0 +1,338 @@
+//! Tests for profile overrides for kinds of targets, like `[profile.dev.bin]`.
+
+use cargo_test_support::prelude::*;
+use cargo_test_support::{basic_lib_manifest, project, str};
+
+#[cargo_test]
+fn gated() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.0.1"
+                edition = "2015"
+
+                [profile.dev.bin]
+                opt-level = 1
+            "#,
+        )
+        .file("src/main.rs", "fn main() {}")
+        .build();
+
+    p.cargo("check")
+        .masquerade_as_nightly_cargo(&["profile-target-overrides"])
+        .with_status(101)
+        .with_stderr_data(str![[r#"
+[ERROR] failed to parse manifest at `[ROOT]/foo/Cargo.toml`
+
+Caused by:
+  feature `profile-target-overrides` is required
+
+  The package requires the Cargo feature called `profile-target-overrides`, but that feature is not stabilized in this version of Cargo ([..]).
+  Consider adding `cargo-features = ["profile-target-overrides"]` to the top of Cargo.toml (above the [package] table) to tell Cargo you are opting in to use this unstable feature.
+  See https://doc.rust-lang.org/nightly/cargo/reference/unstable.html#profile-target-overrides for more information about the status of this feature.
+
+"#]])
+        .run();
+
+    p.change_file(
+        "Cargo.toml",
+        r#"
+            [package]
+            name = "foo"
+            version = "0.0.1"
+            edition = "2015"
+        "#,
+    );
+    p.change_file(
+        ".cargo/config.toml",
+        r#"
+            [profile.dev.bin]
+            opt-level = 1
+        "#,
+    );
+    p.cargo("check")
+        .masquerade_as_nightly_cargo(&["profile-target-overrides"])
+        .with_status(101)
+        .with_stderr_data(str![[r#"
+[ERROR] config profile `dev` is not valid (defined in `[ROOT]/foo/.cargo/config.toml`)
+
+Caused by:
+  feature `profile-target-overrides` is required
+
+  The package requires the Cargo feature called `profile-target-overrides`, but that feature is not stabilized in this version of Cargo ([..]).
+  Consider adding `cargo-features = ["profile-target-overrides"]` to the top of Cargo.toml (above the [package] table) to tell Cargo you are opting in to use this unstable feature.
+  See https://doc.rust-lang.org/nightly/cargo/reference/unstable.html#profile-target-overrides for more information about the status of this feature.
+
+"#]])
+        .run();
+
+    p.cargo("check -v -Zprofile-target-overrides")
+        .masquerade_as_nightly_cargo(&["profile-target-overrides"])
+        .with_stderr_data(str![[r#"
+[CHECKING] foo v0.0.1 ([ROOT]/foo)
+[RUNNING] `rustc --crate-name foo [..] -C opt-level=1 [..]`
+[FINISHED] `dev` profile [unoptimized + debuginfo] target(s) in [ELAPSED]s
+
+"#]])
+        .run();
+}
+
+#[cargo_test]
+fn kinds() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                cargo-features = ["profile-target-overrides"]
+
+                [package]
+                name = "foo"
+                version = "0.0.1"
+                edition = "2015"
+
+                [dependencies]
+                bar = { path = "bar" }
+
+                [profile.dev.lib]
+                opt-level = 1
+
+                [profile.dev.bin]
+                opt-level = 2
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .file("src/main.rs", "fn main() {}")
+        .file("bar/Cargo.toml", &basic_lib_manifest("bar"))
+        .file("bar/src/lib.rs", "")
+        .build();
+
+    p.cargo("build -v")
+        .masquerade_as_nightly_cargo(&["profile-target-overrides"])
+        .with_stderr_data(
+            str![[r#"
+[LOCKING] 1 package to latest compatible version
+[COMPILING] bar v0.5.0 ([ROOT]/foo/bar)
+[RUNNING] `rustc --crate-name bar [..]--crate-type lib [..] -C opt-level=1 [..]`
+[COMPILING] foo v0.0.1 ([ROOT]/foo)
+[RUNNING] `rustc --crate-name foo [..]--crate-type lib [..] -C opt-level=1 [..]`
+[RUNNING] `rustc --crate-name foo [..]--crate-type bin [..] -C opt-level=2 [..]`
+[FINISHED] `dev` profile [unoptimized + debuginfo] target(s) in [ELAPSED]s
+
+"#]]
+            .unordered(),
+        )
+        .run();
+}
+
+#[cargo_test]
+fn test_harnesses() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                cargo-features = ["profile-target-overrides"]
+
+                [package]
+                name = "foo"
+                version = "0.0.1"
+                edition = "2015"
+
+                [dependencies]
+                bar = { path = "bar" }
+
+                [profile.dev.test]
+                opt-level = 1
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .file("src/main.rs", "fn main() {}")
+        .file("tests/t1.rs", "")
+        .file("bar/Cargo.toml", &basic_lib_manifest("bar"))
+        .file("bar/src/lib.rs", "")
+        .build();
+
+    p.cargo("test --no-run -v")
+        .masquerade_as_nightly_cargo(&["profile-target-overrides"])
+        .with_stderr_line_without(&["[RUNNING] `rustc --crate-name bar"], &["-C opt-level"])
+        .with_stderr_data(
+            str![[r#"
+[LOCKING] 1 package to latest compatible version
+[COMPILING] bar v0.5.0 ([ROOT]/foo/bar)
+[RUNNING] `rustc --crate-name bar [..]`
+[COMPILING] foo v0.0.1 ([ROOT]/foo)
+[RUNNING] `rustc --crate-name foo [..]--crate-type lib [..]`
+[RUNNING] `rustc --crate-name foo [..]--emit=dep-info,link -C opt-level=1 [..]--test [..]`
+[RUNNING] `rustc --crate-name foo [..]--emit=dep-info,link -C opt-level=1 [..]--test [..]`
+[RUNNING] `rustc --crate-name t1 [..]--emit=dep-info,link -C opt-level=1 [..]--test [..]`
+[RUNNING] `rustc --crate-name foo [..]--crate-type bin [..]`
+[FINISHED] `test` profile [unoptimized + debuginfo] target(s) in [ELAPSED]s
+[EXECUTABLE] `[ROOT]/foo/target/debug/deps/foo-[HASH][EXE]`
+[EXECUTABLE] `[ROOT]/foo/target/debug/deps/foo-[HASH][EXE]`
+[EXECUTABLE] `[ROOT]/foo/target/debug/deps/t1-[HASH][EXE]`
+
+"#]]
+            .unordered(),
+        )
+        .run();
+}
+
+#[cargo_test]
+fn precedence() {
+    // A package override wins over a target-kind override, which wins over
+    // the profile itself.
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                cargo-features = ["profile-target-overrides"]
+
+                [package]
+                name = "foo"
+                version = "0.0.1"
+                edition = "2015"
+
+                [dependencies]
+                bar = { path = "bar" }
+
+                [profile.dev]
+                opt-level = 1
+
+                [profile.dev.lib]
+                opt-level = 2
+
+                [profile.dev.package.bar]
+                opt-level = 3
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .file("src/main.rs", "fn main() {}")
+        .file("bar/Cargo.toml", &basic_lib_manifest("bar"))
+        .file("bar/src/lib.rs", "")
+        .build();
+
+    p.cargo("build -v")
+        .masquerade_as_nightly_cargo(&["profile-target-overrides"])
+        .with_stderr_data(
+            str![[r#"
+[LOCKING] 1 package to latest compatible version
+[COMPILING] bar v0.5.0 ([ROOT]/foo/bar)
+[RUNNING] `rustc --crate-name bar [..]--crate-type lib [..] -C opt-level=3 [..]`
+[COMPILING] foo v0.0.1 ([ROOT]/foo)
+[RUNNING] `rustc --crate-name foo [..]--crate-type lib [..] -C opt-level=2 [..]`
+[RUNNING] `rustc --crate-name foo [..]--crate-type bin [..] -C opt-level=1 [..]`
+[FINISHED] `dev` profile [optimized + debuginfo] target(s) in [ELAPSED]s
+
+"#]]
+            .unordered(),
+        )
+        .run();
+}
+
+#[cargo_test]
+fn inherited() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                cargo-features = ["profile-target-overrides"]
+
+                [package]
+                name = "foo"
+                version = "0.0.1"
+                edition = "2015"
+
+                [profile.dev.bin]
+                opt-level = 2
+
+                [profile.custom]
+                inherits = "dev"
+            "#,
+        )
+        .file("src/main.rs", "fn main() {}")
+        .build();
+
+    p.cargo("build -v --bins --profile custom")
+        .masquerade_as_nightly_cargo(&["profile-target-overrides"])
+        .with_stderr_data(str![[r#"
+...
+[RUNNING] `rustc --crate-name foo [..]--crate-type bin [..] -C opt-level=2 [..]`
+[FINISHED] `custom` profile [unoptimized + debuginfo] target(s) in [ELAPSED]s
+
+"#]])
+        .run();
+}
+
+#[cargo_test]
+fn bad_settings() {
+    let bad_values = [
+        (
+            "panic = \"abort\"",
+            "`panic` may not be specified in a `bin` profile",
+        ),
+        (
+            "lto = true",
+            "`lto` may not be specified in a `bin` profile",
+        ),
+        ("package = {}", "package-specific profiles cannot be nested"),
+        ("test = {}", "target-kind profiles cannot be nested"),
+    ];
+    for (snippet, expected) in bad_values {
+        let p = project()
+            .file(
+                "Cargo.toml",
+                &format!(
+                    r#"
+                        cargo-features = ["profile-target-overrides"]
+
+                        [package]
+                        name = "foo"
+                        version = "0.0.1"
+                        edition = "2015"
+
+                        [profile.dev.bin]
+                        {snippet}
+                    "#
+                ),
+            )
+            .file("src/main.rs", "fn main() {}")
+            .build();
+
+        p.cargo("check")
+            .masquerade_as_nightly_cargo(&["profile-target-overrides"])
+            .with_status(101)
+            .with_stderr_data(format!("...\n[..]{expected}\n...\n"))
+            .run();
+    }
+
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                cargo-features = ["profile-target-overrides"]
+
+                [package]
+                name = "foo"
+                version = "0.0.1"
+                edition = "2015"
+
+                [profile.dev.package.bar.bin]
+                opt-level = 1
+            "#,
+        )
+        .file("src/main.rs", "fn main() {}")
+        .build();
+    p.cargo("check")
+        .masquerade_as_nightly_cargo(&["profile-target-overrides"])
+        .with_status(101)
+        .with_stderr_data(str![[r#"
+[ERROR] failed to parse manifest at `[ROOT]/foo/Cargo.toml`
+
+Caused by:
+  target-kind profiles cannot be nested
+
+"#]])
+        .run();
+}
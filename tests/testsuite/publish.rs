@@ -1,6 +1,7 @@
 //! Tests for the `cargo publish` command.
 
 use std::fs;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, Mutex};
 
 use cargo_test_support::git::{self, repo};
@@ -3396,6 +3397,8 @@ See https://doc.rust-lang.org/cargo/reference/manifest.html#package-metadata for
 You may press ctrl-c to skip waiting; the crate should be available shortly.
 [WARNING] timed out waiting for `dep v0.0.1` to be available in registry `crates-io`
 [NOTE] the registry may have a backlog that is delaying making the crate available. The crate should be available soon.
+[NOTE] published `dep v0.0.1`, but not `main v0.0.1` or `other v0.0.1`
+Running `cargo publish` again will skip the published versions.
 [ERROR] unable to publish `main v0.0.1` and `other v0.0.1` due to time out while waiting for published dependencies to be available.
 
 "#]])
@@ -3975,7 +3978,32 @@ You may press ctrl-c to skip waiting; the crate should be available shortly.
 "#]])
         .run();
 
-    // Publishing the whole workspace now will fail, as `a` is already published.
+    // Publishing the whole workspace now skips `a`, as it is already published.
+    p.cargo("publish -Zpackage-workspace")
+        .masquerade_as_nightly_cargo(&["package-workspace"])
+        .replace_crates_io(registry.index_url())
+        .with_stderr_data(str![[r#"
+[UPDATING] crates.io index
+[SKIPPING] a v0.0.1 ([ROOT]/foo/a) (already exists on crates.io index)
+[PACKAGING] b v0.0.1 ([ROOT]/foo/b)
+[UPDATING] crates.io index
+[PACKAGED] 4 files, [FILE_SIZE]B ([FILE_SIZE]B compressed)
+[VERIFYING] b v0.0.1 ([ROOT]/foo/b)
+[DOWNLOADING] crates ...
+[DOWNLOADED] a v0.0.1
+[COMPILING] a v0.0.1
+[COMPILING] b v0.0.1 ([ROOT]/foo/target/package/b-0.0.1)
+[FINISHED] `dev` profile [unoptimized + debuginfo] target(s) in [ELAPSED]s
+[UPLOADING] b v0.0.1 ([ROOT]/foo/b)
+[UPLOADED] b v0.0.1 to registry `crates-io`
+[NOTE] waiting for `b v0.0.1` to be available at registry `crates-io`.
+You may press ctrl-c to skip waiting; the crate should be available shortly.
+[PUBLISHED] b v0.0.1 at registry `crates-io`
+
+"#]])
+        .run();
+
+    // Once everything is published, there is nothing left to resume.
     p.cargo("publish -Zpackage-workspace")
         .masquerade_as_nightly_cargo(&["package-workspace"])
         .replace_crates_io(registry.index_url())
@@ -3988,6 +4016,233 @@ You may press ctrl-c to skip waiting; the crate should be available shortly.
         .run();
 }
 
+fn dependent_workspace_project() -> Project {
+    project()
+        .file(
+            "Cargo.toml",
+            r#"
+            [workspace]
+            members = ["a", "b"]
+        "#,
+        )
+        .file(
+            "a/Cargo.toml",
+            r#"
+            [package]
+            name = "a"
+            version = "0.0.1"
+            edition = "2015"
+            authors = []
+            license = "MIT"
+            description = "a"
+            repository = "bar"
+        "#,
+        )
+        .file("a/src/lib.rs", "")
+        .file(
+            "b/Cargo.toml",
+            r#"
+            [package]
+            name = "b"
+            version = "0.0.1"
+            edition = "2015"
+            authors = []
+            license = "MIT"
+            description = "b"
+            repository = "bar"
+
+            [dependencies]
+            a = { path = "../a", version = "0.0.1" }
+        "#,
+        )
+        .file("b/src/lib.rs", "")
+        .build()
+}
+
+/// Fails the upload of `b`, letting every other upload through.
+fn reject_b(req: &registry::Request, server: &registry::HttpServer) -> Response {
+    let body = String::from_utf8_lossy(req.body.as_deref().unwrap_or_default());
+    if body.contains(r#""name":"b""#) {
+        Response {
+            body: br#"{"errors": [{"detail": "b is not allowed"}]}"#.to_vec(),
+            code: 403,
+            headers: vec![],
+        }
+    } else if req.url.path() == "/api/v1/crates/new" {
+        server.check_authorized_publish(req)
+    } else {
+        server.stage_publish(req)
+    }
+}
+
+#[cargo_test]
+fn workspace_staged_publish() {
+    let registry = RegistryBuilder::new()
+        .http_api()
+        .http_index()
+        .staged_publish()
+        .build();
+    let p = dependent_workspace_project();
+
+    p.cargo("publish -Zpackage-workspace --no-verify")
+        .masquerade_as_nightly_cargo(&["package-workspace"])
+        .replace_crates_io(registry.index_url())
+        .with_stderr_data(str![[r#"
+[UPDATING] crates.io index
+[PACKAGING] a v0.0.1 ([ROOT]/foo/a)
+[PACKAGED] 4 files, [FILE_SIZE]B ([FILE_SIZE]B compressed)
+[PACKAGING] b v0.0.1 ([ROOT]/foo/b)
+[UPDATING] crates.io index
+[PACKAGED] 4 files, [FILE_SIZE]B ([FILE_SIZE]B compressed)
+[UPLOADING] a v0.0.1 ([ROOT]/foo/a)
+[UPLOADING] b v0.0.1 ([ROOT]/foo/b)
+[UPLOADED] a v0.0.1, b v0.0.1 to registry `crates-io`
+[NOTE] waiting for `a v0.0.1` or `b v0.0.1` to be available at registry `crates-io`.
+You may press ctrl-c to skip waiting; the crate should be available shortly.
+[PUBLISHED] a v0.0.1, b v0.0.1 at registry `crates-io`
+
+"#]])
+        .run();
+
+    // The staging area is gone after the commit.
+    let staged = registry::api_path().join("api/v1/crates/staged");
+    assert!(staged.read_dir().unwrap().next().is_none());
+}
+
+#[cargo_test]
+fn workspace_staged_publish_failure() {
+    let registry = RegistryBuilder::new()
+        .http_api()
+        .http_index()
+        .staged_publish()
+        .add_responder("/api/v1/crates/staged/new", reject_b)
+        .build();
+    let p = dependent_workspace_project();
+
+    p.cargo("publish -Zpackage-workspace --no-verify")
+        .masquerade_as_nightly_cargo(&["package-workspace"])
+        .replace_crates_io(registry.index_url())
+        .with_status(101)
+        .with_stderr_data(str![[r#"
+[UPDATING] crates.io index
+[PACKAGING] a v0.0.1 ([ROOT]/foo/a)
+[PACKAGED] 4 files, [FILE_SIZE]B ([FILE_SIZE]B compressed)
+[PACKAGING] b v0.0.1 ([ROOT]/foo/b)
+[UPDATING] crates.io index
+[PACKAGED] 4 files, [FILE_SIZE]B ([FILE_SIZE]B compressed)
+[UPLOADING] a v0.0.1 ([ROOT]/foo/a)
+[UPLOADING] b v0.0.1 ([ROOT]/foo/b)
+[NOTE] no packages were published; the staged uploads were discarded
+[ERROR] failed to publish to registry at http://127.0.0.1:[..]/
+
+Caused by:
+  the remote server responded with an error (status 403 Forbidden): b is not allowed
+
+"#]])
+        .run();
+
+    // `a` was staged but never published.
+    assert!(!registry::registry_path().join("1/a").exists());
+    let staged = registry::api_path().join("api/v1/crates/staged");
+    assert!(staged.read_dir().unwrap().next().is_none());
+}
+
+#[cargo_test]
+fn workspace_staged_publish_failure_after_commit() {
+    // The index starts failing once the staged packages are committed, so
+    // cargo can't confirm they were published.
+    let committed = Arc::new(AtomicBool::new(false));
+    let registry = RegistryBuilder::new()
+        .http_api()
+        .http_index()
+        .staged_publish()
+        .add_responder("/api/v1/crates/staged/commit", {
+            let committed = committed.clone();
+            move |req, server| {
+                committed.store(true, Ordering::SeqCst);
+                server.commit_staged(req)
+            }
+        })
+        .add_responder("/index/1/a", move |req, server| {
+            if committed.load(Ordering::SeqCst) {
+                server.internal_server_error(req)
+            } else {
+                server.index(req)
+            }
+        })
+        .build();
+    let p = dependent_workspace_project();
+
+    p.cargo("publish -Zpackage-workspace --no-verify")
+        .masquerade_as_nightly_cargo(&["package-workspace"])
+        .replace_crates_io(registry.index_url())
+        .env("CARGO_NET_RETRY", "0")
+        .with_status(101)
+        .with_stderr_data(str![[r#"
+[UPDATING] crates.io index
+[PACKAGING] a v0.0.1 ([ROOT]/foo/a)
+[PACKAGED] 4 files, [FILE_SIZE]B ([FILE_SIZE]B compressed)
+[PACKAGING] b v0.0.1 ([ROOT]/foo/b)
+[UPDATING] crates.io index
+[PACKAGED] 4 files, [FILE_SIZE]B ([FILE_SIZE]B compressed)
+[UPLOADING] a v0.0.1 ([ROOT]/foo/a)
+[UPLOADING] b v0.0.1 ([ROOT]/foo/b)
+[UPLOADED] a v0.0.1, b v0.0.1 to registry `crates-io`
+[NOTE] waiting for `a v0.0.1` or `b v0.0.1` to be available at registry `crates-io`.
+You may press ctrl-c to skip waiting; the crate should be available shortly.
+[NOTE] published `a v0.0.1` and `b v0.0.1`, but could not confirm that they are available
+[ERROR] download of 1/a failed
+
+Caused by:
+  failed to get successful HTTP response from `http://127.0.0.1:[..]/index/1/a` (127.0.0.1), got 500
+  body:
+  internal server error
+
+"#]])
+        .run();
+
+    // Both packages were published anyway.
+    assert!(registry::registry_path().join("1/a").exists());
+    assert!(registry::registry_path().join("1/b").exists());
+}
+
+#[cargo_test]
+fn workspace_partial_publish_failure() {
+    let registry = RegistryBuilder::new()
+        .http_api()
+        .http_index()
+        .add_responder("/api/v1/crates/new", reject_b)
+        .build();
+    let p = dependent_workspace_project();
+
+    p.cargo("publish -Zpackage-workspace --no-verify")
+        .masquerade_as_nightly_cargo(&["package-workspace"])
+        .replace_crates_io(registry.index_url())
+        .with_status(101)
+        .with_stderr_data(str![[r#"
+[UPDATING] crates.io index
+[PACKAGING] a v0.0.1 ([ROOT]/foo/a)
+[PACKAGED] 4 files, [FILE_SIZE]B ([FILE_SIZE]B compressed)
+[PACKAGING] b v0.0.1 ([ROOT]/foo/b)
+[UPDATING] crates.io index
+[PACKAGED] 4 files, [FILE_SIZE]B ([FILE_SIZE]B compressed)
+[UPLOADING] a v0.0.1 ([ROOT]/foo/a)
+[UPLOADED] a v0.0.1 to registry `crates-io`
+[NOTE] waiting for `a v0.0.1` to be available at registry `crates-io`.
+You may press ctrl-c to skip waiting; the crate should be available shortly.
+[PUBLISHED] a v0.0.1 at registry `crates-io`
+[UPLOADING] b v0.0.1 ([ROOT]/foo/b)
+[NOTE] published `a v0.0.1`, but not `b v0.0.1`
+Running `cargo publish` again will skip the published versions.
+[ERROR] failed to publish to registry at http://127.0.0.1:[..]/
+
+Caused by:
+  the remote server responded with an error (status 403 Forbidden): b is not allowed
+
+"#]])
+        .run();
+}
+
 #[cargo_test]
 fn one_unpublishable_package() {
     let _alt_reg = registry::RegistryBuilder::new()
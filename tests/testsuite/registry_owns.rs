@@ -0,0 +1,172 @@
+//! Tests for `-Zregistry-owns`.
+
+use cargo_test_support::prelude::*;
+use cargo_test_support::registry::{self, Package};
+use cargo_test_support::{project, str, Project};
+
+fn owning_project(dep: &str, policy: &str) -> Project {
+    project()
+        .file(
+            "Cargo.toml",
+            &format!(
+                r#"
+                    [package]
+                    name = "foo"
+                    version = "0.0.1"
+                    edition = "2015"
+
+                    [dependencies]
+                    {dep}
+                "#
+            ),
+        )
+        .file("src/lib.rs", "")
+        .file(
+            ".cargo/config.toml",
+            &format!(
+                r#"
+                    [registries.alternative]
+                    owns = ["acme-*"]
+                    {policy}
+                "#
+            ),
+        )
+        .build()
+}
+
+#[cargo_test]
+fn deny_other_registry() {
+    registry::alt_init();
+    Package::new("acme-util", "1.0.0").publish();
+    let p = owning_project(r#"acme-util = "1.0""#, "");
+
+    p.cargo("check -Zregistry-owns")
+        .masquerade_as_nightly_cargo(&["registry-owns"])
+        .with_status(101)
+        .with_stderr_data(str![[r#"
+[UPDATING] `dummy-registry` index
+[ERROR] failed to get `acme-util` as a dependency of package `foo v0.0.1 ([ROOT]/foo)`
+
+Caused by:
+  `acme-util` was found in registry `crates-io`, but names matching `acme-*` are owned by registry `alternative`
+
+  [HELP] to use the package from `alternative`, add `registry = "alternative"` to the dependency on `acme-util`
+
+"#]])
+        .run();
+
+    // `registry-owns` is only enforced with `-Zregistry-owns`, so this still
+    // resolves `acme-util` from crates.io.
+    p.cargo("check").run();
+}
+
+#[cargo_test]
+fn owner_registry() {
+    registry::alt_init();
+    Package::new("acme-util", "1.0.0").publish();
+    Package::new("acme-util", "1.0.0")
+        .alternative(true)
+        .publish();
+    let p = owning_project(
+        r#"acme-util = { version = "1.0", registry = "alternative" }"#,
+        "",
+    );
+
+    p.cargo("check -Zregistry-owns")
+        .masquerade_as_nightly_cargo(&["registry-owns"])
+        .with_stderr_data(str![[r#"
+[UPDATING] `alternative` index
+[LOCKING] 1 package to latest compatible version
+[DOWNLOADING] crates ...
+[DOWNLOADED] acme-util v1.0.0 (registry `alternative`)
+[CHECKING] acme-util v1.0.0 (registry `alternative`)
+[CHECKING] foo v0.0.1 ([ROOT]/foo)
+[FINISHED] `dev` profile [unoptimized + debuginfo] target(s) in [ELAPSED]s
+
+"#]])
+        .run();
+}
+
+#[cargo_test]
+fn normalized_names() {
+    registry::alt_init();
+    Package::new("acme_util", "1.0.0").publish();
+    let p = owning_project(r#"acme_util = "1.0""#, "");
+
+    p.cargo("check -Zregistry-owns")
+        .masquerade_as_nightly_cargo(&["registry-owns"])
+        .with_status(101)
+        .with_stderr_data(str![[r#"
+[UPDATING] `dummy-registry` index
+[ERROR] failed to get `acme_util` as a dependency of package `foo v0.0.1 ([ROOT]/foo)`
+
+Caused by:
+  `acme_util` was found in registry `crates-io`, but names matching `acme-*` are owned by registry `alternative`
+
+  [HELP] to use the package from `alternative`, add `registry = "alternative"` to the dependency on `acme_util`
+
+"#]])
+        .run();
+}
+
+#[cargo_test]
+fn warn_policy() {
+    registry::alt_init();
+    Package::new("acme-util", "1.0.0").publish();
+    let p = owning_project(r#"acme-util = "1.0""#, r#"owns-policy = "warn""#);
+
+    p.cargo("check -Zregistry-owns")
+        .masquerade_as_nightly_cargo(&["registry-owns"])
+        .with_stderr_data(str![[r#"
+[UPDATING] `dummy-registry` index
+[WARNING] `acme-util` was found in registry `crates-io`, but names matching `acme-*` are owned by registry `alternative`
+[LOCKING] 1 package to latest compatible version
+[DOWNLOADING] crates ...
+[DOWNLOADED] acme-util v1.0.0 (registry `dummy-registry`)
+[CHECKING] acme-util v1.0.0
+[CHECKING] foo v0.0.1 ([ROOT]/foo)
+[FINISHED] `dev` profile [unoptimized + debuginfo] target(s) in [ELAPSED]s
+
+"#]])
+        .run();
+}
+
+#[cargo_test]
+fn patch_from_owner() {
+    registry::alt_init();
+    Package::new("acme-util", "1.0.0").publish();
+    Package::new("acme-util", "1.0.1")
+        .alternative(true)
+        .publish();
+    let p = owning_project(r#"acme-util = "1.0""#, "");
+    p.change_file(
+        "Cargo.toml",
+        r#"
+            [package]
+            name = "foo"
+            version = "0.0.1"
+            edition = "2015"
+
+            [dependencies]
+            acme-util = "1.0"
+
+            [patch.crates-io]
+            acme-util = { version = "1.0.1", registry = "alternative" }
+        "#,
+    );
+
+    p.cargo("check -Zregistry-owns")
+        .masquerade_as_nightly_cargo(&["registry-owns"])
+        .with_stderr_data(str![[r#"
+[UPDATING] `alternative` index
+[UPDATING] `dummy-registry` index
+[LOCKING] 1 package to latest compatible version
+[DOWNLOADING] crates ...
+[DOWNLOADED] acme-util v1.0.1 (registry `alternative`)
+[CHECKING] acme-util v1.0.1 (registry `alternative`)
+[CHECKING] foo v0.0.1 ([ROOT]/foo)
+[FINISHED] `dev` profile [unoptimized + debuginfo] target(s) in [ELAPSED]s
+
+"#]])
+        .run();
+}
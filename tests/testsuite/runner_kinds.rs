@@ -0,0 +1,229 @@
+//! Tests for `-Zrunner-kinds`, a `runner` for each kind of executable.
+
+use cargo_test_support::prelude::*;
+use cargo_test_support::{project, rustc_host, rustc_host_env, str};
+
+#[cargo_test]
+fn gated() {
+    let p = project()
+        .file("src/main.rs", "fn main() {}")
+        .file(
+            ".cargo/config.toml",
+            &format!(
+                r#"
+                    [target.{}.runner]
+                    test = "test-runner"
+                "#,
+                rustc_host()
+            ),
+        )
+        .build();
+
+    p.cargo("run")
+        .with_status(101)
+        .with_stderr_data(str![[r#"
+[ERROR] a runner for each kind of executable in `target.[HOST_TARGET].runner` requires `-Zrunner-kinds`
+defined in [ROOT]/foo/.cargo/config.toml
+
+"#]])
+        .run();
+}
+
+#[cargo_test]
+fn runner_for_each_kind() {
+    let p = project()
+        .file("src/main.rs", "fn main() {}")
+        .file("tests/test.rs", "")
+        .file("benches/bench.rs", "")
+        .file(
+            ".cargo/config.toml",
+            &format!(
+                r#"
+                    [target.{}.runner]
+                    run = "run-runner -r"
+                    test = "test-runner"
+                    bench = ["bench-runner", "--tool=callgrind"]
+                "#,
+                rustc_host()
+            ),
+        )
+        .build();
+
+    p.cargo("run -Zrunner-kinds -- --param")
+        .masquerade_as_nightly_cargo(&["runner-kinds"])
+        .with_status(101)
+        .with_stderr_data(str![[r#"
+[COMPILING] foo v0.0.1 ([ROOT]/foo)
+[FINISHED] `dev` profile [unoptimized + debuginfo] target(s) in [ELAPSED]s
+[RUNNING] `run-runner -r target/debug/foo[EXE] --param`
+...
+"#]])
+        .run();
+
+    p.cargo("test --test test -Zrunner-kinds")
+        .masquerade_as_nightly_cargo(&["runner-kinds"])
+        .with_status(101)
+        .with_stderr_data(str![[r#"
+[COMPILING] foo v0.0.1 ([ROOT]/foo)
+[FINISHED] `test` profile [unoptimized + debuginfo] target(s) in [ELAPSED]s
+[RUNNING] tests/test.rs (target/debug/deps/test-[HASH][EXE])
+[ERROR] test failed, to rerun pass `--test test`
+
+Caused by:
+  could not execute process `test-runner [ROOT]/foo/target/debug/deps/test-[HASH][EXE]` (never executed)
+...
+"#]])
+        .run();
+
+    p.cargo("bench --bench bench -v -Zrunner-kinds")
+        .masquerade_as_nightly_cargo(&["runner-kinds"])
+        .with_status(101)
+        .with_stderr_data(str![[r#"
+...
+[RUNNING] `bench-runner --tool=callgrind [ROOT]/foo/target/release/deps/bench-[HASH][EXE] --bench`
+...
+"#]])
+        .run();
+}
+
+#[cargo_test]
+fn falls_back_to_cfg_runner() {
+    let p = project()
+        .file("src/main.rs", "fn main() {}")
+        .file("tests/test.rs", "")
+        .file(
+            ".cargo/config.toml",
+            &format!(
+                r#"
+                    [target.'cfg(not(target_os = "none"))']
+                    runner = "cfg-runner"
+
+                    [target.{}.runner]
+                    test = "test-runner"
+                "#,
+                rustc_host()
+            ),
+        )
+        .build();
+
+    p.cargo("run -Zrunner-kinds")
+        .masquerade_as_nightly_cargo(&["runner-kinds"])
+        .with_status(101)
+        .with_stderr_data(str![[r#"
+[COMPILING] foo v0.0.1 ([ROOT]/foo)
+[FINISHED] `dev` profile [unoptimized + debuginfo] target(s) in [ELAPSED]s
+[RUNNING] `cfg-runner target/debug/foo[EXE]`
+...
+"#]])
+        .run();
+
+    p.cargo("test --test test -v -Zrunner-kinds")
+        .masquerade_as_nightly_cargo(&["runner-kinds"])
+        .with_status(101)
+        .with_stderr_data(str![[r#"
+...
+[RUNNING] `test-runner [ROOT]/foo/target/debug/deps/test-[HASH][EXE]`
+...
+"#]])
+        .run();
+}
+
+#[cargo_test]
+fn no_runner_for_kind() {
+    let p = project()
+        .file("src/main.rs", "fn main() {}")
+        .file(
+            ".cargo/config.toml",
+            r#"
+                [target.'cfg(not(target_os = "none"))'.runner]
+                test = "test-runner"
+            "#,
+        )
+        .build();
+
+    p.cargo("run -Zrunner-kinds")
+        .masquerade_as_nightly_cargo(&["runner-kinds"])
+        .with_stderr_data(str![[r#"
+[COMPILING] foo v0.0.1 ([ROOT]/foo)
+[FINISHED] `dev` profile [unoptimized + debuginfo] target(s) in [ELAPSED]s
+[RUNNING] `target/debug/foo[EXE]`
+
+"#]])
+        .run();
+}
+
+#[cargo_test]
+fn runner_for_kind_in_env() {
+    let p = project().file("src/main.rs", "fn main() {}").build();
+
+    p.cargo("run -Zrunner-kinds")
+        .masquerade_as_nightly_cargo(&["runner-kinds"])
+        .env(
+            &format!("CARGO_TARGET_{}_RUNNER_RUN", rustc_host_env()),
+            "env-runner --foo",
+        )
+        .with_status(101)
+        .with_stderr_data(str![[r#"
+[COMPILING] foo v0.0.1 ([ROOT]/foo)
+[FINISHED] `dev` profile [unoptimized + debuginfo] target(s) in [ELAPSED]s
+[RUNNING] `env-runner --foo target/debug/foo[EXE]`
+...
+"#]])
+        .run();
+}
+
+#[cargo_test]
+fn unknown_kind() {
+    let p = project()
+        .file("src/main.rs", "fn main() {}")
+        .file(
+            ".cargo/config.toml",
+            r#"
+                [target.'cfg(not(target_os = "none"))'.runner]
+                tset = "test-runner"
+            "#,
+        )
+        .build();
+
+    p.cargo("run -Zrunner-kinds")
+        .masquerade_as_nightly_cargo(&["runner-kinds"])
+        .with_status(101)
+        .with_stderr_data(str![[r#"
+[ERROR] unknown kind of runner `tset` in `target.'cfg(not(target_os = "none"))'.runner`, expected one of `test`, `bench`, `run`, `doctest`
+defined in [ROOT]/foo/.cargo/config.toml
+
+"#]])
+        .run();
+}
+
+#[cargo_test(nightly, reason = "rustdoc's --runtool is unstable")]
+fn doctest_runner() {
+    let p = project()
+        .file(
+            "src/lib.rs",
+            r#"
+                /// ```
+                /// foo::a();
+                /// ```
+                pub fn a() {}
+            "#,
+        )
+        .file(
+            ".cargo/config.toml",
+            r#"
+                [target.'cfg(not(target_os = "none"))'.runner]
+                doctest = "doctest-runner --foo"
+            "#,
+        )
+        .build();
+
+    p.cargo("test --doc -v -Zrunner-kinds")
+        .masquerade_as_nightly_cargo(&["runner-kinds"])
+        .with_status(101)
+        .with_stderr_data(str![[r#"
+...
+[RUNNING] `[..]rustdoc[..] -Zunstable-options --runtool doctest-runner --runtool-arg --foo [..]`
+...
+"#]])
+        .run();
+}
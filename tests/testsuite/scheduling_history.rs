@@ -0,0 +1,114 @@
+//! Tests for `-Zscheduling-history`.
+
+use std::fs;
+
+use cargo_test_support::prelude::*;
+use cargo_test_support::registry::Package;
+use cargo_test_support::{project, str, Project};
+
+fn history_path(p: &Project) -> std::path::PathBuf {
+    p.root().join("target/.unit-history.json")
+}
+
+/// Overwrites the recorded duration of the library unit of each package.
+fn set_durations(p: &Project, durations: &[(&str, f64)]) {
+    let mut history: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(history_path(p)).unwrap()).unwrap();
+    for entry in fs::read_dir(p.root().join("target/debug/.fingerprint")).unwrap() {
+        let dir_name = entry.unwrap().file_name().into_string().unwrap();
+        let (name, unit_id) = dir_name.rsplit_once('-').unwrap();
+        if let Some((_, duration)) = durations.iter().find(|(n, _)| *n == name) {
+            history["units"][unit_id]["duration"] = (*duration).into();
+        }
+    }
+    fs::write(history_path(p), history.to_string()).unwrap();
+}
+
+#[cargo_test]
+fn records_durations() {
+    Package::new("bar", "1.0.0").publish();
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.0.1"
+                edition = "2015"
+
+                [dependencies]
+                bar = "1.0"
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+
+    p.cargo("check").run();
+    assert!(!history_path(&p).exists());
+
+    p.cargo("clean").run();
+    p.cargo("check -Zscheduling-history")
+        .masquerade_as_nightly_cargo(&["scheduling-history"])
+        .run();
+
+    let history: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(history_path(&p)).unwrap()).unwrap();
+    let units = history["units"].as_object().unwrap();
+    assert_eq!(units.len(), 2, "{history:#}");
+    for record in units.values() {
+        assert!(record["duration"].as_f64().unwrap() > 0.0, "{history:#}");
+    }
+}
+
+#[cargo_test]
+fn longest_unit_starts_first() {
+    Package::new("a", "1.0.0").publish();
+    Package::new("b", "1.0.0").publish();
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.0.1"
+                edition = "2015"
+
+                [dependencies]
+                a = "1.0"
+                b = "1.0"
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+
+    p.cargo("check -Zscheduling-history")
+        .masquerade_as_nightly_cargo(&["scheduling-history"])
+        .run();
+
+    set_durations(&p, &[("a", 10.0), ("b", 0.01)]);
+    fs::remove_dir_all(p.root().join("target/debug")).unwrap();
+    p.cargo("check -Zscheduling-history -j1")
+        .masquerade_as_nightly_cargo(&["scheduling-history"])
+        .with_stderr_data(str![[r#"
+[CHECKING] a v1.0.0
+[CHECKING] b v1.0.0
+[CHECKING] foo v0.0.1 ([ROOT]/foo)
+[FINISHED] `dev` profile [unoptimized + debuginfo] target(s) in [ELAPSED]s
+
+"#]])
+        .run();
+
+    // And the other way around.
+    set_durations(&p, &[("a", 0.01), ("b", 10.0)]);
+    fs::remove_dir_all(p.root().join("target/debug")).unwrap();
+    p.cargo("check -Zscheduling-history -j1")
+        .masquerade_as_nightly_cargo(&["scheduling-history"])
+        .with_stderr_data(str![[r#"
+[CHECKING] b v1.0.0
+[CHECKING] a v1.0.0
+[CHECKING] foo v0.0.1 ([ROOT]/foo)
+[FINISHED] `dev` profile [unoptimized + debuginfo] target(s) in [ELAPSED]s
+
+"#]])
+        .run();
+}
@@ -161,12 +161,18 @@ fn enable_build_std(e: &mut Execs, setup: &Setup) {
 
 // Helper methods used in the tests below
 trait BuildStd: Sized {
+    fn enable_build_std(&mut self, setup: &Setup) -> &mut Self;
     fn build_std(&mut self, setup: &Setup) -> &mut Self;
     fn build_std_arg(&mut self, setup: &Setup, arg: &str) -> &mut Self;
     fn target_host(&mut self) -> &mut Self;
 }
 
 impl BuildStd for Execs {
+    fn enable_build_std(&mut self, setup: &Setup) -> &mut Self {
+        enable_build_std(self, setup);
+        self
+    }
+
     fn build_std(&mut self, setup: &Setup) -> &mut Self {
         enable_build_std(self, setup);
         self.arg("-Zbuild-std");
@@ -800,3 +806,313 @@ fn fetch() {
         .with_stderr_does_not_contain("[DOWNLOADED] [..]")
         .run();
 }
+
+#[cargo_test(build_std_mock)]
+fn std_lock_file_missing_dependency() {
+    let setup = setup();
+
+    // Make a copy of the mock standard library with a lock file.
+    fn copy_dir(from: &Path, to: &Path) {
+        std::fs::create_dir_all(to).unwrap();
+        for entry in from.read_dir().unwrap() {
+            let entry = entry.unwrap();
+            let to = to.join(entry.file_name());
+            if entry.file_type().unwrap().is_dir() {
+                copy_dir(&entry.path(), &to);
+            } else {
+                std::fs::copy(entry.path(), to).unwrap();
+            }
+        }
+    }
+    let mock_std = paths::root().join("mock-std");
+    copy_dir(
+        &Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/testsuite/mock-std"),
+        &mock_std,
+    );
+    let library = mock_std.join("library");
+    let p = project().file("src/main.rs", "fn main() {}").build();
+    p.cargo("generate-lockfile --manifest-path")
+        .arg(library.join("Cargo.toml"))
+        .run();
+
+    p.cargo("check")
+        .build_std(&setup)
+        .env("__CARGO_TESTS_ONLY_SRC_ROOT", &library)
+        .target_host()
+        .run();
+
+    // A dependency that isn't locked is an error, rather than being resolved
+    // to the latest version.
+    Package::new("new-dep", "1.0.0").publish();
+    let manifest = library.join("std/Cargo.toml");
+    let contents = std::fs::read_to_string(&manifest).unwrap();
+    std::fs::write(
+        &manifest,
+        contents.replace("[dependencies]", "[dependencies]\nnew-dep = \"1.0\""),
+    )
+    .unwrap();
+    p.cargo("check")
+        .build_std(&setup)
+        .env("__CARGO_TESTS_ONLY_SRC_ROOT", &library)
+        .target_host()
+        .with_status(101)
+        .with_stderr_data(str![[r#"
+[UPDATING] `dummy-registry` index
+[DOWNLOADING] crates ...
+[DOWNLOADED] new-dep v1.0.0 (registry `dummy-registry`)
+[ERROR] the standard library's lock file `[ROOT]/mock-std/library/Cargo.lock` does not contain `new-dep v1.0.0`
+The standard library must be built with the dependencies it was locked with, check that its sources are complete and unmodified.
+
+"#]])
+        .run();
+}
+
+#[cargo_test(build_std_mock)]
+fn profile_build_std() {
+    let setup = setup();
+
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                cargo-features = ["build-std"]
+
+                [package]
+                name = "foo"
+                version = "0.1.0"
+                edition = "2015"
+
+                [profile.dev]
+                build-std = ["std"]
+            "#,
+        )
+        .file(
+            "src/lib.rs",
+            "pub fn f() { #[cfg(debug_assertions)] std::custom_api(); }",
+        )
+        .build();
+
+    p.cargo("build -v")
+        .enable_build_std(&setup)
+        .target_host()
+        .with_stderr_contains("[RUNNING] `[..]--crate-name std [..]`")
+        .run();
+    // Other profiles use the sysroot as usual.
+    p.cargo("build -v --release")
+        .enable_build_std(&setup)
+        .target_host()
+        .with_stderr_does_not_contain("[..]--crate-name std [..]")
+        .run();
+}
+
+#[cargo_test(build_std_mock)]
+fn profile_build_std_features() {
+    let setup = setup();
+
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                cargo-features = ["build-std"]
+
+                [package]
+                name = "foo"
+                version = "0.1.0"
+                edition = "2015"
+
+                [profile.dev]
+                build-std = ["std"]
+                build-std-features = ["feature1"]
+            "#,
+        )
+        .file(
+            "src/lib.rs",
+            "
+                pub fn foo() {
+                    std::conditional_function();
+                }
+            ",
+        )
+        .build();
+
+    p.cargo("build")
+        .enable_build_std(&setup)
+        .target_host()
+        .run();
+}
+
+#[cargo_test(build_std_mock)]
+fn target_build_std() {
+    let setup = setup();
+
+    let p = project()
+        .file(
+            "Cargo.toml",
+            &format!(
+                r#"
+                    cargo-features = ["build-std"]
+
+                    [package]
+                    name = "foo"
+                    version = "0.1.0"
+                    edition = "2015"
+
+                    [target.{}]
+                    build-std = ["core"]
+
+                    [profile.dev]
+                    build-std = ["std"]
+                "#,
+                rustc_host()
+            ),
+        )
+        .file(
+            "src/lib.rs",
+            "
+                #![no_std]
+                pub fn f() { core::custom_api(); }
+            ",
+        )
+        .build();
+
+    // The `[target]` table takes precedence over the profile.
+    p.cargo("build -v")
+        .enable_build_std(&setup)
+        .target_host()
+        .with_stderr_contains("[RUNNING] `[..]--crate-name core [..]`")
+        .with_stderr_does_not_contain("[..]--crate-name std [..]")
+        .run();
+}
+
+#[cargo_test(build_std_mock)]
+fn panic_abort_builds_panic_runtime() {
+    let setup = setup();
+
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                cargo-features = ["build-std"]
+
+                [package]
+                name = "foo"
+                version = "0.1.0"
+                edition = "2015"
+
+                [profile.dev]
+                build-std = ["std"]
+                panic = "abort"
+            "#,
+        )
+        .file("src/main.rs", "fn main() { std::custom_api(); }")
+        .build();
+
+    p.cargo("build -v")
+        .enable_build_std(&setup)
+        .target_host()
+        .with_stderr_contains("[RUNNING] `[..]--crate-name panic_abort [..]`")
+        .run();
+}
+
+#[cargo_test]
+fn profile_build_std_requires_feature() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.1.0"
+                edition = "2015"
+
+                [profile.dev]
+                build-std = ["std"]
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+
+    p.cargo("check")
+        .masquerade_as_nightly_cargo(&["build-std"])
+        .with_status(101)
+        .with_stderr_data(str![[r#"
+[ERROR] failed to parse manifest at `[ROOT]/foo/Cargo.toml`
+
+Caused by:
+  feature `build-std` is required
+
+  The package requires the Cargo feature called `build-std`, but that feature is not stabilized in this version of Cargo ([..]).
+  Consider adding `cargo-features = ["build-std"]` to the top of Cargo.toml (above the [package] table) to tell Cargo you are opting in to use this unstable feature.
+  See https://doc.rust-lang.org/nightly/cargo/reference/unstable.html#build-std for more information about the status of this feature.
+
+"#]])
+        .run();
+}
+
+#[cargo_test]
+fn target_build_std_requires_feature() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.1.0"
+                edition = "2015"
+
+                [target.'cfg(unix)']
+                build-std = ["core"]
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+
+    p.cargo("check")
+        .masquerade_as_nightly_cargo(&["build-std"])
+        .with_status(101)
+        .with_stderr_data(str![[r#"
+[ERROR] failed to parse manifest at `[ROOT]/foo/Cargo.toml`
+
+Caused by:
+  feature `build-std` is required
+
+  The package requires the Cargo feature called `build-std`, but that feature is not stabilized in this version of Cargo ([..]).
+  Consider adding `cargo-features = ["build-std"]` to the top of Cargo.toml (above the [package] table) to tell Cargo you are opting in to use this unstable feature.
+  See https://doc.rust-lang.org/nightly/cargo/reference/unstable.html#build-std for more information about the status of this feature.
+
+"#]])
+        .run();
+}
+
+#[cargo_test]
+fn build_std_in_profile_override() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                cargo-features = ["build-std"]
+
+                [package]
+                name = "foo"
+                version = "0.1.0"
+                edition = "2015"
+
+                [profile.dev.package.bar]
+                build-std = ["std"]
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+
+    p.cargo("check")
+        .masquerade_as_nightly_cargo(&["build-std"])
+        .with_status(101)
+        .with_stderr_data(str![[r#"
+[ERROR] failed to parse manifest at `[ROOT]/foo/Cargo.toml`
+
+Caused by:
+  `build-std` may not be specified in a `package` profile
+
+"#]])
+        .run();
+}
@@ -0,0 +1,266 @@
+//! Tests for last-use tracking of units in the target directory, and
+//! cleaning them with `cargo clean gc`.
+//!
+//! Like the tests in `global_cache_tracker`, these use the
+//! `__CARGO_TEST_LAST_USE_NOW` environment variable to simulate what happens
+//! when time passes. The [`day`] function helps with setting this value.
+
+use std::collections::BTreeSet;
+
+use cargo_test_support::prelude::*;
+use cargo_test_support::{basic_manifest, project, str, Project};
+
+/// A point in time, `n` days after an arbitrary starting point.
+fn day(n: u64) -> String {
+    (1_700_000_000 + n * 24 * 60 * 60).to_string()
+}
+
+/// Helper to get the names of the fingerprint directories of a build
+/// directory, one per unit.
+fn unit_names(p: &Project, dest: &str) -> BTreeSet<String> {
+    let dir = p.build_dir().join(dest).join(".fingerprint");
+    match dir.read_dir() {
+        Ok(entries) => entries
+            .map(|e| e.unwrap().file_name().into_string().unwrap())
+            .collect(),
+        Err(_) => BTreeSet::new(),
+    }
+}
+
+/// Builds `p` at the given time with the given `[profile.dev]` settings,
+/// which must differ from the previous build, and returns the names of the
+/// units of the old and new builds.
+fn rebuild(p: &Project, now: &str, profile: &str) -> (BTreeSet<String>, BTreeSet<String>) {
+    let before = unit_names(p, "debug");
+    p.change_file(
+        ".cargo/config.toml",
+        &format!(
+            r#"
+                [profile.dev]
+                {profile}
+            "#
+        ),
+    );
+    p.cargo("build -Zgc")
+        .masquerade_as_nightly_cargo(&["gc"])
+        .env("__CARGO_TEST_LAST_USE_NOW", now)
+        .run();
+    let new = unit_names(p, "debug")
+        .difference(&before)
+        .cloned()
+        .collect();
+    (before, new)
+}
+
+#[cargo_test]
+fn tracks_units() {
+    let p = project().file("src/lib.rs", "").build();
+    p.cargo("build").run();
+    assert!(!p.build_dir().join("debug/.unit-last-use").exists());
+
+    p.cargo("build -Zgc")
+        .masquerade_as_nightly_cargo(&["gc"])
+        .run();
+    assert!(p.build_dir().join("debug/.unit-last-use").exists());
+}
+
+#[cargo_test]
+fn max_unit_age() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.1.0"
+                edition = "2015"
+
+                [dependencies]
+                bar = { path = "bar" }
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .file("bar/Cargo.toml", &basic_manifest("bar", "1.0.0"))
+        .file("bar/src/lib.rs", "")
+        .build();
+    p.cargo("build -Zgc")
+        .masquerade_as_nightly_cargo(&["gc"])
+        .env("__CARGO_TEST_LAST_USE_NOW", day(0))
+        .run();
+    let (first, second) = rebuild(&p, &day(10), "opt-level = 1");
+    assert_eq!(first.len(), 2);
+    assert_eq!(second.len(), 2);
+
+    // Units that are still young are left alone.
+    p.cargo("clean gc -Zgc --max-unit-age=2weeks")
+        .masquerade_as_nightly_cargo(&["gc"])
+        .env("__CARGO_TEST_LAST_USE_NOW", day(11))
+        .with_stderr_data(str![[r#"
+[REMOVED] 0 files
+
+"#]])
+        .run();
+    assert_eq!(unit_names(&p, "debug"), &first | &second);
+
+    p.cargo("clean gc -Zgc --max-unit-age=1week")
+        .masquerade_as_nightly_cargo(&["gc"])
+        .env("__CARGO_TEST_LAST_USE_NOW", day(11))
+        .with_stderr_data(str![[r#"
+[REMOVED] [FILE_NUM] files, [FILE_SIZE]B total
+
+"#]])
+        .run();
+    assert_eq!(unit_names(&p, "debug"), second);
+    let deps = p.build_dir().join("debug/deps");
+    for name in &first {
+        let hash = name.rsplit_once('-').unwrap().1;
+        assert!(deps.read_dir().unwrap().all(|e| !e
+            .unwrap()
+            .file_name()
+            .to_str()
+            .unwrap()
+            .contains(hash)));
+    }
+
+    // The current build is still intact.
+    p.cargo("build -Zgc")
+        .masquerade_as_nightly_cargo(&["gc"])
+        .env("__CARGO_TEST_LAST_USE_NOW", day(11))
+        .with_stderr_data(str![[r#"
+[FINISHED] `dev` profile [optimized + debuginfo] target(s) in [ELAPSED]s
+
+"#]])
+        .run();
+}
+
+#[cargo_test]
+fn keeps_last_build() {
+    // The units of the most recent build are kept no matter how old they are.
+    let p = project().file("src/lib.rs", "").build();
+    p.cargo("build -Zgc")
+        .masquerade_as_nightly_cargo(&["gc"])
+        .env("__CARGO_TEST_LAST_USE_NOW", day(0))
+        .run();
+    let units = unit_names(&p, "debug");
+
+    p.cargo("clean gc -Zgc --max-unit-age=1day --max-target-size=0")
+        .masquerade_as_nightly_cargo(&["gc"])
+        .env("__CARGO_TEST_LAST_USE_NOW", day(100))
+        .with_stderr_data(str![[r#"
+[REMOVED] 0 files
+
+"#]])
+        .run();
+    assert_eq!(unit_names(&p, "debug"), units);
+}
+
+#[cargo_test]
+fn max_target_size() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.1.0"
+                edition = "2015"
+
+                [dependencies]
+                bar = { path = "bar" }
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .file("bar/Cargo.toml", &basic_manifest("bar", "1.0.0"))
+        .file("bar/src/lib.rs", "")
+        .build();
+    p.cargo("build -Zgc")
+        .masquerade_as_nightly_cargo(&["gc"])
+        .env("__CARGO_TEST_LAST_USE_NOW", day(0))
+        .run();
+    let (first, second) = rebuild(&p, &day(1), "opt-level = 1");
+    let (_, third) = rebuild(&p, &day(2), "opt-level = 2");
+    p.cargo("build -Zgc --release")
+        .masquerade_as_nightly_cargo(&["gc"])
+        .env("__CARGO_TEST_LAST_USE_NOW", day(0))
+        .run();
+    let release = unit_names(&p, "release");
+
+    // A large limit doesn't delete anything.
+    p.cargo("clean gc -Zgc --max-target-size=1GB")
+        .masquerade_as_nightly_cargo(&["gc"])
+        .env("__CARGO_TEST_LAST_USE_NOW", day(3))
+        .with_stderr_data(str![[r#"
+[REMOVED] 0 files
+
+"#]])
+        .run();
+
+    // The oldest units are deleted first.
+    p.cargo("clean gc -Zgc --dry-run --max-target-size=0")
+        .masquerade_as_nightly_cargo(&["gc"])
+        .env("__CARGO_TEST_LAST_USE_NOW", day(3))
+        .with_stderr_data(str![[r#"
+[SUMMARY] [FILE_NUM] files, [FILE_SIZE]B total
+[WARNING] no files deleted due to --dry-run
+
+"#]])
+        .run();
+    assert_eq!(unit_names(&p, "debug"), &(&first | &second) | &third);
+
+    p.cargo("clean gc -Zgc --max-target-size=0")
+        .masquerade_as_nightly_cargo(&["gc"])
+        .env("__CARGO_TEST_LAST_USE_NOW", day(3))
+        .with_stderr_data(str![[r#"
+[REMOVED] [FILE_NUM] files, [FILE_SIZE]B total
+
+"#]])
+        .run();
+    // Only the most recent build of each profile is left.
+    assert_eq!(unit_names(&p, "debug"), third);
+    assert_eq!(unit_names(&p, "release"), release);
+}
+
+#[cargo_test]
+fn untracked_units() {
+    // Units built without `-Zgc` are treated as just used when they are
+    // first seen.
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.1.0"
+                edition = "2015"
+
+                [dependencies]
+                bar = { path = "bar" }
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .file("bar/Cargo.toml", &basic_manifest("bar", "1.0.0"))
+        .file("bar/src/lib.rs", "")
+        .build();
+    p.cargo("build").run();
+    let (first, second) = rebuild(&p, &day(0), "opt-level = 1");
+
+    p.cargo("clean gc -Zgc --max-unit-age=1week")
+        .masquerade_as_nightly_cargo(&["gc"])
+        .env("__CARGO_TEST_LAST_USE_NOW", day(10))
+        .with_stderr_data(str![[r#"
+[REMOVED] 0 files
+
+"#]])
+        .run();
+    assert_eq!(unit_names(&p, "debug"), &first | &second);
+
+    p.cargo("clean gc -Zgc --max-unit-age=1week")
+        .masquerade_as_nightly_cargo(&["gc"])
+        .env("__CARGO_TEST_LAST_USE_NOW", day(20))
+        .with_stderr_data(str![[r#"
+[REMOVED] [FILE_NUM] files, [FILE_SIZE]B total
+
+"#]])
+        .run();
+    assert_eq!(unit_names(&p, "debug"), second);
+}
@@ -0,0 +1,445 @@
+//! Tests for `cargo test --partition`.
+
+use std::collections::BTreeSet;
+
+use cargo_test_support::prelude::*;
+use cargo_test_support::{project, str, Project};
+
+/// Returns the names of the tests that passed, according to the output of
+/// the test harness.
+fn passed_tests(p: &Project, args: &str) -> BTreeSet<String> {
+    let output = p
+        .cargo(args)
+        .arg("-Zunstable-options")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .run();
+    String::from_utf8(output.stdout)
+        .unwrap()
+        .lines()
+        .filter_map(|line| line.strip_prefix("test ")?.strip_suffix(" ... ok"))
+        .map(str::to_string)
+        .collect()
+}
+
+#[cargo_test]
+fn gated() {
+    let p = project().file("src/lib.rs", "").build();
+
+    p.cargo("test --partition hash:1/2")
+        .with_status(101)
+        .with_stderr_data(str![[r#"
+[ERROR] the `--partition` flag is unstable, and only available on the nightly channel of Cargo, but this is the `stable` channel
+See https://doc.rust-lang.org/book/appendix-07-nightly-rust.html for more information about Rust release channels.
+
+"#]])
+        .run();
+}
+
+#[cargo_test]
+fn invalid_partition() {
+    let p = project().file("src/lib.rs", "").build();
+
+    p.cargo("test --partition size:1/2 -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .with_status(101)
+        .with_stderr_data(str![[r#"
+[ERROR] invalid partition strategy `size`, expected `hash` or `count`
+
+"#]])
+        .run();
+
+    p.cargo("test --partition count:3/2 -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .with_status(101)
+        .with_stderr_data(str![[r#"
+[ERROR] invalid partition `count:3/2`, `M` in `M/N` must be between 1 and N
+
+"#]])
+        .run();
+}
+
+#[cargo_test]
+fn partitions_cover_all_tests() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.1.0"
+                edition = "2018"
+
+                [[test]]
+                name = "custom"
+                harness = false
+            "#,
+        )
+        .file(
+            "src/lib.rs",
+            r#"
+                /// ```
+                /// foo::a();
+                /// ```
+                pub fn a() {}
+
+                /// ```
+                /// foo::b();
+                /// ```
+                pub fn b() {}
+
+                #[test] fn lib_1() {}
+                #[test] fn lib_2() {}
+                #[test] fn lib_3() {}
+            "#,
+        )
+        .file(
+            "tests/t.rs",
+            r#"
+                #[test] fn t_1() {}
+                #[test] fn t_2() {}
+                #[test] fn t_3() {}
+            "#,
+        )
+        .file(
+            "tests/custom.rs",
+            r#"fn main() { println!("test custom ... ok"); }"#,
+        )
+        .build();
+    let all_tests: BTreeSet<_> = [
+        "lib_1",
+        "lib_2",
+        "lib_3",
+        "t_1",
+        "t_2",
+        "t_3",
+        "custom",
+        "src/lib.rs - a (line 2)",
+        "src/lib.rs - b (line 7)",
+    ]
+    .into_iter()
+    .map(str::to_string)
+    .collect();
+
+    for strategy in ["hash", "count"] {
+        for total in 1..=3 {
+            let mut seen = BTreeSet::new();
+            for index in 1..=total {
+                let tests =
+                    passed_tests(&p, &format!("test --partition {strategy}:{index}/{total}"));
+                for test in tests {
+                    assert!(seen.insert(test.clone()), "{test} ran twice");
+                }
+            }
+            assert_eq!(seen, all_tests, "{strategy} with {total} partitions");
+        }
+    }
+}
+
+#[cargo_test]
+fn hash_is_stable() {
+    let p = project()
+        .file(
+            "src/lib.rs",
+            r#"
+                #[test] fn a() {}
+                #[test] fn b() {}
+                #[test] fn c() {}
+                #[test] fn d() {}
+            "#,
+        )
+        .build();
+
+    let first = passed_tests(&p, "test --partition hash:1/2");
+    assert_eq!(first, passed_tests(&p, "test --partition hash:1/2"));
+}
+
+#[cargo_test]
+fn count_uses_recorded_durations() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.1.0"
+                edition = "2015"
+
+                [lib]
+                test = false
+                doctest = false
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .file("tests/slow.rs", "#[test] fn slow() {}")
+        .file(
+            "tests/fast.rs",
+            r#"
+                #[test] fn fast_1() {}
+                #[test] fn fast_2() {}
+                #[test] fn fast_3() {}
+            "#,
+        )
+        .build();
+
+    // Without any durations, the tests are dealt out in turn.
+    let tests = passed_tests(&p, "test --partition count:1/2");
+    assert_eq!(tests.len(), 2, "{tests:?}");
+
+    // With the slow test taking as long as all others together, it gets a
+    // partition of its own.
+    let history = serde_json::json!({
+        "version": 0,
+        "units": {},
+        "tests": {
+            "foo v0.1.0 test \"slow\"": 10.0,
+            "foo v0.1.0 test \"fast\"": 1.0,
+        },
+    });
+    p.change_file("durations.json", &history.to_string());
+    let tests = passed_tests(
+        &p,
+        "test --partition count:1/2 --partition-durations durations.json",
+    );
+    assert_eq!(tests, BTreeSet::from(["slow".to_string()]));
+    let tests = passed_tests(
+        &p,
+        "test --partition count:2/2 --partition-durations durations.json",
+    );
+    assert_eq!(tests.len(), 3, "{tests:?}");
+
+    // The durations of this run were recorded in the target directory, and
+    // the shared file was left alone.
+    let recorded: serde_json::Value = serde_json::from_str(
+        &std::fs::read_to_string(p.root().join("target/.unit-history.json")).unwrap(),
+    )
+    .unwrap();
+    assert!(
+        recorded["tests"]["foo v0.1.0 test \"fast\""].is_f64(),
+        "{recorded:#}"
+    );
+    assert_eq!(p.read_file("durations.json"), history.to_string());
+}
+
+#[cargo_test]
+fn count_uses_test_durations() {
+    let p = project()
+        .file(
+            "src/lib.rs",
+            r#"
+                #[test] fn a() {}
+                #[test] fn b() {}
+                #[test] fn c() {}
+                #[test] fn d() {}
+            "#,
+        )
+        .build();
+
+    // The harness reports how long each test took with `--report-time`.
+    p.cargo(
+        "test --lib --partition count:1/1 -Zunstable-options -- -Zunstable-options --report-time",
+    )
+    .masquerade_as_nightly_cargo(&["unstable-options"])
+    .env("RUSTC_BOOTSTRAP", "1")
+    .run();
+    let recorded: serde_json::Value = serde_json::from_str(
+        &std::fs::read_to_string(p.root().join("target/.unit-history.json")).unwrap(),
+    )
+    .unwrap();
+    assert!(
+        recorded["tests"]["foo v0.0.1 lib a"].is_f64(),
+        "{recorded:#}"
+    );
+
+    // A test with a duration of its own is balanced against the average of
+    // the others.
+    let history = serde_json::json!({
+        "version": 0,
+        "units": {},
+        "tests": {
+            "foo v0.0.1 lib": 1.0,
+            "foo v0.0.1 lib c": 10.0,
+        },
+    });
+    p.change_file("durations.json", &history.to_string());
+    let tests = passed_tests(
+        &p,
+        "test --lib --partition count:1/2 --partition-durations durations.json",
+    );
+    assert_eq!(tests, BTreeSet::from(["c".to_string()]));
+}
+
+#[cargo_test]
+fn skips_other_tests_when_shorter() {
+    let p = project()
+        .file(
+            "src/lib.rs",
+            r#"
+                #[test] fn a_test_with_a_long_name() {}
+                #[test] fn b() {}
+                #[test] fn c_test_with_a_long_name() {}
+            "#,
+        )
+        .build();
+
+    // Without durations, the tests are dealt out in turn.
+    p.cargo("test --lib --partition count:1/2 -v -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .with_stderr_data(str![[r#"
+...
+[RUNNING] `[ROOT]/foo/target/debug/deps/foo-[HASH][EXE] --exact --skip b`
+
+"#]])
+        .run();
+    assert_eq!(
+        passed_tests(&p, "test --lib --partition count:1/2"),
+        BTreeSet::from([
+            "a_test_with_a_long_name".to_string(),
+            "c_test_with_a_long_name".to_string()
+        ])
+    );
+}
+
+#[cargo_test]
+fn splits_long_filters() {
+    // The names of the tests of a partition don't fit on one command line.
+    let tests: String = (0..1000)
+        .map(|i| format!("#[test] fn test_with_a_name_long_enough_to_add_up_{i:04}() {{}}\n"))
+        .collect();
+    let p = project().file("src/lib.rs", &tests).build();
+
+    let output = p
+        .cargo("test --lib --partition count:1/2 -v -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .run();
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let runs = stdout.matches("test result: ok.").count();
+    assert!(runs > 1, "{stdout}");
+    assert_eq!(stdout.matches(" ... ok").count(), 500, "{stdout}");
+
+    // The harness of each run reports the tests of the others as filtered
+    // out, which only counts once.
+    let output = p
+        .cargo("test --lib --partition count:1/2 --message-format json -Zunstable-options -Zjson-test-events")
+        .masquerade_as_nightly_cargo(&["unstable-options", "json-test-events"])
+        .run();
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let summary: serde_json::Value = serde_json::from_str(stdout.lines().last().unwrap()).unwrap();
+    assert_eq!(summary["reason"], "test-summary");
+    assert_eq!(summary["passed"], 500);
+    assert_eq!(summary["filtered_out"], 500);
+}
+
+#[cargo_test]
+fn count_ignores_local_history() {
+    // Machines running different partitions can have recorded different
+    // durations in their target directories. Those must not change how the
+    // tests are assigned, or some would run twice and others not at all.
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.1.0"
+                edition = "2015"
+
+                [lib]
+                test = false
+                doctest = false
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .file("tests/a.rs", "#[test] fn a_1() {}")
+        .file(
+            "tests/b.rs",
+            "#[test] fn b_1() {} #[test] fn b_2() {} #[test] fn b_3() {}",
+        )
+        .build();
+
+    let histories = [(10.0, 1.0), (1.0, 1.0)];
+    let mut seen = BTreeSet::new();
+    for (index, (a, b)) in histories.into_iter().enumerate() {
+        let history = serde_json::json!({
+            "version": 0,
+            "units": {},
+            "tests": {
+                "foo v0.1.0 test \"a\"": a,
+                "foo v0.1.0 test \"b\"": b,
+            },
+        });
+        p.change_file("target/.unit-history.json", &history.to_string());
+        let tests = passed_tests(&p, &format!("test --partition count:{}/2", index + 1));
+        for test in tests {
+            assert!(seen.insert(test.clone()), "{test} ran twice");
+        }
+    }
+    assert_eq!(seen.len(), 4, "{seen:?}");
+}
+
+#[cargo_test]
+fn invalid_durations() {
+    let p = project().file("src/lib.rs", "").build();
+    p.change_file("durations.json", "{}");
+
+    p.cargo("test --partition count:1/2 --partition-durations durations.json -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .with_status(101)
+        .with_stderr_data(str![[r#"
+...
+[ERROR] failed to parse unit history `[ROOT]/foo/durations.json`
+
+Caused by:
+  missing field `version` at line 1 column 2
+
+"#]])
+        .run();
+}
+
+#[cargo_test]
+fn hash_with_pipelined_tests() {
+    let p = project()
+        .file(
+            "src/lib.rs",
+            r#"
+                #[test] fn lib_1() {}
+                #[test] fn lib_2() {}
+                #[test] fn lib_3() {}
+            "#,
+        )
+        .file(
+            "tests/t.rs",
+            r#"
+                #[test] fn t_1() {}
+                #[test] fn t_2() {}
+                #[test] fn t_3() {}
+            "#,
+        )
+        .build();
+
+    let mut seen = BTreeSet::new();
+    for index in 1..=2 {
+        let args = format!("test --partition hash:{index}/2");
+        let tests = passed_tests(&p, &args);
+        assert_eq!(
+            tests,
+            passed_tests(&p, &format!("{args} -Zpipelined-tests"))
+        );
+        seen.extend(tests);
+    }
+    assert_eq!(seen.len(), 6, "{seen:?}");
+}
+
+#[cargo_test]
+fn count_not_with_pipelined_tests() {
+    let p = project().file("src/lib.rs", "").build();
+
+    p.cargo("test --partition count:1/2 -Zunstable-options -Zpipelined-tests")
+        .masquerade_as_nightly_cargo(&["unstable-options", "pipelined-tests"])
+        .with_status(101)
+        .with_stderr_data(str![[r#"
+[ERROR] `--partition count:M/N` can't be used with `-Zpipelined-tests`
+[HELP] use `--partition hash:M/N` to run tests as soon as they are built
+
+"#]])
+        .run();
+}
@@ -0,0 +1,296 @@
+//! Tests for `cargo test --report` and `cargo bench --report`.
+
+use cargo_test_support::compare::assert_e2e;
+use cargo_test_support::prelude::*;
+use cargo_test_support::{basic_manifest, project, str};
+
+#[cargo_test]
+fn gated() {
+    let p = project().file("src/lib.rs", "").build();
+
+    p.cargo("test --report junit=report.xml")
+        .with_status(101)
+        .with_stderr_data(str![[r#"
+[ERROR] the `--report` flag is unstable, and only available on the nightly channel of Cargo, but this is the `stable` channel
+See https://doc.rust-lang.org/book/appendix-07-nightly-rust.html for more information about Rust release channels.
+
+"#]])
+        .run();
+}
+
+#[cargo_test]
+fn invalid_report() {
+    let p = project().file("src/lib.rs", "").build();
+
+    p.cargo("test --report xml=report.xml -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .with_status(101)
+        .with_stderr_data(str![[r#"
+[ERROR] unsupported report format `xml`, expected `junit`
+
+"#]])
+        .run();
+
+    p.cargo("test --report junit -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .with_status(101)
+        .with_stderr_data(str![[r#"
+[ERROR] invalid report `junit`, expected `junit=<path>`
+
+"#]])
+        .run();
+}
+
+#[cargo_test]
+fn junit() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.1.0"
+                edition = "2018"
+
+                [[test]]
+                name = "custom"
+                harness = false
+            "#,
+        )
+        .file(
+            "src/lib.rs",
+            r#"
+                /// ```
+                /// foo::a();
+                /// ```
+                pub fn a() {}
+
+                #[test]
+                fn passes() {}
+
+                #[test]
+                fn fails() {
+                    println!("<output>");
+                    panic!("FALSE!");
+                }
+
+                #[test]
+                #[ignore = "too slow"]
+                fn ignored() {}
+            "#,
+        )
+        .file(
+            "tests/custom.rs",
+            r#"fn main() { println!("custom output"); }"#,
+        )
+        .build();
+
+    p.cargo("test --no-fail-fast --report junit=target/report.xml -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .with_status(101)
+        .with_stdout_data(str![[r#"
+...
+test fails ... FAILED
+...
+custom output
+...
+test src/lib.rs - a (line 2) ... ok
+...
+"#]])
+        .with_stderr_data(str![[r#"
+[COMPILING] foo v0.1.0 ([ROOT]/foo)
+[FINISHED] `test` profile [unoptimized + debuginfo] target(s) in [ELAPSED]s
+[RUNNING] unittests src/lib.rs (target/debug/deps/foo-[HASH][EXE])
+[ERROR] test failed, to rerun pass `--lib`
+[RUNNING] tests/custom.rs (target/debug/deps/custom-[HASH][EXE])
+[DOCTEST] foo
+[ERROR] 1 target failed:
+    `--lib`
+
+"#]])
+        .run();
+
+    let report = p.read_file("target/report.xml");
+    assert_e2e().eq(
+        report,
+        str![[r#"
+<?xml version="1.0" encoding="UTF-8"?>
+<testsuites tests="5" failures="1" errors="0" skipped="1" time="[..]">
+  <testsuite name="foo v0.1.0 lib" tests="3" failures="1" errors="0" skipped="1" time="[..]">
+    <testcase name="fails" classname="foo v0.1.0 lib">
+      <failure message="test failed">&lt;output&gt;
+...
+FALSE!
+...
+</failure>
+    </testcase>
+    <testcase name="ignored" classname="foo v0.1.0 lib">
+      <skipped message="too slow"/>
+    </testcase>
+    <testcase name="passes" classname="foo v0.1.0 lib"/>
+  </testsuite>
+  <testsuite name="foo v0.1.0 test &quot;custom&quot;" tests="1" failures="0" errors="0" skipped="0" time="[..]">
+    <testcase name="custom" classname="foo v0.1.0 test &quot;custom&quot;" time="[..]">
+      <system-out>custom output
+</system-out>
+    </testcase>
+  </testsuite>
+  <testsuite name="foo v0.1.0 doctests" tests="1" failures="0" errors="0" skipped="0" time="[..]">
+    <testcase name="src/lib.rs - a (line 2)" classname="foo v0.1.0 doctests"/>
+  </testsuite>
+</testsuites>
+
+"#]],
+    );
+}
+
+#[cargo_test]
+fn quiet_still_reports_tests() {
+    let p = project()
+        .file("Cargo.toml", &basic_manifest("foo", "0.1.0"))
+        .file("src/lib.rs", "#[test] fn passes() {}")
+        .build();
+
+    p.cargo("test --lib -q --report junit=report.xml -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .run();
+
+    assert_e2e().eq(
+        p.read_file("report.xml"),
+        str![[r#"
+<?xml version="1.0" encoding="UTF-8"?>
+<testsuites tests="1" failures="0" errors="0" skipped="0" time="[..]">
+  <testsuite name="foo v0.1.0 lib" tests="1" failures="0" errors="0" skipped="0" time="[..]">
+    <testcase name="passes" classname="foo v0.1.0 lib"/>
+  </testsuite>
+</testsuites>
+
+"#]],
+    );
+}
+
+#[cargo_test]
+fn crashed_test_executable() {
+    let p = project()
+        .file("Cargo.toml", &basic_manifest("foo", "0.1.0"))
+        .file("src/lib.rs", "")
+        .file("tests/t.rs", "#[test] fn exits() { std::process::exit(3) }")
+        .build();
+
+    p.cargo("test --test t --report junit=report.xml -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .with_status(3)
+        .with_stderr_data(str![[r#"
+[COMPILING] foo v0.1.0 ([ROOT]/foo)
+[FINISHED] `test` profile [unoptimized + debuginfo] target(s) in [ELAPSED]s
+[RUNNING] tests/t.rs (target/debug/deps/t-[HASH][EXE])
+[ERROR] test failed, to rerun pass `--test t`
+
+Caused by:
+  process didn't exit successfully: `[ROOT]/foo/target/debug/deps/t-[HASH][EXE] --format pretty` ([EXIT_STATUS]: 3)
+[NOTE] test exited abnormally; to see the full output pass --nocapture to the harness.
+
+"#]])
+        .run();
+
+    assert_e2e().eq(
+        p.read_file("report.xml"),
+        str![[r#"
+<?xml version="1.0" encoding="UTF-8"?>
+<testsuites tests="1" failures="0" errors="1" skipped="0" time="[..]">
+  <testsuite name="foo v0.1.0 test &quot;t&quot;" tests="1" failures="0" errors="1" skipped="0" time="[..]">
+    <testcase name="foo v0.1.0 test &quot;t&quot;" classname="foo v0.1.0 test &quot;t&quot;">
+      <error message="process didn&apos;t exit successfully: `[ROOT]/foo/target/debug/deps/t-[HASH][EXE] --format pretty` ([EXIT_STATUS]: 3)"/>
+    </testcase>
+  </testsuite>
+</testsuites>
+
+"#]],
+    );
+}
+
+#[cargo_test]
+fn bench() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.1.0"
+                edition = "2018"
+
+                [lib]
+                bench = false
+
+                [[bench]]
+                name = "b"
+                harness = false
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .file("benches/b.rs", r#"fn main() { println!("bench output"); }"#)
+        .build();
+
+    p.cargo("bench --report junit=report.xml -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .with_stdout_data(str![[r#"
+bench output
+
+"#]])
+        .run();
+
+    assert_e2e().eq(
+        p.read_file("report.xml"),
+        str![[r#"
+<?xml version="1.0" encoding="UTF-8"?>
+<testsuites tests="1" failures="0" errors="0" skipped="0" time="[..]">
+  <testsuite name="foo v0.1.0 bench &quot;b&quot;" tests="1" failures="0" errors="0" skipped="0" time="[..]">
+    <testcase name="b" classname="foo v0.1.0 bench &quot;b&quot;" time="[..]">
+      <system-out>bench output
+</system-out>
+    </testcase>
+  </testsuite>
+</testsuites>
+
+"#]],
+    );
+}
+
+#[cargo_test]
+fn with_pipelined_tests() {
+    // The report is written even though the failure stops the build.
+    let p = project()
+        .file("Cargo.toml", &basic_manifest("foo", "0.1.0"))
+        .file("src/lib.rs", "")
+        .file("tests/t.rs", "#[test] fn fails() { panic!() }")
+        .build();
+
+    p.cargo("test --test t --report junit=report.xml -Zunstable-options -Zpipelined-tests")
+        .masquerade_as_nightly_cargo(&["unstable-options", "pipelined-tests"])
+        .with_status(101)
+        .with_stderr_data(str![[r#"
+[COMPILING] foo v0.1.0 ([ROOT]/foo)
+[RUNNING] tests/t.rs (target/debug/deps/t-[HASH][EXE])
+[ERROR] test failed, to rerun pass `--test t`
+
+"#]])
+        .run();
+
+    assert_e2e().eq(
+        p.read_file("report.xml"),
+        str![[r#"
+<?xml version="1.0" encoding="UTF-8"?>
+<testsuites tests="1" failures="1" errors="0" skipped="0" time="[..]">
+  <testsuite name="foo v0.1.0 test &quot;t&quot;" tests="1" failures="1" errors="0" skipped="0" time="[..]">
+    <testcase name="fails" classname="foo v0.1.0 test &quot;t&quot;">
+      <failure message="test failed">
+...
+</failure>
+    </testcase>
+  </testsuite>
+</testsuites>
+
+"#]],
+    );
+}
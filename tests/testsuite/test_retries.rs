@@ -0,0 +1,393 @@
+//! Tests for `cargo test --retries` and the `test.quarantine` config.
+
+use cargo_test_support::compare::assert_e2e;
+use cargo_test_support::prelude::*;
+use cargo_test_support::{basic_manifest, project, str};
+
+#[cargo_test]
+fn gated() {
+    let p = project().file("src/lib.rs", "").build();
+
+    p.cargo("test --retries 2")
+        .with_status(101)
+        .with_stderr_data(str![[r#"
+[ERROR] the `--retries` flag is unstable, and only available on the nightly channel of Cargo, but this is the `stable` channel
+See https://doc.rust-lang.org/book/appendix-07-nightly-rust.html for more information about Rust release channels.
+
+"#]])
+        .run();
+}
+
+#[cargo_test]
+fn retries() {
+    // `flaky` only fails the first time it runs, `broken` always fails.
+    let p = project()
+        .file("Cargo.toml", &basic_manifest("foo", "0.1.0"))
+        .file(
+            "src/lib.rs",
+            r#"
+                #[test]
+                fn flaky() {
+                    let marker = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("flaky");
+                    if !marker.exists() {
+                        std::fs::write(&marker, "").unwrap();
+                        panic!("first run");
+                    }
+                }
+
+                #[test]
+                fn broken() {
+                    panic!("always");
+                }
+
+                #[test]
+                fn passes() {}
+            "#,
+        )
+        .build();
+
+    p.cargo("test --lib --retries 2 -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .with_status(101)
+        .with_stdout_data(str![[r#"
+...
+test result: FAILED. 1 passed; 2 failed; 0 ignored; 0 measured; 0 filtered out; finished in [ELAPSED]s
+
+...
+test result: FAILED. 1 passed; 1 failed; 0 ignored; 0 measured; 1 filtered out; finished in [ELAPSED]s
+
+...
+test result: FAILED. 0 passed; 1 failed; 0 ignored; 0 measured; 2 filtered out; finished in [ELAPSED]s
+
+
+"#]])
+        .with_stderr_data(str![[r#"
+[COMPILING] foo v0.1.0 ([ROOT]/foo)
+[FINISHED] `test` profile [unoptimized + debuginfo] target(s) in [ELAPSED]s
+[RUNNING] unittests src/lib.rs (target/debug/deps/foo-[HASH][EXE])
+[RETRYING] 2 failed tests of unittests src/lib.rs (target/debug/deps/foo-[HASH][EXE]), retry 1 of 2
+[WARNING] test `flaky` is flaky, it passed on retry 1
+[RETRYING] 1 failed test of unittests src/lib.rs (target/debug/deps/foo-[HASH][EXE]), retry 2 of 2
+[ERROR] test failed, to rerun pass `--lib`
+
+"#]])
+        .run();
+}
+
+#[cargo_test]
+fn retries_without_failures() {
+    let p = project()
+        .file("Cargo.toml", &basic_manifest("foo", "0.1.0"))
+        .file("src/lib.rs", "#[test] fn passes() {}")
+        .build();
+
+    p.cargo("test --lib --retries 2 -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .with_stderr_data(str![[r#"
+[COMPILING] foo v0.1.0 ([ROOT]/foo)
+[FINISHED] `test` profile [unoptimized + debuginfo] target(s) in [ELAPSED]s
+[RUNNING] unittests src/lib.rs (target/debug/deps/foo-[HASH][EXE])
+
+"#]])
+        .run();
+}
+
+#[cargo_test]
+fn quarantine() {
+    let p = project()
+        .file("Cargo.toml", &basic_manifest("foo", "0.1.0"))
+        .file(
+            "src/lib.rs",
+            r#"
+                #[test]
+                fn flaky() {
+                    let marker = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("flaky");
+                    if !marker.exists() {
+                        std::fs::write(&marker, "").unwrap();
+                        panic!("first run");
+                    }
+                }
+
+                #[test]
+                fn broken() {
+                    panic!("always");
+                }
+
+                #[test]
+                fn passes() {}
+            "#,
+        )
+        .file(
+            ".cargo/config.toml",
+            r#"
+                [test]
+                quarantine = ["foo::foo::broken"]
+            "#,
+        )
+        .build();
+
+    p.cargo(
+        "test --lib --retries 1 --report junit=report.xml -Zunstable-options -Ztest-quarantine",
+    )
+    .masquerade_as_nightly_cargo(&["unstable-options", "test-quarantine"])
+    .with_stderr_data(str![[r#"
+[COMPILING] foo v0.1.0 ([ROOT]/foo)
+[FINISHED] `test` profile [unoptimized + debuginfo] target(s) in [ELAPSED]s
+[RUNNING] unittests src/lib.rs (target/debug/deps/foo-[HASH][EXE])
+[RETRYING] 1 failed test of unittests src/lib.rs (target/debug/deps/foo-[HASH][EXE]), retry 1 of 1
+[WARNING] test `flaky` is flaky, it passed on retry 1
+[WARNING] test `broken` failed, but is quarantined
+
+"#]])
+    .run();
+
+    assert_e2e().eq(
+        p.read_file("report.xml"),
+        str![[r#"
+<?xml version="1.0" encoding="UTF-8"?>
+<testsuites tests="3" failures="0" errors="0" skipped="1" time="[..]">
+  <testsuite name="foo v0.1.0 lib" tests="3" failures="0" errors="0" skipped="1" time="[..]">
+    <testcase name="broken" classname="foo v0.1.0 lib">
+      <skipped message="failed, but quarantined"/>
+      <system-out>
+...
+always
+...
+</system-out>
+    </testcase>
+    <testcase name="flaky" classname="foo v0.1.0 lib">
+      <flakyFailure message="test failed">
+...
+first run
+...
+</flakyFailure>
+    </testcase>
+    <testcase name="passes" classname="foo v0.1.0 lib"/>
+  </testsuite>
+</testsuites>
+
+"#]],
+    );
+}
+
+#[cargo_test]
+fn quarantine_needs_flag() {
+    let p = project()
+        .file("Cargo.toml", &basic_manifest("foo", "0.1.0"))
+        .file(
+            "src/lib.rs",
+            r#"
+                #[test]
+                fn broken() {
+                    panic!("always");
+                }
+
+                #[test]
+                fn passes() {}
+            "#,
+        )
+        .file(
+            ".cargo/config.toml",
+            r#"
+                [test]
+                quarantine = ["foo::foo::broken"]
+            "#,
+        )
+        .build();
+
+    p.cargo("test --lib")
+        .with_status(101)
+        .with_stderr_data(str![[r#"
+[COMPILING] foo v0.1.0 ([ROOT]/foo)
+[FINISHED] `test` profile [unoptimized + debuginfo] target(s) in [ELAPSED]s
+[RUNNING] unittests src/lib.rs (target/debug/deps/foo-[HASH][EXE])
+[ERROR] test failed, to rerun pass `--lib`
+
+"#]])
+        .run();
+
+    p.cargo("test --lib -Ztest-quarantine")
+        .masquerade_as_nightly_cargo(&["test-quarantine"])
+        .with_stderr_data(str![[r#"
+[FINISHED] `test` profile [unoptimized + debuginfo] target(s) in [ELAPSED]s
+[RUNNING] unittests src/lib.rs (target/debug/deps/foo-[HASH][EXE])
+[WARNING] test `broken` failed, but is quarantined
+
+"#]])
+        .run();
+}
+
+#[cargo_test]
+fn quarantine_scoped_to_target() {
+    // Both targets have a failing `shared` test, only one is quarantined.
+    let p = project()
+        .file("Cargo.toml", &basic_manifest("foo", "0.1.0"))
+        .file("src/lib.rs", "")
+        .file("tests/a.rs", "#[test] fn shared() { panic!() }")
+        .file("tests/b.rs", "#[test] fn shared() { panic!() }")
+        .file(
+            ".cargo/config.toml",
+            r#"
+                [test]
+                quarantine = ["foo::a::shared"]
+            "#,
+        )
+        .build();
+
+    p.cargo("test --test a --test b --no-fail-fast -Ztest-quarantine")
+        .masquerade_as_nightly_cargo(&["test-quarantine"])
+        .with_status(101)
+        .with_stderr_data(str![[r#"
+[COMPILING] foo v0.1.0 ([ROOT]/foo)
+[FINISHED] `test` profile [unoptimized + debuginfo] target(s) in [ELAPSED]s
+[RUNNING] tests/a.rs (target/debug/deps/a-[HASH][EXE])
+[WARNING] test `shared` failed, but is quarantined
+[RUNNING] tests/b.rs (target/debug/deps/b-[HASH][EXE])
+[ERROR] test failed, to rerun pass `--test b`
+[ERROR] 1 target failed:
+    `--test b`
+
+"#]])
+        .run();
+}
+
+#[cargo_test]
+fn quarantine_invalid_entry() {
+    let p = project()
+        .file("src/lib.rs", "")
+        .file(
+            ".cargo/config.toml",
+            r#"
+                [test]
+                quarantine = ["broken"]
+            "#,
+        )
+        .build();
+
+    p.cargo("test -Ztest-quarantine")
+        .masquerade_as_nightly_cargo(&["test-quarantine"])
+        .with_status(101)
+        .with_stderr_data(str![[r#"
+[ERROR] invalid entry `broken` in `test.quarantine`, expected `<package>::<target>::<test>`
+
+"#]])
+        .run();
+}
+
+#[cargo_test]
+fn quarantine_with_crash() {
+    // A quarantined test failing doesn't hide that the process crashed
+    // before all tests ran.
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.1.0"
+                edition = "2015"
+
+                [[test]]
+                name = "custom"
+                harness = false
+            "#,
+        )
+        .file(
+            "src/lib.rs",
+            r#"
+                #[test]
+                fn a_broken() {
+                    panic!("broken");
+                }
+
+                #[test]
+                fn b_crashes() {
+                    std::process::abort();
+                }
+            "#,
+        )
+        .file("tests/custom.rs", "fn main() { std::process::abort(); }")
+        .file(
+            ".cargo/config.toml",
+            r#"
+                [test]
+                quarantine = ["foo::foo::a_broken", "foo::custom::custom"]
+            "#,
+        )
+        .build();
+
+    p.cargo("test --lib -Ztest-quarantine -- --test-threads=1")
+        .masquerade_as_nightly_cargo(&["test-quarantine"])
+        .with_status(101)
+        .with_stderr_data(str![[r#"
+[COMPILING] foo v0.1.0 ([ROOT]/foo)
+[FINISHED] `test` profile [unoptimized + debuginfo] target(s) in [ELAPSED]s
+[RUNNING] unittests src/lib.rs (target/debug/deps/foo-[HASH][EXE])
+...
+[WARNING] test `a_broken` failed, but is quarantined
+[ERROR] test failed, to rerun pass `--lib`
+...
+"#]])
+        .run();
+
+    p.cargo("test --test custom -Ztest-quarantine")
+        .masquerade_as_nightly_cargo(&["test-quarantine"])
+        .with_status(101)
+        .with_stderr_data(str![[r#"
+[COMPILING] foo v0.1.0 ([ROOT]/foo)
+[FINISHED] `test` profile [unoptimized + debuginfo] target(s) in [ELAPSED]s
+[RUNNING] tests/custom.rs (target/debug/deps/custom-[HASH][EXE])
+[WARNING] test `custom` failed, but is quarantined
+[ERROR] test failed, to rerun pass `--test custom`
+...
+"#]])
+        .run();
+}
+
+#[cargo_test]
+fn with_pipelined_tests() {
+    let p = project()
+        .file("Cargo.toml", &basic_manifest("foo", "0.1.0"))
+        .file(
+            "src/lib.rs",
+            r#"
+                #[test]
+                fn flaky() {
+                    let marker = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("flaky");
+                    if !marker.exists() {
+                        std::fs::write(&marker, "").unwrap();
+                        panic!("first run");
+                    }
+                }
+
+                #[test]
+                fn broken() {
+                    panic!("always");
+                }
+
+                #[test]
+                fn passes() {}
+            "#,
+        )
+        .file(
+            ".cargo/config.toml",
+            r#"
+                [test]
+                quarantine = ["foo::foo::broken"]
+            "#,
+        )
+        .build();
+
+    p.cargo("test --lib --retries 1 -Zunstable-options -Ztest-quarantine -Zpipelined-tests")
+        .masquerade_as_nightly_cargo(&["unstable-options", "test-quarantine", "pipelined-tests"])
+        .with_stderr_data(str![[r#"
+[COMPILING] foo v0.1.0 ([ROOT]/foo)
+[RUNNING] unittests src/lib.rs (target/debug/deps/foo-[HASH][EXE])
+[RETRYING] 1 failed test of unittests src/lib.rs (target/debug/deps/foo-[HASH][EXE]), retry 1 of 1
+[WARNING] test `flaky` is flaky, it passed on retry 1
+[WARNING] test `broken` failed, but is quarantined
+[FINISHED] `test` profile [unoptimized + debuginfo] target(s) in [ELAPSED]s
+
+"#]])
+        .run();
+}
@@ -1599,6 +1599,72 @@ foo v1.0.0 ([ROOT]/foo)
         .run();
 }
 
+#[cargo_test]
+fn itarget_multiple_targets() {
+    // Features are not unified across multiple `--target`s.
+    Package::new("optdep", "1.0.0").publish();
+    Package::new("common", "1.0.0")
+        .add_dep(Dependency::new("optdep", "1.0").optional(true))
+        .feature("std", &["optdep"])
+        .publish();
+
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+            [package]
+            name = "foo"
+            version = "1.0.0"
+
+            [dependencies]
+            common = "1.0"
+
+            [target.'cfg(not(target_arch = "wasm32"))'.dependencies]
+            common = { version = "1.0", features = ["std"] }
+
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+
+    // Old behavior
+    p.cargo("tree -f")
+        .arg("{p} {f}")
+        .arg("--target=wasm32-unknown-unknown")
+        .arg("--target")
+        .arg(rustc_host())
+        .with_stdout_data(str![[r#"
+foo v1.0.0 ([ROOT]/foo) 
+└── common v1.0.0 optdep,std
+    └── optdep v1.0.0 
+
+foo v1.0.0 ([ROOT]/foo) 
+└── common v1.0.0 optdep,std
+    └── optdep v1.0.0 
+
+"#]])
+        .run();
+
+    // New behavior.
+    switch_to_resolver_2(&p);
+
+    p.cargo("tree -f")
+        .arg("{p} {f}")
+        .arg("--target=wasm32-unknown-unknown")
+        .arg("--target")
+        .arg(rustc_host())
+        .with_stdout_data(str![[r#"
+foo v1.0.0 ([ROOT]/foo) 
+└── common v1.0.0 
+
+foo v1.0.0 ([ROOT]/foo) 
+└── common v1.0.0 optdep,std
+    └── optdep v1.0.0 
+
+"#]])
+        .run();
+}
+
 #[cargo_test]
 fn ambiguous_name() {
     // -p that is ambiguous.